@@ -1,26 +1,83 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use futures_lite::StreamExt;
 use serde::Serialize;
 
+use muralis_core::backend::{self, monitor};
 use muralis_core::config::Config;
-use muralis_core::db::Database;
-use muralis_core::ipc::{self, IpcRequest, IpcResponse};
-use muralis_core::models::DisplayMode;
-use muralis_core::paths::MuralisPaths;
-use muralis_core::sources::{AspectRatioFilter, SourceRegistry, WallpaperSource};
-use muralis_core::wallpapers::WallpaperManager;
+use muralis_core::dates;
+use muralis_core::db::{tags_match, Database, SEARCH_HISTORY_CAP};
+use muralis_core::gui_state::GuiState;
+use muralis_core::instance::{self, LockState};
+use muralis_core::ipc::{self, DaemonStatus, IpcRequest, IpcResponse};
+use muralis_core::models::{
+    CropRect, DisplayMode, MinResolutionStrategy, PreviewFit, SortMode, TagEdit, Wallpaper,
+};
+use muralis_core::paths::{MuralisPaths, CONFIG_ENV, DATA_DIR_ENV, PROFILE_ENV};
+use muralis_core::retry::{with_retry, RetryPolicy};
+use muralis_core::solid;
+use muralis_core::sources::{
+    check_sources, AspectRatioFilter, SearchOptions, SearchResult, SearchStatus, SourceRegistry,
+};
+use muralis_core::wallpapers::{apply_after_favorite, WallpaperManager};
 
 #[derive(Parser)]
 #[command(name = "muralis", about = "Wallpaper manager for Hyprland")]
 struct Cli {
+    /// Use a named profile with its own config, favorites and daemon
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// Config file to use instead of ~/.config/muralis/config.toml
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Keep the database, favorites and cache under this directory
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
     #[command(subcommand)]
     command: Commands,
 }
 
+/// `--profile`, `--config` and `--data-dir`, set once at startup.
+#[derive(Debug, Default)]
+struct PathOverrides {
+    profile: Option<String>,
+    config: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+}
+
+static PATH_OVERRIDES: OnceLock<PathOverrides> = OnceLock::new();
+
+/// `--profile`, else `$MURALIS_PROFILE`.
+fn profile() -> Option<String> {
+    PATH_OVERRIDES
+        .get()
+        .and_then(|o| o.profile.clone())
+        .or_else(MuralisPaths::active_profile)
+}
+
+/// Muralis paths with the global flags applied over the environment.
+fn muralis_paths() -> Result<MuralisPaths> {
+    let mut paths = MuralisPaths::resolve(profile().as_deref())?;
+    if let Some(overrides) = PATH_OVERRIDES.get() {
+        if let Some(config) = &overrides.config {
+            paths = paths.with_config_file(config);
+        }
+        if let Some(dir) = &overrides.data_dir {
+            paths = paths.with_data_dir(dir);
+        }
+    }
+    Ok(paths)
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Show daemon status
     Status,
+    /// Show the current wallpaper's library record (resolution, tags, file path)
+    Current,
     /// Next wallpaper
     Next,
     /// Previous wallpaper
@@ -28,11 +85,18 @@ enum Commands {
     /// Set specific wallpaper by ID or path
     Set {
         /// Wallpaper ID or file path
-        id: String,
+        #[arg(required_unless_present_any = ["color", "gradient"])]
+        id: Option<String>,
+        /// Apply a solid color instead (e.g. '#1e1e2e')
+        #[arg(long, conflicts_with_all = ["id", "gradient"])]
+        color: Option<String>,
+        /// Apply a top-to-bottom gradient through comma-separated colors
+        #[arg(long, value_delimiter = ',', num_args = 1.., conflicts_with = "id")]
+        gradient: Vec<String>,
     },
     /// Switch display mode
     Mode {
-        /// Mode: static, random, random_startup, sequential, workspace, schedule
+        /// Mode: static, random, random_startup, sequential, workspace, schedule, live_random
         mode: String,
     },
     /// Pause wallpaper rotation
@@ -41,11 +105,15 @@ enum Commands {
     Resume,
     /// Reload config
     Reload,
+    /// Re-apply the wallpaper shown before the current one
+    Restore,
+    /// Keep the current wallpaper (favorite it if applied from a file path)
+    Keep,
     /// Search wallpaper sources
     Search {
         /// Search query (empty for browse-all)
         query: Option<String>,
-        /// Source name filter
+        /// Source ID or name filter (see `sources list`)
         #[arg(long)]
         source: Option<String>,
         /// Page number
@@ -57,6 +125,30 @@ enum Commands {
         /// Aspect ratio filter (all, 16x9, 21x9, 32x9, 16x10, 4x3, 3x2)
         #[arg(long, default_value = "all")]
         aspect: String,
+        /// Favorite the first N results not already in the library instead of
+        /// printing them
+        #[arg(long, value_name = "N")]
+        download_first: Option<usize>,
+        /// Wallhaven color filter, a hex from its palette (e.g. 336600)
+        #[arg(long)]
+        color: Option<String>,
+    },
+    /// Print previews from a streaming source as they arrive (one JSON object per line)
+    Subscribe {
+        /// Source ID or name
+        source: String,
+        /// Stop after this many previews
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Show recent search queries
+    History {
+        /// Source name (defaults to all sources)
+        #[arg(long)]
+        source: Option<String>,
+        /// Max queries to show
+        #[arg(long, default_value = "10")]
+        limit: u32,
     },
     /// Manage favorites
     Favorites {
@@ -68,11 +160,64 @@ enum Commands {
         #[command(subcommand)]
         action: SourcesAction,
     },
+    /// Group favorites into collections
+    Collections {
+        #[command(subcommand)]
+        action: CollectionsAction,
+    },
+    /// Hide search results by tag
+    Blacklist {
+        #[command(subcommand)]
+        action: BlacklistAction,
+    },
+    /// Rotation filters
+    Filter {
+        #[command(subcommand)]
+        action: FilterAction,
+    },
     /// Manage cache
     Cache {
         #[command(subcommand)]
         action: CacheAction,
     },
+    /// Share or restore the config file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Maintain the wallpaper database
+    Db {
+        #[command(subcommand)]
+        action: DbAction,
+    },
+    /// Open a wallpaper in an external viewer ($MURALIS_VIEWER or xdg-open)
+    Open {
+        /// Library ID, file path, or image URL (downloaded to a temp file that is
+        /// removed when the viewer exits)
+        target: String,
+    },
+    /// List connected monitors (JSON)
+    Monitors,
+    /// Check the wallpaper backend for common problems
+    Doctor,
+    /// Show display settings used by the GUI (JSON)
+    Settings,
+    /// Remember the GUI window size and active source tab
+    #[command(hide = true)]
+    GuiState {
+        #[arg(long)]
+        width: Option<u32>,
+        #[arg(long)]
+        height: Option<u32>,
+        /// Source tab ID, or "All"
+        #[arg(long)]
+        tab: Option<String>,
+        /// Preview sizing: contain or actual
+        #[arg(long)]
+        preview_fit: Option<PreviewFit>,
+    },
+    /// Launch the GUI, or focus it if already running
+    Gui,
     /// Stop the daemon
     Quit,
 }
@@ -86,15 +231,148 @@ enum CacheAction {
 }
 
 #[derive(Subcommand)]
-enum FavoritesAction {
-    /// List all favorites
+enum ConfigAction {
+    /// Print the config TOML with API keys redacted
+    Export {
+        /// Include API keys and access keys
+        #[arg(long)]
+        with_secrets: bool,
+    },
+    /// Validate a config file and replace the current one (kept as config.toml.bak)
+    Import {
+        /// Path to the TOML file
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbAction {
+    /// Remove favorites whose wallpaper files were deleted
+    PruneMissing {
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Unfavorite least recently used favorites until the library fits in
+    /// `[general] library_max_mb` (favorites in a collection are kept)
+    Evict {
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+        /// Don't ask before removing
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum BlacklistAction {
+    /// List blacklisted tags (JSON)
+    Tags,
+    /// Hide future results carrying a tag
+    AddTag { tag: String },
+    /// Stop hiding results carrying a tag
+    RemoveTag { tag: String },
+}
+
+#[derive(Subcommand)]
+enum CollectionsAction {
+    /// List collections with their sizes (JSON)
     List,
+    /// List the favorites in a collection (JSON)
+    Show { name: String },
+    /// Create an empty collection
+    Create { name: String },
+    /// Delete a collection (its favorites stay in the library)
+    Delete { name: String },
+    /// Add favorites to a collection (created if missing)
+    Add {
+        name: String,
+        /// Wallpaper IDs
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Take favorites out of a collection
+    Remove {
+        name: String,
+        /// Wallpaper IDs
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FilterAction {
+    /// Show the current filter config (JSON)
+    Show,
+    /// Limit rotation to wallpapers within a brightness range (0.0-1.0)
+    Brightness {
+        #[arg(long)]
+        min: Option<f32>,
+        #[arg(long)]
+        max: Option<f32>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FavoritesAction {
+    /// List favorites, optionally by date added
+    List {
+        /// Added at or after: YYYY-MM-DD, RFC 3339, or relative (30m, 12h, 7d, 2w)
+        #[arg(long)]
+        since: Option<String>,
+        /// Added before the end of: YYYY-MM-DD, RFC 3339, or relative
+        #[arg(long)]
+        until: Option<String>,
+        /// Order: added (newest first) or hue (by dominant color)
+        #[arg(long, default_value = "added")]
+        sort: String,
+        /// Only favorites with tags matching every word (case-insensitive)
+        #[arg(long)]
+        query: Option<String>,
+        /// List the running daemon's rotation instead of reading the database
+        #[arg(long)]
+        from_daemon: bool,
+    },
     /// Show favorites stats
     Stats,
     /// Add a wallpaper by URL
     Add {
-        /// Wallpaper URL (e.g. https://wallhaven.cc/w/abc123)
-        url: String,
+        /// Wallpaper URLs (e.g. https://wallhaven.cc/w/abc123)
+        #[arg(required = true)]
+        urls: Vec<String>,
+    },
+    /// Move a favorite into a collection (created if missing)
+    Move {
+        /// Wallpaper ID
+        id: String,
+        /// Collection name
+        collection: String,
+    },
+    /// Set the crop the daemon applies when showing a favorite
+    Crop {
+        /// Wallpaper ID
+        id: String,
+        /// Crop rectangle in source pixels as x,y,width,height
+        #[arg(long, value_parser = parse_crop_rect, conflicts_with = "clear", required_unless_present = "clear")]
+        rect: Option<CropRect>,
+        /// Remove the stored crop
+        #[arg(long)]
+        clear: bool,
+    },
+    /// Edit a favorite's tags (lowercased and deduplicated)
+    Tag {
+        /// Wallpaper ID
+        id: String,
+        /// Tags to add (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        add: Vec<String>,
+        /// Tags to remove (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        remove: Vec<String>,
+        /// Replace all tags before adding/removing (comma-separated; "" clears)
+        #[arg(long, value_delimiter = ',')]
+        set: Option<Vec<String>>,
     },
 }
 
@@ -102,71 +380,176 @@ enum FavoritesAction {
 enum SourcesAction {
     /// List configured sources
     List,
+    /// Enable a source (for this daemon session unless --persist)
+    Enable {
+        /// Source name or type (wallhaven, unsplash, pexels, deviantart, bing, apod, feed, ...)
+        name: String,
+        /// API key / access key for the source (implies --persist)
+        #[arg(long)]
+        key: Option<String>,
+        /// Client secret for OAuth sources such as deviantart (implies --persist)
+        #[arg(long)]
+        secret: Option<String>,
+        /// Folder for the local source to index; repeatable (implies --persist)
+        #[arg(long = "path")]
+        paths: Vec<String>,
+        /// Write the change to config.toml
+        #[arg(long)]
+        persist: bool,
+    },
+    /// Disable a source (for this daemon session unless --persist)
+    Disable {
+        /// Source name or type
+        name: String,
+        /// Write the change to config.toml
+        #[arg(long)]
+        persist: bool,
+    },
+    /// Probe each enabled source and report latency (JSON)
+    Check {
+        /// Seconds to wait for each source
+        #[arg(long, default_value = "10")]
+        timeout: u64,
+    },
 }
 
-#[derive(Serialize)]
-struct SearchOutput {
-    results: Vec<SearchResult>,
-    page: u32,
-    per_page: u32,
-    has_more: bool,
+/// Onboarding hint for an empty registry. Only shown on a terminal so the
+/// GUI, which has its own empty state, isn't sent a duplicate warning.
+fn hint_no_sources() {
+    use std::io::IsTerminal;
+    if std::io::stderr().is_terminal() {
+        eprintln!(
+            "no sources are enabled. Enable one with:\n  \
+             muralis sources enable wallhaven --persist\n  \
+             muralis sources enable unsplash --key <ACCESS_KEY>\n  \
+             muralis sources enable pexels --key <API_KEY>\n\
+             or add a [[sources.feeds]] entry to config.toml"
+        );
+    }
 }
 
-#[derive(Serialize)]
-struct SearchResult {
-    source_type: String,
-    source_id: String,
-    source_url: String,
-    thumbnail_url: String,
-    full_url: String,
-    width: u32,
-    height: u32,
-    tags: Vec<String>,
-    is_favorited: bool,
+/// Say an empty result list was a successful search, on a terminal only;
+/// the GUI reads `status` from the JSON instead.
+fn hint_no_results(query: &str) {
+    use std::io::IsTerminal;
+    if std::io::stderr().is_terminal() {
+        match query.trim() {
+            "" => eprintln!("no results"),
+            q => eprintln!("no results for \"{q}\""),
+        }
+    }
+}
+
+fn invalid_date(s: &str) -> anyhow::Error {
+    anyhow::anyhow!("invalid date {s:?} (use YYYY-MM-DD, RFC 3339, or e.g. 7d)")
+}
+
+fn parse_crop_rect(s: &str) -> std::result::Result<CropRect, String> {
+    let parts: Vec<u32> = s
+        .split(',')
+        .map(|n| n.trim().parse())
+        .collect::<std::result::Result<_, _>>()
+        .map_err(|_| format!("invalid crop {s:?} (use x,y,width,height)"))?;
+    match parts[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(CropRect {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => Err(format!("invalid crop {s:?} (use x,y,width,height)")),
+    }
 }
 
 #[derive(Serialize)]
 struct SourceInfo {
+    id: String,
     name: String,
     source_type: String,
+    enabled: bool,
+    /// Lists results for an empty query.
+    browsable: bool,
+    /// Palette of the source's color filter; empty when it has none.
+    colors: Vec<String>,
 }
 
-fn build_registry(config: &Config) -> Result<(SourceRegistry, reqwest::Client)> {
-    let client = reqwest::Client::builder()
-        .user_agent(concat!(
-            "muralis/",
-            env!("CARGO_PKG_VERSION"),
-            " (wallpaper manager)"
-        ))
-        .build()?;
-    let sources = &config.sources;
-    let mut registry = SourceRegistry::new();
+/// Every configured source, minus those the daemon disabled at runtime.
+async fn build_registry(config: &Config) -> Result<SourceRegistry> {
+    let mut registry = muralis_sources::build_registry(config, &muralis_paths()?)?;
+    apply_runtime_toggles(&mut registry).await;
+    Ok(registry)
+}
 
-    for s in muralis_source_wallhaven::create_sources(sources, client.clone()) {
-        registry.register(s);
-    }
-    for s in muralis_source_unsplash::create_sources(sources, client.clone()) {
-        registry.register(s);
-    }
-    for s in muralis_source_pexels::create_sources(sources, client.clone()) {
-        registry.register(s);
+/// The daemon's rotation list, or None (with a warning) when it can't be
+/// reached.
+async fn daemon_wallpapers() -> Option<Vec<Wallpaper>> {
+    match send_request(&IpcRequest::ListWallpapers { limit: None }).await {
+        Ok(IpcResponse::Ok { data: Some(data) }) => serde_json::from_value(data).ok(),
+        Ok(IpcResponse::Error { message }) => {
+            eprintln!("warning: daemon list failed, reading the database: {message}");
+            None
+        }
+        Ok(IpcResponse::Ok { data: None }) => None,
+        Err(e) => {
+            eprintln!("warning: daemon not running, reading the database ({e})");
+            None
+        }
     }
-    for s in muralis_source_feed::create_sources(sources, client.clone()) {
-        registry.register(s);
+}
+
+/// Ask a yes/no question on the terminal; anything but "y" is a no.
+/// Refuses when stdin isn't a terminal, so scripts must pass `--yes`.
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::{IsTerminal, Write};
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("stdin is not a terminal; pass --yes to confirm");
     }
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
 
-    Ok((registry, client))
+/// Hide sources the running daemon has disabled for this session.
+/// Without a daemon every configured source stays enabled.
+async fn apply_runtime_toggles(registry: &mut SourceRegistry) {
+    let Ok(IpcResponse::Ok { data: Some(data) }) = send_request(&IpcRequest::Status).await else {
+        return;
+    };
+    if let Ok(status) = serde_json::from_value::<DaemonStatus>(data) {
+        for name in &status.disabled_sources {
+            registry.set_enabled(name, false);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // stdout carries command output, so warnings (config, source setup) go to stderr
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| "warn".into()),
+        )
+        .init();
+
     let cli = Cli::parse();
+    let _ = PATH_OVERRIDES.set(PathOverrides {
+        profile: cli.profile,
+        config: cli.config,
+        data_dir: cli.data_dir,
+    });
 
     match cli.command {
         Commands::Status => {
             let resp = send(IpcRequest::Status).await?;
             print_response(resp);
         }
+        Commands::Current => {
+            let resp = send(IpcRequest::CurrentWallpaper).await?;
+            print_response(resp);
+        }
         Commands::Next => {
             let resp = send(IpcRequest::Next).await?;
             print_response(resp);
@@ -175,7 +558,15 @@ async fn main() -> Result<()> {
             let resp = send(IpcRequest::Prev).await?;
             print_response(resp);
         }
-        Commands::Set { id } => {
+        Commands::Set {
+            id,
+            color,
+            gradient,
+        } => {
+            let id = match id {
+                Some(id) => id,
+                None => render_color_wallpaper(color.as_deref(), &gradient).await?,
+            };
             let resp = send(IpcRequest::SetWallpaper { id }).await?;
             print_response(resp);
         }
@@ -196,148 +587,491 @@ async fn main() -> Result<()> {
             let resp = send(IpcRequest::Reload).await?;
             print_response(resp);
         }
+        Commands::Restore => {
+            let resp = send(IpcRequest::RestorePrevious).await?;
+            print_response(resp);
+        }
+        Commands::Keep => {
+            let resp = send(IpcRequest::KeepCurrent).await?;
+            print_response(resp);
+        }
         Commands::Search {
             query,
             source,
             page,
             per_page,
             aspect,
+            download_first,
+            color,
         } => {
-            let paths = MuralisPaths::new()?;
-            let config = Config::load(&paths)?;
-            let (registry, _) = build_registry(&config)?;
+            let paths = muralis_paths()?;
+            let mut config = Config::load(&paths)?;
+            let min_res = monitor::resolve_min_resolution(&config.display).await;
+            if let Some((w, h)) = min_res {
+                // let wallhaven filter server-side unless the user set `atleast` explicitly
+                if let Some(toml::Value::Table(wh)) = config.sources.get_mut("wallhaven") {
+                    wh.entry("atleast")
+                        .or_insert_with(|| toml::Value::String(format!("{w}x{h}")));
+                }
+            }
+            if let Some(color) = color {
+                if let Some(toml::Value::Table(wh)) = config.sources.get_mut("wallhaven") {
+                    wh.insert("colors".into(), toml::Value::String(color));
+                }
+            }
+            let registry = build_registry(&config).await?;
+            if registry.is_empty() {
+                hint_no_sources();
+            }
             let db = Database::open(&paths.db_path())?;
             let aspect: AspectRatioFilter =
                 aspect.parse().map_err(|e: String| anyhow::anyhow!(e))?;
 
-            let mut all_results = Vec::new();
-
-            // Search matching sources
-            let sources: Vec<&dyn WallpaperSource> = if let Some(ref name) = source {
-                registry
-                    .iter()
-                    .filter(|s| s.name() == name.as_str())
-                    .collect()
-            } else {
-                registry.iter().collect()
+            let query = query.unwrap_or_default();
+            let opts = SearchOptions {
+                query: query.clone(),
+                source: source.clone(),
+                page,
+                // fetch enough to seed `download_first` from one page
+                per_page: per_page.max(download_first.unwrap_or(0) as u32),
+                aspect,
+                aspect_tolerance: config.general.aspect_tolerance,
+                min_resolution: min_res,
+                filter: Some(config.filter.clone()),
+                per_source_cap: config.general.browse_per_source_cap,
             };
 
-            let query = query.unwrap_or_default();
+            if let Some(n) = download_first {
+                let manager = WallpaperManager::new(paths)
+                    .with_thumbnails(config.thumbnails)
+                    .with_square_thumbnails(config.general.square_thumbnails)
+                    .with_hash_algo(config.general.hash_algo);
+                let policy = RetryPolicy::download(config.general.download_retries);
+                let (outcomes, failures) = registry
+                    .favorite_first(&db, &manager, &opts, n, policy)
+                    .await;
+                for (name, e) in &failures {
+                    eprintln!("warning: {name} search failed: {e}");
+                }
 
-            for src in &sources {
-                match src.search(&query, page, per_page, aspect).await {
-                    Ok(previews) => {
-                        for p in previews {
-                            // Client-side aspect filter for sources that don't support it natively
-                            if !aspect.matches(p.width, p.height) {
-                                continue;
-                            }
-                            let is_favorited = db
-                                .is_favorited_by_source(p.source_type.as_str(), &p.source_id)
-                                .unwrap_or(false);
-                            all_results.push(SearchResult {
-                                source_type: p.source_type.to_string(),
-                                source_id: p.source_id,
-                                source_url: p.source_url,
-                                thumbnail_url: p.thumbnail_url,
-                                full_url: p.full_url,
-                                width: p.width,
-                                height: p.height,
-                                tags: p.tags,
-                                is_favorited,
+                let mut added = Vec::new();
+                for outcome in &outcomes {
+                    let preview = &outcome.preview;
+                    match &outcome.result {
+                        Ok(id) => {
+                            let out = serde_json::json!({
+                                "id": id,
+                                "source_type": preview.source_type.to_string(),
+                                "source_id": preview.source_id,
+                                "source_url": preview.source_url,
                             });
+                            println!("{}", serde_json::to_string(&out)?);
+                            added.push(id.clone());
+                        }
+                        Err(e) => {
+                            eprintln!("error: {} {}: {e}", preview.source_type, preview.source_id)
                         }
                     }
-                    Err(e) => {
-                        eprintln!("warning: {} search failed: {e}", src.name());
+                }
+                eprintln!("favorited {} of {}", added.len(), outcomes.len());
+                if outcomes.len() < n {
+                    eprintln!(
+                        "warning: only {} new results found of {n} requested",
+                        outcomes.len()
+                    );
+                }
+
+                if let Some(id) = apply_after_favorite(config.general.apply_on_favorite, &added) {
+                    let request = IpcRequest::SetWallpaper { id: id.to_string() };
+                    if let Err(e) = send_request(&request).await {
+                        eprintln!("warning: could not apply {id}: {e}");
                     }
                 }
+                if added.len() < outcomes.len() {
+                    std::process::exit(1);
+                }
+                return Ok(());
+            }
+
+            let output = registry.search_all(&db, &opts).await;
+            for (name, e) in &output.failures {
+                eprintln!("warning: {name} search failed: {e}");
+            }
+            if output.status == SearchStatus::NoResults {
+                hint_no_results(&query);
+            }
+
+            if output.any_ok && !query.trim().is_empty() {
+                let history_source = source.as_deref().unwrap_or("All");
+                if let Err(e) = db.record_query(history_source, query.trim(), SEARCH_HISTORY_CAP) {
+                    eprintln!("warning: failed to record search history: {e}");
+                }
             }
 
-            let has_more = all_results.len() >= per_page as usize;
-            let output = SearchOutput {
-                results: all_results,
-                page,
-                per_page,
-                has_more,
-            };
             println!("{}", serde_json::to_string(&output)?);
         }
+        Commands::Subscribe { source, limit } => {
+            let paths = muralis_paths()?;
+            let config = Config::load(&paths)?;
+            let registry = build_registry(&config).await?;
+            let db = Database::open(&paths.db_path())?;
+            let src = registry
+                .get(&source)
+                .ok_or_else(|| anyhow::anyhow!("unknown source: {source}"))?;
+            let mut stream = src.subscribe().await?;
+            let mut seen = 0;
+            while let Some(p) = stream.next().await {
+                println!(
+                    "{}",
+                    serde_json::to_string(&SearchResult::new(&db, p, AspectRatioFilter::All))?
+                );
+                seen += 1;
+                if limit.is_some_and(|l| seen >= l) {
+                    break;
+                }
+            }
+        }
+        Commands::History { source, limit } => {
+            let paths = muralis_paths()?;
+            let db = Database::open(&paths.db_path())?;
+            let queries = db.recent_queries(source.as_deref().unwrap_or("All"), limit)?;
+            println!("{}", serde_json::to_string(&queries)?);
+        }
         Commands::Favorites { action } => match action {
-            FavoritesAction::List => {
-                let paths = MuralisPaths::new()?;
+            FavoritesAction::List {
+                since,
+                until,
+                sort,
+                query,
+                from_daemon,
+            } => {
+                let sort: SortMode = sort.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+                let paths = muralis_paths()?;
                 let db = Database::open(&paths.db_path())?;
-                let wallpapers = db.list_wallpapers()?;
+                if sort == SortMode::Hue {
+                    // favorites from before palettes were stored
+                    WallpaperManager::new(paths.clone()).backfill_analysis(&db)?;
+                }
+                let now = chrono::Utc::now();
+                let since = since
+                    .map(|s| dates::parse_since(&s, now).ok_or_else(|| invalid_date(&s)))
+                    .transpose()?;
+                let until = until
+                    .map(|s| dates::parse_until(&s, now).ok_or_else(|| invalid_date(&s)))
+                    .transpose()?;
+                let live = if from_daemon {
+                    daemon_wallpapers().await
+                } else {
+                    None
+                };
+                let mut wallpapers = match live {
+                    Some(mut live) => {
+                        live.retain(|wp| {
+                            let added = chrono::DateTime::parse_from_rfc3339(&wp.added_at).ok();
+                            added.is_none_or(|t| {
+                                since.is_none_or(|s| t >= s) && until.is_none_or(|u| t < u)
+                            })
+                        });
+                        live
+                    }
+                    None => db.list_wallpapers_between(since.as_ref(), until.as_ref())?,
+                };
+                if let Some(query) = &query {
+                    wallpapers.retain(|wp| tags_match(&wp.tags, query));
+                }
+                sort.sort(&mut wallpapers);
                 println!("{}", serde_json::to_string(&wallpapers)?);
             }
             FavoritesAction::Stats => {
-                let paths = MuralisPaths::new()?;
+                let paths = muralis_paths()?;
                 let db = Database::open(&paths.db_path())?;
                 let count = db.wallpaper_count()?;
                 let disk_usage = dir_size(&paths.wallpapers_dir());
                 println!("favorites: {count}");
                 println!("disk usage: {}", format_bytes(disk_usage));
             }
-            FavoritesAction::Add { url } => {
-                let paths = MuralisPaths::new()?;
+            FavoritesAction::Add { urls } => {
+                let paths = muralis_paths()?;
                 let config = Config::load(&paths)?;
-                let (registry, _) = build_registry(&config)?;
+                let registry = build_registry(&config).await?;
                 let db = Database::open(&paths.db_path())?;
-                let manager = WallpaperManager::new(paths);
+                let manager = WallpaperManager::new(paths)
+                    .with_thumbnails(config.thumbnails)
+                    .with_square_thumbnails(config.general.square_thumbnails)
+                    .with_hash_algo(config.general.hash_algo);
 
-                // Try each source's resolve_url
-                let mut resolved = None;
-                for src in registry.iter() {
-                    match src.resolve_url(&url).await {
-                        Ok(Some(preview)) => {
-                            // Download the image
-                            let data = src.download(&preview).await?;
-                            let id = manager.favorite(&db, &preview, &data)?;
-                            resolved = Some((id, preview));
-                            break;
+                let download_policy = RetryPolicy::download(config.general.download_retries);
+                let mut added = Vec::new();
+                let mut failed = false;
+                for url in &urls {
+                    // Try each source's resolve_url
+                    let mut resolved = None;
+                    for src in registry.iter() {
+                        match src.resolve_url(url).await {
+                            Ok(Some(preview)) => {
+                                // Download the image
+                                let data =
+                                    with_retry(download_policy, || src.download(&preview)).await?;
+                                let id = manager.favorite(&db, &preview, &data)?;
+                                resolved = Some((id, preview));
+                                break;
+                            }
+                            Ok(None) => continue,
+                            Err(e) => {
+                                eprintln!("warning: {} resolve failed: {e}", src.name());
+                            }
                         }
-                        Ok(None) => continue,
-                        Err(e) => {
-                            eprintln!("warning: {} resolve failed: {e}", src.name());
+                    }
+
+                    match resolved {
+                        Some((id, preview)) => {
+                            let out = serde_json::json!({
+                                "id": id,
+                                "source_type": preview.source_type.to_string(),
+                                "source_id": preview.source_id,
+                                "source_url": preview.source_url,
+                            });
+                            println!("{}", serde_json::to_string(&out)?);
+                            added.push(id);
+                        }
+                        None => {
+                            eprintln!("error: no source could resolve URL: {url}");
+                            failed = true;
                         }
                     }
                 }
 
-                match resolved {
-                    Some((id, preview)) => {
-                        let out = serde_json::json!({
-                            "id": id,
-                            "source_type": preview.source_type.to_string(),
-                            "source_id": preview.source_id,
-                            "source_url": preview.source_url,
-                        });
-                        println!("{}", serde_json::to_string(&out)?);
-                    }
-                    None => {
-                        eprintln!("error: no source could resolve URL: {url}");
-                        std::process::exit(1);
+                if let Some(id) = apply_after_favorite(config.general.apply_on_favorite, &added) {
+                    let request = IpcRequest::SetWallpaper { id: id.to_string() };
+                    if let Err(e) = send_request(&request).await {
+                        eprintln!("warning: could not apply {id}: {e}");
                     }
                 }
+                if failed {
+                    std::process::exit(1);
+                }
+            }
+            FavoritesAction::Move { id, collection } => {
+                let paths = muralis_paths()?;
+                let db = Database::open(&paths.db_path())?;
+                db.move_to_collection(&id, &collection)?;
+                // refresh a collection-scoped rotation; fine if no daemon is running
+                let _ = send_request(&IpcRequest::Reload).await;
+                println!("moved {id} to {collection}");
+            }
+            FavoritesAction::Crop { id, rect, clear } => {
+                let paths = muralis_paths()?;
+                let db = Database::open(&paths.db_path())?;
+                let crop = if clear { None } else { rect };
+                WallpaperManager::new(paths).set_crop(&db, &id, crop)?;
+                let _ = send_request(&IpcRequest::Reload).await;
+                match crop {
+                    Some(c) => println!(
+                        "cropped {id} to {}x{} at {},{}",
+                        c.width, c.height, c.x, c.y
+                    ),
+                    None => println!("cleared crop for {id}"),
+                }
+            }
+            FavoritesAction::Tag {
+                id,
+                add,
+                remove,
+                set,
+            } => {
+                let paths = muralis_paths()?;
+                let db = Database::open(&paths.db_path())?;
+                let wp = db.get_wallpaper(&id)?;
+                let tags = TagEdit { set, add, remove }.apply(&wp.tags);
+                db.update_tags(&id, &tags)?;
+                let _ = send_request(&IpcRequest::Reload).await;
+                println!("{id}: {}", tags.join(", "));
             }
         },
         Commands::Sources { action } => match action {
             SourcesAction::List => {
-                let paths = MuralisPaths::new()?;
+                let paths = muralis_paths()?;
                 let config = Config::load(&paths)?;
-                let (registry, _) = build_registry(&config)?;
+                let registry = build_registry(&config).await?;
+                if registry.is_empty() {
+                    hint_no_sources();
+                }
 
                 let sources: Vec<SourceInfo> = registry
-                    .iter()
+                    .iter_all()
                     .map(|s| SourceInfo {
+                        id: s.id().to_string(),
                         name: s.name().to_string(),
                         source_type: s.source_type().to_string(),
+                        enabled: registry.is_enabled(s.id()),
+                        browsable: s.browsable(),
+                        colors: s.colors().iter().map(|c| c.to_string()).collect(),
                     })
                     .collect();
                 println!("{}", serde_json::to_string(&sources)?);
             }
+            SourcesAction::Enable {
+                name,
+                key,
+                secret,
+                paths: folders,
+                persist,
+            } => {
+                if !folders.is_empty() && name != "local" {
+                    anyhow::bail!("source {name} does not take folders; --path is for local");
+                }
+                let paths = muralis_paths()?;
+                if persist || key.is_some() || secret.is_some() || !folders.is_empty() {
+                    let mut config = load_for_edit(&paths)?;
+                    config.enable_source(&name, key.as_deref(), secret.as_deref())?;
+                    for folder in &folders {
+                        config.add_local_path(folder)?;
+                    }
+                    config.save(&paths)?;
+                    // also lift any session override; fine if no daemon is running
+                    let _ = send_request(&set_source_request(&name, true)).await;
+                    println!("enabled {name}");
+                } else {
+                    toggle_runtime(&paths, &name, true).await?;
+                }
+            }
+            SourcesAction::Disable { name, persist } => {
+                let paths = muralis_paths()?;
+                if persist {
+                    let mut config = load_for_edit(&paths)?;
+                    config.disable_source(&name)?;
+                    config.save(&paths)?;
+                    println!("disabled {name}");
+                } else {
+                    toggle_runtime(&paths, &name, false).await?;
+                }
+            }
+            SourcesAction::Check { timeout } => {
+                let paths = muralis_paths()?;
+                let config = Config::load(&paths)?;
+                let registry = build_registry(&config).await?;
+                if registry.is_empty() {
+                    hint_no_sources();
+                }
+                let report = check_sources(
+                    registry.iter(),
+                    std::time::Duration::from_secs(timeout),
+                    std::time::Instant::now,
+                )
+                .await;
+                println!("{}", serde_json::to_string(&report)?);
+            }
+        },
+        Commands::Collections { action } => {
+            let paths = muralis_paths()?;
+            let db = Database::open(&paths.db_path())?;
+            let changed = match action {
+                CollectionsAction::List => {
+                    println!("{}", serde_json::to_string(&db.list_collections()?)?);
+                    false
+                }
+                CollectionsAction::Show { name } => {
+                    let wallpapers = db.list_collection_wallpapers(&name)?;
+                    println!("{}", serde_json::to_string(&wallpapers)?);
+                    false
+                }
+                CollectionsAction::Create { name } => {
+                    if db.create_collection(&name)? {
+                        println!("created {name}");
+                    } else {
+                        println!("{name} already exists");
+                    }
+                    false
+                }
+                CollectionsAction::Delete { name } => {
+                    let deleted = db.delete_collection(&name)?;
+                    if deleted {
+                        println!("deleted {name}");
+                    } else {
+                        println!("no collection named {name}");
+                    }
+                    deleted
+                }
+                CollectionsAction::Add { name, ids } => {
+                    for id in &ids {
+                        db.add_to_collection(id, &name)?;
+                        println!("added {id} to {name}");
+                    }
+                    true
+                }
+                CollectionsAction::Remove { name, ids } => {
+                    for id in &ids {
+                        if db.remove_from_collection(id, &name)? {
+                            println!("removed {id} from {name}");
+                        } else {
+                            println!("{id} is not in {name}");
+                        }
+                    }
+                    true
+                }
+            };
+            if changed {
+                // refresh a collection-scoped rotation; fine if no daemon is running
+                let _ = send_request(&IpcRequest::Reload).await;
+            }
+        }
+        Commands::Blacklist { action } => {
+            let paths = muralis_paths()?;
+            let db = Database::open(&paths.db_path())?;
+            match action {
+                BlacklistAction::Tags => {
+                    println!("{}", serde_json::to_string(&db.list_tag_blacklist()?)?);
+                }
+                BlacklistAction::AddTag { tag } => {
+                    db.add_tag_blacklist(&tag)?;
+                    println!("hiding results tagged {tag}");
+                }
+                BlacklistAction::RemoveTag { tag } => {
+                    if db.remove_tag_blacklist(&tag)? {
+                        println!("no longer hiding {tag}");
+                    } else {
+                        println!("{tag} was not blacklisted");
+                    }
+                }
+            }
+        }
+        Commands::Filter { action } => match action {
+            FilterAction::Show => {
+                let paths = muralis_paths()?;
+                let config = Config::load_or_default(&paths);
+                println!("{}", serde_json::to_string(&config.filter)?);
+            }
+            FilterAction::Brightness { min, max } => {
+                let paths = muralis_paths()?;
+                let mut config = load_for_edit(&paths)?;
+                let filter = &mut config.filter;
+                if let Some(min) = min {
+                    filter.min_brightness = min.clamp(0.0, 1.0);
+                }
+                if let Some(max) = max {
+                    filter.max_brightness = max.clamp(0.0, 1.0);
+                }
+                if filter.min_brightness > filter.max_brightness {
+                    anyhow::bail!("min brightness must not exceed max brightness");
+                }
+                config.save(&paths)?;
+
+                // analyze favorites added before brightness was tracked
+                let db = Database::open(&paths.db_path())?;
+                let analyzed = WallpaperManager::new(paths).backfill_analysis(&db)?;
+                if analyzed > 0 {
+                    println!("analyzed {analyzed} wallpapers");
+                }
+                // pick up the new range; fine if no daemon is running
+                let _ = send_request(&IpcRequest::Reload).await;
+                println!(
+                    "brightness range {:.2}-{:.2}",
+                    config.filter.min_brightness, config.filter.max_brightness
+                );
+            }
         },
         Commands::Cache { action } => {
-            let paths = MuralisPaths::new()?;
+            let paths = muralis_paths()?;
             match action {
                 CacheAction::Stats => {
                     let stats = muralis_core::cache::cache_stats(&paths);
@@ -351,6 +1085,11 @@ async fn main() -> Result<()> {
                         format_bytes(stats.previews_size),
                         stats.preview_count
                     );
+                    println!(
+                        "cropped:    {} ({} files)",
+                        format_bytes(stats.cropped_size),
+                        stats.cropped_count
+                    );
                     println!("total:      {}", format_bytes(stats.total_size));
                 }
                 CacheAction::Prune => {
@@ -365,6 +1104,149 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Commands::Config { action } => {
+            let paths = muralis_paths()?;
+            match action {
+                ConfigAction::Export { with_secrets } => {
+                    print!("{}", Config::export(&paths, with_secrets)?);
+                }
+                ConfigAction::Import { file } => {
+                    let content = std::fs::read_to_string(&file)?;
+                    for w in Config::import(&paths, &content)? {
+                        eprintln!("warning: {w}");
+                    }
+                    let _ = send_request(&IpcRequest::Reload).await;
+                    println!("imported {}", file.display());
+                }
+            }
+        }
+        Commands::Db { action } => match action {
+            DbAction::PruneMissing { dry_run } => {
+                let paths = muralis_paths()?;
+                let db = Database::open(&paths.db_path())?;
+                let pruned = WallpaperManager::new(paths).prune_missing(&db, dry_run)?;
+                for id in &pruned {
+                    println!("{id}");
+                }
+                if dry_run {
+                    println!("{} missing (dry run, nothing removed)", pruned.len());
+                } else {
+                    if !pruned.is_empty() {
+                        let _ = send_request(&IpcRequest::Reload).await;
+                    }
+                    println!("pruned {} missing", pruned.len());
+                }
+            }
+            DbAction::Evict { dry_run, yes } => {
+                let paths = muralis_paths()?;
+                let config = Config::load(&paths)?;
+                let Some(max_mb) = config.general.library_max_mb else {
+                    println!("no [general] library_max_mb set");
+                    return Ok(());
+                };
+                let db = Database::open(&paths.db_path())?;
+                // don't pull the wallpaper being shown out from under the daemon
+                let current = match send_request(&IpcRequest::Status).await {
+                    Ok(IpcResponse::Ok { data: Some(data) }) => {
+                        serde_json::from_value::<DaemonStatus>(data)
+                            .ok()
+                            .and_then(|s| s.current_wallpaper)
+                    }
+                    _ => None,
+                };
+                let manager = WallpaperManager::new(paths);
+                let max_bytes = max_mb.saturating_mul(1024 * 1024);
+                let planned = manager.evict_library(&db, max_bytes, current.as_deref(), true)?;
+                for id in &planned {
+                    println!("{id}");
+                }
+                if dry_run {
+                    println!("{} to evict (dry run, nothing removed)", planned.len());
+                    return Ok(());
+                }
+                if planned.is_empty() {
+                    println!("library fits in {max_mb} MB; nothing to evict");
+                    return Ok(());
+                }
+                if !yes && !confirm(&format!("permanently delete {} favorites?", planned.len()))? {
+                    println!("nothing removed");
+                    return Ok(());
+                }
+                let evicted = manager.evict_library(&db, max_bytes, current.as_deref(), false)?;
+                let _ = send_request(&IpcRequest::Reload).await;
+                println!("evicted {}", evicted.len());
+            }
+        },
+        Commands::Open { target } => {
+            match resolve_open_target(&target).await? {
+                OpenTarget::Path(path) => muralis_core::viewer::open_external(&path)?,
+                OpenTarget::Download(file) => {
+                    muralis_core::viewer::open_and_wait(file.path())?;
+                    // dropping the temp file removes it
+                }
+            }
+        }
+        Commands::Monitors => {
+            let monitors = monitor::detect_monitors().await?;
+            println!("{}", serde_json::to_string(&monitors)?);
+        }
+        Commands::Doctor => {
+            let paths = muralis_paths()?;
+            let config = Config::load_or_default(&paths);
+            let configured = config.general.backend;
+            let running = backend::running_processes();
+
+            println!("backend: {configured}");
+            if let Some(daemon) = backend::backend_daemon(configured) {
+                if running.iter().any(|p| p == daemon) {
+                    println!("ok: {daemon} is running");
+                } else {
+                    println!("warning: {daemon} is not running");
+                }
+            }
+            for other in backend::detect_conflicting_daemons(configured, &running) {
+                println!("warning: {other} is also running and may override muralis wallpapers");
+            }
+        }
+        Commands::Settings => {
+            let paths = muralis_paths()?;
+            let config = Config::load(&paths)?;
+            let state = GuiState::load(&paths);
+            let out = serde_json::json!({
+                "square_thumbnails": config.general.square_thumbnails,
+                "overlay_darken": config.display.overlay_darken,
+                "overlay_style": config.display.overlay_style,
+                "window_width": state.window_width,
+                "window_height": state.window_height,
+                "last_tab": state.last_tab,
+                "prefetch_on_open": config.gui.prefetch_on_open,
+                "preview_fit": state.preview_fit,
+            });
+            println!("{}", serde_json::to_string(&out)?);
+        }
+        Commands::GuiState {
+            width,
+            height,
+            tab,
+            preview_fit,
+        } => {
+            let paths = muralis_paths()?;
+            let mut state = GuiState::load(&paths);
+            if let Some(width) = width {
+                state.window_width = width;
+            }
+            if let Some(height) = height {
+                state.window_height = height;
+            }
+            if let Some(tab) = tab {
+                state.last_tab = tab;
+            }
+            if let Some(fit) = preview_fit {
+                state.preview_fit = fit;
+            }
+            state.save(&paths)?;
+        }
+        Commands::Gui => launch_gui()?,
         Commands::Quit => {
             let resp = send(IpcRequest::Quit).await?;
             print_response(resp);
@@ -374,10 +1256,183 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Render a solid color or gradient at the largest monitor's resolution
+/// and return its path. The daemon applies it like any file outside the
+/// library, so `muralis keep` can still favorite it.
+async fn render_color_wallpaper(color: Option<&str>, gradient: &[String]) -> Result<String> {
+    let paths = muralis_paths()?;
+    paths.ensure_dirs()?;
+    let monitors = monitor::detect_monitors().await.unwrap_or_default();
+    let (w, h) = monitor::effective_min_resolution(&monitors, MinResolutionStrategy::Largest)
+        .unwrap_or((1920, 1080));
+
+    let (img, key) = match color {
+        Some(c) => {
+            let c = solid::parse_color(c)?;
+            (solid::render_solid(w, h, c), solid::color_hex(c))
+        }
+        None => {
+            let stops = gradient
+                .iter()
+                .map(|c| solid::parse_color(c))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let key = stops
+                .iter()
+                .map(|&c| solid::color_hex(c))
+                .collect::<Vec<_>>()
+                .join("-");
+            (solid::render_gradient(w, h, &stops), key)
+        }
+    };
+
+    let path = paths.generated_dir().join(format!("{key}_{w}x{h}.png"));
+    if !path.exists() {
+        img.save(&path)?;
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// What `muralis open` shows.
+enum OpenTarget {
+    Path(PathBuf),
+    /// An http(s) image in a private temp file, removed once the viewer exits.
+    Download(tempfile::NamedTempFile),
+}
+
+/// Local file to show for `muralis open`: a library ID, an existing path,
+/// or an http(s) URL downloaded to a temp file.
+async fn resolve_open_target(target: &str) -> Result<OpenTarget> {
+    if target.starts_with("http://") || target.starts_with("https://") {
+        // keep the extension, which viewers may go by
+        let ext = target
+            .rsplit('/')
+            .next()
+            .and_then(|s| s.split(['?', '#']).next())
+            .and_then(|name| Path::new(name).extension())
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let bytes = reqwest::get(target)
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        // random name, created exclusively and readable only by the user
+        let mut file = tempfile::Builder::new()
+            .prefix("muralis-view-")
+            .suffix(&ext)
+            .tempfile()?;
+        std::io::Write::write_all(&mut file, &bytes)?;
+        return Ok(OpenTarget::Download(file));
+    }
+
+    let paths = muralis_paths()?;
+    if let Ok(db) = Database::open(&paths.db_path()) {
+        if let Ok(wp) = db.get_wallpaper(target) {
+            return Ok(OpenTarget::Path(wp.file_path.into()));
+        }
+    }
+    let path = PathBuf::from(target);
+    if path.is_file() {
+        return Ok(OpenTarget::Path(path));
+    }
+    anyhow::bail!("no wallpaper, file, or URL matches: {target}")
+}
+
+/// Spawn `muralis-gui` detached, unless another one holds the GUI lock.
+/// The GUI inherits the lock and holds it until it exits.
+fn launch_gui() -> Result<()> {
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    let lock_path = MuralisPaths::gui_lock_path(profile().as_deref())?;
+    let mut lock = match instance::acquire(&lock_path)? {
+        LockState::Acquired(lock) => lock,
+        LockState::Running(Some(pid)) => {
+            // best effort: bring the existing window forward
+            let _ = Command::new("hyprctl")
+                .args(["dispatch", "focuswindow", &format!("pid:{pid}")])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+            println!("muralis-gui already running (pid {pid})");
+            return Ok(());
+        }
+        LockState::Running(None) => {
+            println!("muralis-gui already starting");
+            return Ok(());
+        }
+    };
+
+    let mut command = Command::new("muralis-gui");
+    // the GUI runs this CLI, which should see the same profile
+    if let Some(profile) = profile() {
+        command.env(PROFILE_ENV, profile);
+    }
+    if let Some(overrides) = PATH_OVERRIDES.get() {
+        if let Some(config) = &overrides.config {
+            command.env(CONFIG_ENV, config);
+        }
+        if let Some(dir) = &overrides.data_dir {
+            command.env(DATA_DIR_ENV, dir);
+        }
+    }
+    lock.share_with(&mut command);
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("failed to launch muralis-gui: {e}"))?;
+    lock.write_pid(child.id())?;
+    println!("launched muralis-gui (pid {})", child.id());
+    Ok(())
+}
+
+/// Load the config for editing. A missing file starts from defaults, but a
+/// broken one is an error so it never gets overwritten.
+fn load_for_edit(paths: &MuralisPaths) -> Result<Config> {
+    if paths.config_file().exists() {
+        Ok(Config::load(paths)?)
+    } else {
+        paths.ensure_dirs()?;
+        Ok(Config::default())
+    }
+}
+
+fn set_source_request(name: &str, enabled: bool) -> IpcRequest {
+    IpcRequest::SetSourceEnabled {
+        name: name.to_lowercase(),
+        enabled,
+    }
+}
+
+/// Toggle a configured source for the running daemon session only.
+async fn toggle_runtime(paths: &MuralisPaths, name: &str, enabled: bool) -> Result<()> {
+    let config = Config::load(paths)?;
+    let mut registry = build_registry(&config).await?;
+    if !registry.set_enabled(name, enabled) {
+        anyhow::bail!("unknown source: {name} (use --persist to change config)");
+    }
+    let resp = send(set_source_request(name, enabled)).await?;
+    print_response(resp);
+    Ok(())
+}
+
 async fn send(request: IpcRequest) -> Result<IpcResponse> {
-    ipc::send_request(&request)
-        .await
-        .map_err(|e| anyhow::anyhow!("daemon not running. start with: muralis-daemon\n  ({e})"))
+    send_request(&request).await.map_err(|e| {
+        let start = match profile() {
+            Some(name) => format!("{PROFILE_ENV}={name} muralis-daemon"),
+            None => "muralis-daemon".into(),
+        };
+        anyhow::anyhow!("daemon not running. start with: {start}\n  ({e})")
+    })
+}
+
+/// Send a request to the daemon running the selected profile.
+async fn send_request(request: &IpcRequest) -> muralis_core::error::Result<IpcResponse> {
+    let socket = MuralisPaths::profile_socket_path(profile().as_deref())?;
+    ipc::send_request_to(&socket, request).await
 }
 
 fn print_response(resp: IpcResponse) {
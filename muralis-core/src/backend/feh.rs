@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use tokio::process::Command;
+
+use crate::error::{MuralisError, Result};
+
+use super::WallpaperBackend;
+
+/// X11 backend driving `feh --bg-fill`. feh sets every screen at once, so
+/// per-monitor changes resend the images of all outputs in xrandr order.
+#[derive(Default)]
+pub struct FehBackend {
+    /// Last image applied to each output.
+    per_output: Mutex<BTreeMap<String, PathBuf>>,
+}
+
+impl FehBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `feh --bg-fill` with one image per screen, in screen order. A single
+    /// image covers every screen.
+    fn build_command(paths: &[&Path]) -> Command {
+        let mut cmd = Command::new("feh");
+        cmd.arg("--bg-fill");
+        cmd.args(paths);
+        cmd
+    }
+
+    async fn run(paths: &[&Path]) -> Result<()> {
+        let output = Self::build_command(paths)
+            .output()
+            .await
+            .map_err(|e| MuralisError::Backend(format!("failed to run feh: {e}")))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(MuralisError::Backend(format!("feh failed: {stderr}")));
+        }
+        Ok(())
+    }
+
+    /// Output names in screen order, from `xrandr --listmonitors`.
+    async fn outputs() -> Result<Vec<String>> {
+        let output = Command::new("xrandr")
+            .arg("--listmonitors")
+            .output()
+            .await
+            .map_err(|e| MuralisError::Backend(format!("failed to run xrandr: {e}")))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(MuralisError::Backend(format!("xrandr failed: {stderr}")));
+        }
+        Ok(parse_listmonitors(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Output names from `xrandr --listmonitors`, e.g.
+/// ` 0: +*DP-1 2560/597x1440/336+0+0  DP-1` yields `DP-1`.
+fn parse_listmonitors(stdout: &str) -> Vec<String> {
+    stdout
+        .lines()
+        .filter(|l| {
+            l.trim_start()
+                .split(':')
+                .next()
+                .is_some_and(|n| n.parse::<u32>().is_ok())
+        })
+        .filter_map(|l| l.split_whitespace().last())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Image for each output in order: its own if set, else `fallback`.
+fn ordered_images<'a>(
+    outputs: &[String],
+    per_output: &'a BTreeMap<String, PathBuf>,
+    fallback: &'a Path,
+) -> Vec<&'a Path> {
+    outputs
+        .iter()
+        .map(|o| per_output.get(o).map_or(fallback, PathBuf::as_path))
+        .collect()
+}
+
+#[async_trait]
+impl WallpaperBackend for FehBackend {
+    async fn set_wallpaper(&self, path: &Path, monitor: &str) -> Result<()> {
+        let outputs = Self::outputs().await?;
+        if !outputs.iter().any(|o| o == monitor) {
+            return Err(MuralisError::Backend(format!(
+                "unknown output {monitor} (have: {})",
+                outputs.join(", ")
+            )));
+        }
+        let images: Vec<PathBuf> = {
+            let mut per_output = self.per_output.lock().unwrap_or_else(|e| e.into_inner());
+            per_output.insert(monitor.to_string(), path.to_path_buf());
+            ordered_images(&outputs, &per_output, path)
+                .into_iter()
+                .map(Path::to_path_buf)
+                .collect()
+        };
+        let images: Vec<&Path> = images.iter().map(PathBuf::as_path).collect();
+        Self::run(&images).await
+    }
+
+    async fn set_wallpaper_all(&self, path: &Path) -> Result<()> {
+        Self::run(&[path]).await?;
+        self.per_output
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "feh"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_feh_fill_args() {
+        let cmd = FehBackend::build_command(&[Path::new("/data/wallpapers/abc123.jpg")]);
+        assert_eq!(cmd.as_std().get_program(), "feh");
+        assert_eq!(args(&cmd), vec!["--bg-fill", "/data/wallpapers/abc123.jpg"]);
+
+        let cmd = FehBackend::build_command(&[Path::new("/w/left.png"), Path::new("/w/right.png")]);
+        assert_eq!(args(&cmd), vec!["--bg-fill", "/w/left.png", "/w/right.png"]);
+    }
+
+    #[test]
+    fn test_per_output_images_follow_xrandr_order() {
+        let outputs = parse_listmonitors(
+            "Monitors: 3\n \
+             0: +*DP-1 2560/597x1440/336+0+0  DP-1\n \
+             1: +HDMI-1 1920/527x1080/296+2560+0  HDMI-1\n \
+             2: +eDP-1 1920/344x1080/193+4480+0  eDP-1\n",
+        );
+        assert_eq!(outputs, vec!["DP-1", "HDMI-1", "eDP-1"]);
+
+        let mut per_output = BTreeMap::new();
+        per_output.insert("eDP-1".to_string(), PathBuf::from("/w/laptop.png"));
+        per_output.insert("DP-1".to_string(), PathBuf::from("/w/main.png"));
+        let images = ordered_images(&outputs, &per_output, Path::new("/w/new.png"));
+        assert_eq!(
+            images,
+            vec![
+                Path::new("/w/main.png"),
+                Path::new("/w/new.png"),
+                Path::new("/w/laptop.png")
+            ]
+        );
+    }
+}
@@ -1,12 +1,19 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use async_trait::async_trait;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
 use tokio::process::Command;
 
 use crate::error::{MuralisError, Result};
 
 use super::WallpaperBackend;
 
+/// Longest a socket request may take; a wedged hyprpaper would otherwise
+/// stall the daemon's wallpaper change forever.
+const SOCKET_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub struct HyprpaperBackend;
 
 impl Default for HyprpaperBackend {
@@ -15,11 +22,73 @@ impl Default for HyprpaperBackend {
     }
 }
 
+/// Requests for one wallpaper change, as `(keyword, argument)` pairs.
+/// An empty monitor applies to every monitor.
+fn change_commands(path: &str, monitor: &str) -> [(&'static str, String); 3] {
+    [
+        ("preload", path.to_string()),
+        ("wallpaper", format!("{monitor},{path}")),
+        // unload all unused wallpapers to free memory
+        ("unload", "all".to_string()),
+    ]
+}
+
+/// Wire form of a request on hyprpaper's socket, e.g. `preload /a.jpg`.
+fn encode_command(keyword: &str, arg: &str) -> String {
+    format!("{keyword} {arg}")
+}
+
+/// `$XDG_RUNTIME_DIR/hypr/<instance>/.hyprpaper.sock`, given both variables.
+fn socket_path_from(runtime_dir: Option<&str>, instance: Option<&str>) -> Option<PathBuf> {
+    let runtime_dir = runtime_dir.filter(|s| !s.is_empty())?;
+    let instance = instance.filter(|s| !s.is_empty())?;
+    Some(
+        Path::new(runtime_dir)
+            .join("hypr")
+            .join(instance)
+            .join(".hyprpaper.sock"),
+    )
+}
+
 impl HyprpaperBackend {
     pub fn new() -> Self {
         Self
     }
 
+    /// hyprpaper's IPC socket, if it exists.
+    fn socket_path() -> Option<PathBuf> {
+        let runtime_dir = std::env::var("XDG_RUNTIME_DIR").ok();
+        let instance = std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok();
+        socket_path_from(runtime_dir.as_deref(), instance.as_deref()).filter(|p| p.exists())
+    }
+
+    async fn socket_request(socket: &Path, request: &str) -> Result<()> {
+        let exchange = async {
+            let mut stream = UnixStream::connect(socket).await?;
+            stream.write_all(request.as_bytes()).await?;
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).await?;
+            Ok::<_, std::io::Error>(reply)
+        };
+        let reply = tokio::time::timeout(SOCKET_TIMEOUT, exchange)
+            .await
+            .map_err(|_| {
+                MuralisError::Backend(format!(
+                    "hyprpaper {request:?} timed out after {}s",
+                    SOCKET_TIMEOUT.as_secs()
+                ))
+            })?
+            .map_err(|e| MuralisError::Backend(format!("hyprpaper socket: {e}")))?;
+
+        if reply.trim() != "ok" {
+            return Err(MuralisError::Backend(format!(
+                "hyprpaper {request:?} failed: {}",
+                reply.trim()
+            )));
+        }
+        Ok(())
+    }
+
     async fn hyprctl(args: &[&str]) -> Result<String> {
         let output = Command::new("hyprctl")
             .args(args)
@@ -34,37 +103,37 @@ impl HyprpaperBackend {
 
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
+
+    /// Send the requests over hyprpaper's socket, falling back to
+    /// `hyprctl hyprpaper` when the socket isn't there.
+    async fn change(path: &Path, monitor: &str) -> Result<()> {
+        let path_str = path.to_string_lossy();
+        let commands = change_commands(&path_str, monitor);
+
+        match Self::socket_path() {
+            Some(socket) => {
+                for (keyword, arg) in &commands {
+                    Self::socket_request(&socket, &encode_command(keyword, arg)).await?;
+                }
+            }
+            None => {
+                for (keyword, arg) in &commands {
+                    Self::hyprctl(&["hyprpaper", keyword, arg]).await?;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl WallpaperBackend for HyprpaperBackend {
     async fn set_wallpaper(&self, path: &Path, monitor: &str) -> Result<()> {
-        let path_str = path.to_string_lossy();
-
-        // preload the wallpaper
-        Self::hyprctl(&["hyprpaper", "preload", &path_str]).await?;
-
-        // set wallpaper on specific monitor
-        let wallpaper_arg = format!("{monitor},{path_str}");
-        Self::hyprctl(&["hyprpaper", "wallpaper", &wallpaper_arg]).await?;
-
-        // unload all unused wallpapers to free memory
-        Self::hyprctl(&["hyprpaper", "unload", "all"]).await?;
-
-        Ok(())
+        Self::change(path, monitor).await
     }
 
     async fn set_wallpaper_all(&self, path: &Path) -> Result<()> {
-        let path_str = path.to_string_lossy();
-
-        Self::hyprctl(&["hyprpaper", "preload", &path_str]).await?;
-
-        let wallpaper_arg = format!(",{path_str}");
-        Self::hyprctl(&["hyprpaper", "wallpaper", &wallpaper_arg]).await?;
-
-        Self::hyprctl(&["hyprpaper", "unload", "all"]).await?;
-
-        Ok(())
+        Self::change(path, "").await
     }
 
     fn name(&self) -> &str {
@@ -74,19 +143,65 @@ impl WallpaperBackend for HyprpaperBackend {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use super::*;
 
     #[test]
     fn test_hyprpaper_command_args() {
-        // Verify the argument format for hyprpaper commands
-        let path = PathBuf::from("/data/wallpapers/abc123.jpg");
-        let monitor = "DP-1";
-        let path_str = path.to_string_lossy();
+        let commands = change_commands("/data/wallpapers/abc123.jpg", "DP-1");
+        assert_eq!(
+            commands[0],
+            ("preload", "/data/wallpapers/abc123.jpg".into())
+        );
+        assert_eq!(
+            commands[1],
+            ("wallpaper", "DP-1,/data/wallpapers/abc123.jpg".into())
+        );
+        assert_eq!(commands[2], ("unload", "all".into()));
+
+        let all = change_commands("/data/wallpapers/abc123.jpg", "");
+        assert_eq!(all[1].1, ",/data/wallpapers/abc123.jpg");
+    }
+
+    #[test]
+    fn test_encode_socket_commands() {
+        let encoded: Vec<String> = change_commands("/w/a b.png", "HDMI-A-1")
+            .iter()
+            .map(|(k, a)| encode_command(k, a))
+            .collect();
+        assert_eq!(
+            encoded,
+            vec![
+                "preload /w/a b.png",
+                "wallpaper HDMI-A-1,/w/a b.png",
+                "unload all"
+            ]
+        );
+    }
 
-        let wallpaper_arg = format!("{monitor},{path_str}");
-        assert_eq!(wallpaper_arg, "DP-1,/data/wallpapers/abc123.jpg");
+    #[tokio::test(start_paused = true)]
+    async fn test_socket_request_times_out() {
+        let tmp = tempfile::tempdir().unwrap();
+        let socket = tmp.path().join(".hyprpaper.sock");
+        let listener = tokio::net::UnixListener::bind(&socket).unwrap();
+        // accept, then never answer
+        tokio::spawn(async move {
+            let (_stream, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let err = HyprpaperBackend::socket_request(&socket, "preload /a.jpg")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"), "{err}");
+    }
 
-        let all_arg = format!(",{path_str}");
-        assert_eq!(all_arg, ",/data/wallpapers/abc123.jpg");
+    #[test]
+    fn test_socket_path_from_env() {
+        assert_eq!(
+            socket_path_from(Some("/run/user/1000"), Some("abc_123")),
+            Some(PathBuf::from("/run/user/1000/hypr/abc_123/.hyprpaper.sock"))
+        );
+        assert_eq!(socket_path_from(None, Some("abc_123")), None);
+        assert_eq!(socket_path_from(Some("/run/user/1000"), Some("")), None);
     }
 }
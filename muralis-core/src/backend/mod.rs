@@ -1,5 +1,7 @@
+pub mod feh;
 pub mod hyprpaper;
 pub mod monitor;
+pub mod mpvpaper;
 pub mod swww;
 
 use async_trait::async_trait;
@@ -16,9 +18,101 @@ pub trait WallpaperBackend: Send + Sync {
     fn name(&self) -> &str;
 }
 
+/// Process names of wallpaper daemons that draw on the background layer.
+const KNOWN_DAEMONS: &[&str] = &[
+    "hyprpaper",
+    "swww-daemon",
+    "swaybg",
+    "wpaperd",
+    "mpvpaper",
+    "wbg",
+];
+
+/// Process name of the daemon `backend` drives; None when it sets the
+/// wallpaper without one (feh).
+pub fn backend_daemon(backend: BackendType) -> Option<&'static str> {
+    match backend {
+        BackendType::Hyprpaper => Some("hyprpaper"),
+        BackendType::Swww => Some("swww-daemon"),
+        BackendType::Mpvpaper => Some("mpvpaper"),
+        BackendType::Feh => None,
+    }
+}
+
+/// Known wallpaper daemons in `processes` other than the one `backend`
+/// drives. Each is listed once.
+pub fn detect_conflicting_daemons(backend: BackendType, processes: &[String]) -> Vec<String> {
+    let own = backend_daemon(backend);
+    KNOWN_DAEMONS
+        .iter()
+        .filter(|d| Some(**d) != own && processes.iter().any(|p| p == *d))
+        .map(|d| d.to_string())
+        .collect()
+}
+
+/// Names of running processes, from `/proc/<pid>/comm`.
+pub fn running_processes() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            e.file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_string())
+        .collect()
+}
+
 pub fn create_backend(config: &Config) -> Box<dyn WallpaperBackend> {
     match config.general.backend {
         BackendType::Hyprpaper => Box::new(hyprpaper::HyprpaperBackend::new()),
         BackendType::Swww => Box::new(swww::SwwwBackend::new(config.display.transition.clone())),
+        BackendType::Mpvpaper => Box::new(mpvpaper::MpvpaperBackend::new(
+            config.display.mpvpaper.clone(),
+        )),
+        BackendType::Feh => Box::new(feh::FehBackend::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn procs(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_detect_conflicting_daemons() {
+        let running = procs(&[
+            "systemd",
+            "Hyprland",
+            "hyprpaper",
+            "swww-daemon",
+            "swaybg",
+            "swaybg",
+        ]);
+        assert_eq!(
+            detect_conflicting_daemons(BackendType::Hyprpaper, &running),
+            vec!["swww-daemon", "swaybg"]
+        );
+        assert_eq!(
+            detect_conflicting_daemons(BackendType::Swww, &running),
+            vec!["hyprpaper", "swaybg"]
+        );
+        assert!(detect_conflicting_daemons(
+            BackendType::Swww,
+            &procs(&["Hyprland", "swww-daemon"])
+        )
+        .is_empty());
+        assert_eq!(
+            detect_conflicting_daemons(BackendType::Feh, &procs(&["i3", "swaybg"])),
+            vec!["swaybg"]
+        );
     }
 }
@@ -1,8 +1,9 @@
 use serde::Deserialize;
 use tokio::process::Command;
 
+use crate::config::DisplayConfig;
 use crate::error::{MuralisError, Result};
-use crate::models::MonitorInfo;
+use crate::models::{MinResolutionStrategy, MonitorInfo};
 
 /// Detect connected monitors via `hyprctl monitors -j`.
 pub async fn detect_monitors() -> Result<Vec<MonitorInfo>> {
@@ -32,6 +33,7 @@ fn parse_monitors(json: &str) -> Result<Vec<MonitorInfo>> {
             width: m.width,
             height: m.height,
             scale: m.scale,
+            dpms: m.dpms_status,
         })
         .collect())
 }
@@ -46,6 +48,48 @@ pub fn min_resolution(monitors: &[MonitorInfo]) -> Option<(u32, u32)> {
     Some((min_w, min_h))
 }
 
+/// Get the minimum resolution a wallpaper needs, based on the monitor picked by `strategy`.
+pub fn effective_min_resolution(
+    monitors: &[MonitorInfo],
+    strategy: MinResolutionStrategy,
+) -> Option<(u32, u32)> {
+    match strategy {
+        MinResolutionStrategy::Smallest => min_resolution(monitors),
+        MinResolutionStrategy::Largest => monitors
+            .iter()
+            .max_by_key(|m| m.width as u64 * m.height as u64)
+            .map(|m| (m.width, m.height)),
+        MinResolutionStrategy::Primary => monitors.first().map(|m| (m.width, m.height)),
+    }
+}
+
+/// Parse a resolution string like "2560x1440".
+pub fn parse_resolution(s: &str) -> Option<(u32, u32)> {
+    let (w, h) = s.trim().split_once(['x', 'X'])?;
+    Some((w.trim().parse().ok()?, h.trim().parse().ok()?))
+}
+
+/// Whether `width`x`height` meets `min`. Unknown dimensions (0) always pass.
+pub fn meets_min_resolution(width: u32, height: u32, min: Option<(u32, u32)>) -> bool {
+    min.is_none_or(|(min_w, min_h)| width == 0 || (width >= min_w && height >= min_h))
+}
+
+/// Resolve `[display] min_resolution`: an explicit "WxH", or "auto" to derive it
+/// from the connected monitors using `min_resolution_strategy`.
+pub async fn resolve_min_resolution(display: &DisplayConfig) -> Option<(u32, u32)> {
+    if display.min_resolution.trim() != "auto" {
+        return parse_resolution(&display.min_resolution);
+    }
+    let monitors = detect_monitors().await.ok()?;
+    effective_min_resolution(&monitors, display.min_resolution_strategy)
+}
+
+/// True when monitors were detected and all of them are powered off, so
+/// rotating would only burn CPU (and flash on wake with transitions).
+pub fn should_pause_for_dpms(monitors: &[MonitorInfo]) -> bool {
+    !monitors.is_empty() && monitors.iter().all(|m| !m.dpms)
+}
+
 /// Get the primary (first) monitor's aspect ratio as a string like "16:9".
 pub fn primary_aspect_ratio(monitors: &[MonitorInfo]) -> Option<String> {
     monitors.first().map(|m| {
@@ -63,11 +107,14 @@ fn gcd(a: u32, b: u32) -> u32 {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct HyprMonitor {
     name: String,
     width: u32,
     height: u32,
     scale: f64,
+    #[serde(default = "crate::models::dpms_on")]
+    dpms_status: bool,
 }
 
 #[cfg(test)]
@@ -144,11 +191,72 @@ mod tests {
         assert_eq!(h, 1440);
     }
 
+    #[test]
+    fn test_should_pause_for_dpms() {
+        let mut monitors = parse_monitors(MOCK_MONITORS).unwrap();
+        assert!(monitors.iter().all(|m| m.dpms));
+        assert!(!should_pause_for_dpms(&monitors));
+
+        // one screen still on keeps rotating
+        monitors[0].dpms = false;
+        assert!(!should_pause_for_dpms(&monitors));
+
+        monitors[1].dpms = false;
+        assert!(should_pause_for_dpms(&monitors));
+
+        // nothing detected: don't guess
+        assert!(!should_pause_for_dpms(&[]));
+    }
+
+    #[test]
+    fn test_parse_dpms_off() {
+        let json = r#"[{"name": "DP-1", "width": 1920, "height": 1080, "scale": 1.0, "dpmsStatus": false}]"#;
+        assert!(!parse_monitors(json).unwrap()[0].dpms);
+        let json = r#"[{"name": "DP-1", "width": 1920, "height": 1080, "scale": 1.0}]"#;
+        assert!(parse_monitors(json).unwrap()[0].dpms);
+    }
+
     #[test]
     fn test_min_resolution_empty() {
         assert!(min_resolution(&[]).is_none());
     }
 
+    #[test]
+    fn test_effective_min_resolution_strategies() {
+        let monitors = parse_monitors(MOCK_MONITORS).unwrap();
+        assert_eq!(
+            effective_min_resolution(&monitors, MinResolutionStrategy::Smallest),
+            Some((2560, 1440))
+        );
+        assert_eq!(
+            effective_min_resolution(&monitors, MinResolutionStrategy::Largest),
+            Some((3840, 2160))
+        );
+        assert_eq!(
+            effective_min_resolution(&monitors, MinResolutionStrategy::Primary),
+            Some((3840, 2160))
+        );
+
+        // primary follows monitor order, not size
+        let reversed: Vec<MonitorInfo> = monitors.into_iter().rev().collect();
+        assert_eq!(
+            effective_min_resolution(&reversed, MinResolutionStrategy::Primary),
+            Some((2560, 1440))
+        );
+        assert_eq!(
+            effective_min_resolution(&[], MinResolutionStrategy::Largest),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_resolution() {
+        assert_eq!(parse_resolution("2560x1440"), Some((2560, 1440)));
+        assert_eq!(parse_resolution(" 1920X1080 "), Some((1920, 1080)));
+        assert_eq!(parse_resolution("auto"), None);
+        assert_eq!(parse_resolution("1920x"), None);
+    }
+
     #[test]
     fn test_primary_aspect_ratio() {
         let monitors = parse_monitors(MOCK_MONITORS).unwrap();
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use async_trait::async_trait;
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
+
+use crate::config::MpvpaperConfig;
+use crate::error::{MuralisError, Result};
+
+use super::WallpaperBackend;
+
+/// Output name mpvpaper takes for every output.
+const ALL_OUTPUTS: &str = "*";
+
+/// Plays images and videos through mpvpaper. Each wallpaper is a running
+/// mpvpaper process, replaced when its output gets a new one.
+pub struct MpvpaperBackend {
+    options: MpvpaperConfig,
+    /// Running mpvpaper per output name (`*` for all).
+    children: Mutex<HashMap<String, Child>>,
+}
+
+impl MpvpaperBackend {
+    pub fn new(options: MpvpaperConfig) -> Self {
+        Self {
+            options,
+            children: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn build_command(&self, path: &Path, output: &str) -> Command {
+        let mut cmd = Command::new("mpvpaper");
+        let mut mpv_options = Vec::new();
+        if self.options.r#loop {
+            mpv_options.push("--loop");
+        }
+        if self.options.mute {
+            mpv_options.push("--no-audio");
+        }
+        if !mpv_options.is_empty() {
+            cmd.arg("-o").arg(mpv_options.join(" "));
+        }
+        cmd.arg(output).arg(path);
+        // a daemon that exits or restarts doesn't leave players behind
+        cmd.kill_on_drop(true);
+        cmd
+    }
+
+    async fn stop(child: &mut Child) {
+        if let Err(e) = child.kill().await {
+            tracing::debug!("failed to stop mpvpaper: {e}");
+        }
+    }
+
+    async fn spawn(&self, path: &Path, output: &str) -> Result<()> {
+        let mut children = self.children.lock().await;
+        for name in conflicting(children.keys(), output) {
+            if let Some(mut child) = children.remove(&name) {
+                Self::stop(&mut child).await;
+            }
+        }
+
+        let child = self
+            .build_command(path, output)
+            .spawn()
+            .map_err(|e| MuralisError::Backend(format!("failed to run mpvpaper: {e}")))?;
+        children.insert(output.to_string(), child);
+        Ok(())
+    }
+}
+
+/// Running outputs a new player on `output` would draw over: all of them
+/// for `*`, else the output itself and a `*` player covering it.
+fn conflicting<'a>(running: impl Iterator<Item = &'a String>, output: &str) -> Vec<String> {
+    running
+        .filter(|name| output == ALL_OUTPUTS || *name == output || *name == ALL_OUTPUTS)
+        .cloned()
+        .collect()
+}
+
+#[async_trait]
+impl WallpaperBackend for MpvpaperBackend {
+    async fn set_wallpaper(&self, path: &Path, monitor: &str) -> Result<()> {
+        self.spawn(path, monitor).await
+    }
+
+    async fn set_wallpaper_all(&self, path: &Path) -> Result<()> {
+        self.spawn(path, ALL_OUTPUTS).await
+    }
+
+    fn name(&self) -> &str {
+        "mpvpaper"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_mpvpaper_command_args() {
+        let backend = MpvpaperBackend::new(MpvpaperConfig::default());
+        let cmd = backend.build_command(Path::new("/data/wallpapers/loop.mp4"), "DP-1");
+        assert_eq!(cmd.as_std().get_program(), "mpvpaper");
+        assert_eq!(
+            args(&cmd),
+            vec![
+                "-o",
+                "--loop --no-audio",
+                "DP-1",
+                "/data/wallpapers/loop.mp4"
+            ]
+        );
+
+        let backend = MpvpaperBackend::new(MpvpaperConfig {
+            r#loop: false,
+            mute: false,
+        });
+        let cmd = backend.build_command(Path::new("/w/a.mp4"), ALL_OUTPUTS);
+        assert_eq!(args(&cmd), vec!["*", "/w/a.mp4"]);
+    }
+
+    #[test]
+    fn test_conflicting_players() {
+        let names = |running: &[&str], output| {
+            let running: Vec<String> = running.iter().map(|s| s.to_string()).collect();
+            let mut stopped = conflicting(running.iter(), output);
+            stopped.sort();
+            stopped
+        };
+        assert_eq!(names(&["DP-1", "HDMI-A-1"], "DP-1"), vec!["DP-1"]);
+        // the all-outputs player would cover DP-1 too
+        assert_eq!(names(&["*"], "DP-1"), vec!["*"]);
+        assert_eq!(names(&["*", "HDMI-A-1"], "DP-1"), vec!["*"]);
+        assert_eq!(names(&["DP-1", "HDMI-A-1"], "*"), vec!["DP-1", "HDMI-A-1"]);
+        assert!(names(&[], "DP-1").is_empty());
+    }
+}
@@ -8,26 +8,33 @@ use crate::paths::MuralisPaths;
 pub struct CacheStats {
     pub thumbnails_size: u64,
     pub previews_size: u64,
+    /// Crops and per-monitor copies, recreated when next applied.
+    pub cropped_size: u64,
     pub total_size: u64,
     pub thumbnail_count: usize,
     pub preview_count: usize,
+    pub cropped_count: usize,
 }
 
 /// Scan cache directories and return size stats.
 pub fn cache_stats(paths: &MuralisPaths) -> CacheStats {
     let (thumbnails_size, thumbnail_count) = dir_stats(&paths.thumbnails_dir());
     let (previews_size, preview_count) = dir_stats(&paths.previews_dir());
+    let (cropped_size, cropped_count) = dir_stats(&paths.cropped_dir());
     CacheStats {
         thumbnails_size,
         previews_size,
-        total_size: thumbnails_size + previews_size,
+        cropped_size,
+        total_size: thumbnails_size + previews_size + cropped_size,
         thumbnail_count,
         preview_count,
+        cropped_count,
     }
 }
 
 /// Prune cache to stay under max_bytes. Deletes oldest preview files first,
-/// then oldest thumbnails. Never touches wallpaper files (those are favorites).
+/// then oldest cropped copies, then oldest thumbnails. Never touches
+/// wallpaper files (those are favorites).
 pub fn prune_cache(paths: &MuralisPaths, max_bytes: u64) -> Result<u64> {
     let stats = cache_stats(paths);
     if stats.total_size <= max_bytes {
@@ -40,6 +47,11 @@ pub fn prune_cache(paths: &MuralisPaths, max_bytes: u64) -> Result<u64> {
     // prune previews first (less important)
     freed += prune_dir(&paths.previews_dir(), target)?;
 
+    if freed < target {
+        // crops are recreated from the originals on the next apply
+        freed += prune_dir(&paths.cropped_dir(), target - freed)?;
+    }
+
     if freed < target {
         // prune thumbnails if still over
         freed += prune_dir(&paths.thumbnails_dir(), target - freed)?;
@@ -98,11 +110,7 @@ mod tests {
     #[test]
     fn test_cache_stats_empty() {
         let tmp = tempfile::tempdir().unwrap();
-        let paths = MuralisPaths {
-            config_dir: tmp.path().join("config"),
-            data_dir: tmp.path().join("data"),
-            cache_dir: tmp.path().join("cache"),
-        };
+        let paths = MuralisPaths::for_test(tmp.path());
         paths.ensure_dirs().unwrap();
 
         let stats = cache_stats(&paths);
@@ -114,11 +122,7 @@ mod tests {
     #[test]
     fn test_prune_cache() {
         let tmp = tempfile::tempdir().unwrap();
-        let paths = MuralisPaths {
-            config_dir: tmp.path().join("config"),
-            data_dir: tmp.path().join("data"),
-            cache_dir: tmp.path().join("cache"),
-        };
+        let paths = MuralisPaths::for_test(tmp.path());
         paths.ensure_dirs().unwrap();
 
         // create some preview files
@@ -139,5 +143,17 @@ mod tests {
 
         let stats = cache_stats(&paths);
         assert_eq!(stats.preview_count, 3);
+
+        // crops go once the previews are gone
+        std::fs::create_dir_all(paths.cropped_dir()).unwrap();
+        for i in 0..2 {
+            let path = paths.cropped_dir().join(format!("crop_{i}.jpg"));
+            std::fs::write(&path, vec![0u8; 1000]).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(cache_stats(&paths).total_size, 5000);
+        assert_eq!(prune_cache(&paths, 1000).unwrap(), 4000);
+        let stats = cache_stats(&paths);
+        assert_eq!((stats.preview_count, stats.cropped_count), (0, 1));
     }
 }
@@ -1,13 +1,125 @@
 use serde::{Deserialize, Serialize};
 
 use crate::error::{MuralisError, Result};
-use crate::models::{BackendType, DisplayMode};
+use crate::models::{
+    BackendType, ChromaSubsampling, DisplayMode, HashAlgo, MinResolutionStrategy, OverlayStyle,
+    WallpaperPreview,
+};
 use crate::paths::MuralisPaths;
 
+/// Current config schema version, bumped when a migration is needed.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Keys each section understands. `sources` is plugin-owned and not checked.
+/// Update alongside the structs below.
+const KNOWN_KEYS: &[(&str, &[&str])] = &[
+    (
+        "",
+        &[
+            "version",
+            "general",
+            "thumbnails",
+            "display",
+            "sources",
+            "workspaces",
+            "schedules",
+            "filter",
+            "gui",
+        ],
+    ),
+    (
+        "general",
+        &[
+            "backend",
+            "cache_max_mb",
+            "library_max_mb",
+            "thumbnail_zoom",
+            "square_thumbnails",
+            "browse_per_source_cap",
+            "hash_algo",
+            "prune_missing_on_start",
+            "apply_on_favorite",
+            "download_retries",
+            "http_retries",
+            "request_timeout_secs",
+            "user_agent",
+            "aspect_tolerance",
+            "watch_config",
+            "on_change_command",
+        ],
+    ),
+    (
+        "thumbnails",
+        &["quality", "optimize", "progressive", "subsampling"],
+    ),
+    (
+        "display",
+        &[
+            "mode",
+            "interval",
+            "min_resolution",
+            "min_resolution_strategy",
+            "aspect_ratio",
+            "transition",
+            "workspace_default",
+            "collection",
+            "overlay_darken",
+            "overlay_style",
+            "pause_when_screens_off",
+            "live_source",
+            "live_query",
+            "min_switch_interval_ms",
+            "history",
+            "skip_unchanged",
+            "per_monitor_crop",
+            "theme",
+            "mpvpaper",
+        ],
+    ),
+    (
+        "display.theme",
+        &[
+            "follow",
+            "light_tags",
+            "dark_tags",
+            "light_from",
+            "dark_from",
+        ],
+    ),
+    ("display.transition", &["type", "duration", "fps"]),
+    ("display.mpvpaper", &["loop", "mute"]),
+    ("workspaces", &["workspace", "wallpaper"]),
+    ("schedules", &["time", "tags"]),
+    (
+        "filter",
+        &[
+            "min_width",
+            "min_height",
+            "exclude_tags",
+            "min_brightness",
+            "max_brightness",
+            "skip_low_res",
+        ],
+    ),
+    (
+        "gui",
+        &[
+            "prefetch_on_open",
+            // moved to the GUI state file; read once to migrate
+            "window_width",
+            "window_height",
+            "last_tab",
+            "preview_fit",
+        ],
+    ),
+];
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
+    pub version: u32,
     pub general: GeneralConfig,
+    pub thumbnails: ThumbnailConfig,
     pub display: DisplayConfig,
     #[serde(default = "default_sources")]
     pub sources: toml::Table,
@@ -16,6 +128,7 @@ pub struct Config {
     #[serde(default)]
     pub schedules: Vec<ScheduleEntry>,
     pub filter: FilterConfig,
+    pub gui: GuiConfig,
 }
 
 fn default_sources() -> toml::Table {
@@ -31,36 +144,485 @@ fn default_sources() -> toml::Table {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            version: CONFIG_VERSION,
             general: GeneralConfig::default(),
+            thumbnails: ThumbnailConfig::default(),
             display: DisplayConfig::default(),
             sources: default_sources(),
             workspaces: Vec::new(),
             schedules: Vec::new(),
             filter: FilterConfig::default(),
+            gui: GuiConfig::default(),
         }
     }
 }
 
 impl Config {
+    /// Wallpaper key (ID/tag or "random") for a workspace: its explicit
+    /// mapping, else `[display] workspace_default`.
+    pub fn workspace_wallpaper(&self, workspace: u32) -> Option<&str> {
+        self.workspaces
+            .iter()
+            .find(|w| w.workspace == workspace)
+            .map(|w| w.wallpaper.as_str())
+            .or(self.display.workspace_default.as_deref())
+    }
+
     pub fn load(paths: &MuralisPaths) -> Result<Self> {
         let path = paths.config_file();
         let content = std::fs::read_to_string(&path)
             .map_err(|e| MuralisError::Config(format!("failed to read {}: {e}", path.display())))?;
-        let config: Self = toml::from_str(&content)?;
+        let (config, warnings) = Self::parse(&content)?;
+        for w in warnings {
+            tracing::warn!("{}: {w}", path.display());
+        }
         Ok(config)
     }
 
+    /// Parse config TOML, returning warnings for unrecognized keys, unset
+    /// `${VAR}`s and versions newer than this build understands.
+    pub fn parse(content: &str) -> Result<(Self, Vec<String>)> {
+        let mut raw: toml::Table = toml::from_str(content)?;
+        let mut warnings = unknown_keys(&raw)
+            .into_iter()
+            .map(|k| format!("unknown config key `{k}` (ignored)"))
+            .collect::<Vec<_>>();
+        warnings.extend(interpolate_sources(&mut raw, &env_var));
+        let config: Self = toml::Value::Table(raw).try_into()?;
+        if config.version > CONFIG_VERSION {
+            warnings.push(format!(
+                "config version {} is newer than supported version {CONFIG_VERSION}",
+                config.version
+            ));
+        }
+        Ok((config, warnings))
+    }
+
     pub fn load_or_default(paths: &MuralisPaths) -> Self {
         Self::load(paths).unwrap_or_default()
     }
 
+    /// Enable a `[sources.<name>]` table, creating it if missing, and optionally
+    /// set its API credential and, for OAuth sources, its client secret.
+    pub fn enable_source(
+        &mut self,
+        name: &str,
+        key: Option<&str>,
+        secret: Option<&str>,
+    ) -> Result<()> {
+        let table = self.source_table_mut(name)?;
+        table.insert("enabled".into(), toml::Value::Boolean(true));
+        if let Some(key) = key {
+            let field = source_key_field(name).ok_or_else(|| {
+                MuralisError::Config(format!("source {name} does not take an API key"))
+            })?;
+            table.insert(field.into(), toml::Value::String(key.into()));
+        }
+        if let Some(secret) = secret {
+            let field = source_secret_field(name).ok_or_else(|| {
+                MuralisError::Config(format!("source {name} does not take a client secret"))
+            })?;
+            table.insert(field.into(), toml::Value::String(secret.into()));
+        }
+        Ok(())
+    }
+
+    /// Add a folder to `[sources.local] paths`, unless it is already listed.
+    pub fn add_local_path(&mut self, path: &str) -> Result<()> {
+        let table = self.source_table_mut("local")?;
+        let paths = table
+            .entry("paths")
+            .or_insert_with(|| toml::Value::Array(Vec::new()))
+            .as_array_mut()
+            .ok_or_else(|| MuralisError::Config("[sources.local] paths is not a list".into()))?;
+        if !paths.iter().any(|p| p.as_str() == Some(path)) {
+            paths.push(toml::Value::String(path.into()));
+        }
+        Ok(())
+    }
+
+    /// Disable a `[sources.<name>]` table, keeping its other settings.
+    pub fn disable_source(&mut self, name: &str) -> Result<()> {
+        let table = self.source_table_mut(name)?;
+        table.insert("enabled".into(), toml::Value::Boolean(false));
+        Ok(())
+    }
+
+    fn source_table_mut(&mut self, name: &str) -> Result<&mut toml::Table> {
+        self.sources
+            .entry(name)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| MuralisError::Config(format!("[sources.{name}] is not a table")))
+    }
+
+    /// Write the config file. An existing file is edited in place, so
+    /// comments and formatting survive on every entry that didn't change;
+    /// comments inside a changed table or array are lost.
     pub fn save(&self, paths: &MuralisPaths) -> Result<()> {
-        let content = toml::to_string_pretty(self)
-            .map_err(|e| MuralisError::Config(format!("failed to serialize config: {e}")))?;
         let path = paths.config_file();
+        let existing = std::fs::read_to_string(&path).ok();
+        let content = self.to_toml(existing.as_deref())?;
         std::fs::write(&path, content)
             .map_err(|e| MuralisError::Config(format!("failed to write {}: {e}", path.display())))
     }
+
+    /// Serialize, merged into `existing` file content when it parses.
+    pub fn to_toml(&self, existing: Option<&str>) -> Result<String> {
+        let serialize_err = |e: &dyn std::fmt::Display| {
+            MuralisError::Config(format!("failed to serialize config: {e}"))
+        };
+        let fresh = toml::to_string_pretty(self).map_err(|e| serialize_err(&e))?;
+        let Some(existing) = existing else {
+            return Ok(fresh);
+        };
+        let (Ok(mut doc), Ok(mut old)) = (
+            existing.parse::<toml_edit::DocumentMut>(),
+            toml::from_str::<toml::Table>(existing),
+        ) else {
+            return Ok(fresh);
+        };
+        // compare against what was loaded, so unchanged `${VAR}`s stay in
+        // the file instead of their values
+        interpolate_sources(&mut old, &env_var);
+        let fresh_doc: toml_edit::DocumentMut = fresh.parse().map_err(|e| serialize_err(&e))?;
+        let new: toml::Table = toml::from_str(&fresh)?;
+        let mut next_position = last_position(doc.as_table()) + 1;
+        merge_table(
+            doc.as_table_mut(),
+            &old,
+            fresh_doc.as_table(),
+            &new,
+            &mut next_position,
+        );
+        Ok(doc.to_string())
+    }
+
+    /// Config TOML for sharing, with secrets masked unless `with_secrets`.
+    /// Falls back to the defaults when no config file exists yet.
+    pub fn export(paths: &MuralisPaths, with_secrets: bool) -> Result<String> {
+        let path = paths.config_file();
+        let mut raw: toml::Table = if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            toml::from_str(&content)?
+        } else {
+            toml::Table::try_from(Config::default())
+                .map_err(|e| MuralisError::Config(format!("failed to serialize config: {e}")))?
+        };
+        if !with_secrets {
+            redact_secrets(&mut raw);
+        }
+        toml::to_string_pretty(&raw)
+            .map_err(|e| MuralisError::Config(format!("failed to serialize config: {e}")))
+    }
+
+    /// Replace the config with `content` after validating it. The old file is
+    /// kept as `config.toml.bak`. Returns the parse warnings.
+    pub fn import(paths: &MuralisPaths, content: &str) -> Result<Vec<String>> {
+        let (_, mut warnings) = Self::parse(content)?;
+        if content.contains(REDACTED) {
+            warnings.push(
+                "config contains redacted secrets; set keys with `muralis sources enable <name> --key`"
+                    .into(),
+            );
+        }
+        let path = paths.config_file();
+        if path.exists() {
+            std::fs::copy(&path, path.with_extension("toml.bak"))?;
+        }
+        std::fs::create_dir_all(&paths.config_dir)?;
+        std::fs::write(&path, content).map_err(|e| {
+            MuralisError::Config(format!("failed to write {}: {e}", path.display()))
+        })?;
+        Ok(warnings)
+    }
+}
+
+/// Apply the `old` -> `new` change to `doc`, touching only entries that differ.
+/// `fresh` is `new` as a document, the source of inserted items, which are
+/// placed after everything already in the file.
+fn merge_table(
+    doc: &mut dyn toml_edit::TableLike,
+    old: &toml::Table,
+    fresh: &dyn toml_edit::TableLike,
+    new: &toml::Table,
+    next_position: &mut usize,
+) {
+    for key in old.keys().filter(|k| !new.contains_key(*k)) {
+        doc.remove(key);
+    }
+    for (key, new_value) in new {
+        let Some(fresh_item) = fresh.get(key) else {
+            continue;
+        };
+        match (old.get(key), doc.get_mut(key)) {
+            (Some(old_value), Some(item)) => {
+                merge_item(item, old_value, fresh_item, new_value, next_position)
+            }
+            _ => {
+                let mut item = fresh_item.clone();
+                reposition(&mut item, next_position);
+                doc.insert(key, item);
+            }
+        }
+    }
+}
+
+fn merge_item(
+    item: &mut toml_edit::Item,
+    old: &toml::Value,
+    fresh: &toml_edit::Item,
+    new: &toml::Value,
+    next_position: &mut usize,
+) {
+    if same_value(old, new) {
+        return;
+    }
+    match (old, new) {
+        (toml::Value::Table(old_t), toml::Value::Table(new_t)) => {
+            if let (Some(doc), Some(fresh)) = (item.as_table_like_mut(), fresh.as_table_like()) {
+                merge_table(doc, old_t, fresh, new_t, next_position);
+                return;
+            }
+        }
+        // arrays of tables (e.g. feeds) edit element-wise while the count holds
+        (toml::Value::Array(old_a), toml::Value::Array(new_a)) if old_a.len() == new_a.len() => {
+            if let (Some(doc), Some(fresh)) =
+                (item.as_array_of_tables_mut(), fresh.as_array_of_tables())
+            {
+                let pairs = old_a.iter().zip(new_a);
+                for ((table, fresh), (old, new)) in doc.iter_mut().zip(fresh.iter()).zip(pairs) {
+                    if let (toml::Value::Table(old), toml::Value::Table(new)) = (old, new) {
+                        merge_table(table, old, fresh, new, next_position);
+                    }
+                }
+                return;
+            }
+        }
+        _ => {}
+    }
+    // replace, keeping any comment attached to the old value
+    let decor = item.as_value().map(|v| v.decor().clone());
+    *item = fresh.clone();
+    reposition(item, next_position);
+    if let (Some(decor), Some(value)) = (decor, item.as_value_mut()) {
+        *value.decor_mut() = decor;
+    }
+}
+
+/// Equal, except floats only need to agree at f32 precision (what the
+/// config structs store), so `0.4` in the file matches a saved `0.4_f32`.
+fn same_value(a: &toml::Value, b: &toml::Value) -> bool {
+    use toml::Value;
+    match (a, b) {
+        (Value::Float(x), Value::Float(y)) => *x as f32 == *y as f32,
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| same_value(x, y))
+        }
+        (Value::Table(x), Value::Table(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(k, v)| y.get(k).is_some_and(|w| same_value(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Highest table position in the document.
+fn last_position(table: &toml_edit::Table) -> usize {
+    let mut last = table.position().unwrap_or(0);
+    for (_, item) in table.iter() {
+        match item {
+            toml_edit::Item::Table(t) => last = last.max(last_position(t)),
+            toml_edit::Item::ArrayOfTables(a) => {
+                for t in a.iter() {
+                    last = last.max(last_position(t));
+                }
+            }
+            _ => {}
+        }
+    }
+    last
+}
+
+/// Renumber the tables in `item` to follow `next_position`, in order.
+fn reposition(item: &mut toml_edit::Item, next_position: &mut usize) {
+    let place = |table: &mut toml_edit::Table, next_position: &mut usize| {
+        table.set_position(*next_position);
+        *next_position += 1;
+        for (_, child) in table.iter_mut() {
+            reposition(child, next_position);
+        }
+    };
+    match item {
+        toml_edit::Item::Table(t) => place(t, next_position),
+        toml_edit::Item::ArrayOfTables(a) => {
+            for t in a.iter_mut() {
+                place(t, next_position);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+/// Replace `${VAR}` in every string under `[sources]` with the variable's
+/// value, so API keys can live outside the file. Unset variables are left
+/// as written; returns a warning for each.
+fn interpolate_sources(
+    raw: &mut toml::Table,
+    lookup: &dyn Fn(&str) -> Option<String>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Some(toml::Value::Table(sources)) = raw.get_mut("sources") {
+        for (key, value) in sources.iter_mut() {
+            interpolate_value(value, &format!("sources.{key}"), lookup, &mut warnings);
+        }
+    }
+    warnings
+}
+
+fn interpolate_value(
+    value: &mut toml::Value,
+    path: &str,
+    lookup: &dyn Fn(&str) -> Option<String>,
+    warnings: &mut Vec<String>,
+) {
+    match value {
+        toml::Value::String(s) => {
+            let (expanded, unset) = interpolate(s, lookup);
+            *s = expanded;
+            warnings.extend(
+                unset
+                    .into_iter()
+                    .map(|var| format!("`{path}`: environment variable {var} is not set")),
+            );
+        }
+        toml::Value::Table(t) => {
+            for (key, value) in t.iter_mut() {
+                interpolate_value(value, &format!("{path}.{key}"), lookup, warnings);
+            }
+        }
+        toml::Value::Array(items) => {
+            for (i, value) in items.iter_mut().enumerate() {
+                interpolate_value(value, &format!("{path}[{i}]"), lookup, warnings);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// `s` with each `${VAR}` replaced by its value, and the names of the
+/// variables that aren't set.
+fn interpolate(s: &str, lookup: &dyn Fn(&str) -> Option<String>) -> (String, Vec<String>) {
+    let mut out = String::with_capacity(s.len());
+    let mut unset = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let token = &rest[start..start + len + 1];
+        let name = &token[2..token.len() - 1];
+        out.push_str(&rest[..start]);
+        let is_name = !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        match is_name.then(|| lookup(name)).flatten() {
+            Some(value) => out.push_str(&value),
+            None => {
+                if is_name {
+                    unset.push(name.to_string());
+                }
+                out.push_str(token);
+            }
+        }
+        rest = &rest[start + token.len()..];
+    }
+    out.push_str(rest);
+    (out, unset)
+}
+
+/// Keys holding credentials, masked by `config export`.
+const SECRET_KEYS: &[&str] = &["api_key", "access_key", "client_id", "client_secret"];
+
+/// Placeholder written in place of a secret value.
+pub const REDACTED: &str = "<redacted>";
+
+/// Mask every secret value in `table`, at any depth.
+pub fn redact_secrets(table: &mut toml::Table) {
+    for (key, value) in table.iter_mut() {
+        match value {
+            toml::Value::String(s) if SECRET_KEYS.contains(&key.as_str()) => {
+                *s = REDACTED.to_string();
+            }
+            _ => redact_value(value),
+        }
+    }
+}
+
+fn redact_value(value: &mut toml::Value) {
+    match value {
+        toml::Value::Table(t) => redact_secrets(t),
+        toml::Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// Dotted paths of keys not listed in `KNOWN_KEYS`.
+fn unknown_keys(raw: &toml::Table) -> Vec<String> {
+    fn check(section: &str, table: &toml::Table, out: &mut Vec<String>) {
+        let Some((_, known)) = KNOWN_KEYS.iter().find(|(s, _)| *s == section) else {
+            return;
+        };
+        for (key, value) in table {
+            let path = if section.is_empty() {
+                key.clone()
+            } else {
+                format!("{section}.{key}")
+            };
+            if !known.contains(&key.as_str()) {
+                out.push(path);
+                continue;
+            }
+            match value {
+                toml::Value::Table(t) => check(&path, t, out),
+                toml::Value::Array(items) => {
+                    for item in items {
+                        if let toml::Value::Table(t) = item {
+                            check(&path, t, out);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    check("", raw, &mut out);
+    out
+}
+
+/// Config key holding the API credential for a built-in source, if it takes one.
+pub fn source_key_field(source: &str) -> Option<&'static str> {
+    match source {
+        "wallhaven" | "pexels" | "apod" => Some("api_key"),
+        "unsplash" => Some("access_key"),
+        "deviantart" => Some("client_id"),
+        _ => None,
+    }
+}
+
+/// Config key holding the client secret for a built-in OAuth source.
+pub fn source_secret_field(source: &str) -> Option<&'static str> {
+    match source {
+        "deviantart" => Some("client_secret"),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,7 +630,41 @@ impl Config {
 pub struct GeneralConfig {
     pub backend: BackendType,
     pub cache_max_mb: u64,
+    /// Size `muralis db evict` trims the library to.
+    pub library_max_mb: Option<u64>,
     pub thumbnail_zoom: f32,
+    /// Crop thumbnails to squares for a uniform grid.
+    pub square_thumbnails: bool,
+    /// Max results each source contributes to a browse-all search.
+    pub browse_per_source_cap: Option<usize>,
+    /// Hash for new favorite IDs; existing IDs are kept.
+    pub hash_algo: HashAlgo,
+    /// Drop favorites whose files were deleted when the daemon starts.
+    /// Skipped while the wallpapers folder is missing or empty.
+    pub prune_missing_on_start: bool,
+    /// Set a wallpaper as soon as it is favorited (the first of a batch).
+    pub apply_on_favorite: bool,
+    /// Retries for downloads that time out, fail to connect or hit a server
+    /// error, with backoff (searches retry once). Busy responses are left
+    /// to `http_retries`.
+    pub download_retries: u32,
+    /// Retries for source requests answered with 429/502/503/504.
+    pub http_retries: u32,
+    /// Give up on a source request that can't connect, or stops sending
+    /// data, for this long; 0 waits forever. Slow but live downloads and
+    /// streams aren't cut off.
+    pub request_timeout_secs: u64,
+    /// Sent to every source instead of the muralis default.
+    pub user_agent: Option<String>,
+    /// How far an image's width/height may be from the searched aspect
+    /// ratio and still be shown.
+    pub aspect_tolerance: f64,
+    /// Reload the daemon when the config file changes.
+    pub watch_config: bool,
+    /// Shell command run after each wallpaper change, with the applied file
+    /// in `$MURALIS_PATH`; `{path}`, quoted or not, expands to it
+    /// (e.g. `wal -i {path}`).
+    pub on_change_command: Option<String>,
 }
 
 impl Default for GeneralConfig {
@@ -76,7 +672,45 @@ impl Default for GeneralConfig {
         Self {
             backend: BackendType::Hyprpaper,
             cache_max_mb: 500,
+            library_max_mb: None,
             thumbnail_zoom: 1.0,
+            square_thumbnails: false,
+            browse_per_source_cap: None,
+            hash_algo: HashAlgo::default(),
+            prune_missing_on_start: false,
+            apply_on_favorite: false,
+            download_retries: 3,
+            http_retries: 3,
+            request_timeout_secs: 30,
+            user_agent: None,
+            aspect_tolerance: crate::sources::DEFAULT_ASPECT_TOLERANCE,
+            watch_config: true,
+            on_change_command: None,
+        }
+    }
+}
+
+/// JPEG encoding of stored thumbnails.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThumbnailConfig {
+    /// JPEG quality, 1-100.
+    pub quality: u8,
+    /// Huffman tables built for each image rather than the standard ones:
+    /// the same pixels in fewer bytes, for a little more encoding time.
+    pub optimize: bool,
+    /// Progressive scans instead of baseline.
+    pub progressive: bool,
+    pub subsampling: ChromaSubsampling,
+}
+
+impl Default for ThumbnailConfig {
+    fn default() -> Self {
+        Self {
+            quality: 75,
+            optimize: true,
+            progressive: false,
+            subsampling: ChromaSubsampling::default(),
         }
     }
 }
@@ -87,8 +721,36 @@ pub struct DisplayConfig {
     pub mode: DisplayMode,
     pub interval: String,
     pub min_resolution: String,
+    pub min_resolution_strategy: MinResolutionStrategy,
     pub aspect_ratio: String,
     pub transition: TransitionConfig,
+    /// Workspace mode fallback when no `[[workspaces]]` entry matches:
+    /// a wallpaper ID/tag, or "random".
+    pub workspace_default: Option<String>,
+    /// Rotate only within this favorites collection.
+    pub collection: Option<String>,
+    /// Brightness kept in cropped regions by the `darken` overlay (0.0-1.0).
+    pub overlay_darken: f32,
+    pub overlay_style: OverlayStyle,
+    /// Skip rotation while every monitor is powered off (DPMS).
+    pub pause_when_screens_off: bool,
+    /// Source ID/name `live_random` fetches from; unset picks any enabled source.
+    pub live_source: Option<String>,
+    /// Search query for `live_random` (empty browses).
+    pub live_query: String,
+    /// Ignore `next`/`prev` requests arriving sooner than this after the
+    /// last one. 0 disables the cooldown.
+    pub min_switch_interval_ms: u64,
+    /// Random rotation avoids this many recent picks, at most half the
+    /// rotation pool.
+    pub history: usize,
+    /// Don't re-apply the wallpaper already showing (explicit `set` still does).
+    pub skip_unchanged: bool,
+    /// Give each monitor its own copy cropped to its aspect and resolution
+    /// instead of one image for all.
+    pub per_monitor_crop: bool,
+    pub theme: ThemeConfig,
+    pub mpvpaper: MpvpaperConfig,
 }
 
 impl Default for DisplayConfig {
@@ -97,8 +759,22 @@ impl Default for DisplayConfig {
             mode: DisplayMode::Random,
             interval: "30m".into(),
             min_resolution: "auto".into(),
+            min_resolution_strategy: MinResolutionStrategy::Smallest,
             aspect_ratio: "auto".into(),
             transition: TransitionConfig::default(),
+            workspace_default: None,
+            collection: None,
+            overlay_darken: crate::crop_overlay::DEFAULT_DARKEN,
+            overlay_style: OverlayStyle::default(),
+            pause_when_screens_off: true,
+            live_source: None,
+            live_query: String::new(),
+            min_switch_interval_ms: 0,
+            history: 20,
+            skip_unchanged: true,
+            per_monitor_crop: false,
+            theme: ThemeConfig::default(),
+            mpvpaper: MpvpaperConfig::default(),
         }
     }
 }
@@ -121,6 +797,49 @@ impl Default for TransitionConfig {
     }
 }
 
+/// Rotate through light or dark tagged wallpapers following the desktop's
+/// light/dark preference, or the time of day where there is none.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub follow: bool,
+    pub light_tags: Vec<String>,
+    pub dark_tags: Vec<String>,
+    /// Without a desktop preference, light from this time ("HH:MM")...
+    pub light_from: String,
+    /// ...until this one.
+    pub dark_from: String,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            follow: false,
+            light_tags: vec!["light".into()],
+            dark_tags: vec!["dark".into()],
+            light_from: "07:00".into(),
+            dark_from: "19:00".into(),
+        }
+    }
+}
+
+/// Playback flags for the mpvpaper backend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MpvpaperConfig {
+    pub r#loop: bool,
+    pub mute: bool,
+}
+
+impl Default for MpvpaperConfig {
+    fn default() -> Self {
+        Self {
+            r#loop: true,
+            mute: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceConfig {
     pub workspace: u32,
@@ -139,6 +858,11 @@ pub struct FilterConfig {
     pub min_width: u32,
     pub min_height: u32,
     pub exclude_tags: Vec<String>,
+    /// Rotation only uses wallpapers with mean luma in this range (0.0-1.0).
+    pub min_brightness: f32,
+    pub max_brightness: f32,
+    /// Rotation skips wallpapers below the minimum resolution.
+    pub skip_low_res: bool,
 }
 
 impl Default for FilterConfig {
@@ -147,6 +871,43 @@ impl Default for FilterConfig {
             min_width: 1920,
             min_height: 1080,
             exclude_tags: Vec::new(),
+            min_brightness: 0.0,
+            max_brightness: 1.0,
+            skip_low_res: false,
+        }
+    }
+}
+
+impl FilterConfig {
+    /// Unknown brightness (not yet analyzed) always passes.
+    pub fn allows_brightness(&self, brightness: Option<f32>) -> bool {
+        brightness.is_none_or(|b| b >= self.min_brightness && b <= self.max_brightness)
+    }
+
+    /// Whether a search result passes: no excluded tag, and at least
+    /// `min_width`x`min_height`. Unknown dimensions (0, e.g. feeds) skip
+    /// the size check.
+    pub fn matches(&self, preview: &WallpaperPreview) -> bool {
+        if preview.has_blocked_tag(&self.exclude_tags) {
+            return false;
+        }
+        let known = preview.width > 0 && preview.height > 0;
+        !known || (preview.width >= self.min_width && preview.height >= self.min_height)
+    }
+}
+
+/// GUI state restored on the next launch.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct GuiConfig {
+    /// Browse a source the first time its tab opens, when it supports it.
+    pub prefetch_on_open: bool,
+}
+
+impl Default for GuiConfig {
+    fn default() -> Self {
+        Self {
+            prefetch_on_open: true,
         }
     }
 }
@@ -154,6 +915,7 @@ impl Default for FilterConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::WallpaperMeta;
 
     #[test]
     fn test_default_config() {
@@ -184,6 +946,69 @@ mode = "static"
         assert_eq!(config.filter.min_width, 1920);
     }
 
+    #[test]
+    fn test_enable_source_with_key() {
+        let mut config = Config::default();
+        config
+            .enable_source("unsplash", Some("my_key"), None)
+            .unwrap();
+
+        let table = config.sources.get("unsplash").unwrap().as_table().unwrap();
+        assert_eq!(table.get("enabled").unwrap().as_bool(), Some(true));
+        assert_eq!(table.get("access_key").unwrap().as_str(), Some("my_key"));
+
+        let out = toml::to_string_pretty(&config).unwrap();
+        assert!(out.contains("[sources.unsplash]"));
+        assert!(out.contains(r#"access_key = "my_key""#));
+
+        // disabling keeps the key
+        config.disable_source("unsplash").unwrap();
+        let table = config.sources.get("unsplash").unwrap().as_table().unwrap();
+        assert_eq!(table.get("enabled").unwrap().as_bool(), Some(false));
+        assert_eq!(table.get("access_key").unwrap().as_str(), Some("my_key"));
+
+        // feeds take no key
+        assert!(config.enable_source("feeds", Some("x"), None).is_err());
+        assert!(config.enable_source("unsplash", None, Some("x")).is_err());
+    }
+
+    #[test]
+    fn test_add_local_path() {
+        let mut config = Config::default();
+        config.enable_source("local", None, None).unwrap();
+        config.add_local_path("~/Pictures/Wallpapers").unwrap();
+        config.add_local_path("/srv/walls").unwrap();
+        // listed once
+        config.add_local_path("~/Pictures/Wallpapers").unwrap();
+
+        let table = config.sources.get("local").unwrap().as_table().unwrap();
+        let paths: Vec<_> = table["paths"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p.as_str().unwrap())
+            .collect();
+        assert_eq!(paths, ["~/Pictures/Wallpapers", "/srv/walls"]);
+    }
+
+    #[test]
+    fn test_enable_source_with_client_secret() {
+        let mut config = Config::default();
+        config
+            .enable_source("deviantart", Some("123"), Some("s3cret"))
+            .unwrap();
+
+        let table = config
+            .sources
+            .get("deviantart")
+            .unwrap()
+            .as_table()
+            .unwrap();
+        assert_eq!(table.get("enabled").unwrap().as_bool(), Some(true));
+        assert_eq!(table.get("client_id").unwrap().as_str(), Some("123"));
+        assert_eq!(table.get("client_secret").unwrap().as_str(), Some("s3cret"));
+    }
+
     #[test]
     fn test_parse_full_toml() {
         let toml_str = r#"
@@ -191,10 +1016,16 @@ mode = "static"
 backend = "hyprpaper"
 cache_max_mb = 1000
 
+[thumbnails]
+quality = 80
+progressive = true
+subsampling = "4:4:4"
+
 [display]
 mode = "random"
 interval = "15m"
 min_resolution = "2560x1440"
+min_resolution_strategy = "largest"
 aspect_ratio = "16:9"
 
 [display.transition]
@@ -206,6 +1037,7 @@ fps = 30
 min_width = 2560
 min_height = 1440
 exclude_tags = ["anime", "cartoon"]
+max_brightness = 0.4
 
 [sources.wallhaven]
 enabled = true
@@ -235,7 +1067,20 @@ tags = ["bright", "morning"]
 "#;
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.general.cache_max_mb, 1000);
+        assert_eq!(
+            config.thumbnails,
+            ThumbnailConfig {
+                quality: 80,
+                optimize: true,
+                progressive: true,
+                subsampling: ChromaSubsampling::Yuv444,
+            }
+        );
         assert_eq!(config.display.transition.r#type, "wipe");
+        assert_eq!(
+            config.display.min_resolution_strategy,
+            MinResolutionStrategy::Largest
+        );
         // sources is now a raw table
         let wh = config.sources.get("wallhaven").unwrap().as_table().unwrap();
         assert_eq!(wh.get("api_key").unwrap().as_str(), Some("test_key"));
@@ -244,5 +1089,350 @@ tags = ["bright", "morning"]
         assert_eq!(config.workspaces.len(), 1);
         assert_eq!(config.schedules.len(), 1);
         assert_eq!(config.filter.exclude_tags, vec!["anime", "cartoon"]);
+        assert_eq!(config.filter.min_brightness, 0.0);
+        assert_eq!(config.filter.max_brightness, 0.4);
+    }
+
+    #[test]
+    fn test_unknown_key_warns_but_loads() {
+        let (config, warnings) = Config::parse(
+            r#"
+[general]
+cache_max_mb = 800
+chace_max_mb = 100
+
+[display.transition]
+typ = "wipe"
+
+[[workspaces]]
+workspace = 1
+wallpaper = "nature"
+wallpapr = "typo"
+
+[sources.wallhaven]
+anything_goes = true
+
+[extra]
+x = 1
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.general.cache_max_mb, 800);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(warnings.len(), 4);
+        for key in [
+            "general.chace_max_mb",
+            "display.transition.typ",
+            "workspaces.wallpapr",
+            "`extra`",
+        ] {
+            assert!(warnings.iter().any(|w| w.contains(key)), "{key}");
+        }
+    }
+
+    #[test]
+    fn test_known_keys_cover_default_config() {
+        let mut config = Config::default();
+        config.display.workspace_default = Some("random".into());
+        config.workspaces.push(WorkspaceConfig {
+            workspace: 1,
+            wallpaper: "x".into(),
+        });
+        config.schedules.push(ScheduleEntry {
+            time: "08:00".into(),
+            tags: Vec::new(),
+        });
+        let raw: toml::Table = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+        assert!(unknown_keys(&raw).is_empty(), "{:?}", unknown_keys(&raw));
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        let mut raw: toml::Table = r#"
+[general]
+backend = "swww"
+
+[sources.wallhaven]
+enabled = true
+api_key = "wh-secret"
+
+[sources.unsplash]
+access_key = "un-secret"
+
+[sources.unsplash.headers]
+Accept-Version = "v1"
+
+[[sources.feeds]]
+name = "Bing"
+url = "https://example.com/feed.xml"
+api_key = "feed-secret"
+
+[sources.deviantart]
+enabled = true
+client_id = "da-secret-id"
+client_secret = "da-secret"
+"#
+        .parse()
+        .unwrap();
+        redact_secrets(&mut raw);
+        let out = toml::to_string(&raw).unwrap();
+        // key names say "secret" too; the values all contain "-secret"
+        assert!(!out.contains("-secret"));
+        assert_eq!(
+            raw["sources"]["wallhaven"]["api_key"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            raw["sources"]["unsplash"]["access_key"].as_str(),
+            Some(REDACTED)
+        );
+        assert_eq!(
+            raw["sources"]["feeds"][0]["api_key"].as_str(),
+            Some(REDACTED)
+        );
+        for key in ["client_id", "client_secret"] {
+            assert_eq!(raw["sources"]["deviantart"][key].as_str(), Some(REDACTED));
+        }
+        // everything else is untouched
+        assert_eq!(raw["general"]["backend"].as_str(), Some("swww"));
+        assert_eq!(raw["sources"]["wallhaven"]["enabled"].as_bool(), Some(true));
+        assert_eq!(raw["sources"]["feeds"][0]["name"].as_str(), Some("Bing"));
+        assert_eq!(
+            raw["sources"]["unsplash"]["headers"]["Accept-Version"].as_str(),
+            Some("v1")
+        );
+    }
+
+    #[test]
+    fn test_import_validates_and_backs_up() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        std::fs::write(paths.config_file(), "[general]\nbackend = \"swww\"\n").unwrap();
+
+        assert!(Config::import(&paths, "[general]\nbackend = 3").is_err());
+        assert!(Config::load(&paths).is_ok());
+
+        let exported = Config::export(&paths, false).unwrap();
+        let warnings = Config::import(&paths, "[display]\ninterval = \"5m\"\n").unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(Config::load(&paths).unwrap().display.interval, "5m");
+        let backup = std::fs::read_to_string(paths.config_file().with_extension("toml.bak"));
+        assert!(backup.unwrap().contains("swww"));
+        assert!(exported.contains("swww"));
+    }
+
+    #[test]
+    fn test_newer_version_warns() {
+        let (_, warnings) = Config::parse("version = 99").unwrap();
+        assert!(warnings[0].contains("newer"));
+    }
+
+    #[test]
+    fn test_workspace_wallpaper_fallback() {
+        let mut config = Config::default();
+        config.workspaces.push(WorkspaceConfig {
+            workspace: 1,
+            wallpaper: "nature".into(),
+        });
+        assert_eq!(config.workspace_wallpaper(1), Some("nature"));
+        assert_eq!(config.workspace_wallpaper(2), None);
+
+        config.display.workspace_default = Some("random".into());
+        assert_eq!(config.workspace_wallpaper(1), Some("nature"));
+        assert_eq!(config.workspace_wallpaper(2), Some("random"));
+    }
+
+    const COMMENTED: &str = r#"# my wallpaper setup
+version = 1
+
+[general]
+backend = "swww"   # hyprpaper flickers here
+
+[sources.wallhaven]
+enabled = true
+api_key = "abc"    # personal key
+purity = "110"
+
+# photography feeds
+[[sources.feeds]]
+name = "Bing Daily"
+url = "https://example.com/bing.rss"
+enabled = true
+
+[[sources.feeds]]
+name = "NASA"   # APOD
+url = "https://example.com/apod.rss"
+enabled = false
+"#;
+
+    #[test]
+    fn test_save_keeps_sources_section_byte_stable() {
+        let (config, _) = Config::parse(COMMENTED).unwrap();
+        let saved = config.to_toml(Some(COMMENTED)).unwrap();
+        let sources_start = COMMENTED.find("[sources.wallhaven]").unwrap();
+        assert!(saved.contains(&COMMENTED[sources_start..]), "{saved}");
+        assert!(saved.starts_with("# my wallpaper setup\n"));
+        assert!(saved.contains("backend = \"swww\"   # hyprpaper flickers here"));
+
+        let (reparsed, _) = Config::parse(&saved).unwrap();
+        assert_eq!(reparsed.sources, config.sources);
+        // saving again changes nothing
+        assert_eq!(reparsed.to_toml(Some(&saved)).unwrap(), saved);
+    }
+
+    #[test]
+    fn test_save_edits_only_changed_values() {
+        let (mut config, _) = Config::parse(COMMENTED).unwrap();
+        config.disable_source("wallhaven").unwrap();
+        config.sources["feeds"].as_array_mut().unwrap()[1]
+            .as_table_mut()
+            .unwrap()
+            .insert("enabled".into(), toml::Value::Boolean(true));
+        config.enable_source("pexels", Some("k"), None).unwrap();
+
+        let saved = config.to_toml(Some(COMMENTED)).unwrap();
+        assert!(saved.contains("enabled = false\napi_key = \"abc\"    # personal key"));
+        assert!(saved.contains(
+            "name = \"NASA\"   # APOD\nurl = \"https://example.com/apod.rss\"\nenabled = true"
+        ));
+        assert!(saved.contains("# photography feeds\n[[sources.feeds]]"));
+
+        let (reparsed, _) = Config::parse(&saved).unwrap();
+        assert_eq!(reparsed.sources, config.sources);
+        assert_eq!(reparsed.general.backend, BackendType::Swww);
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        (name == "WALLHAVEN_API_KEY").then(|| "s3cret".to_string())
+    }
+
+    #[test]
+    fn test_interpolate_set_var() {
+        let mut raw: toml::Table = r#"
+            [sources.wallhaven]
+            api_key = "${WALLHAVEN_API_KEY}"
+            [[sources.feeds]]
+            url = "https://example.com/rss?token=${WALLHAVEN_API_KEY}&page=1"
+        "#
+        .parse()
+        .unwrap();
+        assert!(interpolate_sources(&mut raw, &lookup).is_empty());
+        let sources = &raw["sources"];
+        assert_eq!(sources["wallhaven"]["api_key"].as_str(), Some("s3cret"));
+        assert_eq!(
+            sources["feeds"][0]["url"].as_str(),
+            Some("https://example.com/rss?token=s3cret&page=1")
+        );
+    }
+
+    #[test]
+    fn test_interpolate_unset_var_kept_with_warning() {
+        let mut raw: toml::Table = "[sources.pexels]\napi_key = \"${PEXELS_API_KEY}\""
+            .parse()
+            .unwrap();
+        let warnings = interpolate_sources(&mut raw, &lookup);
+        assert_eq!(
+            warnings,
+            vec!["`sources.pexels.api_key`: environment variable PEXELS_API_KEY is not set"]
+        );
+        assert_eq!(
+            raw["sources"]["pexels"]["api_key"].as_str(),
+            Some("${PEXELS_API_KEY}")
+        );
+    }
+
+    #[test]
+    fn test_interpolate_leaves_plain_values() {
+        for plain in [
+            "abc123",
+            "$HOME",
+            "${}",
+            "${not a var}",
+            "${UNCLOSED",
+            "cost: $5",
+        ] {
+            assert_eq!(interpolate(plain, &lookup), (plain.to_string(), vec![]));
+        }
+        // only [sources] is interpolated
+        let mut raw: toml::Table = "[general]\nuser_agent = \"${WALLHAVEN_API_KEY}\""
+            .parse()
+            .unwrap();
+        interpolate_sources(&mut raw, &lookup);
+        assert_eq!(
+            raw["general"]["user_agent"].as_str(),
+            Some("${WALLHAVEN_API_KEY}")
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_var_references() {
+        // HOME is set wherever the tests run
+        let content = "[sources.wallhaven]\nenabled = true\napi_key = \"${HOME}\"\n";
+        let (mut config, warnings) = Config::parse(content).unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(
+            config.sources["wallhaven"]["api_key"].as_str(),
+            Some(&*home)
+        );
+
+        config.disable_source("wallhaven").unwrap();
+        let saved = config.to_toml(Some(content)).unwrap();
+        assert!(saved.contains("api_key = \"${HOME}\""), "{saved}");
+        assert!(saved.contains("enabled = false"));
+    }
+
+    #[test]
+    fn test_allows_brightness() {
+        let filter = FilterConfig {
+            min_brightness: 0.1,
+            max_brightness: 0.4,
+            ..FilterConfig::default()
+        };
+        assert!(filter.allows_brightness(Some(0.3)));
+        assert!(!filter.allows_brightness(Some(0.8)));
+        assert!(!filter.allows_brightness(Some(0.05)));
+        assert!(filter.allows_brightness(None));
+    }
+
+    fn preview(width: u32, height: u32, tags: &[&str]) -> WallpaperPreview {
+        WallpaperPreview {
+            source_type: crate::models::SourceType::new("wallhaven"),
+            source_id: "abc".into(),
+            source_url: String::new(),
+            thumbnail_url: String::new(),
+            full_url: String::new(),
+            sample_url: None,
+            width,
+            height,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            meta: WallpaperMeta::default(),
+        }
+    }
+
+    #[test]
+    fn test_filter_matches_min_resolution() {
+        let filter = FilterConfig {
+            min_width: 2560,
+            min_height: 1440,
+            ..FilterConfig::default()
+        };
+        assert!(filter.matches(&preview(3840, 2160, &[])));
+        assert!(!filter.matches(&preview(1920, 1080, &[])));
+        assert!(!filter.matches(&preview(3840, 1080, &[])));
+        // unknown size (feeds) passes
+        assert!(filter.matches(&preview(0, 0, &[])));
+    }
+
+    #[test]
+    fn test_filter_matches_excluded_tags() {
+        let filter = FilterConfig {
+            exclude_tags: vec!["anime".into()],
+            ..FilterConfig::default()
+        };
+        assert!(!filter.matches(&preview(3840, 2160, &["Anime"])));
+        assert!(!filter.matches(&preview(0, 0, &["anime"])));
+        assert!(filter.matches(&preview(3840, 2160, &["nature"])));
     }
 }
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use image::codecs::jpeg::JpegEncoder;
-use image::ImageReader;
+use image::{ImageFormat, ImageReader, RgbaImage};
 use std::io::Cursor;
 
 /// Compute the crop rectangle that a fill algorithm would use.
@@ -22,36 +22,46 @@ pub fn compute_crop_rect(img_w: u32, img_h: u32, mon_w: u32, mon_h: u32) -> (u32
     }
 }
 
+/// Default brightness kept in cropped regions (30%).
+pub const DEFAULT_DARKEN: f32 = 0.3;
+
 /// Generate a version of the image with cropped regions darkened.
 /// `darken_factor` controls brightness of cropped areas (0.3 = 30% brightness).
+/// PNG sources stay PNG, keeping transparency; everything else becomes JPEG.
 pub fn generate_crop_overlay(
     image_bytes: &[u8],
     mon_w: u32,
     mon_h: u32,
     darken_factor: f32,
 ) -> Result<Vec<u8>> {
-    let img = ImageReader::new(Cursor::new(image_bytes))
-        .with_guessed_format()?
-        .decode()?;
+    let reader = ImageReader::new(Cursor::new(image_bytes)).with_guessed_format()?;
+    let is_png = reader.format() == Some(ImageFormat::Png);
+    let img = reader.decode()?;
 
     let mut rgba = img.to_rgba8();
-    let (iw, ih) = (rgba.width(), rgba.height());
-    let (cx, cy, cw, ch) = compute_crop_rect(iw, ih, mon_w, mon_h);
+    let rect = compute_crop_rect(rgba.width(), rgba.height(), mon_w, mon_h);
+    darken_outside(&mut rgba, rect, darken_factor.clamp(0.0, 1.0));
+
+    let mut buf = Vec::new();
+    if is_png {
+        rgba.write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)?;
+    } else {
+        let rgb = image::DynamicImage::ImageRgba8(rgba).to_rgb8();
+        let encoder = JpegEncoder::new_with_quality(&mut buf, 85);
+        rgb.write_with_encoder(encoder)?;
+    }
+    Ok(buf)
+}
 
-    for (x, y, pixel) in rgba.enumerate_pixels_mut() {
+fn darken_outside(img: &mut RgbaImage, (cx, cy, cw, ch): (u32, u32, u32, u32), factor: f32) {
+    for (x, y, pixel) in img.enumerate_pixels_mut() {
         let inside = x >= cx && x < cx + cw && y >= cy && y < cy + ch;
         if !inside {
-            pixel[0] = (pixel[0] as f32 * darken_factor) as u8;
-            pixel[1] = (pixel[1] as f32 * darken_factor) as u8;
-            pixel[2] = (pixel[2] as f32 * darken_factor) as u8;
+            pixel[0] = (pixel[0] as f32 * factor) as u8;
+            pixel[1] = (pixel[1] as f32 * factor) as u8;
+            pixel[2] = (pixel[2] as f32 * factor) as u8;
         }
     }
-
-    let rgb = image::DynamicImage::ImageRgba8(rgba).to_rgb8();
-    let mut buf = Vec::new();
-    let encoder = JpegEncoder::new_with_quality(&mut buf, 85);
-    rgb.write_with_encoder(encoder)?;
-    Ok(buf)
 }
 
 /// Returns true if image and monitor aspect ratios match within tolerance.
@@ -67,6 +77,7 @@ pub fn ratios_match(img_w: u32, img_h: u32, mon_w: u32, mon_h: u32, tolerance: f
 #[cfg(test)]
 mod tests {
     use super::*;
+    use image::Rgba;
 
     #[test]
     fn same_aspect_ratio_no_crop() {
@@ -110,6 +121,15 @@ mod tests {
         assert!(!ratios_match(3440, 1440, 1920, 1080, 0.01));
     }
 
+    #[test]
+    fn darken_dims_only_outside() {
+        let mut img = RgbaImage::from_pixel(400, 100, Rgba([200, 200, 200, 255]));
+        let rect = compute_crop_rect(400, 100, 1920, 1080);
+        darken_outside(&mut img, rect, 0.5);
+        assert_eq!(img.get_pixel(0, 50)[0], 100);
+        assert_eq!(img.get_pixel(200, 50)[0], 200);
+    }
+
     #[test]
     fn overlay_produces_valid_jpeg() {
         // Create a tiny test image
@@ -130,4 +150,18 @@ mod tests {
         assert_eq!(decoded.width(), 100);
         assert_eq!(decoded.height(), 100);
     }
+
+    #[test]
+    fn png_overlay_stays_png_with_alpha() {
+        let img = RgbaImage::from_pixel(100, 50, Rgba([128, 128, 128, 64]));
+        let mut bytes = Vec::new();
+        img.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+
+        let result = generate_crop_overlay(&bytes, 1080, 1080, 0.3).unwrap();
+        assert_eq!(image::guess_format(&result).unwrap(), ImageFormat::Png);
+        let decoded = image::load_from_memory(&result).unwrap().to_rgba8();
+        assert_eq!(decoded.get_pixel(50, 25)[3], 64);
+        assert_eq!(decoded.get_pixel(0, 25)[0], 38);
+    }
 }
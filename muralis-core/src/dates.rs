@@ -0,0 +1,85 @@
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone, Utc};
+
+/// Parse a `--since` bound: an RFC 3339 timestamp, a `YYYY-MM-DD` date
+/// (local midnight), or a relative age like `30m`, `12h`, `7d`, `2w`.
+pub fn parse_since(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_bound(s, now, false)
+}
+
+/// Parse an `--until` bound. Same forms as [`parse_since`], but a plain date
+/// covers the whole day, so the bound is the following local midnight.
+pub fn parse_until(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    parse_bound(s, now, true)
+}
+
+fn parse_bound(s: &str, now: DateTime<Utc>, end_of_day: bool) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Some(age) = parse_relative(s) {
+        return now.checked_sub_signed(age);
+    }
+    if let Ok(ts) = DateTime::parse_from_rfc3339(s) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    let mut date = NaiveDate::parse_from_str(s, "%Y-%m-%d").ok()?;
+    if end_of_day {
+        date = date.succ_opt()?;
+    }
+    let midnight = date.and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn parse_relative(s: &str) -> Option<Duration> {
+    let unit = s.chars().last()?;
+    let num: i64 = s[..s.len() - unit.len_utf8()].parse().ok()?;
+    match unit {
+        'm' => Duration::try_minutes(num),
+        'h' => Duration::try_hours(num),
+        'd' => Duration::try_days(num),
+        'w' => Duration::try_weeks(num),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2025-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_relative() {
+        assert_eq!(
+            parse_since("7d", now()).unwrap().to_rfc3339(),
+            "2025-03-03T12:00:00+00:00"
+        );
+        assert_eq!(
+            parse_since("12h", now()).unwrap().to_rfc3339(),
+            "2025-03-10T00:00:00+00:00"
+        );
+        assert_eq!(parse_since("2w", now()), parse_since("14d", now()));
+    }
+
+    #[test]
+    fn test_absolute() {
+        let ts = parse_since("2025-01-01T08:30:00+02:00", now()).unwrap();
+        assert_eq!(ts.to_rfc3339(), "2025-01-01T06:30:00+00:00");
+
+        let since = parse_since("2025-01-01", now()).unwrap();
+        let until = parse_until("2025-01-01", now()).unwrap();
+        assert_eq!(until - since, Duration::days(1));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for s in ["", "d", "7x", "yesterday", "2025-13-01", "-"] {
+            assert!(parse_since(s, now()).is_none(), "{s}");
+        }
+    }
+}
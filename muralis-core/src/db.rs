@@ -1,62 +1,210 @@
-use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rusqlite::backup::Progress;
+use rusqlite::{params, Connection, DatabaseName, OptionalExtension};
 
 use crate::error::{MuralisError, Result};
-use crate::models::{BlacklistEntry, SourceType, Wallpaper};
+use crate::models::{BlacklistEntry, Collection, CropRect, SourceType, Wallpaper, WallpaperMeta};
+
+/// Max recent queries kept per source in the search history.
+pub const SEARCH_HISTORY_CAP: u32 = 20;
+/// Number of applied wallpapers remembered for `restore`.
+pub const APPLIED_HISTORY_CAP: u32 = 50;
 
 pub struct Database {
     conn: Connection,
 }
 
+/// One schema step. `MIGRATIONS[n]` takes `user_version` from n to n + 1.
+type Migration = fn(&Connection) -> Result<()>;
+
+/// Schema history, oldest first. Append new steps; never edit shipped ones.
+const MIGRATIONS: &[Migration] = &[baseline_schema];
+
+/// Run the migrations past the database's `user_version`, each in its own
+/// transaction with the version bump, so a failing step leaves the schema
+/// and version as they were.
+fn run_migrations(conn: &mut Connection, migrations: &[Migration]) -> Result<()> {
+    let current: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (version, migration) in migrations.iter().enumerate().skip(current.max(0) as usize) {
+        let tx = conn.transaction()?;
+        migration(&tx)?;
+        tx.pragma_update(None, "user_version", version as i64 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+fn open_file(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    conn.pragma_update(None, "foreign_keys", "ON")?;
+    Ok(conn)
+}
+
+/// `<db>.bak`, the copy taken before migrating.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Migrate the database at `path`. When it already holds data and has
+/// migrations pending, it is copied to `<db>.bak` first; if a step fails,
+/// the copy is put back so the database is exactly as it was.
+fn migrate_with_backup(
+    mut conn: Connection,
+    path: &Path,
+    migrations: &[Migration],
+) -> Result<Connection> {
+    let current: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if current.max(0) as usize >= migrations.len() {
+        return Ok(conn);
+    }
+    let tables: i64 = conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| row.get(0))?;
+    if tables == 0 {
+        // brand-new database: nothing to protect
+        run_migrations(&mut conn, migrations)?;
+        return Ok(conn);
+    }
+
+    let backup = backup_path(path);
+    let _ = std::fs::remove_file(&backup);
+    conn.execute("VACUUM INTO ?1", params![backup.to_string_lossy()])?;
+
+    let Err(e) = run_migrations(&mut conn, migrations) else {
+        tracing::info!(backup = %backup.display(), "database migrated to version {}", migrations.len());
+        return Ok(conn);
+    };
+    // copy back through SQLite, which keeps the WAL and any connections
+    // other processes (the daemon) hold open consistent
+    if let Err(restore) = conn.restore(DatabaseName::Main, &backup, None::<fn(Progress)>) {
+        return Err(MuralisError::Migration(format!(
+            "{e}; restoring {} from {} failed too: {restore}",
+            path.display(),
+            backup.display()
+        )));
+    }
+    Err(MuralisError::Migration(format!(
+        "{e}; restored {} from {}",
+        path.display(),
+        backup.display()
+    )))
+}
+
+/// Whether every whitespace-separated word of `query` is a case-insensitive
+/// substring of some tag. An empty query matches everything.
+pub fn tags_match(tags: &[String], query: &str) -> bool {
+    let tags: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+    query
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .all(|word| tags.iter().any(|t| t.contains(&word)))
+}
+
+/// Migration 0: the schema as of versioning. Idempotent, so databases
+/// created before `user_version` was tracked upgrade in place.
+fn baseline_schema(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS wallpapers (
+            id TEXT PRIMARY KEY,
+            source_type TEXT NOT NULL,
+            source_id TEXT NOT NULL,
+            source_url TEXT,
+            width INTEGER NOT NULL,
+            height INTEGER NOT NULL,
+            tags TEXT NOT NULL DEFAULT '[]',
+            file_path TEXT NOT NULL,
+            added_at TEXT NOT NULL,
+            last_used TEXT,
+            use_count INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE TABLE IF NOT EXISTS blacklist (
+            source_id TEXT NOT NULL,
+            source TEXT NOT NULL,
+            blacklisted_at TEXT NOT NULL,
+            PRIMARY KEY (source_id, source)
+        );
+        CREATE TABLE IF NOT EXISTS blacklist_tags (
+            tag TEXT PRIMARY KEY,
+            blacklisted_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS search_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            source TEXT NOT NULL,
+            query TEXT NOT NULL,
+            searched_at TEXT NOT NULL,
+            UNIQUE (source, query)
+        );
+        CREATE TABLE IF NOT EXISTS applied_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            wallpaper TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS collections (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS collection_members (
+            collection_id INTEGER NOT NULL REFERENCES collections(id) ON DELETE CASCADE,
+            wallpaper_id TEXT NOT NULL,
+            PRIMARY KEY (collection_id, wallpaper_id)
+        );",
+    )?;
+    ensure_column(conn, "wallpapers", "brightness", "REAL")?;
+    for column in ["crop_x", "crop_y", "crop_w", "crop_h"] {
+        ensure_column(conn, "wallpapers", column, "INTEGER")?;
+    }
+    ensure_column(conn, "wallpapers", "captured_at", "TEXT")?;
+    ensure_column(conn, "wallpapers", "photographer", "TEXT")?;
+    ensure_column(conn, "wallpapers", "file_size", "INTEGER")?;
+    ensure_column(conn, "wallpapers", "colors", "TEXT")?;
+    Ok(())
+}
+
+/// Add a column to an existing table if an older schema lacks it.
+fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({table})"))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {table} ADD COLUMN {column} {decl}"))?;
+    }
+    Ok(())
+}
+
 impl Database {
-    pub fn open(path: &std::path::Path) -> Result<Self> {
-        let conn = Connection::open(path)?;
-        conn.pragma_update(None, "journal_mode", "WAL")?;
-        conn.pragma_update(None, "foreign_keys", "ON")?;
-        let db = Self { conn };
-        db.migrate()?;
-        Ok(db)
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = migrate_with_backup(open_file(path)?, path, MIGRATIONS)?;
+        Ok(Self { conn })
     }
 
     pub fn open_in_memory() -> Result<Self> {
         let conn = Connection::open_in_memory()?;
-        let db = Self { conn };
+        let mut db = Self { conn };
         db.migrate()?;
         Ok(db)
     }
 
-    fn migrate(&self) -> Result<()> {
-        self.conn.execute_batch(
-            "CREATE TABLE IF NOT EXISTS wallpapers (
-                id TEXT PRIMARY KEY,
-                source_type TEXT NOT NULL,
-                source_id TEXT NOT NULL,
-                source_url TEXT,
-                width INTEGER NOT NULL,
-                height INTEGER NOT NULL,
-                tags TEXT NOT NULL DEFAULT '[]',
-                file_path TEXT NOT NULL,
-                added_at TEXT NOT NULL,
-                last_used TEXT,
-                use_count INTEGER NOT NULL DEFAULT 0
-            );
-            CREATE TABLE IF NOT EXISTS blacklist (
-                source_id TEXT NOT NULL,
-                source TEXT NOT NULL,
-                blacklisted_at TEXT NOT NULL,
-                PRIMARY KEY (source_id, source)
-            );",
-        )?;
-        Ok(())
+    /// Apply the migrations this database hasn't seen yet.
+    fn migrate(&mut self) -> Result<()> {
+        run_migrations(&mut self.conn, MIGRATIONS)
     }
 
     // -- Wallpaper CRUD --
 
     pub fn insert_wallpaper(&self, wp: &Wallpaper) -> Result<()> {
         let tags_json = serde_json::to_string(&wp.tags)?;
+        let colors_json = serde_json::to_string(&wp.colors)?;
         self.conn.execute(
             "INSERT OR REPLACE INTO wallpapers
-             (id, source_type, source_id, source_url, width, height, tags, file_path, added_at, last_used, use_count)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+             (id, source_type, source_id, source_url, width, height, tags, file_path, added_at, last_used, use_count, brightness, crop_x, crop_y, crop_w, crop_h, captured_at, photographer, file_size, colors)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20)",
             params![
                 wp.id,
                 wp.source_type.to_string(),
@@ -69,62 +217,64 @@ impl Database {
                 wp.added_at,
                 wp.last_used,
                 wp.use_count,
+                wp.brightness,
+                wp.crop.map(|c| c.x),
+                wp.crop.map(|c| c.y),
+                wp.crop.map(|c| c.width),
+                wp.crop.map(|c| c.height),
+                wp.meta.captured_at,
+                wp.meta.photographer,
+                wp.meta.file_size.map(|n| n as i64),
+                colors_json,
             ],
         )?;
         Ok(())
     }
 
     pub fn get_wallpaper(&self, id: &str) -> Result<Wallpaper> {
-        let wp = self.conn.query_row(
-            "SELECT id, source_type, source_id, source_url, width, height, tags, file_path, added_at, last_used, use_count
-             FROM wallpapers WHERE id = ?1",
-            params![id],
-            |row| {
-                let tags_str: String = row.get(6)?;
-                let source_str: String = row.get(1)?;
-                Ok(WallpaperRow {
-                    id: row.get(0)?,
-                    source_type: source_str,
-                    source_id: row.get(2)?,
-                    source_url: row.get(3)?,
-                    width: row.get(4)?,
-                    height: row.get(5)?,
-                    tags: tags_str,
-                    file_path: row.get(7)?,
-                    added_at: row.get(8)?,
-                    last_used: row.get(9)?,
-                    use_count: row.get(10)?,
-                })
-            },
-        ).map_err(|e| match e {
-            rusqlite::Error::QueryReturnedNoRows => MuralisError::WallpaperNotFound(id.to_string()),
-            other => MuralisError::Database(other),
-        })?;
+        let wp = self
+            .conn
+            .query_row(
+                &format!("SELECT {WALLPAPER_COLUMNS} FROM wallpapers WHERE id = ?1"),
+                params![id],
+                read_wallpaper_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => {
+                    MuralisError::WallpaperNotFound(id.to_string())
+                }
+                other => MuralisError::Database(other),
+            })?;
         row_to_wallpaper(wp)
     }
 
     pub fn list_wallpapers(&self) -> Result<Vec<Wallpaper>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, source_type, source_id, source_url, width, height, tags, file_path, added_at, last_used, use_count
-             FROM wallpapers ORDER BY added_at DESC",
-        )?;
-        let rows = stmt.query_map([], |row| {
-            let tags_str: String = row.get(6)?;
-            let source_str: String = row.get(1)?;
-            Ok(WallpaperRow {
-                id: row.get(0)?,
-                source_type: source_str,
-                source_id: row.get(2)?,
-                source_url: row.get(3)?,
-                width: row.get(4)?,
-                height: row.get(5)?,
-                tags: tags_str,
-                file_path: row.get(7)?,
-                added_at: row.get(8)?,
-                last_used: row.get(9)?,
-                use_count: row.get(10)?,
-            })
-        })?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {WALLPAPER_COLUMNS} FROM wallpapers ORDER BY added_at DESC"
+        ))?;
+        let rows = stmt.query_map([], read_wallpaper_row)?;
+        let mut wallpapers = Vec::new();
+        for row in rows {
+            wallpapers.push(row_to_wallpaper(row?)?);
+        }
+        Ok(wallpapers)
+    }
+
+    /// Wallpapers added in `[since, until)`, newest first. Either bound may be open.
+    pub fn list_wallpapers_between(
+        &self,
+        since: Option<&DateTime<Utc>>,
+        until: Option<&DateTime<Utc>>,
+    ) -> Result<Vec<Wallpaper>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {WALLPAPER_COLUMNS} FROM wallpapers
+             WHERE (?1 IS NULL OR julianday(added_at) >= julianday(?1))
+               AND (?2 IS NULL OR julianday(added_at) < julianday(?2))
+             ORDER BY added_at DESC"
+        ))?;
+        let since = since.map(|t| t.to_rfc3339());
+        let until = until.map(|t| t.to_rfc3339());
+        let rows = stmt.query_map(params![since, until], read_wallpaper_row)?;
         let mut wallpapers = Vec::new();
         for row in rows {
             wallpapers.push(row_to_wallpaper(row?)?);
@@ -136,6 +286,10 @@ impl Database {
         let count = self
             .conn
             .execute("DELETE FROM wallpapers WHERE id = ?1", params![id])?;
+        self.conn.execute(
+            "DELETE FROM collection_members WHERE wallpaper_id = ?1",
+            params![id],
+        )?;
         Ok(count > 0)
     }
 
@@ -148,6 +302,51 @@ impl Database {
         Ok(())
     }
 
+    pub fn set_brightness(&self, id: &str, brightness: f32) -> Result<()> {
+        self.conn.execute(
+            "UPDATE wallpapers SET brightness = ?1 WHERE id = ?2",
+            params![brightness, id],
+        )?;
+        Ok(())
+    }
+
+    pub fn set_colors(&self, id: &str, colors: &[[u8; 3]]) -> Result<()> {
+        self.conn.execute(
+            "UPDATE wallpapers SET colors = ?1 WHERE id = ?2",
+            params![serde_json::to_string(colors)?, id],
+        )?;
+        Ok(())
+    }
+
+    /// Store (or clear) the crop applied when this wallpaper is set.
+    pub fn set_crop(&self, id: &str, crop: Option<CropRect>) -> Result<()> {
+        let count = self.conn.execute(
+            "UPDATE wallpapers SET crop_x = ?1, crop_y = ?2, crop_w = ?3, crop_h = ?4 WHERE id = ?5",
+            params![
+                crop.map(|c| c.x),
+                crop.map(|c| c.y),
+                crop.map(|c| c.width),
+                crop.map(|c| c.height),
+                id
+            ],
+        )?;
+        if count == 0 {
+            return Err(MuralisError::WallpaperNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn update_tags(&self, id: &str, tags: &[String]) -> Result<()> {
+        let count = self.conn.execute(
+            "UPDATE wallpapers SET tags = ?1 WHERE id = ?2",
+            params![serde_json::to_string(tags)?, id],
+        )?;
+        if count == 0 {
+            return Err(MuralisError::WallpaperNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
     pub fn wallpaper_count(&self) -> Result<u32> {
         let count: u32 = self
             .conn
@@ -164,6 +363,23 @@ impl Database {
         Ok(count > 0)
     }
 
+    /// ID and palette of the favorite from this source, or None if it isn't one.
+    pub fn favorite_by_source(
+        &self,
+        source_type: &str,
+        source_id: &str,
+    ) -> Result<Option<(String, Vec<[u8; 3]>)>> {
+        let row: Option<(String, Option<String>)> = self
+            .conn
+            .query_row(
+                "SELECT id, colors FROM wallpapers WHERE source_type = ?1 AND source_id = ?2 LIMIT 1",
+                params![source_type, source_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(row.map(|(id, c)| (id, parse_colors(c.as_deref()))))
+    }
+
     pub fn wallpaper_exists(&self, id: &str) -> Result<bool> {
         let count: u32 = self.conn.query_row(
             "SELECT COUNT(*) FROM wallpapers WHERE id = ?1",
@@ -225,10 +441,283 @@ impl Database {
         }
         Ok(entries)
     }
+
+    // -- Tag blacklist --
+
+    /// Hide future results carrying `tag`. Tags are stored lowercased.
+    pub fn add_tag_blacklist(&self, tag: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO blacklist_tags (tag, blacklisted_at) VALUES (?1, ?2)",
+            params![tag.trim().to_lowercase(), now],
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_tag_blacklist(&self, tag: &str) -> Result<bool> {
+        let count = self.conn.execute(
+            "DELETE FROM blacklist_tags WHERE tag = ?1",
+            params![tag.trim().to_lowercase()],
+        )?;
+        Ok(count > 0)
+    }
+
+    pub fn list_tag_blacklist(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT tag FROM blacklist_tags ORDER BY tag")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut tags = Vec::new();
+        for row in rows {
+            tags.push(row?);
+        }
+        Ok(tags)
+    }
+
+    // -- Collections --
+
+    /// Create an empty collection. Returns false if it already exists.
+    pub fn create_collection(&self, name: &str) -> Result<bool> {
+        let now = chrono::Utc::now().to_rfc3339();
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO collections (name, created_at) VALUES (?1, ?2)",
+            params![name, now],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Delete a collection; its favorites stay in the library.
+    /// Returns false if there was no such collection.
+    pub fn delete_collection(&self, name: &str) -> Result<bool> {
+        self.conn.execute(
+            "DELETE FROM collection_members
+             WHERE collection_id IN (SELECT id FROM collections WHERE name = ?1)",
+            params![name],
+        )?;
+        let deleted = self
+            .conn
+            .execute("DELETE FROM collections WHERE name = ?1", params![name])?;
+        Ok(deleted > 0)
+    }
+
+    /// Add a favorite to a collection, creating the collection if needed.
+    /// A favorite may be in several collections.
+    pub fn add_to_collection(&self, wallpaper_id: &str, name: &str) -> Result<()> {
+        if !self.wallpaper_exists(wallpaper_id)? {
+            return Err(MuralisError::WallpaperNotFound(wallpaper_id.to_string()));
+        }
+        self.create_collection(name)?;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO collection_members (collection_id, wallpaper_id)
+             SELECT id, ?2 FROM collections WHERE name = ?1",
+            params![name, wallpaper_id],
+        )?;
+        Ok(())
+    }
+
+    /// Take a favorite out of a collection. Returns false if it wasn't in it.
+    pub fn remove_from_collection(&self, wallpaper_id: &str, name: &str) -> Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM collection_members
+             WHERE wallpaper_id = ?1
+               AND collection_id IN (SELECT id FROM collections WHERE name = ?2)",
+            params![wallpaper_id, name],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// IDs of favorites in at least one collection.
+    pub fn collected_wallpaper_ids(&self) -> Result<HashSet<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT wallpaper_id FROM collection_members")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut ids = HashSet::new();
+        for row in rows {
+            ids.insert(row?);
+        }
+        Ok(ids)
+    }
+
+    /// All collections by name, with their sizes.
+    pub fn list_collections(&self) -> Result<Vec<Collection>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.name, c.created_at, COUNT(m.wallpaper_id)
+             FROM collections c
+             LEFT JOIN collection_members m ON m.collection_id = c.id
+             GROUP BY c.id
+             ORDER BY c.name",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(Collection {
+                name: row.get(0)?,
+                created_at: row.get(1)?,
+                size: row.get(2)?,
+            })
+        })?;
+        let mut collections = Vec::new();
+        for row in rows {
+            collections.push(row?);
+        }
+        Ok(collections)
+    }
+
+    /// Make `name` the only collection containing the wallpaper,
+    /// creating the collection if needed.
+    pub fn move_to_collection(&self, wallpaper_id: &str, name: &str) -> Result<()> {
+        if !self.wallpaper_exists(wallpaper_id)? {
+            return Err(MuralisError::WallpaperNotFound(wallpaper_id.to_string()));
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT OR IGNORE INTO collections (name, created_at) VALUES (?1, ?2)",
+            params![name, now],
+        )?;
+        self.conn.execute(
+            "DELETE FROM collection_members WHERE wallpaper_id = ?1",
+            params![wallpaper_id],
+        )?;
+        self.conn.execute(
+            "INSERT INTO collection_members (collection_id, wallpaper_id)
+             SELECT id, ?2 FROM collections WHERE name = ?1",
+            params![name, wallpaper_id],
+        )?;
+        Ok(())
+    }
+
+    /// Wallpapers in the named collection, newest first.
+    /// Unknown collections are empty.
+    pub fn list_collection_wallpapers(&self, name: &str) -> Result<Vec<Wallpaper>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {WALLPAPER_COLUMNS} FROM wallpapers WHERE id IN (
+                SELECT m.wallpaper_id FROM collection_members m
+                JOIN collections c ON c.id = m.collection_id
+                WHERE c.name = ?1
+             )
+             ORDER BY added_at DESC"
+        ))?;
+        let rows = stmt.query_map(params![name], read_wallpaper_row)?;
+        let mut wallpapers = Vec::new();
+        for row in rows {
+            wallpapers.push(row_to_wallpaper(row?)?);
+        }
+        Ok(wallpapers)
+    }
+
+    // -- Search history --
+
+    /// Record a query for a source, moving it to the front if already present.
+    /// Keeps at most `cap` entries per source.
+    pub fn record_query(&self, source: &str, query: &str, cap: u32) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT OR REPLACE INTO search_history (source, query, searched_at) VALUES (?1, ?2, ?3)",
+            params![source, query, now],
+        )?;
+        self.conn.execute(
+            "DELETE FROM search_history WHERE source = ?1 AND id NOT IN (
+                SELECT id FROM search_history WHERE source = ?1 ORDER BY id DESC LIMIT ?2
+            )",
+            params![source, cap],
+        )?;
+        Ok(())
+    }
+
+    /// Most recent queries for a source, newest first.
+    pub fn recent_queries(&self, source: &str, limit: u32) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT query FROM search_history WHERE source = ?1 ORDER BY id DESC LIMIT ?2",
+        )?;
+        let rows = stmt.query_map(params![source, limit], |row| row.get::<_, String>(0))?;
+        let mut queries = Vec::new();
+        for row in rows {
+            queries.push(row?);
+        }
+        Ok(queries)
+    }
+
+    // -- Applied history --
+
+    /// Remember that `wallpaper` (a library ID or file path) was applied,
+    /// keeping the newest `cap` entries. Re-applying the newest is a no-op.
+    pub fn record_applied(&self, wallpaper: &str, cap: u32) -> Result<()> {
+        if self.recent_applied(1)?.first().map(String::as_str) == Some(wallpaper) {
+            return Ok(());
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "INSERT INTO applied_history (wallpaper, applied_at) VALUES (?1, ?2)",
+            params![wallpaper, now],
+        )?;
+        self.conn.execute(
+            "DELETE FROM applied_history WHERE id NOT IN (
+                SELECT id FROM applied_history ORDER BY id DESC LIMIT ?1
+            )",
+            params![cap],
+        )?;
+        Ok(())
+    }
+
+    /// Recently applied wallpapers, newest (the current one) first.
+    pub fn recent_applied(&self, limit: u32) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT wallpaper FROM applied_history ORDER BY id DESC LIMIT ?1")?;
+        let rows = stmt.query_map(params![limit], |row| row.get::<_, String>(0))?;
+        let mut applied = Vec::new();
+        for row in rows {
+            applied.push(row?);
+        }
+        Ok(applied)
+    }
 }
 
 // Internal helper types
 
+/// Column order read by `read_wallpaper_row`.
+const WALLPAPER_COLUMNS: &str = "id, source_type, source_id, source_url, width, height, tags, \
+     file_path, added_at, last_used, use_count, brightness, crop_x, crop_y, crop_w, crop_h, \
+     captured_at, photographer, file_size, colors";
+
+fn read_wallpaper_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<WallpaperRow> {
+    let crop = match (row.get(12)?, row.get(13)?, row.get(14)?, row.get(15)?) {
+        (Some(x), Some(y), Some(width), Some(height)) => Some(CropRect {
+            x,
+            y,
+            width,
+            height,
+        }),
+        _ => None,
+    };
+    Ok(WallpaperRow {
+        id: row.get(0)?,
+        source_type: row.get(1)?,
+        source_id: row.get(2)?,
+        source_url: row.get(3)?,
+        width: row.get(4)?,
+        height: row.get(5)?,
+        tags: row.get(6)?,
+        file_path: row.get(7)?,
+        added_at: row.get(8)?,
+        last_used: row.get(9)?,
+        use_count: row.get(10)?,
+        brightness: row.get(11)?,
+        crop,
+        meta: WallpaperMeta {
+            captured_at: row.get(16)?,
+            photographer: row.get(17)?,
+            file_size: row.get::<_, Option<i64>>(18)?.map(|n| n as u64),
+        },
+        colors: row.get(19)?,
+    })
+}
+
+/// Palette from its JSON column; missing or malformed reads as unanalyzed.
+fn parse_colors(json: Option<&str>) -> Vec<[u8; 3]> {
+    json.and_then(|c| serde_json::from_str(c).ok())
+        .unwrap_or_default()
+}
+
 struct WallpaperRow {
     id: String,
     source_type: String,
@@ -241,11 +730,19 @@ struct WallpaperRow {
     added_at: String,
     last_used: Option<String>,
     use_count: u32,
+    brightness: Option<f32>,
+    crop: Option<CropRect>,
+    meta: WallpaperMeta,
+    colors: Option<String>,
 }
 
 fn row_to_wallpaper(row: WallpaperRow) -> Result<Wallpaper> {
     let source_type = SourceType::new(row.source_type);
-    let tags: Vec<String> = serde_json::from_str(&row.tags)?;
+    // one corrupt tags cell shouldn't take down the whole listing
+    let tags: Vec<String> = serde_json::from_str(&row.tags).unwrap_or_else(|e| {
+        tracing::warn!(id = %row.id, "malformed tags JSON, ignoring: {e}");
+        Vec::new()
+    });
     Ok(Wallpaper {
         id: row.id,
         source_type,
@@ -258,6 +755,10 @@ fn row_to_wallpaper(row: WallpaperRow) -> Result<Wallpaper> {
         added_at: row.added_at,
         last_used: row.last_used,
         use_count: row.use_count,
+        brightness: row.brightness,
+        crop: row.crop,
+        colors: parse_colors(row.colors.as_deref()),
+        meta: row.meta,
     })
 }
 
@@ -279,6 +780,10 @@ mod tests {
             added_at: "2025-01-01T00:00:00Z".into(),
             last_used: None,
             use_count: 0,
+            brightness: None,
+            crop: None,
+            colors: Vec::new(),
+            meta: Default::default(),
         }
     }
 
@@ -305,6 +810,62 @@ mod tests {
         assert_eq!(list.len(), 2);
     }
 
+    #[test]
+    fn test_list_wallpapers_between() {
+        let db = Database::open_in_memory().unwrap();
+        for (id, added_at) in [
+            ("old", "2025-01-01T00:00:00Z".to_string()),
+            ("mid", "2025-02-15T10:00:00+00:00".to_string()),
+            // chrono's own format, with sub-second precision
+            ("new", "2025-03-01T08:00:00.123456789+00:00".to_string()),
+        ] {
+            let mut wp = test_wallpaper(id);
+            wp.added_at = added_at;
+            db.insert_wallpaper(&wp).unwrap();
+        }
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let ids = |list: Vec<Wallpaper>| list.into_iter().map(|w| w.id).collect::<Vec<_>>();
+
+        let since = at("2025-02-01T00:00:00Z");
+        let until = at("2025-03-01T00:00:00Z");
+        assert_eq!(
+            ids(db
+                .list_wallpapers_between(Some(&since), Some(&until))
+                .unwrap()),
+            vec!["mid"]
+        );
+        assert_eq!(
+            ids(db.list_wallpapers_between(Some(&since), None).unwrap()),
+            vec!["new", "mid"]
+        );
+        assert_eq!(
+            ids(db.list_wallpapers_between(None, Some(&since)).unwrap()),
+            vec!["old"]
+        );
+        assert_eq!(db.list_wallpapers_between(None, None).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_crop_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("c")).unwrap();
+        assert!(db.get_wallpaper("c").unwrap().crop.is_none());
+
+        let rect = CropRect {
+            x: 10,
+            y: 20,
+            width: 1600,
+            height: 900,
+        };
+        db.set_crop("c", Some(rect)).unwrap();
+        assert_eq!(db.get_wallpaper("c").unwrap().crop, Some(rect));
+        assert_eq!(db.list_wallpapers().unwrap()[0].crop, Some(rect));
+
+        db.set_crop("c", None).unwrap();
+        assert!(db.get_wallpaper("c").unwrap().crop.is_none());
+        assert!(db.set_crop("missing", None).is_err());
+    }
+
     #[test]
     fn test_delete_wallpaper() {
         let db = Database::open_in_memory().unwrap();
@@ -331,6 +892,182 @@ mod tests {
         assert_eq!(wp.use_count, 2);
     }
 
+    #[test]
+    fn test_brightness_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("dark")).unwrap();
+        assert_eq!(db.get_wallpaper("dark").unwrap().brightness, None);
+
+        db.set_brightness("dark", 0.25).unwrap();
+        assert_eq!(db.get_wallpaper("dark").unwrap().brightness, Some(0.25));
+    }
+
+    #[test]
+    fn test_colors_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("pal")).unwrap();
+        assert!(db.get_wallpaper("pal").unwrap().colors.is_empty());
+
+        db.set_colors("pal", &[[200, 10, 10], [0, 0, 0]]).unwrap();
+        assert_eq!(
+            db.get_wallpaper("pal").unwrap().colors,
+            vec![[200, 10, 10], [0, 0, 0]]
+        );
+        assert_eq!(
+            db.favorite_by_source("wallhaven", "wh_123").unwrap(),
+            Some(("pal".to_string(), vec![[200, 10, 10], [0, 0, 0]]))
+        );
+        assert_eq!(db.favorite_by_source("wallhaven", "nope").unwrap(), None);
+    }
+
+    #[test]
+    fn test_ensure_column_upgrades_old_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE wallpapers (
+                id TEXT PRIMARY KEY, source_type TEXT NOT NULL, source_id TEXT NOT NULL,
+                source_url TEXT, width INTEGER NOT NULL, height INTEGER NOT NULL,
+                tags TEXT NOT NULL DEFAULT '[]', file_path TEXT NOT NULL,
+                added_at TEXT NOT NULL, last_used TEXT, use_count INTEGER NOT NULL DEFAULT 0
+            );",
+        )
+        .unwrap();
+        let mut db = Database { conn };
+        db.migrate().unwrap();
+        db.insert_wallpaper(&test_wallpaper("old")).unwrap();
+        assert_eq!(db.get_wallpaper("old").unwrap().brightness, None);
+        // idempotent
+        baseline_schema(&db.conn).unwrap();
+    }
+
+    #[test]
+    fn test_migrations_apply_pending_only() {
+        fn add_rating(conn: &Connection) -> Result<()> {
+            conn.execute_batch("ALTER TABLE wallpapers ADD COLUMN rating INTEGER")?;
+            Ok(())
+        }
+        fn broken(conn: &Connection) -> Result<()> {
+            conn.execute_batch("ALTER TABLE wallpapers ADD COLUMN flagged INTEGER; SELECT nope")?;
+            Ok(())
+        }
+        let version = |conn: &Connection| -> i64 {
+            conn.pragma_query_value(None, "user_version", |row| row.get(0))
+                .unwrap()
+        };
+
+        let mut conn = Connection::open_in_memory().unwrap();
+        assert_eq!(version(&conn), 0);
+        run_migrations(&mut conn, &[baseline_schema]).unwrap();
+        assert_eq!(version(&conn), 1);
+        let mut db = Database { conn };
+        db.insert_wallpaper(&test_wallpaper("old")).unwrap();
+
+        run_migrations(&mut db.conn, &[baseline_schema, add_rating]).unwrap();
+        assert_eq!(version(&db.conn), 2);
+        assert!(db.get_wallpaper("old").is_ok());
+        let rating: Option<i64> = db
+            .conn
+            .query_row("SELECT rating FROM wallpapers WHERE id = 'old'", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(rating, None);
+
+        // a failing step rolls back its changes and keeps the version
+        assert!(run_migrations(&mut db.conn, &[baseline_schema, add_rating, broken]).is_err());
+        assert_eq!(version(&db.conn), 2);
+        assert!(db.conn.prepare("SELECT flagged FROM wallpapers").is_err());
+        db.migrate().unwrap();
+        assert_eq!(version(&db.conn), 2);
+    }
+
+    #[test]
+    fn test_failed_migration_restores_backup() {
+        fn add_rating(conn: &Connection) -> Result<()> {
+            conn.execute_batch("ALTER TABLE wallpapers ADD COLUMN rating INTEGER")?;
+            Ok(())
+        }
+        fn broken(conn: &Connection) -> Result<()> {
+            conn.execute_batch("DROP TABLE wallpapers; SELECT nope")?;
+            Ok(())
+        }
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("muralis.db");
+        Database::open(&path)
+            .unwrap()
+            .insert_wallpaper(&test_wallpaper("kept"))
+            .unwrap();
+
+        let migrations = [baseline_schema, add_rating, broken];
+
+        // another connection, like the daemon's, mid-read throughout
+        let reader = open_file(&path).unwrap();
+        reader
+            .execute_batch("BEGIN; SELECT count(*) FROM wallpapers;")
+            .unwrap();
+        let conn = open_file(&path).unwrap();
+        let err = migrate_with_backup(conn, &path, &migrations).err().unwrap();
+        assert!(matches!(err, MuralisError::Migration(_)));
+        assert!(backup_path(&path).exists());
+        reader.execute_batch("COMMIT").unwrap();
+        assert!(reader.prepare("SELECT rating FROM wallpapers").is_err());
+
+        // the step that succeeded before the failure is undone as well
+        let db = Database::open(&path).unwrap();
+        assert!(db.get_wallpaper("kept").is_ok());
+        assert!(db.conn.prepare("SELECT rating FROM wallpapers").is_err());
+        let version: i64 = db
+            .conn
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+    }
+
+    #[test]
+    fn test_tags_match() {
+        let favorites: Vec<(&str, Vec<String>)> = [
+            ("forest", vec!["Nature", "forest"]),
+            ("city", vec!["city", "night"]),
+            ("lake", vec!["nature", "lake", "night"]),
+        ]
+        .into_iter()
+        .map(|(id, tags)| (id, tags.into_iter().map(String::from).collect()))
+        .collect();
+        let ids = |query: &str| -> Vec<&str> {
+            favorites
+                .iter()
+                .filter(|(_, tags)| tags_match(tags, query))
+                .map(|(id, _)| *id)
+                .collect()
+        };
+
+        assert_eq!(ids("nature"), vec!["forest", "lake"]);
+        assert_eq!(ids("NIGH"), vec!["city", "lake"]);
+        // every word must match
+        assert_eq!(ids("nature night"), vec!["lake"]);
+        assert!(ids("nature desert").is_empty());
+        assert_eq!(ids("  ").len(), 3);
+    }
+
+    #[test]
+    fn test_malformed_tags_tolerated() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("good")).unwrap();
+        db.insert_wallpaper(&test_wallpaper("bad")).unwrap();
+        db.conn
+            .execute(
+                "UPDATE wallpapers SET tags = '[\"broken' WHERE id = 'bad'",
+                [],
+            )
+            .unwrap();
+
+        let list = db.list_wallpapers().unwrap();
+        assert_eq!(list.len(), 2);
+        let bad = list.iter().find(|w| w.id == "bad").unwrap();
+        assert!(bad.tags.is_empty());
+        assert_eq!(db.get_wallpaper("good").unwrap().tags.len(), 2);
+    }
+
     #[test]
     fn test_wallpaper_not_found() {
         let db = Database::open_in_memory().unwrap();
@@ -356,6 +1093,19 @@ mod tests {
         assert!(!db.is_blacklisted("wh_bad", &source).unwrap());
     }
 
+    #[test]
+    fn test_tag_blacklist_crud() {
+        let db = Database::open_in_memory().unwrap();
+        db.add_tag_blacklist("Blurry").unwrap();
+        db.add_tag_blacklist(" anime ").unwrap();
+        db.add_tag_blacklist("blurry").unwrap();
+        assert_eq!(db.list_tag_blacklist().unwrap(), vec!["anime", "blurry"]);
+
+        assert!(db.remove_tag_blacklist("BLURRY").unwrap());
+        assert!(!db.remove_tag_blacklist("blurry").unwrap());
+        assert_eq!(db.list_tag_blacklist().unwrap(), vec!["anime"]);
+    }
+
     #[test]
     fn test_wallpaper_count() {
         let db = Database::open_in_memory().unwrap();
@@ -379,4 +1129,119 @@ mod tests {
         assert_eq!(loaded.tags, vec!["updated"]);
         assert_eq!(db.wallpaper_count().unwrap(), 1);
     }
+
+    #[test]
+    fn test_update_tags() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("tagged")).unwrap();
+        db.update_tags("tagged", &["city".into(), "rain".into()])
+            .unwrap();
+        assert_eq!(
+            db.get_wallpaper("tagged").unwrap().tags,
+            vec!["city", "rain"]
+        );
+        assert!(db.update_tags("missing", &[]).is_err());
+    }
+
+    #[test]
+    fn test_move_to_collection() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("a")).unwrap();
+        db.insert_wallpaper(&test_wallpaper("b")).unwrap();
+
+        db.move_to_collection("a", "work").unwrap();
+        db.move_to_collection("b", "work").unwrap();
+        assert_eq!(db.list_collection_wallpapers("work").unwrap().len(), 2);
+
+        // moving reassigns rather than adds
+        db.move_to_collection("a", "gaming").unwrap();
+        let work = db.list_collection_wallpapers("work").unwrap();
+        assert_eq!(work.len(), 1);
+        assert_eq!(work[0].id, "b");
+        assert_eq!(db.list_collection_wallpapers("gaming").unwrap()[0].id, "a");
+
+        db.delete_wallpaper("b").unwrap();
+        assert!(db.list_collection_wallpapers("work").unwrap().is_empty());
+        assert!(db.list_collection_wallpapers("nope").unwrap().is_empty());
+        assert!(matches!(
+            db.move_to_collection("missing", "work"),
+            Err(MuralisError::WallpaperNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_collection_crud() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&test_wallpaper("a")).unwrap();
+        db.insert_wallpaper(&test_wallpaper("b")).unwrap();
+
+        assert!(db.create_collection("nature").unwrap());
+        assert!(!db.create_collection("nature").unwrap());
+        db.add_to_collection("a", "nature").unwrap();
+        db.add_to_collection("b", "nature").unwrap();
+        // adding keeps other memberships and is idempotent
+        db.add_to_collection("a", "work").unwrap();
+        db.add_to_collection("a", "work").unwrap();
+
+        let names_sizes: Vec<(String, u32)> = db
+            .list_collections()
+            .unwrap()
+            .into_iter()
+            .map(|c| (c.name, c.size))
+            .collect();
+        assert_eq!(
+            names_sizes,
+            vec![("nature".to_string(), 2), ("work".to_string(), 1)]
+        );
+
+        assert!(db.remove_from_collection("a", "nature").unwrap());
+        assert!(!db.remove_from_collection("a", "nature").unwrap());
+        let nature = db.list_collection_wallpapers("nature").unwrap();
+        assert_eq!(nature.len(), 1);
+        assert_eq!(nature[0].id, "b");
+
+        assert!(db.delete_collection("nature").unwrap());
+        assert!(!db.delete_collection("nature").unwrap());
+        assert!(db.list_collection_wallpapers("nature").unwrap().is_empty());
+        assert_eq!(db.wallpaper_count().unwrap(), 2);
+        assert_eq!(db.list_collection_wallpapers("work").unwrap().len(), 1);
+
+        assert!(matches!(
+            db.add_to_collection("missing", "work"),
+            Err(MuralisError::WallpaperNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_search_history_cap_and_order() {
+        let db = Database::open_in_memory().unwrap();
+        for q in ["mountains", "ocean", "forest", "city"] {
+            db.record_query("Wallhaven", q, 3).unwrap();
+        }
+        // re-searching moves the query to the front
+        db.record_query("Wallhaven", "ocean", 3).unwrap();
+        db.record_query("Unsplash", "desert", 3).unwrap();
+
+        let recent = db.recent_queries("Wallhaven", 10).unwrap();
+        assert_eq!(recent, vec!["ocean", "city", "forest"]);
+
+        let recent = db.recent_queries("Wallhaven", 2).unwrap();
+        assert_eq!(recent, vec!["ocean", "city"]);
+
+        assert_eq!(db.recent_queries("Unsplash", 10).unwrap(), vec!["desert"]);
+        assert!(db.recent_queries("Pexels", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_applied_history_cap_and_dedup() {
+        let db = Database::open_in_memory().unwrap();
+        for wp in ["a", "b", "b", "c", "/tmp/x.png"] {
+            db.record_applied(wp, 3).unwrap();
+        }
+        assert_eq!(db.recent_applied(10).unwrap(), vec!["/tmp/x.png", "c", "b"]);
+
+        // going back to an older wallpaper is a new entry
+        db.record_applied("c", 3).unwrap();
+        assert_eq!(db.recent_applied(2).unwrap(), vec!["c", "/tmp/x.png"]);
+    }
 }
@@ -8,12 +8,18 @@ pub enum MuralisError {
     #[error("database error: {0}")]
     Database(#[from] rusqlite::Error),
 
+    #[error("database migration failed: {0}")]
+    Migration(String),
+
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("http error: {0}")]
     Http(#[from] reqwest::Error),
 
+    #[error("{context} failed: HTTP {status}")]
+    HttpStatus { status: u16, context: String },
+
     #[error("json error: {0}")]
     Json(#[from] serde_json::Error),
 
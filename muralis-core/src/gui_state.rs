@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MuralisError, Result};
+use crate::models::PreviewFit;
+use crate::paths::MuralisPaths;
+use crate::wallpapers::write_atomic;
+
+/// Window and view state the GUI restores on launch. Kept out of
+/// config.toml so saving it never looks like a config edit to the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GuiState {
+    pub window_width: u32,
+    pub window_height: u32,
+    /// ID of the last selected source tab, or "All".
+    pub last_tab: String,
+    /// Last preview sizing chosen in the GUI.
+    pub preview_fit: PreviewFit,
+}
+
+impl Default for GuiState {
+    fn default() -> Self {
+        Self {
+            window_width: 1400,
+            window_height: 900,
+            last_tab: "All".into(),
+            preview_fit: PreviewFit::Contain,
+        }
+    }
+}
+
+impl GuiState {
+    /// The saved state. Before the first save, state an older version
+    /// left in config.toml's `[gui]` table is picked up; else defaults.
+    pub fn load(paths: &MuralisPaths) -> Self {
+        let from = |content: String| toml::from_str::<Self>(&content).ok();
+        if let Some(state) = std::fs::read_to_string(paths.gui_state_file())
+            .ok()
+            .and_then(from)
+        {
+            return state;
+        }
+        std::fs::read_to_string(paths.config_file())
+            .ok()
+            .and_then(|c| toml::from_str::<toml::Table>(&c).ok())
+            .and_then(|mut raw| raw.remove("gui"))
+            .and_then(|gui| gui.try_into().ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, paths: &MuralisPaths) -> Result<()> {
+        let path = paths.gui_state_file();
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| MuralisError::Config(format!("failed to serialize GUI state: {e}")))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        write_atomic(&path, content.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_paths(tmp: &tempfile::TempDir) -> MuralisPaths {
+        MuralisPaths::for_test(tmp.path())
+    }
+
+    #[test]
+    fn test_gui_state_round_trip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = test_paths(&tmp);
+        assert_eq!(GuiState::load(&paths), GuiState::default());
+
+        let state = GuiState {
+            window_width: 1680,
+            window_height: 1050,
+            last_tab: "feed:Bing Daily".into(),
+            preview_fit: PreviewFit::Actual,
+        };
+        state.save(&paths).unwrap();
+        assert_eq!(GuiState::load(&paths), state);
+        // config.toml is left alone
+        assert!(!paths.config_file().exists());
+
+        let partial: GuiState = toml::from_str("last_tab = \"wallhaven\"\n").unwrap();
+        assert_eq!(partial.last_tab, "wallhaven");
+        assert_eq!(partial.window_width, 1400);
+    }
+
+    #[test]
+    fn test_gui_state_migrates_from_config() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = test_paths(&tmp);
+        std::fs::create_dir_all(&paths.config_dir).unwrap();
+        std::fs::write(
+            paths.config_file(),
+            "[gui]\nwindow_width = 1920\nlast_tab = \"bing\"\nprefetch_on_open = false\n",
+        )
+        .unwrap();
+
+        let state = GuiState::load(&paths);
+        assert_eq!(state.window_width, 1920);
+        assert_eq!(state.last_tab, "bing");
+
+        // once saved, the state file wins
+        GuiState {
+            last_tab: "apod".into(),
+            ..state
+        }
+        .save(&paths)
+        .unwrap();
+        assert_eq!(GuiState::load(&paths).last_tab, "apod");
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::Result;
+
+/// Descriptor of the lock a launched process inherited. It should mark it
+/// close-on-exec so its own children don't keep the lock alive.
+pub const LOCK_FD_ENV: &str = "MURALIS_LOCK_FD";
+
+/// Outcome of trying to claim a single-instance lock file.
+#[derive(Debug)]
+pub enum LockState {
+    Acquired(InstanceLock),
+    /// Someone holds the lock; the pid it recorded, if any yet.
+    Running(Option<u32>),
+}
+
+/// An exclusive `flock` on the lock file. The kernel drops it when the last
+/// descriptor closes, so a crashed holder never leaves a stale lock.
+#[derive(Debug)]
+pub struct InstanceLock {
+    file: File,
+}
+
+/// Claim `path` unless another process holds it.
+pub fn acquire(path: &Path) -> Result<LockState> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?;
+    if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+        let err = std::io::Error::last_os_error();
+        if err.kind() == ErrorKind::WouldBlock {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            return Ok(LockState::Running(pid.trim().parse().ok()));
+        }
+        return Err(err.into());
+    }
+    Ok(LockState::Acquired(InstanceLock { file }))
+}
+
+impl InstanceLock {
+    /// Record `pid` as the holder, for `Running` to report.
+    pub fn write_pid(&mut self, pid: u32) -> Result<()> {
+        self.file.set_len(0)?;
+        self.file.rewind()?;
+        write!(self.file, "{pid}")?;
+        Ok(())
+    }
+
+    /// Let the process `cmd` spawns inherit the lock, holding it for as long
+    /// as it runs. Its descriptor number is passed in [`LOCK_FD_ENV`].
+    pub fn share_with(&self, cmd: &mut Command) {
+        let fd = self.file.as_raw_fd();
+        cmd.env(LOCK_FD_ENV, fd.to_string());
+        // only the child's copy loses close-on-exec
+        unsafe {
+            cmd.pre_exec(move || {
+                if libc::fcntl(fd, libc::F_SETFD, 0) == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn running(state: LockState) -> Option<u32> {
+        match state {
+            LockState::Running(pid) => pid,
+            LockState::Acquired(_) => panic!("lock should be held"),
+        }
+    }
+
+    #[test]
+    fn test_acquire_and_detect_running() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("run/gui.pid");
+
+        let LockState::Acquired(mut lock) = acquire(&path).unwrap() else {
+            panic!("fresh lock should be acquired");
+        };
+        assert_eq!(running(acquire(&path).unwrap()), None);
+        lock.write_pid(42).unwrap();
+        assert_eq!(running(acquire(&path).unwrap()), Some(42));
+
+        // released on drop; the leftover pid means nothing
+        drop(lock);
+        assert!(matches!(acquire(&path).unwrap(), LockState::Acquired(_)));
+    }
+
+    #[test]
+    fn test_child_holds_shared_lock() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("gui.pid");
+
+        let LockState::Acquired(lock) = acquire(&path).unwrap() else {
+            panic!("fresh lock should be acquired");
+        };
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        lock.share_with(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        drop(lock);
+
+        // the child keeps it after the launcher lets go
+        assert!(matches!(acquire(&path).unwrap(), LockState::Running(_)));
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(matches!(acquire(&path).unwrap(), LockState::Acquired(_)));
+    }
+}
@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use serde::{Deserialize, Serialize};
 
 use crate::error::{MuralisError, Result};
@@ -10,11 +12,30 @@ pub enum IpcRequest {
     Status,
     Next,
     Prev,
-    SetWallpaper { id: String },
-    SetMode { mode: DisplayMode },
+    SetWallpaper {
+        id: String,
+    },
+    SetMode {
+        mode: DisplayMode,
+    },
     Pause,
     Resume,
     Reload,
+    KeepCurrent,
+    /// Re-apply the wallpaper shown before the current one.
+    RestorePrevious,
+    /// The library record of the wallpaper being shown.
+    CurrentWallpaper,
+    /// Wallpapers in the daemon's rotation, for building menus.
+    ListWallpapers {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        limit: Option<u32>,
+    },
+    /// Toggle a source for this daemon session only (reverts on reload/restart).
+    SetSourceEnabled {
+        name: String,
+        enabled: bool,
+    },
     Quit,
 }
 
@@ -38,6 +59,15 @@ pub struct DaemonStatus {
     pub current_wallpaper: Option<String>,
     pub wallpaper_count: u32,
     pub next_change: Option<String>,
+    /// IDs of sources switched off at runtime via `SetSourceEnabled`.
+    #[serde(default)]
+    pub disabled_sources: Vec<String>,
+    /// Name of the active wallpaper backend, e.g. "swww".
+    #[serde(default)]
+    pub backend: String,
+    /// Why the last apply failed; cleared by the next successful one.
+    #[serde(default)]
+    pub last_error: Option<String>,
 }
 
 impl IpcResponse {
@@ -58,11 +88,15 @@ impl IpcResponse {
 
 /// Send a request to the daemon and receive a response.
 pub async fn send_request(request: &IpcRequest) -> Result<IpcResponse> {
+    send_request_to(&MuralisPaths::socket_path()?, request).await
+}
+
+/// Send a request to the daemon listening on `socket_path`.
+pub async fn send_request_to(socket_path: &Path, request: &IpcRequest) -> Result<IpcResponse> {
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
     use tokio::net::UnixStream;
 
-    let socket_path = MuralisPaths::socket_path();
-    let stream = UnixStream::connect(&socket_path)
+    let stream = UnixStream::connect(socket_path)
         .await
         .map_err(|e| MuralisError::Ipc(format!("failed to connect to daemon: {e}")))?;
 
@@ -113,6 +147,71 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_current_wallpaper_roundtrip() {
+        let json = serde_json::to_string(&IpcRequest::CurrentWallpaper).unwrap();
+        assert_eq!(json, r#"{"command":"current_wallpaper"}"#);
+        let parsed: IpcRequest = serde_json::from_str(&json).unwrap();
+        assert!(matches!(parsed, IpcRequest::CurrentWallpaper));
+    }
+
+    #[test]
+    fn test_list_wallpapers_roundtrip() {
+        let json = serde_json::to_string(&IpcRequest::ListWallpapers { limit: None }).unwrap();
+        assert_eq!(json, r#"{"command":"list_wallpapers"}"#);
+        let parsed: IpcRequest =
+            serde_json::from_str(r#"{"command":"list_wallpapers","limit":2}"#).unwrap();
+        assert!(matches!(
+            parsed,
+            IpcRequest::ListWallpapers { limit: Some(2) }
+        ));
+
+        let wp = crate::models::Wallpaper {
+            id: "abc123".into(),
+            source_type: crate::models::SourceType::new("wallhaven"),
+            source_id: "x1".into(),
+            source_url: None,
+            width: 3840,
+            height: 2160,
+            tags: vec!["forest".into()],
+            file_path: "/data/wallpapers/abc123.jpg".into(),
+            added_at: "2025-01-01T00:00:00Z".into(),
+            last_used: None,
+            use_count: 0,
+            brightness: None,
+            crop: None,
+            colors: Vec::new(),
+            meta: Default::default(),
+        };
+        let resp = IpcResponse::ok_with_data(serde_json::to_value(vec![&wp]).unwrap());
+        let json = serde_json::to_string(&resp).unwrap();
+        let IpcResponse::Ok { data: Some(data) } = serde_json::from_str(&json).unwrap() else {
+            panic!("expected data");
+        };
+        let listed: Vec<crate::models::Wallpaper> = serde_json::from_value(data).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, "abc123");
+        assert_eq!(listed[0].tags, vec!["forest"]);
+    }
+
+    #[test]
+    fn test_set_source_enabled_serialize() {
+        let req = IpcRequest::SetSourceEnabled {
+            name: "pexels".into(),
+            enabled: false,
+        };
+        let json = serde_json::to_string(&req).unwrap();
+        assert_eq!(
+            json,
+            r#"{"command":"set_source_enabled","name":"pexels","enabled":false}"#
+        );
+        let parsed: IpcRequest = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            parsed,
+            IpcRequest::SetSourceEnabled { ref name, enabled: false } if name == "pexels"
+        ));
+    }
+
     #[test]
     fn test_response_serialize() {
         let resp = IpcResponse::ok();
@@ -134,6 +233,9 @@ mod tests {
             current_wallpaper: Some("abc123".into()),
             wallpaper_count: 42,
             next_change: Some("2025-01-01T01:00:00Z".into()),
+            disabled_sources: vec!["Wallhaven".into()],
+            backend: "swww".into(),
+            last_error: None,
         };
         let data = serde_json::to_value(&status).unwrap();
         let resp = IpcResponse::ok_with_data(data);
@@ -155,6 +257,14 @@ mod tests {
             IpcRequest::Pause,
             IpcRequest::Resume,
             IpcRequest::Reload,
+            IpcRequest::KeepCurrent,
+            IpcRequest::RestorePrevious,
+            IpcRequest::CurrentWallpaper,
+            IpcRequest::ListWallpapers { limit: Some(5) },
+            IpcRequest::SetSourceEnabled {
+                name: "wallhaven".into(),
+                enabled: false,
+            },
             IpcRequest::Quit,
         ];
 
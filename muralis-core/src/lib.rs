@@ -2,10 +2,18 @@ pub mod backend;
 pub mod cache;
 pub mod config;
 pub mod crop_overlay;
+pub mod dates;
 pub mod db;
 pub mod error;
+pub mod gui_state;
+pub mod instance;
 pub mod ipc;
 pub mod models;
 pub mod paths;
+pub mod retry;
+pub mod solid;
 pub mod sources;
+#[cfg(any(test, feature = "test-support"))]
+pub mod testing;
+pub mod viewer;
 pub mod wallpapers;
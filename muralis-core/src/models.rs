@@ -41,6 +41,62 @@ pub struct Wallpaper {
     pub added_at: String,
     pub last_used: Option<String>,
     pub use_count: u32,
+    /// Mean luma in 0.0..=1.0, computed when favorited. None for older entries.
+    #[serde(default)]
+    pub brightness: Option<f32>,
+    /// User-chosen region (image pixels) applied instead of the backend's fill.
+    #[serde(default)]
+    pub crop: Option<CropRect>,
+    /// Dominant colors as RGB, most common first. Empty until analyzed.
+    #[serde(default)]
+    pub colors: Vec<[u8; 3]>,
+    #[serde(default)]
+    pub meta: WallpaperMeta,
+}
+
+/// Optional details a source knows about a wallpaper, kept with the favorite.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WallpaperMeta {
+    /// When the photo was taken or uploaded (RFC 3339).
+    pub captured_at: Option<String>,
+    pub photographer: Option<String>,
+    /// Size in bytes of the stored file.
+    pub file_size: Option<u64>,
+}
+
+/// Tag edits for one wallpaper: `set` replaces all tags before `add`/`remove` apply.
+#[derive(Debug, Clone, Default)]
+pub struct TagEdit {
+    pub set: Option<Vec<String>>,
+    pub add: Vec<String>,
+    pub remove: Vec<String>,
+}
+
+impl TagEdit {
+    /// The resulting tags: trimmed, lowercased, deduplicated, in first-seen order.
+    pub fn apply(&self, current: &[String]) -> Vec<String> {
+        let remove: Vec<String> = self.remove.iter().map(|t| normalize_tag(t)).collect();
+        let base = self.set.as_deref().unwrap_or(current);
+        let mut tags: Vec<String> = Vec::new();
+        for tag in base.iter().chain(&self.add).map(|t| normalize_tag(t)) {
+            if !tag.is_empty() && !tags.contains(&tag) && !remove.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+}
+
+pub fn normalize_tag(tag: &str) -> String {
+    tag.trim().to_lowercase()
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CropRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,9 +106,34 @@ pub struct WallpaperPreview {
     pub source_url: String,
     pub thumbnail_url: String,
     pub full_url: String,
+    /// Medium-size rendition, quick to load for the preview; the original
+    /// at `full_url` is still what gets downloaded.
+    #[serde(default)]
+    pub sample_url: Option<String>,
     pub width: u32,
     pub height: u32,
     pub tags: Vec<String>,
+    /// Carried into the favorite's row when it's saved.
+    #[serde(default)]
+    pub meta: WallpaperMeta,
+}
+
+impl WallpaperPreview {
+    /// Whether any tag matches the blocked list (case-insensitive).
+    pub fn has_blocked_tag(&self, blocked: &[String]) -> bool {
+        self.tags
+            .iter()
+            .any(|t| blocked.iter().any(|b| b.eq_ignore_ascii_case(t.trim())))
+    }
+}
+
+/// A named group of favorites.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Collection {
+    pub name: String,
+    pub created_at: String,
+    /// Number of favorites in it.
+    pub size: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -71,6 +152,8 @@ pub enum DisplayMode {
     Sequential,
     Workspace,
     Schedule,
+    /// Fetch a fresh result from a source on every rotation.
+    LiveRandom,
 }
 
 impl DisplayMode {
@@ -81,6 +164,7 @@ impl DisplayMode {
         DisplayMode::Sequential,
         DisplayMode::Workspace,
         DisplayMode::Schedule,
+        DisplayMode::LiveRandom,
     ];
 }
 
@@ -93,6 +177,7 @@ impl std::fmt::Display for DisplayMode {
             Self::Sequential => write!(f, "sequential"),
             Self::Workspace => write!(f, "workspace"),
             Self::Schedule => write!(f, "schedule"),
+            Self::LiveRandom => write!(f, "live_random"),
         }
     }
 }
@@ -108,20 +193,100 @@ impl std::str::FromStr for DisplayMode {
             "sequential" => Ok(Self::Sequential),
             "workspace" => Ok(Self::Workspace),
             "schedule" => Ok(Self::Schedule),
+            "live_random" => Ok(Self::LiveRandom),
             other => Err(format!("unknown display mode: {other}")),
         }
     }
 }
 
+/// Order for listing favorites.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    /// Newest first.
+    #[default]
+    Added,
+    /// Around the color wheel by dominant color; greys and unanalyzed
+    /// wallpapers last, dark to light.
+    Hue,
+}
+
+impl SortMode {
+    pub fn sort(self, wallpapers: &mut [Wallpaper]) {
+        match self {
+            Self::Added => wallpapers.sort_by(|a, b| b.added_at.cmp(&a.added_at)),
+            Self::Hue => wallpapers.sort_by(|a, b| {
+                let (ka, kb) = (hue_key(a), hue_key(b));
+                ka.0.cmp(&kb.0).then(ka.1.total_cmp(&kb.1))
+            }),
+        }
+    }
+}
+
+/// `(group, position)`: colorful wallpapers by hue, then greys by lightness.
+fn hue_key(wp: &Wallpaper) -> (u8, f32) {
+    let Some(&[r, g, b]) = wp.colors.first() else {
+        return (2, 0.0);
+    };
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let chroma = max - min;
+    // near-grey colors have no meaningful hue
+    if max == 0.0 || chroma / max < 0.15 {
+        return (1, max);
+    }
+    let hue = if max == r {
+        ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / chroma + 2.0
+    } else {
+        (r - g) / chroma + 4.0
+    };
+    (0, hue * 60.0)
+}
+
+impl std::fmt::Display for SortMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added => write!(f, "added"),
+            Self::Hue => write!(f, "hue"),
+        }
+    }
+}
+
+impl std::str::FromStr for SortMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "added" => Ok(Self::Added),
+            "hue" => Ok(Self::Hue),
+            other => Err(format!(
+                "unknown sort mode: {other} (expected added or hue)"
+            )),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum BackendType {
     Hyprpaper,
     Swww,
+    /// Video wallpapers through mpvpaper.
+    Mpvpaper,
+    /// X11 via `feh --bg-fill`.
+    Feh,
 }
 
 impl BackendType {
-    pub const ALL: &[BackendType] = &[BackendType::Hyprpaper, BackendType::Swww];
+    pub const ALL: &[BackendType] = &[
+        BackendType::Hyprpaper,
+        BackendType::Swww,
+        BackendType::Mpvpaper,
+        BackendType::Feh,
+    ];
 }
 
 impl std::fmt::Display for BackendType {
@@ -129,6 +294,103 @@ impl std::fmt::Display for BackendType {
         match self {
             Self::Hyprpaper => write!(f, "hyprpaper"),
             Self::Swww => write!(f, "swww"),
+            Self::Mpvpaper => write!(f, "mpvpaper"),
+            Self::Feh => write!(f, "feh"),
+        }
+    }
+}
+
+/// Content hash used for new favorite IDs. IDs are opaque, so libraries
+/// may mix algorithms.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HashAlgo {
+    #[default]
+    Sha256,
+    Blake3,
+}
+
+impl std::fmt::Display for HashAlgo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Sha256 => write!(f, "sha256"),
+            Self::Blake3 => write!(f, "blake3"),
+        }
+    }
+}
+
+/// Chroma subsampling for stored thumbnails: how much color resolution is
+/// kept relative to brightness.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChromaSubsampling {
+    #[serde(rename = "4:4:4")]
+    Yuv444,
+    #[serde(rename = "4:2:2")]
+    Yuv422,
+    #[default]
+    #[serde(rename = "4:2:0")]
+    Yuv420,
+}
+
+/// How the crop preview marks what a monitor will show.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayStyle {
+    /// Dim the regions cropped away.
+    #[default]
+    Darken,
+    /// Draw a border around the kept region, leaving pixels untouched.
+    Outline,
+}
+
+/// How the GUI preview sizes the image.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewFit {
+    /// Scale to fit the preview pane.
+    #[default]
+    Contain,
+    /// One image pixel per screen pixel, scrolling when larger.
+    Actual,
+}
+
+impl std::fmt::Display for PreviewFit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Contain => write!(f, "contain"),
+            Self::Actual => write!(f, "actual"),
+        }
+    }
+}
+
+impl std::str::FromStr for PreviewFit {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "contain" => Ok(Self::Contain),
+            "actual" => Ok(Self::Actual),
+            other => Err(format!("unknown preview fit: {other}")),
+        }
+    }
+}
+
+/// Which monitor drives the `auto` minimum resolution on multi-monitor setups.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MinResolutionStrategy {
+    #[default]
+    Smallest,
+    Largest,
+    Primary,
+}
+
+impl std::fmt::Display for MinResolutionStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Smallest => write!(f, "smallest"),
+            Self::Largest => write!(f, "largest"),
+            Self::Primary => write!(f, "primary"),
         }
     }
 }
@@ -139,4 +401,109 @@ pub struct MonitorInfo {
     pub width: u32,
     pub height: u32,
     pub scale: f64,
+    /// False while the monitor is powered off (DPMS).
+    #[serde(default = "dpms_on")]
+    pub dpms: bool,
+}
+
+/// Serde default for DPMS fields: a monitor is on unless reported off.
+pub(crate) fn dpms_on() -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_blocked_tag() {
+        let preview = WallpaperPreview {
+            source_type: SourceType::new("wallhaven"),
+            source_id: "x".into(),
+            source_url: String::new(),
+            thumbnail_url: String::new(),
+            full_url: String::new(),
+            sample_url: None,
+            width: 0,
+            height: 0,
+            tags: vec!["Mountain".into(), "Blurry ".into()],
+            meta: WallpaperMeta::default(),
+        };
+        assert!(preview.has_blocked_tag(&["blurry".into()]));
+        assert!(!preview.has_blocked_tag(&["anime".into(), "moun".into()]));
+        assert!(!preview.has_blocked_tag(&[]));
+    }
+
+    #[test]
+    fn test_sort_by_hue() {
+        let wp = |id: &str, colors: Vec<[u8; 3]>| Wallpaper {
+            id: id.into(),
+            source_type: SourceType::new("local"),
+            source_id: id.into(),
+            source_url: None,
+            width: 0,
+            height: 0,
+            tags: Vec::new(),
+            file_path: String::new(),
+            added_at: String::new(),
+            last_used: None,
+            use_count: 0,
+            brightness: None,
+            crop: None,
+            colors,
+            meta: Default::default(),
+        };
+        let mut wallpapers = vec![
+            wp("unknown", Vec::new()),
+            wp("white", vec![[240, 240, 240]]),
+            wp("blue", vec![[20, 40, 200]]),
+            wp("black", vec![[5, 5, 5]]),
+            wp("green", vec![[30, 180, 40], [200, 0, 0]]),
+            wp("red", vec![[220, 30, 20]]),
+        ];
+        SortMode::Hue.sort(&mut wallpapers);
+        let ids: Vec<&str> = wallpapers.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["red", "green", "blue", "black", "white", "unknown"]
+        );
+        assert_eq!("hue".parse::<SortMode>(), Ok(SortMode::Hue));
+        assert!("rainbow".parse::<SortMode>().is_err());
+    }
+
+    #[test]
+    fn test_preview_fit_parse() {
+        assert_eq!(PreviewFit::default(), PreviewFit::Contain);
+        for fit in [PreviewFit::Contain, PreviewFit::Actual] {
+            assert_eq!(fit.to_string().parse::<PreviewFit>(), Ok(fit));
+        }
+        assert!("cover".parse::<PreviewFit>().is_err());
+    }
+
+    #[test]
+    fn test_tag_edit_apply() {
+        let current = vec!["Nature".to_string(), "forest".into()];
+        let tags = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let edit = TagEdit {
+            add: tags(&[" Mist ", "nature", ""]),
+            remove: tags(&["FOREST"]),
+            ..Default::default()
+        };
+        assert_eq!(edit.apply(&current), vec!["nature", "mist"]);
+
+        let edit = TagEdit {
+            set: Some(tags(&["City", "night", "city"])),
+            add: tags(&["rain"]),
+            remove: tags(&["night"]),
+        };
+        assert_eq!(edit.apply(&current), vec!["city", "rain"]);
+
+        assert_eq!(TagEdit::default().apply(&current), vec!["nature", "forest"]);
+        let clear = TagEdit {
+            set: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert!(clear.apply(&current).is_empty());
+    }
 }
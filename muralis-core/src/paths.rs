@@ -2,15 +2,47 @@ use std::path::PathBuf;
 
 use crate::error::{MuralisError, Result};
 
+/// Config file to use instead of `$XDG_CONFIG_HOME/muralis/config.toml`.
+pub const CONFIG_ENV: &str = "MURALIS_CONFIG";
+
+/// Directory for the database, favorites and cache instead of the XDG dirs.
+pub const DATA_DIR_ENV: &str = "MURALIS_DATA_DIR";
+
+/// Named profile to run as; see [`MuralisPaths::for_profile`].
+pub const PROFILE_ENV: &str = "MURALIS_PROFILE";
+
 #[derive(Debug, Clone)]
 pub struct MuralisPaths {
     pub config_dir: PathBuf,
     pub data_dir: PathBuf,
     pub cache_dir: PathBuf,
+    /// Overrides `config.toml` in `config_dir`.
+    pub config_file: Option<PathBuf>,
 }
 
 impl MuralisPaths {
+    /// XDG locations for `$MURALIS_PROFILE`, overridden by
+    /// `$MURALIS_CONFIG` and `$MURALIS_DATA_DIR` when set.
     pub fn new() -> Result<Self> {
+        Self::resolve(Self::active_profile().as_deref())
+    }
+
+    /// Like [`new`](Self::new), for `profile` instead of `$MURALIS_PROFILE`.
+    pub fn resolve(profile: Option<&str>) -> Result<Self> {
+        let mut paths = match profile {
+            Some(name) => Self::for_profile(name)?,
+            None => Self::xdg()?,
+        };
+        if let Some(file) = env_path(CONFIG_ENV) {
+            paths = paths.with_config_file(file);
+        }
+        if let Some(dir) = env_path(DATA_DIR_ENV) {
+            paths = paths.with_data_dir(dir);
+        }
+        Ok(paths)
+    }
+
+    fn xdg() -> Result<Self> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| MuralisError::Config("cannot resolve XDG config dir".into()))?
             .join("muralis");
@@ -27,11 +59,68 @@ impl MuralisPaths {
             config_dir,
             data_dir,
             cache_dir,
+            config_file: None,
         })
     }
 
+    /// Config, data and cache dirs under `dir`, for tests.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn for_test(dir: &std::path::Path) -> Self {
+        Self {
+            config_dir: dir.join("config"),
+            data_dir: dir.join("data"),
+            cache_dir: dir.join("cache"),
+            config_file: None,
+        }
+    }
+
+    /// Separate config, favorites and cache for a named profile, under
+    /// `profiles/<name>/` in each XDG dir.
+    pub fn for_profile(name: &str) -> Result<Self> {
+        check_profile_name(name)?;
+        let xdg = Self::xdg()?;
+        let profile = |dir: PathBuf| dir.join("profiles").join(name);
+        Ok(Self {
+            config_dir: profile(xdg.config_dir),
+            data_dir: profile(xdg.data_dir),
+            cache_dir: profile(xdg.cache_dir),
+            config_file: None,
+        })
+    }
+
+    /// The profile from `$MURALIS_PROFILE`, if any.
+    pub fn active_profile() -> Option<String> {
+        std::env::var(PROFILE_ENV).ok().filter(|p| !p.is_empty())
+    }
+
+    /// Read and save the config at `path`.
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        self.config_dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        self.config_file = Some(path);
+        self
+    }
+
+    /// Keep the database, favorites and cache (in `cache/`) under `dir`.
+    pub fn with_data_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        self.cache_dir = dir.join("cache");
+        self.data_dir = dir;
+        self
+    }
+
     pub fn config_file(&self) -> PathBuf {
-        self.config_dir.join("config.toml")
+        self.config_file
+            .clone()
+            .unwrap_or_else(|| self.config_dir.join("config.toml"))
+    }
+
+    /// Window size and tab the GUI restores; see `GuiState`.
+    pub fn gui_state_file(&self) -> PathBuf {
+        self.data_dir.join("gui-state.toml")
     }
 
     pub fn db_path(&self) -> PathBuf {
@@ -50,9 +139,59 @@ impl MuralisPaths {
         self.cache_dir.join("previews")
     }
 
-    pub fn socket_path() -> PathBuf {
+    /// Pre-cropped copies of wallpapers with a stored crop.
+    pub fn cropped_dir(&self) -> PathBuf {
+        self.cache_dir.join("cropped")
+    }
+
+    /// Feed responses kept for conditional requests, one file per feed URL.
+    pub fn feed_cache_dir(&self) -> PathBuf {
+        self.cache_dir.join("feeds")
+    }
+
+    /// Rendered solid colors and gradients applied with `set --color`.
+    pub fn generated_dir(&self) -> PathBuf {
+        self.cache_dir.join("generated")
+    }
+
+    /// Socket of the daemon running `$MURALIS_PROFILE`.
+    pub fn socket_path() -> Result<PathBuf> {
+        Self::profile_socket_path(Self::active_profile().as_deref())
+    }
+
+    /// Socket of the daemon running `profile`, so profiles can run side by
+    /// side.
+    pub fn profile_socket_path(profile: Option<&str>) -> Result<PathBuf> {
         let uid = unsafe { libc::getuid() };
-        PathBuf::from(format!("/tmp/muralis-{uid}.sock"))
+        Ok(match profile {
+            Some(name) => {
+                check_profile_name(name)?;
+                PathBuf::from(format!("/tmp/muralis-{uid}-{name}.sock"))
+            }
+            None => PathBuf::from(format!("/tmp/muralis-{uid}.sock")),
+        })
+    }
+
+    /// Per-user runtime dir for pid/lock files ($XDG_RUNTIME_DIR/muralis).
+    pub fn runtime_dir() -> PathBuf {
+        match dirs::runtime_dir() {
+            Some(dir) => dir.join("muralis"),
+            None => {
+                let uid = unsafe { libc::getuid() };
+                PathBuf::from(format!("/tmp/muralis-{uid}"))
+            }
+        }
+    }
+
+    /// Lock of the GUI showing `profile`, so each profile gets one window.
+    pub fn gui_lock_path(profile: Option<&str>) -> Result<PathBuf> {
+        Ok(match profile {
+            Some(name) => {
+                check_profile_name(name)?;
+                Self::runtime_dir().join(format!("gui-{name}.pid"))
+            }
+            None => Self::runtime_dir().join("gui.pid"),
+        })
     }
 
     pub fn ensure_dirs(&self) -> Result<()> {
@@ -63,6 +202,8 @@ impl MuralisPaths {
             &self.wallpapers_dir(),
             &self.thumbnails_dir(),
             &self.previews_dir(),
+            &self.cropped_dir(),
+            &self.generated_dir(),
         ] {
             std::fs::create_dir_all(dir)?;
         }
@@ -71,10 +212,9 @@ impl MuralisPaths {
 
     /// Install app icon to XDG icon dir if missing/outdated.
     pub fn install_icon(&self) -> Result<()> {
-        let icon_dir = self
-            .data_dir
-            .parent()
-            .unwrap_or(&self.data_dir)
+        // the desktop looks in the XDG dir even when data_dir is overridden
+        let icon_dir = dirs::data_dir()
+            .unwrap_or_else(|| self.data_dir.parent().unwrap_or(&self.data_dir).into())
             .join("icons/hicolor/scalable/apps");
         std::fs::create_dir_all(&icon_dir)?;
         let dest = icon_dir.join("muralis.svg");
@@ -90,3 +230,110 @@ impl MuralisPaths {
         Ok(())
     }
 }
+
+/// A path from `var`, ignoring it when unset or empty.
+fn env_path(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Profile names become path components, so they must be plain names.
+fn check_profile_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(MuralisError::Config(format!(
+            "invalid profile name: {name:?}"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xdg_paths() -> MuralisPaths {
+        MuralisPaths {
+            config_dir: PathBuf::from("/home/u/.config/muralis"),
+            data_dir: PathBuf::from("/home/u/.local/share/muralis"),
+            cache_dir: PathBuf::from("/home/u/.cache/muralis"),
+            config_file: None,
+        }
+    }
+
+    #[test]
+    fn test_config_file_override() {
+        let paths = xdg_paths();
+        assert_eq!(
+            paths.config_file(),
+            PathBuf::from("/home/u/.config/muralis/config.toml")
+        );
+
+        let paths = xdg_paths().with_config_file("/tmp/profiles/test.toml");
+        assert_eq!(
+            paths.config_file(),
+            PathBuf::from("/tmp/profiles/test.toml")
+        );
+        assert_eq!(paths.config_dir, PathBuf::from("/tmp/profiles"));
+        // state stays where it was
+        assert_eq!(paths.db_path(), xdg_paths().db_path());
+
+        let paths = xdg_paths().with_config_file("test.toml");
+        assert_eq!(paths.config_file(), PathBuf::from("test.toml"));
+        assert_eq!(paths.config_dir, PathBuf::from("."));
+    }
+
+    #[test]
+    fn test_data_dir_override() {
+        let paths = xdg_paths().with_data_dir("/tmp/profile");
+        assert_eq!(paths.db_path(), PathBuf::from("/tmp/profile/muralis.db"));
+        assert_eq!(
+            paths.wallpapers_dir(),
+            PathBuf::from("/tmp/profile/wallpapers")
+        );
+        assert_eq!(
+            paths.thumbnails_dir(),
+            PathBuf::from("/tmp/profile/cache/thumbnails")
+        );
+        assert_eq!(paths.config_file(), xdg_paths().config_file());
+    }
+
+    #[test]
+    fn test_profiles_are_distinct() {
+        let default = MuralisPaths::xdg().unwrap();
+        let work = MuralisPaths::for_profile("work").unwrap();
+        let home = MuralisPaths::for_profile("home").unwrap();
+
+        assert!(work.config_file().ends_with("profiles/work/config.toml"));
+        assert!(work.db_path().ends_with("profiles/work/muralis.db"));
+        for (a, b) in [(&work, &home), (&work, &default), (&home, &default)] {
+            assert_ne!(a.config_file(), b.config_file());
+            assert_ne!(a.db_path(), b.db_path());
+            assert_ne!(a.wallpapers_dir(), b.wallpapers_dir());
+            assert_ne!(a.thumbnails_dir(), b.thumbnails_dir());
+        }
+
+        for path in [
+            MuralisPaths::profile_socket_path,
+            MuralisPaths::gui_lock_path,
+        ] {
+            let all = [
+                path(None).unwrap(),
+                path(Some("work")).unwrap(),
+                path(Some("home")).unwrap(),
+            ];
+            assert_ne!(all[0], all[1]);
+            assert_ne!(all[0], all[2]);
+            assert_ne!(all[1], all[2]);
+        }
+    }
+
+    #[test]
+    fn test_profile_name_must_be_a_plain_name() {
+        for name in ["", "../work", "a/b", ".hidden"] {
+            assert!(MuralisPaths::for_profile(name).is_err(), "{name:?}");
+            assert!(MuralisPaths::profile_socket_path(Some(name)).is_err());
+            assert!(MuralisPaths::gui_lock_path(Some(name)).is_err());
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::future::Future;
+use std::time::Duration;
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response};
+
+use crate::error::{MuralisError, Result};
+
+/// A `Retry-After` longer than this isn't waited for; the response is
+/// returned instead.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// How often a failed network call is retried, with exponential backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first.
+    pub retries: u32,
+    /// Wait before the first retry; doubled for each one after.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Interactive searches: one quick retry so the grid isn't held up.
+    pub const SEARCH: Self = Self {
+        retries: 1,
+        base_delay: Duration::from_millis(250),
+    };
+
+    /// Downloads (favorites, live fetches) from `[general] download_retries`.
+    pub fn download(retries: u32) -> Self {
+        Self {
+            retries,
+            base_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Source requests rejected as rate-limited or overloaded.
+    pub fn http(retries: u32) -> Self {
+        Self {
+            retries,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.min(16)))
+    }
+}
+
+/// Statuses [`send_with_retry`] resends. [`with_retry`] leaves them
+/// alone, so no failure is retried by both layers.
+const RETRIED_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+/// Failures worth retrying: timeouts, dropped connections and server
+/// errors that [`send_with_retry`] doesn't already retry.
+pub fn is_transient(err: &MuralisError) -> bool {
+    let status_transient = |s: u16| (500..600).contains(&s) && !RETRIED_STATUSES.contains(&s);
+    match err {
+        MuralisError::Http(e) => {
+            e.is_timeout()
+                || e.is_connect()
+                || e.is_request()
+                || e.status().is_some_and(|s| status_transient(s.as_u16()))
+        }
+        MuralisError::HttpStatus { status, .. } => status_transient(*status),
+        _ => false,
+    }
+}
+
+/// Run `op`, retrying transient failures per `policy`.
+pub async fn with_retry<T, F, Fut>(policy: RetryPolicy, mut op: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if attempt < policy.retries && is_transient(&e) => {
+                let delay = policy.delay(attempt);
+                tracing::debug!(attempt, ?delay, "retrying after transient error: {e}");
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Send `request`, retrying 429/502/503/504 responses up to `retries` times
+/// with exponential backoff, or after the server's `Retry-After`. Returns
+/// the last response whatever its status; connection errors aren't retried
+/// here.
+pub async fn send_with_retry(request: RequestBuilder, retries: u32) -> Result<Response> {
+    let policy = RetryPolicy::http(retries);
+    let mut attempt = 0;
+    loop {
+        // bodies that can't be cloned can't be resent
+        let Some(req) = request.try_clone() else {
+            return Ok(request.send().await?);
+        };
+        let resp = req.send().await?;
+        let status = resp.status().as_u16();
+        if attempt >= policy.retries || !RETRIED_STATUSES.contains(&status) {
+            return Ok(resp);
+        }
+        let delay = match retry_after(resp.headers()) {
+            Some(wait) if wait > MAX_RETRY_AFTER => return Ok(resp),
+            Some(wait) => wait,
+            None => policy.delay(attempt),
+        };
+        tracing::debug!(status, attempt, ?delay, url = %resp.url(), "retrying request");
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// `Retry-After` as delay-seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or(Duration::ZERO))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockServer;
+    use std::cell::Cell;
+
+    fn unavailable() -> MuralisError {
+        MuralisError::HttpStatus {
+            status: 500,
+            context: "download of x".into(),
+        }
+    }
+
+    async fn attempts_until_give_up(policy: RetryPolicy) -> u32 {
+        let calls = Cell::new(0);
+        let result: Result<()> = with_retry(policy, || {
+            calls.set(calls.get() + 1);
+            async { Err(unavailable()) }
+        })
+        .await;
+        assert!(result.is_err());
+        calls.get()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_download_policy_independent_of_search() {
+        assert_eq!(attempts_until_give_up(RetryPolicy::download(3)).await, 4);
+        assert_eq!(attempts_until_give_up(RetryPolicy::SEARCH).await, 2);
+        assert_eq!(attempts_until_give_up(RetryPolicy::download(0)).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retry_stops_on_success_or_permanent_error() {
+        let calls = Cell::new(0);
+        let result = with_retry(RetryPolicy::download(5), || {
+            calls.set(calls.get() + 1);
+            let n = calls.get();
+            async move {
+                if n < 3 {
+                    Err(unavailable())
+                } else {
+                    Ok(n)
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+
+        let calls = Cell::new(0);
+        let result: Result<()> = with_retry(RetryPolicy::download(5), || {
+            calls.set(calls.get() + 1);
+            async {
+                Err(MuralisError::HttpStatus {
+                    status: 404,
+                    context: "download of x".into(),
+                })
+            }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_succeeds_after_transient_failures() {
+        let server = MockServer::statuses(vec![503, 429]).await;
+        let url = server.url("/search");
+        let client = reqwest::Client::new();
+        let resp = send_with_retry(client.get(&url), 3).await.unwrap();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.text().await.unwrap(), "ok");
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_retry_gives_up_and_skips_permanent_errors() {
+        let server = MockServer::statuses(vec![503, 503, 503]).await;
+        let url = server.url("/search");
+        let client = reqwest::Client::new();
+        let resp = send_with_retry(client.get(&url), 1).await.unwrap();
+        assert_eq!(resp.status(), 503);
+        assert_eq!(server.requests().len(), 2);
+
+        let server = MockServer::statuses(vec![404]).await;
+        let url = server.url("/search");
+        let resp = send_with_retry(client.get(&url), 3).await.unwrap();
+        assert_eq!(resp.status(), 404);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_busy_status_retried_in_one_layer() {
+        let server = MockServer::statuses(vec![503; 20]).await;
+        let url = server.url("/search");
+        let client = reqwest::Client::new();
+        let result: Result<()> = with_retry(RetryPolicy::download(3), || async {
+            let resp = send_with_retry(client.get(&url), 2).await?;
+            Err(MuralisError::HttpStatus {
+                status: resp.status().as_u16(),
+                context: "download of x".into(),
+            })
+        })
+        .await;
+        assert!(result.is_err());
+        // the HTTP layer's 2 retries, not multiplied by the download policy
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_retry_after_forms() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+        headers.insert(RETRY_AFTER, "7".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(7)));
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy::download(3);
+        assert_eq!(policy.delay(0), Duration::from_secs(1));
+        assert_eq!(policy.delay(2), Duration::from_secs(4));
+    }
+}
@@ -0,0 +1,96 @@
+use image::{Rgb, RgbImage};
+
+use crate::error::{MuralisError, Result};
+
+/// Parse a `#rrggbb` / `#rgb` hex color (the `#` is optional).
+pub fn parse_color(s: &str) -> Result<Rgb<u8>> {
+    let hex = s.trim().trim_start_matches('#');
+    let expanded: String = match hex.len() {
+        3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        6 => hex.to_string(),
+        _ => return Err(invalid_color(s)),
+    };
+    let channel = |i: usize| {
+        expanded
+            .get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| invalid_color(s))
+    };
+    Ok(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+}
+
+fn invalid_color(s: &str) -> MuralisError {
+    MuralisError::Config(format!("invalid color {s:?} (use #rrggbb)"))
+}
+
+/// Lowercase hex form of a color, without the `#`.
+pub fn color_hex(c: Rgb<u8>) -> String {
+    format!("{:02x}{:02x}{:02x}", c[0], c[1], c[2])
+}
+
+/// Flat single-color image.
+pub fn render_solid(width: u32, height: u32, color: Rgb<u8>) -> RgbImage {
+    RgbImage::from_pixel(width, height, color)
+}
+
+/// Top-to-bottom linear gradient through evenly spaced color stops.
+/// A single stop renders as a solid color.
+pub fn render_gradient(width: u32, height: u32, stops: &[Rgb<u8>]) -> RgbImage {
+    let Some(&first) = stops.first() else {
+        return render_solid(width, height, Rgb([0, 0, 0]));
+    };
+    if stops.len() == 1 || height < 2 {
+        return render_solid(width, height, first);
+    }
+
+    let segments = (stops.len() - 1) as f32;
+    let mut img = RgbImage::new(width, height);
+    for y in 0..height {
+        let t = y as f32 / (height - 1) as f32 * segments;
+        let i = (t.floor() as usize).min(stops.len() - 2);
+        let row = lerp(stops[i], stops[i + 1], t - i as f32);
+        for x in 0..width {
+            img.put_pixel(x, y, row);
+        }
+    }
+    img
+}
+
+fn lerp(a: Rgb<u8>, b: Rgb<u8>, t: f32) -> Rgb<u8> {
+    let mix = |i: usize| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8;
+    Rgb([mix(0), mix(1), mix(2)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#1e1e2e").unwrap(), Rgb([0x1e, 0x1e, 0x2e]));
+        assert_eq!(parse_color("FFF").unwrap(), Rgb([255, 255, 255]));
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("#gggggg").is_err());
+        assert_eq!(color_hex(Rgb([0x1e, 0x1e, 0x2e])), "1e1e2e");
+    }
+
+    #[test]
+    fn test_render_solid() {
+        let img = render_solid(64, 36, Rgb([30, 30, 46]));
+        assert_eq!(img.dimensions(), (64, 36));
+        assert_eq!(*img.get_pixel(0, 0), Rgb([30, 30, 46]));
+        assert_eq!(*img.get_pixel(63, 35), Rgb([30, 30, 46]));
+    }
+
+    #[test]
+    fn test_render_gradient_corners() {
+        let stops = [Rgb([0, 0, 0]), Rgb([255, 0, 0]), Rgb([255, 255, 255])];
+        let img = render_gradient(40, 21, &stops);
+        assert_eq!(img.dimensions(), (40, 21));
+        assert_eq!(*img.get_pixel(0, 0), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(39, 0), Rgb([0, 0, 0]));
+        assert_eq!(*img.get_pixel(0, 10), Rgb([255, 0, 0]));
+        assert_eq!(*img.get_pixel(0, 20), Rgb([255, 255, 255]));
+        assert_eq!(*img.get_pixel(39, 20), Rgb([255, 255, 255]));
+    }
+}
@@ -1,9 +1,29 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use futures_lite::Stream;
+use reqwest::StatusCode;
+use serde::Serialize;
 
-use crate::error::Result;
+use crate::config::GeneralConfig;
+use crate::error::{MuralisError, Result};
 use crate::models::WallpaperPreview;
+use crate::retry::send_with_retry;
+
+mod search;
+
+pub use search::{FavoriteOutcome, SearchOptions, SearchOutput, SearchResult, SearchStatus};
+
+/// Previews pushed by a streaming source as they arrive.
+pub type PreviewStream = Pin<Box<dyn Stream<Item = WallpaperPreview> + Send>>;
+
+/// How far (in width/height) an image may be from the target ratio and
+/// still match, unless `[general] aspect_tolerance` says otherwise.
+pub const DEFAULT_ASPECT_TOLERANCE: f64 = 0.1;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AspectRatioFilter {
@@ -43,7 +63,22 @@ impl AspectRatioFilter {
         self.ratio_pair().map(|(w, h)| w as f64 / h as f64)
     }
 
+    /// Height/width ratio to reserve for a preview's grid cell before its
+    /// thumbnail loads: the preview's own dimensions when known, else this
+    /// filter's ratio, else 16:9.
+    pub fn cell_ratio(self, width: u32, height: u32) -> f64 {
+        if width > 0 && height > 0 {
+            return height as f64 / width as f64;
+        }
+        self.ratio_value().map_or(9.0 / 16.0, |r| 1.0 / r)
+    }
+
     pub fn matches(self, width: u32, height: u32) -> bool {
+        self.matches_with_tolerance(width, height, DEFAULT_ASPECT_TOLERANCE)
+    }
+
+    /// Unknown dimensions always match.
+    pub fn matches_with_tolerance(self, width: u32, height: u32, tolerance: f64) -> bool {
         let Some(target) = self.ratio_value() else {
             return true;
         };
@@ -51,7 +86,7 @@ impl AspectRatioFilter {
             return true;
         }
         let ratio = width as f64 / height as f64;
-        (ratio - target).abs() < 0.1
+        (ratio - target).abs() < tolerance
     }
 
     pub fn from_dimensions(w: u32, h: u32) -> Self {
@@ -117,10 +152,188 @@ impl fmt::Display for AspectRatioFilter {
     }
 }
 
+/// Source ID for one entry of a multi-instance source (`feed:<name>`),
+/// suffixed with a counter when another entry already took the name.
+pub fn instance_id(kind: &str, name: &str, taken: &mut HashSet<String>) -> String {
+    let base = format!("{kind}:{name}");
+    let mut id = base.clone();
+    let mut n = 2;
+    while !taken.insert(id.clone()) {
+        id = format!("{base}-{n}");
+        n += 1;
+    }
+    id
+}
+
+/// User-Agent sent to sources unless `[general] user_agent` is set.
+pub const DEFAULT_USER_AGENT: &str = concat!(
+    "muralis/",
+    env!("CARGO_PKG_VERSION"),
+    " (wallpaper manager)"
+);
+
+/// The HTTP client all sources share, with `[general]`
+/// `request_timeout_secs` (0 for none), `user_agent` and `http_retries`
+/// applied.
+pub fn http_client(general: &GeneralConfig) -> Result<HttpClient> {
+    let mut builder = reqwest::Client::builder().user_agent(
+        general
+            .user_agent
+            .as_deref()
+            .filter(|ua| !ua.trim().is_empty())
+            .unwrap_or(DEFAULT_USER_AGENT),
+    );
+    // per connect and per read rather than in total, so long downloads and
+    // `subscribe` streams live as long as data keeps arriving
+    if general.request_timeout_secs > 0 {
+        let timeout = Duration::from_secs(general.request_timeout_secs);
+        builder = builder.connect_timeout(timeout).read_timeout(timeout);
+    }
+    Ok(HttpClient::new(builder.build()?, general.http_retries))
+}
+
+/// A reqwest client plus how often its rate-limited requests are retried.
+/// Cheap to clone; clones share the connection pool.
+#[derive(Debug, Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    retries: u32,
+}
+
+impl HttpClient {
+    pub fn new(client: reqwest::Client, retries: u32) -> Self {
+        Self { client, retries }
+    }
+
+    pub fn get<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Send `request`, retrying 429/502/503/504 responses; see
+    /// [`send_with_retry`].
+    pub async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        send_with_retry(request, self.retries).await
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(
+            reqwest::Client::new(),
+            GeneralConfig::default().http_retries,
+        )
+    }
+}
+
+/// Merge a source's default API headers with user-configured `headers`.
+/// Config entries override defaults; invalid names or values are skipped.
+pub fn build_headers(
+    defaults: &[(&str, &str)],
+    extra: &BTreeMap<String, String>,
+) -> reqwest::header::HeaderMap {
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    let pairs = defaults
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .chain(extra.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+    for (name, value) in pairs {
+        match (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => tracing::warn!(header = name, "ignoring invalid source header"),
+        }
+    }
+    headers
+}
+
+/// Path of `url` on `host` (http(s), optional `www.`), without query or
+/// fragment. None when the URL is on another host.
+pub fn url_path<'a>(url: &'a str, host: &str) -> Option<&'a str> {
+    let rest = url
+        .trim()
+        .strip_prefix("https://")
+        .or_else(|| url.trim().strip_prefix("http://"))?;
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    let path = rest.strip_prefix(host)?;
+    if !path.is_empty() && !path.starts_with('/') {
+        return None;
+    }
+    Some(path.split(['?', '#']).next().unwrap_or(path))
+}
+
+/// Merge per-source results round-robin, taking at most `cap` from each
+/// source (unlimited when `None`).
+pub fn interleave_capped<T>(per_source: Vec<Vec<T>>, cap: Option<usize>) -> Vec<T> {
+    let cap = cap.unwrap_or(usize::MAX);
+    let mut iters: Vec<_> = per_source
+        .into_iter()
+        .map(|results| results.into_iter().take(cap))
+        .collect();
+    let mut merged = Vec::new();
+    loop {
+        let before = merged.len();
+        for it in iters.iter_mut() {
+            merged.extend(it.next());
+        }
+        if merged.len() == before {
+            return merged;
+        }
+    }
+}
+
+/// Download `preview.full_url`, falling back to the thumbnail when the full
+/// image is gone (404/410). Shared by the source crates' `download`.
+pub async fn download_preview(
+    client: &HttpClient,
+    preview: &WallpaperPreview,
+) -> Result<bytes::Bytes> {
+    download_with(preview, |url| async move {
+        let resp = client.send(client.get(&url)).await?;
+        let status = resp.status();
+        Ok((status, resp.bytes().await?))
+    })
+    .await
+}
+
+async fn download_with<F, Fut>(preview: &WallpaperPreview, fetch: F) -> Result<bytes::Bytes>
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<(StatusCode, bytes::Bytes)>>,
+{
+    let (mut status, mut body) = fetch(preview.full_url.clone()).await?;
+    let gone = status == StatusCode::NOT_FOUND || status == StatusCode::GONE;
+    if gone && !preview.thumbnail_url.is_empty() && preview.thumbnail_url != preview.full_url {
+        tracing::warn!(
+            url = %preview.full_url,
+            "full image returned {status}, falling back to thumbnail"
+        );
+        (status, body) = fetch(preview.thumbnail_url.clone()).await?;
+    }
+    if !status.is_success() {
+        return Err(MuralisError::HttpStatus {
+            status: status.as_u16(),
+            context: format!("download of {}", preview.source_id),
+        });
+    }
+    Ok(body)
+}
+
 #[async_trait]
 pub trait WallpaperSource: Send + Sync {
     /// Display name / tab label (e.g. "Wallhaven", "Bing Daily")
     fn name(&self) -> &str;
+    /// Stable key used to select the source (e.g. "wallhaven", "feed:Bing Daily").
+    /// Unique per registry; sources with one instance per config entry override it.
+    fn id(&self) -> &str {
+        self.source_type()
+    }
     /// DB type string (e.g. "wallhaven", "feed")
     fn source_type(&self) -> &str;
     async fn search(
@@ -132,42 +345,166 @@ pub trait WallpaperSource: Send + Sync {
     ) -> Result<Vec<WallpaperPreview>>;
     async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes>;
 
+    /// Whether an empty query lists something (latest, top, the feed), so a
+    /// tab can show results before anything is typed. Search-only APIs
+    /// override this to false.
+    fn browsable(&self) -> bool {
+        true
+    }
+
+    /// Hex colors (lowercase, no `#`) the source can filter by; empty when
+    /// it has no color filter.
+    fn colors(&self) -> &[&'static str] {
+        &[]
+    }
+
     /// Resolve a URL from this source into a WallpaperPreview.
     /// Sources opt in by overriding; default returns None.
     async fn resolve_url(&self, _url: &str) -> Result<Option<WallpaperPreview>> {
         Ok(None)
     }
+
+    /// Cheap request proving the source is reachable and configured.
+    /// Defaults to a one-result browse.
+    async fn health_check(&self) -> Result<()> {
+        self.search("", 1, 1, AspectRatioFilter::All)
+            .await
+            .map(|_| ())
+    }
+
+    /// Subscribe to previews pushed by the source (live photo streams).
+    /// Sources opt in by overriding; default is unsupported.
+    async fn subscribe(&self) -> Result<PreviewStream> {
+        Err(MuralisError::Source(format!(
+            "{} does not support streaming",
+            self.name()
+        )))
+    }
+}
+
+/// Outcome of probing one source with `sources check`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceHealth {
+    pub id: String,
+    pub name: String,
+    pub ok: bool,
+    /// Round trip, or time until giving up on a timeout.
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Probe each source in turn, timing it with `now` and giving up after `timeout`.
+pub async fn check_sources<'a>(
+    sources: impl IntoIterator<Item = &'a dyn WallpaperSource>,
+    timeout: Duration,
+    now: impl Fn() -> Instant,
+) -> Vec<SourceHealth> {
+    let mut report = Vec::new();
+    for source in sources {
+        let start = now();
+        let result = tokio::time::timeout(timeout, source.health_check()).await;
+        let latency = now().saturating_duration_since(start);
+        let error = match result {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(_) => Some(format!("timed out after {}s", timeout.as_secs_f32())),
+        };
+        report.push(SourceHealth {
+            id: source.id().to_string(),
+            name: source.name().to_string(),
+            ok: error.is_none(),
+            latency_ms: latency.as_millis() as u64,
+            error,
+        });
+    }
+    report
 }
 
 pub struct SourceRegistry {
-    sources: Vec<Box<dyn WallpaperSource>>,
+    sources: Vec<Arc<dyn WallpaperSource>>,
+    /// IDs of sources switched off at runtime.
+    disabled: HashSet<String>,
 }
 
 impl SourceRegistry {
     pub fn new() -> Self {
         Self {
             sources: Vec::new(),
+            disabled: HashSet::new(),
         }
     }
 
     pub fn register(&mut self, source: Box<dyn WallpaperSource>) {
-        self.sources.push(source);
+        self.sources.push(Arc::from(source));
     }
 
     pub fn names(&self) -> Vec<&str> {
-        self.sources.iter().map(|s| s.name()).collect()
+        self.iter().map(|s| s.name()).collect()
     }
 
-    pub fn get(&self, name: &str) -> Option<&dyn WallpaperSource> {
+    /// True when no source is enabled, i.e. searches can't return anything.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Look up an enabled source by ID, falling back to its display name.
+    pub fn get(&self, key: &str) -> Option<&dyn WallpaperSource> {
+        self.iter()
+            .find(|s| s.id() == key)
+            .or_else(|| self.iter().find(|s| s.name() == key))
+    }
+
+    /// An enabled source by ID, as a handle that can move into a task.
+    pub fn shared(&self, id: &str) -> Option<Arc<dyn WallpaperSource>> {
         self.sources
             .iter()
-            .find(|s| s.name() == name)
-            .map(|s| s.as_ref())
+            .find(|s| s.id() == id && self.is_enabled(id))
+            .cloned()
     }
 
+    /// Enabled sources only.
     pub fn iter(&self) -> impl Iterator<Item = &dyn WallpaperSource> {
+        self.iter_all().filter(|s| self.is_enabled(s.id()))
+    }
+
+    /// All registered sources, including ones disabled at runtime.
+    pub fn iter_all(&self) -> impl Iterator<Item = &dyn WallpaperSource> {
         self.sources.iter().map(|s| s.as_ref())
     }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        !self.disabled.contains(id)
+    }
+
+    /// IDs of the sources disabled at runtime, sorted.
+    pub fn disabled_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.disabled.iter().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Toggle every source whose ID, display name or type matches `name`
+    /// (case-insensitive). Returns false when nothing matched.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let matched: Vec<String> = self
+            .sources
+            .iter()
+            .filter(|s| {
+                s.id().eq_ignore_ascii_case(name)
+                    || s.name().eq_ignore_ascii_case(name)
+                    || s.source_type().eq_ignore_ascii_case(name)
+            })
+            .map(|s| s.id().to_string())
+            .collect();
+        for n in &matched {
+            if enabled {
+                self.disabled.remove(n);
+            } else {
+                self.disabled.insert(n.clone());
+            }
+        }
+        !matched.is_empty()
+    }
 }
 
 impl Default for SourceRegistry {
@@ -175,3 +512,388 @@ impl Default for SourceRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WallpaperMeta;
+    use crate::testing::{MockResponse, MockServer};
+
+    struct StubSource {
+        id: &'static str,
+        name: &'static str,
+        source_type: &'static str,
+    }
+
+    #[async_trait]
+    impl WallpaperSource for StubSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+        fn id(&self) -> &str {
+            self.id
+        }
+        fn source_type(&self) -> &str {
+            self.source_type
+        }
+        async fn search(
+            &self,
+            _query: &str,
+            _page: u32,
+            _per_page: u32,
+            _aspect: AspectRatioFilter,
+        ) -> Result<Vec<WallpaperPreview>> {
+            Ok(Vec::new())
+        }
+        async fn download(&self, _preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+            Ok(bytes::Bytes::new())
+        }
+    }
+
+    fn registry() -> SourceRegistry {
+        let mut registry = SourceRegistry::new();
+        for (id, name, source_type) in [
+            ("wallhaven", "Wallhaven", "wallhaven"),
+            ("feed:Bing Daily", "Bing Daily", "feed"),
+            ("feed:NASA APOD", "NASA APOD", "feed"),
+        ] {
+            registry.register(Box::new(StubSource {
+                id,
+                name,
+                source_type,
+            }));
+        }
+        registry
+    }
+
+    #[test]
+    fn test_set_enabled_by_name() {
+        let mut registry = registry();
+        assert!(registry.set_enabled("wallhaven", false));
+        assert_eq!(registry.names(), vec!["Bing Daily", "NASA APOD"]);
+        assert!(registry.get("Wallhaven").is_none());
+        assert_eq!(registry.iter_all().count(), 3);
+
+        assert!(registry.set_enabled("Wallhaven", true));
+        assert!(registry.get("Wallhaven").is_some());
+        assert!(!registry.set_enabled("nope", false));
+    }
+
+    #[test]
+    fn test_get_by_id_or_name() {
+        let registry = registry();
+        assert_eq!(registry.get("wallhaven").unwrap().name(), "Wallhaven");
+        assert_eq!(registry.get("Wallhaven").unwrap().id(), "wallhaven");
+        assert!(registry.get("missing").is_none());
+    }
+
+    #[test]
+    fn test_instance_id_dedupes() {
+        let mut taken = HashSet::new();
+        assert_eq!(instance_id("feed", "Bing", &mut taken), "feed:Bing");
+        assert_eq!(instance_id("feed", "Bing", &mut taken), "feed:Bing-2");
+        assert_eq!(instance_id("stream", "Bing", &mut taken), "stream:Bing");
+    }
+
+    struct ProbeSource {
+        id: &'static str,
+        delay: Duration,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl WallpaperSource for ProbeSource {
+        fn name(&self) -> &str {
+            self.id
+        }
+        fn source_type(&self) -> &str {
+            self.id
+        }
+        async fn search(
+            &self,
+            _query: &str,
+            _page: u32,
+            _per_page: u32,
+            _aspect: AspectRatioFilter,
+        ) -> Result<Vec<WallpaperPreview>> {
+            tokio::time::sleep(self.delay).await;
+            if self.fail {
+                return Err(MuralisError::Source("401 unauthorized".into()));
+            }
+            Ok(Vec::new())
+        }
+        async fn download(&self, _preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+            Ok(bytes::Bytes::new())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_check_sources_records_latency() {
+        let probes = [
+            ProbeSource {
+                id: "fast",
+                delay: Duration::ZERO,
+                fail: false,
+            },
+            ProbeSource {
+                id: "broken",
+                delay: Duration::ZERO,
+                fail: true,
+            },
+            ProbeSource {
+                id: "slow",
+                delay: Duration::from_secs(30),
+                fail: false,
+            },
+        ];
+        // each reading of the clock advances it by 40ms
+        let base = Instant::now();
+        let ticks = std::cell::Cell::new(0u32);
+        let now = || {
+            ticks.set(ticks.get() + 1);
+            base + Duration::from_millis(40) * ticks.get()
+        };
+
+        let report = check_sources(
+            probes.iter().map(|p| p as &dyn WallpaperSource),
+            Duration::from_secs(5),
+            now,
+        )
+        .await;
+
+        let summary: Vec<_> = report
+            .iter()
+            .map(|h| (h.id.as_str(), h.ok, h.latency_ms))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("fast", true, 40),
+                ("broken", false, 40),
+                ("slow", false, 40)
+            ]
+        );
+        assert!(report[0].error.is_none());
+        assert!(report[1].error.as_deref().unwrap().contains("401"));
+        assert_eq!(report[2].error.as_deref(), Some("timed out after 5s"));
+    }
+
+    #[test]
+    fn test_matches_with_tolerance() {
+        let wide = AspectRatioFilter::Ratio16x9;
+        assert!(wide.matches(1920, 1080));
+        // a few pixels short of 16:9
+        assert!(wide.matches_with_tolerance(1920, 1090, 0.02));
+        assert!(!wide.matches_with_tolerance(1920, 1090, 0.01));
+        // 16:10 only passes a loose tolerance
+        assert!(!wide.matches(1920, 1200));
+        assert!(wide.matches_with_tolerance(1920, 1200, 0.2));
+        assert!(wide.matches_with_tolerance(0, 1200, 0.0));
+        assert!(AspectRatioFilter::All.matches_with_tolerance(100, 1, 0.0));
+    }
+
+    #[test]
+    fn test_cell_ratio_precedence() {
+        // known dimensions win over the filter
+        assert_eq!(AspectRatioFilter::Ratio21x9.cell_ratio(1000, 500), 0.5);
+        // unknown dimensions fall back to the filter ratio
+        assert_eq!(AspectRatioFilter::Ratio4x3.cell_ratio(0, 0), 0.75);
+        assert_eq!(AspectRatioFilter::Ratio16x10.cell_ratio(1920, 0), 0.625);
+        // then 16:9
+        assert_eq!(AspectRatioFilter::All.cell_ratio(0, 0), 0.5625);
+    }
+
+    #[test]
+    fn test_is_empty() {
+        assert!(SourceRegistry::new().is_empty());
+
+        let mut registry = registry();
+        assert!(!registry.is_empty());
+        for name in ["Wallhaven", "Bing Daily", "NASA APOD"] {
+            registry.set_enabled(name, false);
+        }
+        assert!(registry.is_empty());
+        assert_eq!(registry.iter_all().count(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_unsupported_by_default() {
+        let registry = registry();
+        let err = registry.get("Wallhaven").unwrap().subscribe().await.err();
+        assert!(err
+            .unwrap()
+            .to_string()
+            .contains("does not support streaming"));
+    }
+
+    fn previews(source: &str, n: usize) -> Vec<WallpaperPreview> {
+        (0..n)
+            .map(|i| WallpaperPreview {
+                source_type: crate::models::SourceType::new(source),
+                source_id: format!("{source}{i}"),
+                source_url: String::new(),
+                thumbnail_url: String::new(),
+                full_url: String::new(),
+                sample_url: None,
+                width: 0,
+                height: 0,
+                tags: Vec::new(),
+                meta: WallpaperMeta::default(),
+            })
+            .collect()
+    }
+
+    fn ids(results: &[WallpaperPreview]) -> Vec<&str> {
+        results.iter().map(|p| p.source_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_download_falls_back_to_thumbnail() {
+        let mut preview = previews("feed", 1).remove(0);
+        preview.full_url = "https://x/full.jpg".into();
+        preview.thumbnail_url = "https://x/thumb.jpg".into();
+
+        let fetched = std::sync::Mutex::new(Vec::new());
+        let fetch = |url: String| {
+            fetched.lock().unwrap().push(url.clone());
+            async move {
+                if url.ends_with("full.jpg") {
+                    Ok((StatusCode::NOT_FOUND, bytes::Bytes::from_static(b"<html>")))
+                } else {
+                    Ok((StatusCode::OK, bytes::Bytes::from_static(b"thumb")))
+                }
+            }
+        };
+        let body = download_with(&preview, fetch).await.unwrap();
+        assert_eq!(&body[..], b"thumb");
+        assert_eq!(fetched.lock().unwrap().len(), 2);
+
+        // other failures don't fall back, and error pages are never returned as images
+        let err = download_with(&preview, |_| async {
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, bytes::Bytes::new()))
+        })
+        .await;
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_url_path() {
+        assert_eq!(
+            url_path(
+                "https://www.unsplash.com/photos/abc?utm=x#top",
+                "unsplash.com"
+            ),
+            Some("/photos/abc")
+        );
+        assert_eq!(
+            url_path("http://pexels.com/photo/1/", "pexels.com"),
+            Some("/photo/1/")
+        );
+        assert_eq!(
+            url_path("https://unsplash.community/x", "unsplash.com"),
+            None
+        );
+        assert_eq!(
+            url_path("https://example.com/photos/abc", "unsplash.com"),
+            None
+        );
+        assert_eq!(url_path("unsplash.com/photos/abc", "unsplash.com"), None);
+    }
+
+    #[test]
+    fn test_interleave_round_robin() {
+        let merged = interleave_capped(vec![previews("w", 4), previews("u", 2)], None);
+        assert_eq!(ids(&merged), vec!["w0", "u0", "w1", "u1", "w2", "w3"]);
+    }
+
+    #[test]
+    fn test_interleave_cap() {
+        let merged = interleave_capped(
+            vec![previews("w", 5), previews("u", 1), previews("p", 3)],
+            Some(2),
+        );
+        assert_eq!(ids(&merged), vec!["w0", "u0", "p0", "w1", "p1"]);
+        assert!(interleave_capped(vec![previews("w", 3)], Some(0)).is_empty());
+        assert!(interleave_capped::<WallpaperPreview>(Vec::new(), None).is_empty());
+    }
+
+    #[test]
+    fn test_build_headers_merges_config() {
+        let mut extra = BTreeMap::new();
+        extra.insert("Accept-Version".to_string(), "v2".to_string());
+        extra.insert("X-Trace".to_string(), "1".to_string());
+        extra.insert("bad header".to_string(), "x".to_string());
+
+        let headers = build_headers(&[("Accept-Version", "v1"), ("X-Default", "d")], &extra);
+        assert_eq!(headers["accept-version"], "v2");
+        assert_eq!(headers["x-default"], "d");
+        assert_eq!(headers["x-trace"], "1");
+        assert_eq!(headers.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_http_client_applies_general_config() {
+        let server = MockServer::start(|_| MockResponse::silent()).await;
+        let general = GeneralConfig {
+            request_timeout_secs: 1,
+            user_agent: Some("muralis-test/1".into()),
+            ..GeneralConfig::default()
+        };
+        let client = http_client(&general).unwrap();
+
+        let err = client.get(server.url("/")).send().await.unwrap_err();
+        assert!(err.is_timeout());
+        let head = &server.requests()[0];
+        assert!(head.contains("user-agent: muralis-test/1"), "{head}");
+    }
+
+    #[tokio::test]
+    async fn test_http_client_timeout_spares_slow_live_streams() {
+        let lines: Vec<_> = (0..3).map(|i| format!("line {i}\n").into_bytes()).collect();
+        let server = MockServer::start(move |_| {
+            MockResponse::streamed(lines.clone(), Duration::from_millis(600))
+        })
+        .await;
+        let general = GeneralConfig {
+            request_timeout_secs: 1,
+            ..GeneralConfig::default()
+        };
+        let client = http_client(&general).unwrap();
+
+        // 1.8s in total, but never a second without data
+        let started = Instant::now();
+        let body = client
+            .get(server.url("/stream"))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(started.elapsed() > Duration::from_secs(1));
+        assert_eq!(body, "line 0\nline 1\nline 2\n");
+    }
+
+    #[test]
+    fn test_set_enabled_by_type() {
+        let mut registry = registry();
+        assert!(registry.set_enabled("feed", false));
+        assert_eq!(registry.names(), vec!["Wallhaven"]);
+        assert!(!registry.is_enabled("feed:NASA APOD"));
+
+        // a name-level enable lifts a type-level disable for that one only
+        assert!(registry.set_enabled("Bing Daily", true));
+        assert_eq!(registry.names(), vec!["Wallhaven", "Bing Daily"]);
+        assert_eq!(registry.disabled_ids(), vec!["feed:NASA APOD"]);
+
+        // and replaying the reported IDs, as the CLI does, agrees
+        let mut replayed = self::registry();
+        for id in registry.disabled_ids() {
+            replayed.set_enabled(&id, false);
+        }
+        assert_eq!(replayed.names(), registry.names());
+
+        assert!(registry.set_enabled("feed", true));
+        assert!(registry.disabled_ids().is_empty());
+    }
+}
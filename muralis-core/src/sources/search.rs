@@ -0,0 +1,626 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+
+use serde::Serialize;
+
+use crate::backend::monitor::meets_min_resolution;
+use crate::config::FilterConfig;
+use crate::db::Database;
+use crate::error::Result;
+use crate::models::WallpaperPreview;
+use crate::retry::{with_retry, RetryPolicy};
+use crate::wallpapers::WallpaperManager;
+
+use super::{
+    interleave_capped, AspectRatioFilter, SourceRegistry, WallpaperSource, DEFAULT_ASPECT_TOLERANCE,
+};
+
+/// What [`SourceRegistry::search_all`] searches for and how results are filtered.
+#[derive(Debug, Clone)]
+pub struct SearchOptions {
+    pub query: String,
+    /// Source ID or name; `None` searches every enabled source.
+    pub source: Option<String>,
+    pub page: u32,
+    pub per_page: u32,
+    pub aspect: AspectRatioFilter,
+    /// Allowed distance from the aspect filter's ratio.
+    pub aspect_tolerance: f64,
+    /// Drop results known to be smaller than this.
+    pub min_resolution: Option<(u32, u32)>,
+    /// The `[filter]` config: minimum size and excluded tags.
+    pub filter: Option<FilterConfig>,
+    /// Per-source cap when interleaving all sources.
+    pub per_source_cap: Option<usize>,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            query: String::new(),
+            source: None,
+            page: 1,
+            per_page: 24,
+            aspect: AspectRatioFilter::All,
+            aspect_tolerance: DEFAULT_ASPECT_TOLERANCE,
+            min_resolution: None,
+            filter: None,
+            per_source_cap: None,
+        }
+    }
+}
+
+/// How a search came out, so an empty grid can say why it is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStatus {
+    Results,
+    /// Sources answered, but nothing matched.
+    NoResults,
+    /// Every source searched failed.
+    Failed,
+}
+
+impl SearchStatus {
+    fn of(found_any: bool, any_ok: bool, failures: &[(String, String)]) -> Self {
+        if found_any {
+            Self::Results
+        } else if !any_ok && !failures.is_empty() {
+            Self::Failed
+        } else {
+            Self::NoResults
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchOutput {
+    pub results: Vec<SearchResult>,
+    pub page: u32,
+    pub per_page: u32,
+    pub has_more: bool,
+    pub status: SearchStatus,
+    /// The first source error, when the search failed outright.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// At least one source answered.
+    #[serde(skip)]
+    pub any_ok: bool,
+    /// `(source name, error)` for each source that failed.
+    #[serde(skip)]
+    pub failures: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchResult {
+    pub source_type: String,
+    pub source_id: String,
+    pub source_url: String,
+    pub thumbnail_url: String,
+    pub full_url: String,
+    /// Shown in the preview while `full_url` loads; `full_url` when the
+    /// source has no sample.
+    pub sample_url: String,
+    pub width: u32,
+    pub height: u32,
+    pub tags: Vec<String>,
+    pub is_favorited: bool,
+    /// Library ID of the matching favorite; empty when not favorited.
+    pub favorite_id: String,
+    /// Palette of the matching favorite; empty when not favorited.
+    pub colors: Vec<[u8; 3]>,
+    /// Height/width to reserve for the grid cell while the thumbnail loads.
+    pub cell_ratio: f64,
+}
+
+impl SearchResult {
+    pub fn new(db: &Database, p: WallpaperPreview, aspect: AspectRatioFilter) -> Self {
+        let (favorite_id, colors) = db
+            .favorite_by_source(p.source_type.as_str(), &p.source_id)
+            .unwrap_or(None)
+            .unwrap_or_default();
+        Self {
+            cell_ratio: aspect.cell_ratio(p.width, p.height),
+            source_type: p.source_type.to_string(),
+            source_id: p.source_id,
+            source_url: p.source_url,
+            thumbnail_url: p.thumbnail_url,
+            sample_url: p.sample_url.unwrap_or_else(|| p.full_url.clone()),
+            full_url: p.full_url,
+            width: p.width,
+            height: p.height,
+            tags: p.tags,
+            is_favorited: !favorite_id.is_empty(),
+            favorite_id,
+            colors,
+        }
+    }
+}
+
+/// Pages [`SourceRegistry::favorite_first`] reads before giving up on
+/// finding `n` new results.
+pub const FAVORITE_MAX_PAGES: u32 = 10;
+
+/// Outcome of favoriting one search result with [`SourceRegistry::favorite_first`].
+#[derive(Debug)]
+pub struct FavoriteOutcome {
+    pub preview: WallpaperPreview,
+    /// The new favorite's ID, or why it failed.
+    pub result: Result<String>,
+}
+
+/// Filtered, deduplicated previews with the source each came from.
+struct Found<'a> {
+    previews: Vec<(&'a dyn WallpaperSource, WallpaperPreview)>,
+    any_ok: bool,
+    any_full_page: bool,
+    failures: Vec<(String, String)>,
+}
+
+impl SourceRegistry {
+    /// Search the selected source, or all enabled ones concurrently, then
+    /// filter by aspect, resolution, blacklists and tags, drop duplicates
+    /// and mark results already in the library.
+    pub async fn search_all(&self, db: &Database, opts: &SearchOptions) -> SearchOutput {
+        let found = self.search_previews(db, opts).await;
+        let results: Vec<SearchResult> = found
+            .previews
+            .into_iter()
+            .map(|(_, p)| SearchResult::new(db, p, opts.aspect))
+            .collect();
+
+        let status = SearchStatus::of(!results.is_empty(), found.any_ok, &found.failures);
+        let error = (status == SearchStatus::Failed)
+            .then(|| {
+                found
+                    .failures
+                    .first()
+                    .map(|(name, e)| format!("{name}: {e}"))
+            })
+            .flatten();
+        SearchOutput {
+            has_more: found.any_full_page || results.len() >= opts.per_page as usize,
+            results,
+            status,
+            error,
+            page: opts.page,
+            per_page: opts.per_page,
+            any_ok: found.any_ok,
+            failures: found.failures,
+        }
+    }
+
+    /// Search like [`search_all`](Self::search_all), then download and
+    /// favorite the first `n` results not already in the library, in order,
+    /// reading further pages until `n` are tried or the results run out
+    /// (at most [`FAVORITE_MAX_PAGES`]). A failed download doesn't stop the
+    /// rest. Also returns the sources whose search failed.
+    pub async fn favorite_first(
+        &self,
+        db: &Database,
+        manager: &WallpaperManager,
+        opts: &SearchOptions,
+        n: usize,
+        policy: RetryPolicy,
+    ) -> (Vec<FavoriteOutcome>, Vec<(String, String)>) {
+        let mut opts = opts.clone();
+        let mut outcomes = Vec::new();
+        let mut failures: Vec<(String, String)> = Vec::new();
+        let mut tried = HashSet::new();
+        for _ in 0..FAVORITE_MAX_PAGES {
+            let found = self.search_previews(db, &opts).await;
+            for failure in found.failures {
+                if !failures.iter().any(|(name, _)| *name == failure.0) {
+                    failures.push(failure);
+                }
+            }
+            for (src, preview) in found.previews {
+                if outcomes.len() >= n {
+                    break;
+                }
+                let key = (preview.source_type.to_string(), preview.source_id.clone());
+                if !tried.insert(key)
+                    || db
+                        .is_favorited_by_source(preview.source_type.as_str(), &preview.source_id)
+                        .unwrap_or(false)
+                {
+                    continue;
+                }
+                let result = match with_retry(policy, || src.download(&preview)).await {
+                    Ok(data) => manager.favorite(db, &preview, &data),
+                    Err(e) => Err(e),
+                };
+                outcomes.push(FavoriteOutcome { preview, result });
+            }
+            if outcomes.len() >= n || !found.any_full_page {
+                break;
+            }
+            opts.page += 1;
+        }
+        (outcomes, failures)
+    }
+
+    async fn search_previews(&self, db: &Database, opts: &SearchOptions) -> Found<'_> {
+        let sources: Vec<&dyn WallpaperSource> = match &opts.source {
+            Some(key) => self.get(key).into_iter().collect(),
+            None => self.iter().collect(),
+        };
+        let responses = join_all(sources.iter().map(|s| {
+            with_retry(RetryPolicy::SEARCH, || {
+                s.search(&opts.query, opts.page, opts.per_page, opts.aspect)
+            })
+        }))
+        .await;
+
+        let blocked = db.list_tag_blacklist().unwrap_or_default();
+
+        let mut any_ok = false;
+        let mut any_full_page = false;
+        let mut failures = Vec::new();
+        let mut per_source = Vec::new();
+        for (&src, response) in sources.iter().zip(responses) {
+            match response {
+                Ok(previews) => {
+                    any_ok = true;
+                    any_full_page |= previews.len() >= opts.per_page as usize;
+                    let kept: Vec<(&dyn WallpaperSource, WallpaperPreview)> = previews
+                        .into_iter()
+                        // client-side aspect filter for sources that don't support it natively
+                        .filter(|p| {
+                            opts.aspect.matches_with_tolerance(
+                                p.width,
+                                p.height,
+                                opts.aspect_tolerance,
+                            )
+                        })
+                        .filter(|p| !p.has_blocked_tag(&blocked))
+                        .filter(|p| opts.filter.as_ref().is_none_or(|f| f.matches(p)))
+                        .filter(|p| meets_min_resolution(p.width, p.height, opts.min_resolution))
+                        .filter(|p| {
+                            !db.is_blacklisted(&p.source_id, &p.source_type)
+                                .unwrap_or(false)
+                        })
+                        .map(|p| (src, p))
+                        .collect();
+                    per_source.push(kept);
+                }
+                Err(e) => failures.push((src.name().to_string(), e.to_string())),
+            }
+        }
+
+        // browse-all: round-robin so one prolific source doesn't fill the grid
+        let merged = if opts.source.is_none() {
+            interleave_capped(per_source, opts.per_source_cap)
+        } else {
+            per_source.into_iter().flatten().collect()
+        };
+        let mut seen = HashSet::new();
+        let previews = merged
+            .into_iter()
+            .filter(|(_, p)| seen.insert((p.source_type.to_string(), p.source_id.clone())))
+            .collect();
+
+        Found {
+            previews,
+            any_ok,
+            any_full_page,
+            failures,
+        }
+    }
+}
+
+/// Poll every future on the current task until all finish, keeping order.
+async fn join_all<F: Future>(futures: impl IntoIterator<Item = F>) -> Vec<F::Output> {
+    let mut futures: Vec<Pin<Box<F>>> = futures.into_iter().map(Box::pin).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+    std::future::poll_fn(|cx| {
+        let mut pending = false;
+        for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                match future.as_mut().poll(cx) {
+                    Poll::Ready(v) => *output = Some(v),
+                    Poll::Pending => pending = true,
+                }
+            }
+        }
+        if pending {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MuralisError;
+    use crate::models::{SourceType, WallpaperMeta};
+    use crate::paths::MuralisPaths;
+    use async_trait::async_trait;
+
+    struct FixedSource {
+        id: &'static str,
+        results: Vec<WallpaperPreview>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl WallpaperSource for FixedSource {
+        fn name(&self) -> &str {
+            self.id
+        }
+        fn source_type(&self) -> &str {
+            self.id
+        }
+        async fn search(
+            &self,
+            _query: &str,
+            page: u32,
+            per_page: u32,
+            _aspect: AspectRatioFilter,
+        ) -> Result<Vec<WallpaperPreview>> {
+            if self.fail {
+                return Err(MuralisError::Source("503".into()));
+            }
+            let skip = (page.saturating_sub(1) * per_page) as usize;
+            Ok(self
+                .results
+                .iter()
+                .skip(skip)
+                .take(per_page as usize)
+                .cloned()
+                .collect())
+        }
+        async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+            if preview.source_id == "broken" {
+                return Err(MuralisError::HttpStatus {
+                    status: 404,
+                    context: "download of broken".into(),
+                });
+            }
+            // a distinct image per preview so each gets its own hash
+            let shade = preview.source_id.bytes().fold(0u8, u8::wrapping_add);
+            let mut png = Vec::new();
+            image::RgbImage::from_pixel(16, 9, image::Rgb([shade, 64, 128]))
+                .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+                .unwrap();
+            Ok(png.into())
+        }
+    }
+
+    fn preview(source: &str, id: &str, w: u32, h: u32, tags: &[&str]) -> WallpaperPreview {
+        WallpaperPreview {
+            source_type: SourceType::new(source),
+            source_id: id.into(),
+            source_url: String::new(),
+            thumbnail_url: String::new(),
+            full_url: String::new(),
+            sample_url: None,
+            width: w,
+            height: h,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            meta: WallpaperMeta::default(),
+        }
+    }
+
+    fn registry() -> SourceRegistry {
+        let mut registry = SourceRegistry::new();
+        registry.register(Box::new(FixedSource {
+            id: "a",
+            results: vec![
+                preview("a", "ok", 1920, 1080, &[]),
+                preview("a", "small", 800, 600, &[]),
+                preview("a", "blocked", 1920, 1080, &["Anime"]),
+                preview("a", "excluded", 1920, 1080, &["cartoon"]),
+                preview("a", "banned", 1920, 1080, &[]),
+                preview("a", "ok", 1920, 1080, &[]),
+                preview("a", "unknown", 0, 0, &[]),
+            ],
+            fail: false,
+        }));
+        registry.register(Box::new(FixedSource {
+            id: "b",
+            results: vec![preview("b", "fav", 2560, 1440, &[])],
+            fail: false,
+        }));
+        registry.register(Box::new(FixedSource {
+            id: "down",
+            results: Vec::new(),
+            fail: true,
+        }));
+        registry
+    }
+
+    fn ids(out: &SearchOutput) -> Vec<&str> {
+        out.results.iter().map(|r| r.source_id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_all_filters_and_dedups() {
+        let db = Database::open_in_memory().unwrap();
+        db.add_tag_blacklist("anime").unwrap();
+        db.add_blacklist("banned", &SourceType::new("a")).unwrap();
+
+        let opts = SearchOptions {
+            source: Some("a".into()),
+            min_resolution: Some((1920, 1080)),
+            filter: Some(FilterConfig {
+                min_width: 0,
+                min_height: 0,
+                exclude_tags: vec!["cartoon".into()],
+                ..FilterConfig::default()
+            }),
+            ..SearchOptions::default()
+        };
+        let out = registry().search_all(&db, &opts).await;
+        assert_eq!(ids(&out), vec!["ok", "unknown"]);
+        assert!(out.any_ok);
+        assert!(out.failures.is_empty());
+        assert!(!out.has_more);
+    }
+
+    #[tokio::test]
+    async fn test_search_all_marks_favorites_and_reports_failures() {
+        let db = Database::open_in_memory().unwrap();
+        let wp = crate::models::Wallpaper {
+            id: "hash".into(),
+            source_type: SourceType::new("b"),
+            source_id: "fav".into(),
+            source_url: None,
+            width: 2560,
+            height: 1440,
+            tags: Vec::new(),
+            file_path: "/tmp/hash.jpg".into(),
+            added_at: "2025-01-01T00:00:00Z".into(),
+            last_used: None,
+            use_count: 0,
+            brightness: None,
+            crop: None,
+            colors: vec![[10, 20, 30]],
+            meta: Default::default(),
+        };
+        db.insert_wallpaper(&wp).unwrap();
+
+        let opts = SearchOptions {
+            per_source_cap: Some(1),
+            ..SearchOptions::default()
+        };
+        let out = registry().search_all(&db, &opts).await;
+        assert_eq!(ids(&out), vec!["ok", "fav"]);
+        assert!(!out.results[0].is_favorited);
+        assert!(out.results[1].is_favorited);
+        assert_eq!(out.results[1].favorite_id, "hash");
+        assert_eq!(out.results[1].colors, vec![[10, 20, 30]]);
+        assert!(out.results[0].colors.is_empty());
+        assert_eq!(out.failures.len(), 1);
+        assert_eq!(out.failures[0].0, "down");
+    }
+
+    #[tokio::test]
+    async fn test_empty_success_is_not_a_failure() {
+        let db = Database::open_in_memory().unwrap();
+
+        let out = registry().search_all(&db, &SearchOptions::default()).await;
+        assert_eq!(out.status, SearchStatus::Results);
+
+        // "b" answers, but its only result is filtered out
+        let opts = SearchOptions {
+            source: Some("b".into()),
+            min_resolution: Some((3840, 2160)),
+            ..SearchOptions::default()
+        };
+        let out = registry().search_all(&db, &opts).await;
+        assert_eq!(out.status, SearchStatus::NoResults);
+        assert_eq!(out.error, None);
+
+        let opts = SearchOptions {
+            source: Some("down".into()),
+            ..SearchOptions::default()
+        };
+        let out = registry().search_all(&db, &opts).await;
+        assert_eq!(out.status, SearchStatus::Failed);
+        assert!(out.error.as_deref().unwrap().starts_with("down: "));
+        let json = serde_json::to_value(&out).unwrap();
+        assert_eq!(json["status"], "failed");
+    }
+
+    #[tokio::test]
+    async fn test_favorite_first_takes_first_n_in_order() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let manager = WallpaperManager::new(paths);
+        let db = Database::open_in_memory().unwrap();
+
+        let mut registry = SourceRegistry::new();
+        registry.register(Box::new(FixedSource {
+            id: "s",
+            results: ["one", "broken", "three", "four"]
+                .iter()
+                .map(|id| preview("s", id, 1920, 1080, &[]))
+                .collect(),
+            fail: false,
+        }));
+        let opts = SearchOptions {
+            source: Some("s".into()),
+            ..SearchOptions::default()
+        };
+        let no_retry = RetryPolicy {
+            retries: 0,
+            base_delay: std::time::Duration::ZERO,
+        };
+        let (outcomes, failures) = registry
+            .favorite_first(&db, &manager, &opts, 3, no_retry)
+            .await;
+
+        assert!(failures.is_empty());
+        let tried: Vec<&str> = outcomes
+            .iter()
+            .map(|o| o.preview.source_id.as_str())
+            .collect();
+        assert_eq!(tried, vec!["one", "broken", "three"]);
+        assert!(outcomes[0].result.is_ok());
+        assert!(outcomes[1].result.is_err());
+        assert!(outcomes[2].result.is_ok());
+        assert_eq!(db.wallpaper_count().unwrap(), 2);
+        assert!(db.is_favorited_by_source("s", "three").unwrap());
+        assert!(!db.is_favorited_by_source("s", "four").unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_favorite_first_skips_library_and_reads_more_pages() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let manager = WallpaperManager::new(paths);
+        let db = Database::open_in_memory().unwrap();
+
+        let mut registry = SourceRegistry::new();
+        registry.register(Box::new(FixedSource {
+            id: "s",
+            results: ["one", "two", "three", "four", "five"]
+                .iter()
+                .map(|id| preview("s", id, 1920, 1080, &[]))
+                .collect(),
+            fail: false,
+        }));
+        let opts = SearchOptions {
+            source: Some("s".into()),
+            per_page: 2,
+            ..SearchOptions::default()
+        };
+        let no_retry = RetryPolicy {
+            retries: 0,
+            base_delay: std::time::Duration::ZERO,
+        };
+        let tried = |outcomes: &[FavoriteOutcome]| -> Vec<String> {
+            outcomes
+                .iter()
+                .map(|o| o.preview.source_id.clone())
+                .collect()
+        };
+
+        let (outcomes, _) = registry
+            .favorite_first(&db, &manager, &opts, 1, no_retry)
+            .await;
+        assert_eq!(tried(&outcomes), vec!["one"]);
+
+        // "one" is already in the library; the rest span pages 1 to 3
+        let (outcomes, _) = registry
+            .favorite_first(&db, &manager, &opts, 3, no_retry)
+            .await;
+        assert_eq!(tried(&outcomes), vec!["two", "three", "four"]);
+
+        // only "five" is left: a shortfall, not a re-download
+        let (outcomes, _) = registry
+            .favorite_first(&db, &manager, &opts, 3, no_retry)
+            .await;
+        assert_eq!(tried(&outcomes), vec!["five"]);
+        assert_eq!(db.wallpaper_count().unwrap(), 5);
+    }
+}
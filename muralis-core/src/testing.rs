@@ -0,0 +1,188 @@
+//! A scripted HTTP server for tests of code that talks to sources.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// What the server sends back for one request.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    /// Sent as a chunked body, waiting `delay` before each chunk.
+    chunks: Option<(Vec<Vec<u8>>, Duration)>,
+    silent: bool,
+}
+
+impl MockResponse {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            chunks: None,
+            silent: false,
+        }
+    }
+
+    /// 200 with `body`.
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::new(200).body(body)
+    }
+
+    /// Never answered; the connection is held open until the server drops.
+    pub fn silent() -> Self {
+        Self {
+            silent: true,
+            ..Self::new(200)
+        }
+    }
+
+    /// 200 with a chunked body, sending each of `chunks` after `delay`.
+    pub fn streamed(chunks: Vec<Vec<u8>>, delay: Duration) -> Self {
+        Self {
+            chunks: Some((chunks, delay)),
+            ..Self::new(200)
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+}
+
+/// Serves every request on its own connection with a response picked from
+/// the request, recording what it received.
+pub struct MockServer {
+    base: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    /// Start serving; `respond` gets each request's head and body, lowercased.
+    pub async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(&str) -> MockResponse + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let respond = Arc::new(respond);
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let log = log.clone();
+                let respond = respond.clone();
+                tokio::spawn(async move {
+                    let request = read_request(&mut stream).await;
+                    let response = respond(&request);
+                    log.lock().unwrap().push(request);
+                    write_response(stream, response).await;
+                });
+            }
+        });
+        Self { base, requests }
+    }
+
+    /// Answer the `n`th request (from 0) with `statuses[n]`, 200 after that,
+    /// always with `Retry-After: 0`.
+    pub async fn statuses(statuses: Vec<u16>) -> Self {
+        let served = std::sync::atomic::AtomicUsize::new(0);
+        Self::start(move |_| {
+            let n = served.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let status = statuses.get(n).copied().unwrap_or(200);
+            let body = if status == 200 { "ok" } else { "busy" };
+            MockResponse::new(status)
+                .header("retry-after", "0")
+                .body(body)
+        })
+        .await
+    }
+
+    /// `path` on this server, e.g. `url("/feed.xml")`.
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{path}", self.base)
+    }
+
+    /// Requests received so far, lowercased, oldest first.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// The request head plus as much body as `content-length` announces.
+async fn read_request(stream: &mut TcpStream) -> String {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    let head_end = loop {
+        if let Some(i) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break data.len(),
+            Ok(n) => data.extend_from_slice(&buf[..n]),
+        }
+    };
+    let head = String::from_utf8_lossy(&data[..head_end]).to_lowercase();
+    let body_len = head
+        .lines()
+        .find_map(|l| l.strip_prefix("content-length:"))
+        .and_then(|v| v.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+    while data.len() < head_end + body_len {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => data.extend_from_slice(&buf[..n]),
+        }
+    }
+    String::from_utf8_lossy(&data).to_lowercase()
+}
+
+async fn write_response(mut stream: TcpStream, response: MockResponse) {
+    if response.silent {
+        // hold the connection until the test's runtime shuts down
+        std::future::pending::<()>().await;
+    }
+    let reason = reqwest::StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|s| s.canonical_reason())
+        .unwrap_or("Unknown");
+    let mut head = format!("HTTP/1.1 {} {reason}\r\n", response.status);
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    match &response.chunks {
+        Some(_) => head.push_str("transfer-encoding: chunked\r\n"),
+        None => head.push_str(&format!("content-length: {}\r\n", response.body.len())),
+    }
+    head.push_str("connection: close\r\n\r\n");
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    let Some((chunks, delay)) = response.chunks else {
+        let _ = stream.write_all(&response.body).await;
+        return;
+    };
+    for chunk in chunks {
+        tokio::time::sleep(delay).await;
+        let mut framed = format!("{:x}\r\n", chunk.len()).into_bytes();
+        framed.extend_from_slice(&chunk);
+        framed.extend_from_slice(b"\r\n");
+        if stream.write_all(&framed).await.is_err() {
+            return;
+        }
+    }
+    let _ = stream.write_all(b"0\r\n\r\n").await;
+}
@@ -0,0 +1,67 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::error::{MuralisError, Result};
+
+/// Env var naming the image viewer command, e.g. `imv` or `feh -F`.
+pub const VIEWER_ENV: &str = "MURALIS_VIEWER";
+
+/// Viewer program and args: the override if set (split on whitespace),
+/// otherwise `xdg-open`.
+pub fn viewer_command(override_cmd: Option<&str>) -> Vec<String> {
+    match override_cmd.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(cmd) => cmd.split_whitespace().map(String::from).collect(),
+        None => vec!["xdg-open".to_string()],
+    }
+}
+
+/// Open `path` in the user's viewer, detached from the calling process.
+pub fn open_external(path: &Path) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    let (name, mut command) = viewer(path);
+    command
+        .process_group(0)
+        .spawn()
+        .map_err(|e| MuralisError::Backend(format!("failed to launch {name}: {e}")))?;
+    Ok(())
+}
+
+/// Open `path` in the user's viewer and wait for the viewer to exit, so
+/// the caller can clean the file up afterwards.
+pub fn open_and_wait(path: &Path) -> Result<()> {
+    let (name, mut command) = viewer(path);
+    command
+        .status()
+        .map_err(|e| MuralisError::Backend(format!("failed to launch {name}: {e}")))?;
+    Ok(())
+}
+
+fn viewer(path: &Path) -> (String, Command) {
+    let env = std::env::var(VIEWER_ENV).ok();
+    let cmd = viewer_command(env.as_deref());
+    let mut command = Command::new(&cmd[0]);
+    command
+        .args(&cmd[1..])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    (cmd[0].clone(), command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewer_command_resolution() {
+        assert_eq!(viewer_command(None), vec!["xdg-open"]);
+        assert_eq!(viewer_command(Some("  ")), vec!["xdg-open"]);
+        assert_eq!(viewer_command(Some("imv")), vec!["imv"]);
+        assert_eq!(
+            viewer_command(Some("feh -F --zoom max")),
+            vec!["feh", "-F", "--zoom", "max"]
+        );
+    }
+}
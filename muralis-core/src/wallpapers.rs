@@ -1,35 +1,71 @@
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
 
+use image::codecs::jpeg::JpegEncoder;
 use image::imageops::FilterType;
-use image::ImageReader;
+use image::{DynamicImage, GenericImageView, ImageReader};
 use sha2::{Digest, Sha256};
 
+use crate::config::ThumbnailConfig;
 use crate::db::Database;
 use crate::error::{MuralisError, Result};
-use crate::models::{SourceType, Wallpaper, WallpaperPreview};
+use crate::models::{
+    ChromaSubsampling, CropRect, HashAlgo, MonitorInfo, SourceType, Wallpaper, WallpaperMeta,
+    WallpaperPreview,
+};
 use crate::paths::MuralisPaths;
 
 const THUMBNAIL_WIDTH: u32 = 300;
 
+/// Colors kept per wallpaper for color browsing.
+pub const PALETTE_SIZE: usize = 5;
+
 pub struct WallpaperManager {
     paths: MuralisPaths,
+    thumbnails: ThumbnailConfig,
+    square_thumbnails: bool,
+    hash_algo: HashAlgo,
 }
 
 impl WallpaperManager {
     pub fn new(paths: MuralisPaths) -> Self {
-        Self { paths }
+        Self {
+            paths,
+            thumbnails: ThumbnailConfig::default(),
+            square_thumbnails: false,
+            hash_algo: HashAlgo::default(),
+        }
+    }
+
+    /// JPEG settings for thumbnails written from now on.
+    pub fn with_thumbnails(mut self, thumbnails: ThumbnailConfig) -> Self {
+        self.thumbnails = thumbnails;
+        self
+    }
+
+    /// Hash used for new favorite IDs.
+    pub fn with_hash_algo(mut self, algo: HashAlgo) -> Self {
+        self.hash_algo = algo;
+        self
+    }
+
+    /// Crop stored thumbnails to squares for a uniform grid.
+    pub fn with_square_thumbnails(mut self, square: bool) -> Self {
+        self.square_thumbnails = square;
+        self
     }
 
     /// Favorite a wallpaper: download, hash, save, generate thumbnail, insert to DB.
-    /// Returns the wallpaper ID (SHA-256 hash).
+    /// Returns the wallpaper ID (content hash). Non-zero preview dimensions
+    /// and the preview's `meta` are stored as given; the file size defaults
+    /// to `data.len()`.
     pub fn favorite(
         &self,
         db: &Database,
         preview: &WallpaperPreview,
         data: &[u8],
     ) -> Result<String> {
-        let hash = sha256_hex(data);
+        let hash = content_hash(data, self.hash_algo);
 
         // dedup check
         if db.wallpaper_exists(&hash)? {
@@ -38,10 +74,15 @@ impl WallpaperManager {
 
         let ext = guess_extension(data);
         let file_path = self.paths.wallpapers_dir().join(format!("{hash}.{ext}"));
-        std::fs::write(&file_path, data)?;
+        write_atomic(&file_path, data)?;
 
-        // generate thumbnail
-        self.generate_thumbnail(data, &hash)?;
+        // the thumbnail pass decodes anyway, so it also supplies unknown dimensions (e.g. feeds)
+        let (thumb, (decoded_w, decoded_h)) = self.generate_thumbnail(data, &hash)?;
+        let (width, height) = if preview.width == 0 || preview.height == 0 {
+            (decoded_w, decoded_h)
+        } else {
+            (preview.width, preview.height)
+        };
 
         let now = chrono::Utc::now().to_rfc3339();
         let wp = Wallpaper {
@@ -49,30 +90,20 @@ impl WallpaperManager {
             source_type: preview.source_type.clone(),
             source_id: preview.source_id.clone(),
             source_url: Some(preview.source_url.clone()),
-            width: preview.width,
-            height: preview.height,
+            width,
+            height,
             tags: preview.tags.clone(),
             file_path: file_path.to_string_lossy().to_string(),
             added_at: now,
             last_used: None,
             use_count: 0,
-        };
-
-        // if dimensions unknown (e.g. feed), read from image
-        let wp = if wp.width == 0 || wp.height == 0 {
-            match ImageReader::new(Cursor::new(data)).with_guessed_format() {
-                Ok(reader) => match reader.decode() {
-                    Ok(img) => Wallpaper {
-                        width: img.width(),
-                        height: img.height(),
-                        ..wp
-                    },
-                    Err(_) => wp,
-                },
-                Err(_) => wp,
-            }
-        } else {
-            wp
+            brightness: Some(mean_luma(&thumb)),
+            crop: None,
+            colors: dominant_colors(&thumb, PALETTE_SIZE),
+            meta: WallpaperMeta {
+                file_size: preview.meta.file_size.or(Some(data.len() as u64)),
+                ..preview.meta.clone()
+            },
         };
 
         db.insert_wallpaper(&wp)?;
@@ -94,11 +125,89 @@ impl WallpaperManager {
         if thumb_path.exists() {
             std::fs::remove_file(thumb_path)?;
         }
+        self.remove_cropped(id)?;
 
         db.delete_wallpaper(id)?;
         Ok(())
     }
 
+    /// Store `id`'s crop, dropping any crop file cut with the old rect.
+    pub fn set_crop(&self, db: &Database, id: &str, crop: Option<CropRect>) -> Result<()> {
+        db.set_crop(id, crop)?;
+        self.remove_cropped(id)
+    }
+
+    /// Delete the cached crops and per-monitor copies made from `id`'s file,
+    /// all named `<id>_...`.
+    pub fn remove_cropped(&self, id: &str) -> Result<()> {
+        let prefix = format!("{id}_");
+        let Ok(entries) = std::fs::read_dir(self.paths.cropped_dir()) else {
+            return Ok(());
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove library entries whose wallpaper file no longer exists, along
+    /// with their thumbnails and crops. Returns the affected IDs; `dry_run` only reports.
+    pub fn prune_missing(&self, db: &Database, dry_run: bool) -> Result<Vec<String>> {
+        let mut pruned = Vec::new();
+        for wp in db.list_wallpapers()? {
+            if Path::new(&wp.file_path).exists() {
+                continue;
+            }
+            if !dry_run {
+                let thumb_path = self.thumbnail_path(&wp.id);
+                if thumb_path.exists() {
+                    std::fs::remove_file(thumb_path)?;
+                }
+                self.remove_cropped(&wp.id)?;
+                db.delete_wallpaper(&wp.id)?;
+            }
+            pruned.push(wp.id);
+        }
+        Ok(pruned)
+    }
+
+    /// Unfavorite the least recently used favorites until the library's
+    /// files fit in `max_bytes`. Favorites in a collection and `keep` are
+    /// never evicted. Returns the evicted IDs; `dry_run` only reports.
+    pub fn evict_library(
+        &self,
+        db: &Database,
+        max_bytes: u64,
+        keep: Option<&str>,
+        dry_run: bool,
+    ) -> Result<Vec<String>> {
+        let collected = db.collected_wallpaper_ids()?;
+        let entries: Vec<LibraryEntry> = db
+            .list_wallpapers()?
+            .into_iter()
+            .map(|wp| LibraryEntry {
+                bytes: std::fs::metadata(&wp.file_path)
+                    .map(|m| m.len())
+                    .ok()
+                    .or(wp.meta.file_size)
+                    .unwrap_or(0),
+                pinned: collected.contains(&wp.id) || keep == Some(wp.id.as_str()),
+                last_used: wp.last_used.unwrap_or(wp.added_at),
+                use_count: wp.use_count,
+                id: wp.id,
+            })
+            .collect();
+        let evicted = select_library_evictions(&entries, max_bytes);
+        if !dry_run {
+            for id in &evicted {
+                self.unfavorite(db, id)?;
+            }
+        }
+        Ok(evicted)
+    }
+
     /// List all favorited wallpapers.
     pub fn list(&self, db: &Database) -> Result<Vec<Wallpaper>> {
         db.list_wallpapers()
@@ -119,6 +228,76 @@ impl WallpaperManager {
         self.paths.thumbnails_dir().join(format!("{id}_thumb.jpg"))
     }
 
+    /// File to hand the backend for `wp`: its stored crop, cached on first
+    /// use, or the original file when it has no crop.
+    pub fn display_path(&self, wp: &Wallpaper) -> Result<PathBuf> {
+        let Some(rect) = wp.crop else {
+            return Ok(PathBuf::from(&wp.file_path));
+        };
+        let dest = self.paths.cropped_dir().join(format!(
+            "{}_{}_{}_{}x{}.jpg",
+            wp.id, rect.x, rect.y, rect.width, rect.height
+        ));
+        if !dest.exists() {
+            let img = image::open(&wp.file_path)?;
+            self.write_cropped(&dest, &crop_to(&img, rect))?;
+        }
+        Ok(dest)
+    }
+
+    /// `path` cropped to `monitor`'s aspect ratio and scaled to its exact
+    /// resolution, cached per file and monitor. Monitors without a known
+    /// size, and files that aren't still images (videos for mpvpaper), get
+    /// the file as-is. Decodes and resizes, so keep it off async threads.
+    pub fn monitor_path(&self, path: &Path, monitor: &MonitorInfo) -> Result<PathBuf> {
+        if monitor.width == 0 || monitor.height == 0 || image::ImageFormat::from_path(path).is_err()
+        {
+            return Ok(path.to_path_buf());
+        }
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy())
+            .unwrap_or_default();
+        // the same file name can turn up in different folders
+        let path_hash = blake3::hash(path.as_os_str().as_encoded_bytes()).to_hex();
+        let dest = self.paths.cropped_dir().join(format!(
+            "{stem}_{}_{}_{}x{}.jpg",
+            &path_hash[..8],
+            monitor.name,
+            monitor.width,
+            monitor.height
+        ));
+        if !dest.exists() {
+            let img = image::open(path)?;
+            let (x, y, width, height) = crate::crop_overlay::compute_crop_rect(
+                img.width(),
+                img.height(),
+                monitor.width,
+                monitor.height,
+            );
+            let fitted = crop_to(
+                &img,
+                CropRect {
+                    x,
+                    y,
+                    width,
+                    height,
+                },
+            )
+            .resize_exact(monitor.width, monitor.height, FilterType::Lanczos3);
+            self.write_cropped(&dest, &fitted)?;
+        }
+        Ok(dest)
+    }
+
+    fn write_cropped(&self, dest: &Path, img: &DynamicImage) -> Result<()> {
+        std::fs::create_dir_all(self.paths.cropped_dir())?;
+        let mut buf = Vec::new();
+        let encoder = JpegEncoder::new_with_quality(&mut buf, 95);
+        img.to_rgb8().write_with_encoder(encoder)?;
+        write_atomic(dest, &buf)
+    }
+
     /// Favorite from a local file path.
     pub fn favorite_local(&self, db: &Database, path: &Path) -> Result<String> {
         if !path.exists() {
@@ -126,7 +305,7 @@ impl WallpaperManager {
         }
 
         let data = std::fs::read(path)?;
-        let hash = sha256_hex(&data);
+        let hash = content_hash(&data, self.hash_algo);
 
         if db.wallpaper_exists(&hash)? {
             return Ok(hash);
@@ -136,11 +315,7 @@ impl WallpaperManager {
         let dest = self.paths.wallpapers_dir().join(format!("{hash}.{ext}"));
         std::fs::copy(path, &dest)?;
 
-        self.generate_thumbnail(&data, &hash)?;
-
-        let img = ImageReader::new(Cursor::new(&data))
-            .with_guessed_format()?
-            .decode()?;
+        let (thumb, (width, height)) = self.generate_thumbnail(&data, &hash)?;
 
         let now = chrono::Utc::now().to_rfc3339();
         let wp = Wallpaper {
@@ -148,20 +323,60 @@ impl WallpaperManager {
             source_type: SourceType::new("local"),
             source_id: path.to_string_lossy().to_string(),
             source_url: None,
-            width: img.width(),
-            height: img.height(),
+            width,
+            height,
             tags: Vec::new(),
             file_path: dest.to_string_lossy().to_string(),
             added_at: now,
             last_used: None,
             use_count: 0,
+            brightness: Some(mean_luma(&thumb)),
+            crop: None,
+            colors: dominant_colors(&thumb, PALETTE_SIZE),
+            meta: WallpaperMeta {
+                file_size: Some(data.len() as u64),
+                ..Default::default()
+            },
         };
 
         db.insert_wallpaper(&wp)?;
         Ok(hash)
     }
 
-    fn generate_thumbnail(&self, data: &[u8], hash: &str) -> Result<()> {
+    /// Compute brightness and palette for library entries added before they
+    /// were tracked. Uses the stored thumbnail when present. Returns the
+    /// number updated.
+    pub fn backfill_analysis(&self, db: &Database) -> Result<u32> {
+        let mut updated = 0;
+        for wp in db.list_wallpapers()? {
+            if wp.brightness.is_some() && !wp.colors.is_empty() {
+                continue;
+            }
+            let thumb = self.thumbnail_path(&wp.id);
+            let source = if thumb.exists() {
+                thumb
+            } else {
+                PathBuf::from(&wp.file_path)
+            };
+            match image::open(&source) {
+                Ok(img) => {
+                    if wp.brightness.is_none() {
+                        db.set_brightness(&wp.id, mean_luma(&img))?;
+                    }
+                    if wp.colors.is_empty() {
+                        db.set_colors(&wp.id, &dominant_colors(&img, PALETTE_SIZE))?;
+                    }
+                    updated += 1;
+                }
+                Err(e) => tracing::warn!(id = %wp.id, "image analysis failed: {e}"),
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Write the thumbnail for `data` and return it for further analysis,
+    /// along with the full image's dimensions.
+    fn generate_thumbnail(&self, data: &[u8], hash: &str) -> Result<(DynamicImage, (u32, u32))> {
         let img = ImageReader::new(Cursor::new(data))
             .with_guessed_format()?
             .decode()?;
@@ -170,9 +385,275 @@ impl WallpaperManager {
             (THUMBNAIL_WIDTH as f64 / img.width() as f64 * img.height() as f64) as u32;
         let thumb = img.resize_exact(THUMBNAIL_WIDTH, thumb_height, FilterType::Lanczos3);
 
+        // analysis still sees the whole image; only the stored copy is cropped
+        let stored = if self.square_thumbnails {
+            square_crop(&img).resize_exact(THUMBNAIL_WIDTH, THUMBNAIL_WIDTH, FilterType::Lanczos3)
+        } else {
+            thumb.clone()
+        };
+
         let thumb_path = self.thumbnail_path(hash);
-        thumb.save(&thumb_path)?;
-        Ok(())
+        std::fs::write(&thumb_path, encode_thumbnail(&stored, &self.thumbnails)?)?;
+        Ok((thumb, img.dimensions()))
+    }
+}
+
+/// A favorite's footprint and use, for [`select_library_evictions`].
+#[derive(Debug, Clone)]
+pub struct LibraryEntry {
+    pub id: String,
+    /// Size of the stored file.
+    pub bytes: u64,
+    /// Last shown (RFC 3339), or when it was added if never shown.
+    pub last_used: String,
+    pub use_count: u32,
+    /// Never evicted.
+    pub pinned: bool,
+}
+
+/// IDs to evict so the library fits in `target_bytes`: least recently used
+/// first, fewer uses breaking ties. Pinned entries are skipped, so the
+/// library can stay over the limit when they alone exceed it.
+pub fn select_library_evictions(entries: &[LibraryEntry], target_bytes: u64) -> Vec<String> {
+    let mut total: u64 = entries.iter().map(|e| e.bytes).sum();
+    let mut candidates: Vec<&LibraryEntry> = entries.iter().filter(|e| !e.pinned).collect();
+    candidates.sort_by(|a, b| {
+        a.last_used
+            .cmp(&b.last_used)
+            .then(a.use_count.cmp(&b.use_count))
+    });
+
+    let mut evicted = Vec::new();
+    for entry in candidates {
+        if total <= target_bytes {
+            break;
+        }
+        total -= entry.bytes;
+        evicted.push(entry.id.clone());
+    }
+    evicted
+}
+
+/// Wallpaper to apply after favoriting `added` (in selection order): the
+/// first one when `apply_on_favorite` is set, so batches apply only once.
+pub fn apply_after_favorite(apply_on_favorite: bool, added: &[String]) -> Option<&str> {
+    if apply_on_favorite {
+        added.first().map(String::as_str)
+    } else {
+        None
+    }
+}
+
+/// Extension of files still being written; renamed into place when complete.
+const PARTIAL_EXT: &str = "part";
+
+/// Write `data` to `<path>.part`, then rename it over `path`, so an
+/// interrupted write never leaves a truncated image behind.
+pub fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".");
+    partial.push(PARTIAL_EXT);
+    let partial = PathBuf::from(partial);
+    std::fs::write(&partial, data)?;
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Delete `.part` files left in `dir` by interrupted writes, sparing any
+/// modified within `min_age` that another process may still be writing.
+/// Returns how many were removed.
+pub fn remove_partial_files(dir: &Path, min_age: std::time::Duration) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            e.metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|t| t.elapsed().is_ok_and(|age| age >= min_age))
+        })
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == PARTIAL_EXT))
+        .filter(|p| match std::fs::remove_file(p) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(path = %p.display(), "failed to remove partial file: {e}");
+                false
+            }
+        })
+        .count()
+}
+
+/// Crop to `rect`, clamped to the image bounds.
+pub fn crop_to(img: &DynamicImage, rect: CropRect) -> DynamicImage {
+    let x = rect.x.min(img.width().saturating_sub(1));
+    let y = rect.y.min(img.height().saturating_sub(1));
+    let width = rect.width.clamp(1, img.width() - x);
+    let height = rect.height.clamp(1, img.height() - y);
+    img.crop_imm(x, y, width, height)
+}
+
+/// Mean luma of an image in 0.0 (black) ..= 1.0 (white), sampled from a
+/// small downscaled copy so it stays cheap for large inputs.
+pub fn mean_luma(img: &DynamicImage) -> f32 {
+    let small = img.thumbnail(64, 64).to_luma8();
+    let pixels = small.as_raw();
+    if pixels.is_empty() {
+        return 0.0;
+    }
+    let sum: u64 = pixels.iter().map(|&p| p as u64).sum();
+    sum as f32 / (pixels.len() as f32 * 255.0)
+}
+
+/// Up to `k` dominant colors, most common first, by median cut over a
+/// small downscaled copy.
+pub fn dominant_colors(img: &DynamicImage, k: usize) -> Vec<[u8; 3]> {
+    let small = img.thumbnail(64, 64).to_rgb8();
+    let pixels: Vec<[u8; 3]> = small.pixels().map(|p| p.0).collect();
+    if pixels.is_empty() || k == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![pixels];
+    while boxes.len() < k {
+        // split the box spanning the widest channel range at its median
+        let Some((i, channel, range)) = boxes
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                let (channel, range) = widest_channel(b);
+                (i, channel, range)
+            })
+            .max_by_key(|&(_, _, range)| range)
+        else {
+            break;
+        };
+        if range == 0 {
+            break;
+        }
+        let mut lower = boxes.swap_remove(i);
+        lower.sort_unstable_by_key(|p| p[channel]);
+        let upper = lower.split_off(lower.len() / 2);
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    boxes.sort_by_key(|b| std::cmp::Reverse(b.len()));
+    boxes
+        .iter()
+        .map(|b| {
+            let mut sum = [0u64; 3];
+            for p in b {
+                for c in 0..3 {
+                    sum[c] += p[c] as u64;
+                }
+            }
+            let n = b.len() as u64;
+            [(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8]
+        })
+        .collect()
+}
+
+/// Channel with the largest value spread in `pixels`, and that spread.
+fn widest_channel(pixels: &[[u8; 3]]) -> (usize, u8) {
+    (0..3)
+        .map(|c| {
+            let (lo, hi) = pixels.iter().fold((u8::MAX, u8::MIN), |(lo, hi), p| {
+                (lo.min(p[c]), hi.max(p[c]))
+            });
+            (c, hi.saturating_sub(lo))
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap_or((0, 0))
+}
+
+/// Crop to a square along the long axis, keeping the window with the most
+/// edge detail so the subject tends to stay in frame. Flat images crop centered.
+pub fn square_crop(img: &DynamicImage) -> DynamicImage {
+    let (w, h) = (img.width(), img.height());
+    let side = w.min(h);
+    if w == h || side == 0 {
+        return img.clone();
+    }
+    let offset = busiest_offset(img, side);
+    if w > h {
+        img.crop_imm(offset, 0, side, side)
+    } else {
+        img.crop_imm(0, offset, side, side)
+    }
+}
+
+/// Offset along the long axis of the `side`-long window with the highest
+/// gradient energy, measured on a small luma copy.
+fn busiest_offset(img: &DynamicImage, side: u32) -> u32 {
+    let landscape = img.width() > img.height();
+    let long = img.width().max(img.height());
+    let small = img.thumbnail(128, 128).to_luma8();
+    let (sw, sh) = small.dimensions();
+
+    // energy per column (landscape) or row (portrait)
+    let len = if landscape { sw } else { sh };
+    let mut energy = vec![0u64; len as usize];
+    for y in 1..sh {
+        for x in 1..sw {
+            let p = small.get_pixel(x, y)[0] as i32;
+            let dx = (p - small.get_pixel(x - 1, y)[0] as i32).unsigned_abs();
+            let dy = (p - small.get_pixel(x, y - 1)[0] as i32).unsigned_abs();
+            let i = if landscape { x } else { y };
+            energy[i as usize] += (dx + dy) as u64;
+        }
+    }
+
+    let scale = len as f64 / long as f64;
+    let window = ((side as f64 * scale).round() as usize).clamp(1, len as usize);
+    let center = (len as usize - window) / 2;
+    let mut sum: u64 = energy[..window].iter().sum();
+    let mut best = (sum, center.abs_diff(0), 0);
+    for start in 1..=(len as usize - window) {
+        sum = sum + energy[start + window - 1] - energy[start - 1];
+        // ties go to the window nearest the center
+        let candidate = (sum, center.abs_diff(start), start);
+        if candidate.0 > best.0 || (candidate.0 == best.0 && candidate.1 < best.1) {
+            best = candidate;
+        }
+    }
+    let offset = (best.2 as f64 / scale).round() as u32;
+    offset.min(long - side)
+}
+
+/// Encode a thumbnail as RGB JPEG (alpha is dropped; JPEG can't store it).
+/// The image crate only writes baseline JPEG with standard tables, so this
+/// goes through jpeg-encoder for optimized and progressive output.
+fn encode_thumbnail(img: &DynamicImage, settings: &ThumbnailConfig) -> Result<Vec<u8>> {
+    let rgb = img.to_rgb8();
+    let (Ok(width), Ok(height)) = (u16::try_from(rgb.width()), u16::try_from(rgb.height())) else {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{}x{} is too large for JPEG", rgb.width(), rgb.height()),
+        )
+        .into());
+    };
+    let mut buf = Vec::new();
+    let mut encoder = jpeg_encoder::Encoder::new(&mut buf, settings.quality.clamp(1, 100));
+    encoder.set_optimized_huffman_tables(settings.optimize);
+    encoder.set_progressive(settings.progressive);
+    encoder.set_sampling_factor(match settings.subsampling {
+        ChromaSubsampling::Yuv444 => jpeg_encoder::SamplingFactor::R_4_4_4,
+        ChromaSubsampling::Yuv422 => jpeg_encoder::SamplingFactor::R_4_2_2,
+        ChromaSubsampling::Yuv420 => jpeg_encoder::SamplingFactor::R_4_2_0,
+    });
+    encoder
+        .encode(&rgb, width, height, jpeg_encoder::ColorType::Rgb)
+        .map_err(std::io::Error::other)?;
+    Ok(buf)
+}
+
+/// Hex content hash of `data`, used as the wallpaper ID.
+pub fn content_hash(data: &[u8], algo: HashAlgo) -> String {
+    match algo {
+        HashAlgo::Sha256 => sha256_hex(data),
+        HashAlgo::Blake3 => blake3::hash(data).to_hex().to_string(),
     }
 }
 
@@ -206,6 +687,33 @@ mod tests {
     use super::*;
     use crate::db::Database;
 
+    #[test]
+    fn test_remove_partial_files_keeps_complete_ones() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_atomic(&tmp.path().join("done.jpg"), b"jpeg").unwrap();
+        std::fs::write(tmp.path().join("cut.jpg.part"), b"jp").unwrap();
+        std::fs::write(tmp.path().join("other.png.part"), b"").unwrap();
+        let hour = std::time::Duration::from_secs(3600);
+
+        // fresh ones may still be mid-write
+        assert_eq!(remove_partial_files(tmp.path(), hour), 0);
+        for name in ["cut.jpg.part", "other.png.part"] {
+            std::fs::File::options()
+                .write(true)
+                .open(tmp.path().join(name))
+                .unwrap()
+                .set_modified(std::time::SystemTime::now() - hour * 2)
+                .unwrap();
+        }
+        assert_eq!(remove_partial_files(tmp.path(), hour), 2);
+        let left: Vec<_> = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(left, vec!["done.jpg"]);
+        assert_eq!(remove_partial_files(&tmp.path().join("missing"), hour), 0);
+    }
+
     #[test]
     fn test_sha256_hex() {
         let hash = sha256_hex(b"hello world");
@@ -215,6 +723,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_content_hash_algos() {
+        for algo in [HashAlgo::Sha256, HashAlgo::Blake3] {
+            assert_eq!(content_hash(b"abc", algo), content_hash(b"abc", algo));
+            assert_ne!(content_hash(b"abc", algo), content_hash(b"abd", algo));
+            assert_eq!(content_hash(b"abc", algo).len(), 64);
+        }
+        assert_eq!(
+            content_hash(b"hello world", HashAlgo::Sha256),
+            sha256_hex(b"hello world")
+        );
+        assert_eq!(
+            content_hash(b"hello world", HashAlgo::Blake3),
+            "d74981efa70a0c880b8d8c1985d075dbcbf679b99a5f9914e5aaf96b831a9e24"
+        );
+        assert_ne!(
+            content_hash(b"hello world", HashAlgo::Sha256),
+            content_hash(b"hello world", HashAlgo::Blake3)
+        );
+    }
+
     #[test]
     fn test_guess_extension() {
         assert_eq!(guess_extension(&[0x89, b'P', b'N', b'G']), "png");
@@ -223,14 +752,378 @@ mod tests {
         assert_eq!(guess_extension(b"unknown"), "jpg");
     }
 
+    /// Whether `jpeg` starts its frame with `marker` (0xC0 baseline, 0xC2 progressive).
+    fn has_marker(jpeg: &[u8], marker: u8) -> bool {
+        jpeg.windows(2).any(|w| w == [0xFF, marker])
+    }
+
     #[test]
-    fn test_favorite_roundtrip() {
+    fn test_optimized_thumbnail_no_larger_than_baseline() {
+        // gradient + noise so the encoder has real detail to work with
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(300, 200, |x, y| {
+            let n = ((x * 7919 + y * 104_729) % 61) as u8;
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, n.wrapping_mul(4)])
+        }));
+        let optimized = ThumbnailConfig::default();
+        assert!(optimized.optimize);
+        let baseline = ThumbnailConfig {
+            optimize: false,
+            ..optimized
+        };
+
+        let small = encode_thumbnail(&img, &optimized).unwrap();
+        let plain = encode_thumbnail(&img, &baseline).unwrap();
+        assert!(
+            small.len() <= plain.len(),
+            "{} > {}",
+            small.len(),
+            plain.len()
+        );
+        for data in [small, plain] {
+            let decoded = image::load_from_memory(&data).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (300, 200));
+        }
+    }
+
+    #[test]
+    fn test_progressive_thumbnail_reaches_stored_file() {
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(300, 200, |x, y| {
+            image::Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        }));
+        let mut png = Vec::new();
+        img.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+
+        let baseline = WallpaperManager::new(paths.clone());
+        baseline.generate_thumbnail(&png, "base").unwrap();
+        let stored = std::fs::read(baseline.thumbnail_path("base")).unwrap();
+        assert!(has_marker(&stored, 0xC0) && !has_marker(&stored, 0xC2));
+
+        let progressive = WallpaperManager::new(paths).with_thumbnails(ThumbnailConfig {
+            progressive: true,
+            subsampling: ChromaSubsampling::Yuv444,
+            ..ThumbnailConfig::default()
+        });
+        progressive.generate_thumbnail(&png, "prog").unwrap();
+        let stored = std::fs::read(progressive.thumbnail_path("prog")).unwrap();
+        assert!(has_marker(&stored, 0xC2));
+        let decoded = image::load_from_memory(&stored).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (300, 200));
+    }
+
+    #[test]
+    fn test_mean_luma() {
+        let white = DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            200,
+            100,
+            image::Rgb([255, 255, 255]),
+        ));
+        let black = DynamicImage::ImageRgb8(image::RgbImage::new(200, 100));
+        assert!((mean_luma(&white) - 1.0).abs() < 0.01);
+        assert!(mean_luma(&black) < 0.01);
+    }
+
+    #[test]
+    fn test_dominant_colors_mostly_red() {
+        // 80% red, 20% blue
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(100, 100, |x, _| {
+            if x < 80 {
+                image::Rgb([220, 20, 30])
+            } else {
+                image::Rgb([20, 40, 200])
+            }
+        }));
+        let colors = dominant_colors(&img, 3);
+        assert!(!colors.is_empty() && colors.len() <= 3);
+        let [r, g, b] = colors[0];
+        assert!(
+            r > 180 && g < 60 && b < 60,
+            "expected red, got {:?}",
+            colors[0]
+        );
+        assert!(colors.iter().any(|c| c[2] > 150 && c[0] < 60));
+
+        let flat =
+            DynamicImage::ImageRgb8(image::RgbImage::from_pixel(10, 10, image::Rgb([9, 9, 9])));
+        assert_eq!(dominant_colors(&flat, 5), vec![[9, 9, 9]]);
+    }
+
+    #[test]
+    fn test_square_thumbnail_from_landscape() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let manager = WallpaperManager::new(paths).with_square_thumbnails(true);
+
+        let img = image::RgbImage::from_fn(800, 400, |x, _| image::Rgb([(x % 256) as u8, 0, 0]));
+        let mut buf = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png)
+            .unwrap();
+
+        manager.generate_thumbnail(&buf, "sq").unwrap();
+        let thumb = image::open(manager.thumbnail_path("sq")).unwrap();
+        assert_eq!(thumb.width(), thumb.height());
+    }
+
+    #[test]
+    fn test_display_path_applies_stored_crop() {
         let tmp = tempfile::tempdir().unwrap();
-        let paths = MuralisPaths {
-            config_dir: tmp.path().join("config"),
-            data_dir: tmp.path().join("data"),
-            cache_dir: tmp.path().join("cache"),
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let manager = WallpaperManager::new(paths.clone());
+
+        let file = paths.wallpapers_dir().join("wide.png");
+        image::RgbImage::new(3440, 1440).save(&file).unwrap();
+        let db = Database::open_in_memory().unwrap();
+        let id = manager.favorite_local(&db, &file).unwrap();
+        let mut wp = db.get_wallpaper(&id).unwrap();
+
+        // no crop: the original file
+        assert_eq!(manager.display_path(&wp).unwrap(), Path::new(&wp.file_path));
+
+        let rect = CropRect {
+            x: 400,
+            y: 0,
+            width: 2560,
+            height: 1440,
         };
+        db.set_crop(&id, Some(rect)).unwrap();
+        wp = db.get_wallpaper(&id).unwrap();
+        let cropped = manager.display_path(&wp).unwrap();
+        assert!(cropped.starts_with(paths.cropped_dir()));
+        let img = image::open(&cropped).unwrap();
+        assert_eq!((img.width(), img.height()), (rect.width, rect.height));
+
+        // out-of-bounds rects are clamped
+        let clamped = crop_to(
+            &img,
+            CropRect {
+                x: 2000,
+                y: 0,
+                width: 5000,
+                height: 5000,
+            },
+        );
+        assert_eq!((clamped.width(), clamped.height()), (560, 1440));
+
+        // changing the rect drops the crop cut with the old one
+        manager.set_crop(&db, &id, None).unwrap();
+        assert!(!cropped.exists());
+    }
+
+    fn library_entry(id: &str, mb: u64, last_used: &str, use_count: u32) -> LibraryEntry {
+        LibraryEntry {
+            id: id.into(),
+            bytes: mb * 1024 * 1024,
+            last_used: last_used.into(),
+            use_count,
+            pinned: false,
+        }
+    }
+
+    #[test]
+    fn test_library_evictions_least_recently_used_first() {
+        let entries = vec![
+            library_entry("recent", 4, "2025-06-01T00:00:00Z", 1),
+            library_entry("stale", 4, "2025-01-01T00:00:00Z", 9),
+            library_entry("stale-unloved", 4, "2025-01-01T00:00:00Z", 2),
+            library_entry("middle", 4, "2025-03-01T00:00:00Z", 5),
+        ];
+        assert!(select_library_evictions(&entries, 16 * 1024 * 1024).is_empty());
+        assert_eq!(
+            select_library_evictions(&entries, 10 * 1024 * 1024),
+            vec!["stale-unloved", "stale"]
+        );
+        assert_eq!(
+            select_library_evictions(&entries, 0),
+            vec!["stale-unloved", "stale", "middle", "recent"]
+        );
+    }
+
+    #[test]
+    fn test_library_evictions_skip_pinned() {
+        let mut entries = vec![
+            library_entry("old-pinned", 8, "2024-01-01T00:00:00Z", 0),
+            library_entry("newer", 4, "2025-01-01T00:00:00Z", 0),
+            library_entry("newest", 4, "2025-02-01T00:00:00Z", 0),
+        ];
+        entries[0].pinned = true;
+        assert_eq!(
+            select_library_evictions(&entries, 12 * 1024 * 1024),
+            vec!["newer"]
+        );
+        // pinned alone is over the limit: evict everything else and stop
+        assert_eq!(
+            select_library_evictions(&entries, 1024 * 1024),
+            vec!["newer", "newest"]
+        );
+    }
+
+    #[test]
+    fn test_prune_missing_keeps_present_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let manager = WallpaperManager::new(paths.clone());
+        let db = Database::open_in_memory().unwrap();
+
+        let mut ids = Vec::new();
+        for (name, shade) in [("kept.png", 10), ("gone.png", 200)] {
+            let file = tmp.path().join(name);
+            image::RgbImage::from_pixel(32, 32, image::Rgb([shade; 3]))
+                .save(&file)
+                .unwrap();
+            ids.push(manager.favorite_local(&db, &file).unwrap());
+        }
+        let gone = db.get_wallpaper(&ids[1]).unwrap();
+        std::fs::remove_file(&gone.file_path).unwrap();
+
+        assert_eq!(
+            manager.prune_missing(&db, true).unwrap(),
+            vec![ids[1].clone()]
+        );
+        assert_eq!(db.wallpaper_count().unwrap(), 2);
+
+        assert_eq!(
+            manager.prune_missing(&db, false).unwrap(),
+            vec![ids[1].clone()]
+        );
+        assert_eq!(db.wallpaper_count().unwrap(), 1);
+        assert!(db.get_wallpaper(&ids[0]).is_ok());
+        assert!(manager.thumbnail_path(&ids[0]).exists());
+        assert!(!manager.thumbnail_path(&ids[1]).exists());
+        assert!(manager.prune_missing(&db, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_evict_removes_cropped_copies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let manager = WallpaperManager::new(paths.clone());
+        let db = Database::open_in_memory().unwrap();
+
+        let file = tmp.path().join("wide.png");
+        image::RgbImage::from_pixel(64, 32, image::Rgb([90; 3]))
+            .save(&file)
+            .unwrap();
+        let id = manager.favorite_local(&db, &file).unwrap();
+        let rect = CropRect {
+            x: 0,
+            y: 0,
+            width: 32,
+            height: 32,
+        };
+        db.set_crop(&id, Some(rect)).unwrap();
+        let cropped = manager
+            .display_path(&db.get_wallpaper(&id).unwrap())
+            .unwrap();
+        assert!(cropped.exists());
+
+        assert_eq!(
+            manager.evict_library(&db, 0, None, false).unwrap(),
+            vec![id]
+        );
+        assert!(!cropped.exists());
+        assert_eq!(std::fs::read_dir(paths.cropped_dir()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_favorite_keeps_source_meta() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let db = Database::open_in_memory().unwrap();
+        let manager = WallpaperManager::new(paths);
+
+        let mut buf = Vec::new();
+        image::RgbImage::new(40, 30)
+            .write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png)
+            .unwrap();
+        // the source's dimensions are trusted over the bytes
+        let preview = WallpaperPreview {
+            source_type: SourceType::new("pexels"),
+            source_id: "px_1".into(),
+            source_url: "https://example.com/p/1".into(),
+            thumbnail_url: String::new(),
+            full_url: String::new(),
+            sample_url: None,
+            width: 6000,
+            height: 4000,
+            tags: Vec::new(),
+            meta: WallpaperMeta {
+                captured_at: Some("2024-05-01T10:00:00Z".into()),
+                photographer: Some("Ada".into()),
+                file_size: None,
+            },
+        };
+
+        let id = manager.favorite(&db, &preview, &buf).unwrap();
+        let wp = db.get_wallpaper(&id).unwrap();
+        assert_eq!((wp.width, wp.height), (6000, 4000));
+        assert_eq!(wp.meta.photographer.as_deref(), Some("Ada"));
+        assert_eq!(wp.meta.captured_at.as_deref(), Some("2024-05-01T10:00:00Z"));
+        assert_eq!(wp.meta.file_size, Some(buf.len() as u64));
+
+        // unknown dimensions come from the image itself
+        let mut unknown = preview.clone();
+        unknown.source_id = "px_2".into();
+        unknown.width = 0;
+        unknown.meta = WallpaperMeta::default();
+        buf.clear();
+        image::RgbImage::new(41, 30)
+            .write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png)
+            .unwrap();
+        let id = manager.favorite(&db, &unknown, &buf).unwrap();
+        let wp = db.get_wallpaper(&id).unwrap();
+        assert_eq!((wp.width, wp.height), (41, 30));
+        assert_eq!(wp.meta.photographer, None);
+    }
+
+    #[test]
+    fn test_apply_after_favorite() {
+        let one = vec!["a".to_string()];
+        let batch = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        assert_eq!(apply_after_favorite(true, &one), Some("a"));
+        assert_eq!(apply_after_favorite(true, &batch), Some("a"));
+        assert_eq!(apply_after_favorite(true, &[]), None);
+        assert_eq!(apply_after_favorite(false, &batch), None);
+    }
+
+    #[test]
+    fn test_square_crop_follows_detail() {
+        // flat left half, checkerboard right half
+        let img = image::RgbImage::from_fn(400, 100, |x, y| {
+            let v = if x >= 300 && (x / 4 + y / 4) % 2 == 0 {
+                255
+            } else {
+                0
+            };
+            image::Rgb([v, v, v])
+        });
+        let img = DynamicImage::ImageRgb8(img);
+        assert_eq!(busiest_offset(&img, 100), 300);
+        assert_eq!(square_crop(&img).width(), 100);
+
+        // no detail at all: centered
+        let flat = DynamicImage::ImageRgb8(image::RgbImage::new(400, 100));
+        assert_eq!(busiest_offset(&flat, 100), 150);
+    }
+
+    #[test]
+    fn test_thumbnail_encoding_drops_alpha() {
+        let img = DynamicImage::ImageRgba8(image::RgbaImage::new(10, 10));
+        let data = encode_thumbnail(&img, &ThumbnailConfig::default()).unwrap();
+        assert_eq!(guess_extension(&data), "jpg");
+    }
+
+    #[test]
+    fn test_favorite_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
         paths.ensure_dirs().unwrap();
 
         let db = Database::open_in_memory().unwrap();
@@ -248,9 +1141,11 @@ mod tests {
             source_url: "https://example.com".into(),
             thumbnail_url: "https://example.com/thumb.jpg".into(),
             full_url: "https://example.com/full.jpg".into(),
+            sample_url: None,
             width: 100,
             height: 100,
             tags: vec!["test".into()],
+            meta: WallpaperMeta::default(),
         };
 
         // favorite
@@ -265,6 +1160,9 @@ mod tests {
 
         // thumbnail exists
         assert!(manager.thumbnail_path(&id).exists());
+        // all-black test image
+        assert!(wp.brightness.unwrap() < 0.01);
+        assert_eq!(wp.colors, vec![[0, 0, 0]]);
 
         // dedup: same data returns same hash, no error
         let id2 = manager.favorite(&db, &preview, &buf).unwrap();
@@ -0,0 +1,166 @@
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
+
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info, warn};
+
+use muralis_core::config::Config;
+use muralis_core::paths::MuralisPaths;
+
+use crate::display::DaemonCommand;
+
+/// How often the config file's modification time is checked.
+const POLL: Duration = Duration::from_millis(250);
+
+/// Quiet period after the last change before reloading, so an editor's
+/// save (truncate, write, rename) reloads once and never mid-write.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Debounces modification times into settled changes.
+#[derive(Debug)]
+struct ChangeTracker {
+    seen: Option<SystemTime>,
+    pending: Option<Instant>,
+}
+
+impl ChangeTracker {
+    fn new(mtime: Option<SystemTime>) -> Self {
+        Self {
+            seen: mtime,
+            pending: None,
+        }
+    }
+
+    /// Whether the file, last modified at `mtime`, has changed and then
+    /// stayed put for `DEBOUNCE` as of `now`.
+    fn settled(&mut self, mtime: Option<SystemTime>, now: Instant) -> bool {
+        if mtime != self.seen {
+            self.seen = mtime;
+            self.pending = Some(now);
+            return false;
+        }
+        match self.pending {
+            Some(since) if now.duration_since(since) >= DEBOUNCE => {
+                self.pending = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// The config as a comparable value.
+fn snapshot(config: &Config) -> Option<serde_json::Value> {
+    serde_json::to_value(config).ok()
+}
+
+/// Reloads the daemon whenever the config file's settings change, until
+/// shutdown or until a reload turns `watch_config` off. Edits that don't
+/// parse keep the current config, and writes that leave the settings as
+/// they were (comments, formatting, a save that changed nothing) are
+/// ignored.
+pub async fn watch_config(
+    paths: MuralisPaths,
+    cmd_tx: mpsc::Sender<DaemonCommand>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let path = paths.config_file();
+    let mut tracker = ChangeTracker::new(modified(&path));
+    let mut loaded = Config::load(&paths).ok().as_ref().and_then(snapshot);
+    let mut ticker = tokio::time::interval(POLL);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if !tracker.settled(modified(&path), Instant::now()) {
+                    continue;
+                }
+                let config = match Config::load(&paths) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        warn!("config changed but failed to load, keeping the current one: {e}");
+                        continue;
+                    }
+                };
+                let current = snapshot(&config);
+                if current.is_some() && current == loaded {
+                    debug!("config file written without changes, not reloading");
+                    continue;
+                }
+                loaded = current;
+                info!("config file changed, reloading");
+                if cmd_tx.send(DaemonCommand::Reload).await.is_err() {
+                    break;
+                }
+                if !config.general.watch_config {
+                    info!("watch_config disabled, no longer watching the config file");
+                    break;
+                }
+            }
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unchanged_write_does_not_reload() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        let original = "[general]\nbackend = \"swww\"\n";
+        std::fs::write(paths.config_file(), original).unwrap();
+
+        let (cmd_tx, mut cmd_rx) = mpsc::channel(4);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let watcher = tokio::spawn(watch_config(paths.clone(), cmd_tx, shutdown_rx));
+        let settle = POLL * 2 + DEBOUNCE * 2;
+        tokio::time::sleep(POLL).await;
+
+        // same settings, different text: what a GUI or CLI save looks like
+        std::fs::write(
+            paths.config_file(),
+            "# edited by muralis\n[general]\nbackend = \"swww\"   # no flicker\n",
+        )
+        .unwrap();
+        tokio::time::sleep(settle).await;
+        assert!(cmd_rx.try_recv().is_err(), "reloaded on an unchanged write");
+
+        std::fs::write(paths.config_file(), "[general]\nbackend = \"feh\"\n").unwrap();
+        let cmd = tokio::time::timeout(settle * 2, cmd_rx.recv()).await;
+        assert!(matches!(cmd, Ok(Some(DaemonCommand::Reload))));
+
+        let _ = shutdown_tx.send(true);
+        watcher.await.unwrap();
+    }
+
+    #[test]
+    fn test_change_settles_after_debounce() {
+        let t0 = SystemTime::UNIX_EPOCH;
+        let t1 = t0 + Duration::from_secs(1);
+        let t2 = t0 + Duration::from_secs(2);
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+
+        let mut tracker = ChangeTracker::new(Some(t0));
+        assert!(!tracker.settled(Some(t0), at(0)));
+
+        // a write followed by another before the debounce runs out
+        assert!(!tracker.settled(Some(t1), at(250)));
+        assert!(!tracker.settled(Some(t2), at(500)));
+        assert!(!tracker.settled(Some(t2), at(750)));
+        assert!(tracker.settled(Some(t2), at(1000)));
+        // reported once
+        assert!(!tracker.settled(Some(t2), at(2000)));
+
+        // deleting the file is a change too
+        assert!(!tracker.settled(None, at(2250)));
+        assert!(tracker.settled(None, at(2750)));
+    }
+}
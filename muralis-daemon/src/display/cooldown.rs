@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Rejects manual switches arriving within `min_gap` of the last accepted
+/// one, so a spammed hotkey can't stack backend transitions.
+#[derive(Debug, Default)]
+pub struct SwitchGate {
+    min_gap: Duration,
+    last: Option<Instant>,
+}
+
+impl SwitchGate {
+    pub fn new(min_gap: Duration) -> Self {
+        Self {
+            min_gap,
+            last: None,
+        }
+    }
+
+    pub fn set_min_gap(&mut self, min_gap: Duration) {
+        self.min_gap = min_gap;
+    }
+
+    /// Accept a switch at `now` and restart the cooldown, or return how long
+    /// until the next one is allowed.
+    pub fn try_pass(&mut self, now: Instant) -> Result<(), Duration> {
+        if let Some(last) = self.last {
+            let elapsed = now.saturating_duration_since(last);
+            if elapsed < self.min_gap {
+                return Err(self.min_gap - elapsed);
+            }
+        }
+        self.last = Some(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gate_rejects_within_cooldown() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut gate = SwitchGate::new(ms(500));
+
+        assert_eq!(gate.try_pass(start), Ok(()));
+        assert_eq!(gate.try_pass(start + ms(200)), Err(ms(300)));
+        // rejected attempts don't extend the cooldown
+        assert_eq!(gate.try_pass(start + ms(500)), Ok(()));
+        assert_eq!(gate.try_pass(start + ms(999)), Err(ms(1)));
+        assert_eq!(gate.try_pass(start + ms(1000)), Ok(()));
+    }
+
+    #[test]
+    fn test_zero_gap_always_passes() {
+        let now = Instant::now();
+        let mut gate = SwitchGate::default();
+        assert_eq!(gate.try_pass(now), Ok(()));
+        assert_eq!(gate.try_pass(now), Ok(()));
+    }
+}
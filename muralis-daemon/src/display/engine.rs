@@ -1,21 +1,42 @@
-use std::path::Path;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
+use chrono::{DateTime, Local, TimeDelta};
 use tokio::sync::mpsc;
-use tokio::time::{interval, Instant, MissedTickBehavior};
-use tracing::{info, warn};
+use tokio::time::{interval, sleep_until, Instant, MissedTickBehavior};
+use tracing::{debug, info, warn};
 
-use muralis_core::backend::WallpaperBackend;
+use muralis_core::backend::{monitor, WallpaperBackend};
 use muralis_core::cache;
 use muralis_core::config::Config;
-use muralis_core::db::Database;
+use muralis_core::db::{Database, APPLIED_HISTORY_CAP};
 use muralis_core::ipc::DaemonStatus;
-use muralis_core::models::{DisplayMode, Wallpaper};
+use muralis_core::models::{DisplayMode, MonitorInfo, Wallpaper};
 use muralis_core::paths::MuralisPaths;
+use muralis_core::retry::{with_retry, RetryPolicy};
+use muralis_core::sources::{AspectRatioFilter, SourceRegistry, WallpaperSource};
+use muralis_core::wallpapers::WallpaperManager;
 
+use super::cooldown::SwitchGate;
+use super::hook;
 use super::scheduler::{next_schedule_trigger, parse_interval};
+use super::theme::{self, theme_tags_for, ColorScheme};
 use super::DaemonCommand;
 
+/// Minimum gap between `live_random` fetches, so manual `next` spam
+/// can't hammer a source's rate limit.
+const LIVE_MIN_GAP: Duration = Duration::from_secs(60);
+/// `live_random` searches a random page in `1..=LIVE_MAX_PAGE`.
+const LIVE_MAX_PAGE: u32 = 5;
+const LIVE_PER_PAGE: u32 = 24;
+/// How often `[display.theme] follow` checks the desktop color scheme.
+const THEME_POLL: Duration = Duration::from_secs(60);
+/// Longest the schedule timer sleeps before checking the wall clock again;
+/// the monotonic clock stands still while the machine is suspended.
+const SCHEDULE_RECHECK: Duration = Duration::from_secs(60);
+
 pub struct DisplayEngine {
     config: Config,
     paths: MuralisPaths,
@@ -24,13 +45,44 @@ pub struct DisplayEngine {
     paused: bool,
     current_index: usize,
     current_wallpaper: Option<String>,
+    /// Set when the current wallpaper was applied from a file outside the library.
+    current_ephemeral: Option<PathBuf>,
     wallpapers: Vec<Wallpaper>,
     next_change: Option<Instant>,
+    /// Sources `live_random` fetches from, rebuilt on reload, which also
+    /// drops the ones disabled for this session.
+    sources: SourceRegistry,
+    build_sources: Option<fn(&Config, &MuralisPaths) -> SourceRegistry>,
+    last_live_fetch: Option<Instant>,
+    /// Set while a live fetch runs in the background.
+    live_pending: bool,
+    live_tx: mpsc::Sender<LiveResult>,
+    /// Taken by `run`, which selects on it.
+    live_rx: Option<mpsc::Receiver<LiveResult>>,
+    /// `[display] min_resolution` resolved against the monitors, refreshed on reload.
+    display_min: Option<(u32, u32)>,
+    /// Connected monitors for `per_monitor_crop`, refreshed on reload.
+    monitors: Vec<MonitorInfo>,
+    /// Color scheme the rotation follows; None unless `[display.theme] follow`.
+    theme: Option<ColorScheme>,
+    /// Cooldown for manual `next`/`prev`.
+    switch_gate: SwitchGate,
+    /// Indices of recent random picks, oldest first; cleared on reload.
+    recent: VecDeque<usize>,
+    /// File last handed to the backend for all monitors; cleared on reload.
+    applied: Option<PathBuf>,
+    /// When the next schedule entry fires by the wall clock, and its tags;
+    /// re-armed on reload.
+    schedule_at: Option<(DateTime<Local>, Vec<String>)>,
+    /// Why the last backend call failed, until one succeeds.
+    last_error: Option<String>,
 }
 
 impl DisplayEngine {
     pub fn new(config: Config, paths: MuralisPaths, backend: Box<dyn WallpaperBackend>) -> Self {
         let mode = config.display.mode;
+        let switch_gate = SwitchGate::new(min_switch_interval(&config));
+        let (live_tx, live_rx) = mpsc::channel(1);
         Self {
             config,
             paths,
@@ -39,22 +91,46 @@ impl DisplayEngine {
             paused: false,
             current_index: 0,
             current_wallpaper: None,
+            current_ephemeral: None,
             wallpapers: Vec::new(),
             next_change: None,
+            sources: SourceRegistry::new(),
+            build_sources: None,
+            last_live_fetch: None,
+            live_pending: false,
+            live_tx,
+            live_rx: Some(live_rx),
+            display_min: None,
+            monitors: Vec::new(),
+            theme: None,
+            switch_gate,
+            recent: VecDeque::new(),
+            applied: None,
+            schedule_at: None,
+            last_error: None,
         }
     }
 
+    /// Build the source registry from config, now and on every reload.
+    pub fn with_sources(mut self, build: fn(&Config, &MuralisPaths) -> SourceRegistry) -> Self {
+        self.sources = build(&self.config, &self.paths);
+        self.build_sources = Some(build);
+        self
+    }
+
     pub async fn run(
         mut self,
         mut cmd_rx: mpsc::Receiver<DaemonCommand>,
         mut shutdown: tokio::sync::watch::Receiver<bool>,
     ) {
-        self.reload_wallpapers();
-
-        // RandomStartup: pick one wallpaper at launch, then behave like Static
-        if self.mode == DisplayMode::RandomStartup {
-            self.next().await;
+        if self.config.general.prune_missing_on_start {
+            self.prune_missing();
         }
+        self.display_min = monitor::resolve_min_resolution(&self.config.display).await;
+        self.refresh_monitors().await;
+        self.refresh_theme().await;
+        self.reload_wallpapers();
+        self.pick_startup().await;
 
         // initial cache prune
         self.prune_cache();
@@ -72,42 +148,70 @@ impl DisplayEngine {
         cache_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
         cache_timer.tick().await;
 
+        let mut theme_timer = interval(THEME_POLL);
+        theme_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        theme_timer.tick().await;
+
         self.update_next_change(tick_duration);
+        self.arm_schedule();
 
+        let mut live_rx = self.live_rx.take().expect("run consumes the engine");
         loop {
+            let schedule_wake = self
+                .schedule_at
+                .as_ref()
+                .map(|(due, _)| schedule_wake(*due));
             tokio::select! {
                 _ = timer.tick() => {
-                    if !self.paused {
-                        match self.mode {
-                            DisplayMode::Random | DisplayMode::Sequential => {
-                                self.next().await;
-                                self.update_next_change(tick_duration);
-                            }
-                            DisplayMode::Schedule => {
-                                self.handle_schedule().await;
+                    if !self.paused && !self.screens_off().await {
+                        self.on_tick(tick_duration).await;
+                    }
+                }
+                _ = sleep_until(schedule_wake.unwrap_or_else(Instant::now)),
+                    if schedule_wake.is_some() && self.mode == DisplayMode::Schedule =>
+                {
+                    let due = self.schedule_at.as_ref().is_some_and(|(at, _)| Local::now() >= *at);
+                    if due {
+                        if let Some((_, tags)) = self.schedule_at.take() {
+                            if !self.paused && !self.screens_off().await {
+                                self.handle_schedule(&tags).await;
                             }
-                            _ => {}
                         }
+                        self.arm_schedule();
                     }
                 }
+                Some(result) = live_rx.recv() => {
+                    self.on_live_fetched(result).await;
+                }
                 _ = cache_timer.tick() => {
                     self.prune_cache();
                 }
+                _ = theme_timer.tick(), if self.config.display.theme.follow => {
+                    self.on_theme_tick().await;
+                }
                 Some(cmd) = cmd_rx.recv() => {
                     match cmd {
                         DaemonCommand::Status { respond } => {
                             let status = self.status();
                             let _ = respond.send(status);
                         }
-                        DaemonCommand::Next => {
-                            self.next().await;
-                            self.update_next_change(tick_duration);
-                            timer.reset();
+                        DaemonCommand::Next { respond } => {
+                            let allowed = self.try_switch();
+                            if allowed.is_ok() {
+                                self.next().await;
+                                self.update_next_change(tick_duration);
+                                timer.reset();
+                            }
+                            let _ = respond.send(allowed);
                         }
-                        DaemonCommand::Prev => {
-                            self.prev().await;
-                            self.update_next_change(tick_duration);
-                            timer.reset();
+                        DaemonCommand::Prev { respond } => {
+                            let allowed = self.try_switch();
+                            if allowed.is_ok() {
+                                self.prev().await;
+                                self.update_next_change(tick_duration);
+                                timer.reset();
+                            }
+                            let _ = respond.send(allowed);
                         }
                         DaemonCommand::SetWallpaper { id, respond } => {
                             let result = self.set_wallpaper(&id).await;
@@ -116,6 +220,8 @@ impl DisplayEngine {
                         DaemonCommand::SetMode { mode } => {
                             info!(mode = %mode, "display mode changed");
                             self.mode = mode;
+                            self.update_next_change(tick_duration);
+                            self.arm_schedule();
                         }
                         DaemonCommand::Pause => {
                             self.paused = true;
@@ -124,14 +230,53 @@ impl DisplayEngine {
                         DaemonCommand::Resume => {
                             self.paused = false;
                             self.update_next_change(tick_duration);
+                            self.arm_schedule();
                             timer.reset();
                             info!("rotation resumed");
                         }
                         DaemonCommand::Reload => {
                             self.config = Config::load_or_default(&self.paths);
+                            self.switch_gate
+                                .set_min_gap(min_switch_interval(&self.config));
+                            if let Some(build) = self.build_sources {
+                                self.sources = build(&self.config, &self.paths);
+                            }
+                            self.display_min =
+                                monitor::resolve_min_resolution(&self.config.display).await;
+                            self.refresh_monitors().await;
+                            self.refresh_theme().await;
+                            // the backend or its settings may have changed
+                            self.applied = None;
                             self.reload_wallpapers();
+                            self.pick_startup().await;
+                            self.arm_schedule();
                             info!("config reloaded");
                         }
+                        DaemonCommand::KeepCurrent { respond } => {
+                            let result = self.keep_current();
+                            let _ = respond.send(result.map_err(|e| e.to_string()));
+                        }
+                        DaemonCommand::ListWallpapers { limit, respond } => {
+                            let take = limit.map_or(usize::MAX, |l| l as usize);
+                            let _ = respond
+                                .send(self.wallpapers.iter().take(take).cloned().collect());
+                        }
+                        DaemonCommand::CurrentWallpaper { respond } => {
+                            let result = self.current_record();
+                            let _ = respond.send(result.map_err(|e| e.to_string()));
+                        }
+                        DaemonCommand::RestorePrevious { respond } => {
+                            let result = self.restore_previous().await;
+                            let _ = respond.send(result.map_err(|e| e.to_string()));
+                        }
+                        DaemonCommand::SetSourceEnabled { name, enabled } => {
+                            info!(source = %name, enabled, "source toggled at runtime");
+                            // resolved to IDs now, so a later toggle of one
+                            // feed overrides an earlier one of all feeds
+                            if !self.sources.set_enabled(&name, enabled) {
+                                warn!(source = %name, "no such source to toggle");
+                            }
+                        }
                         DaemonCommand::WorkspaceChanged { id } => {
                             self.handle_workspace_change(id).await;
                         }
@@ -149,29 +294,119 @@ impl DisplayEngine {
         }
     }
 
+    /// All monitors are in DPMS-off, so this rotation tick is skipped.
+    async fn screens_off(&self) -> bool {
+        if !self.config.display.pause_when_screens_off {
+            return false;
+        }
+        let off = monitor::detect_monitors()
+            .await
+            .is_ok_and(|m| monitor::should_pause_for_dpms(&m));
+        if off {
+            info!("screens off, skipping rotation");
+        }
+        off
+    }
+
+    fn prune_missing(&self) {
+        // an unmounted or wiped library would otherwise lose every favorite
+        let dir = self.paths.wallpapers_dir();
+        let populated = std::fs::read_dir(&dir).is_ok_and(|mut e| e.next().is_some());
+        if !populated {
+            warn!(dir = %dir.display(), "library folder missing or empty; not pruning");
+            return;
+        }
+        let result = Database::open(&self.paths.db_path())
+            .and_then(|db| self.manager().prune_missing(&db, false));
+        match result {
+            Ok(pruned) if !pruned.is_empty() => {
+                info!(count = pruned.len(), "pruned favorites with missing files")
+            }
+            Ok(_) => {}
+            Err(e) => warn!("failed to prune missing wallpapers: {e}"),
+        }
+    }
+
     fn reload_wallpapers(&mut self) {
         match Database::open(&self.paths.db_path()) {
-            Ok(db) => match db.list_wallpapers() {
-                Ok(wps) => {
-                    info!(count = wps.len(), "loaded wallpapers from DB");
-                    self.wallpapers = wps;
+            Ok(db) => {
+                match rotation_pool(&db, &self.config, self.rotation_min(), &self.theme_tags()) {
+                    Ok(wps) => {
+                        info!(count = wps.len(), "loaded wallpapers from DB");
+                        self.wallpapers = wps;
+                        // indices may now point elsewhere
+                        self.recent.clear();
+                    }
+                    Err(e) => warn!("failed to load wallpapers: {e}"),
                 }
-                Err(e) => warn!("failed to load wallpapers: {e}"),
-            },
+            }
             Err(e) => warn!("failed to open database: {e}"),
         }
     }
 
+    /// Rotation timer fired.
+    async fn on_tick(&mut self, tick_duration: Duration) {
+        match self.mode {
+            DisplayMode::Random | DisplayMode::Sequential | DisplayMode::LiveRandom => {
+                self.next().await;
+                self.update_next_change(tick_duration);
+            }
+            // RandomStartup picked its wallpaper at launch/reload and then holds it
+            // Schedule runs off its own timer, see `arm_schedule`
+            DisplayMode::RandomStartup
+            | DisplayMode::Static
+            | DisplayMode::Workspace
+            | DisplayMode::Schedule => {}
+        }
+    }
+
+    /// RandomStartup: show one random wallpaper, then behave like Static.
+    async fn pick_startup(&mut self) {
+        if self.mode == DisplayMode::RandomStartup {
+            self.next().await;
+            self.next_change = None;
+        }
+    }
+
+    /// Check a manual switch against `[display] min_switch_interval_ms`.
+    fn try_switch(&mut self) -> Result<(), String> {
+        self.switch_gate.try_pass(Instant::now()).map_err(|wait| {
+            format!(
+                "too soon, next switch allowed in {}ms",
+                wait.as_millis().max(1)
+            )
+        })
+    }
+
+    /// Smallest size rotation accepts with `skip_low_res`: the display
+    /// minimum when known, else `[filter]` min_width/min_height.
+    fn rotation_min(&self) -> Option<(u32, u32)> {
+        let filter = &self.config.filter;
+        filter.skip_low_res.then(|| {
+            self.display_min
+                .unwrap_or((filter.min_width, filter.min_height))
+        })
+    }
+
     async fn next(&mut self) {
+        if self.mode == DisplayMode::LiveRandom {
+            match self.start_live_fetch() {
+                // applied by `on_live_fetched` once it's done
+                Ok(()) => return,
+                Err(e) => warn!("live fetch failed, using library: {e}"),
+            }
+        }
+        self.next_from_library().await;
+    }
+
+    async fn next_from_library(&mut self) {
         if self.wallpapers.is_empty() {
             return;
         }
 
         match self.mode {
-            DisplayMode::Random | DisplayMode::RandomStartup => {
-                use rand::Rng;
-                let idx = rand::rng().random_range(0..self.wallpapers.len());
-                self.current_index = idx;
+            DisplayMode::Random | DisplayMode::RandomStartup | DisplayMode::LiveRandom => {
+                self.current_index = self.pick_random();
             }
             DisplayMode::Sequential => {
                 self.current_index = (self.current_index + 1) % self.wallpapers.len();
@@ -182,6 +417,119 @@ impl DisplayEngine {
         self.apply_current().await;
     }
 
+    /// Start fetching a live wallpaper in the background, so IPC stays
+    /// responsive while the source is slow. A fetch already running is
+    /// left to finish.
+    fn start_live_fetch(&mut self) -> muralis_core::error::Result<()> {
+        use muralis_core::error::MuralisError;
+
+        if self.live_pending {
+            return Ok(());
+        }
+        if self
+            .last_live_fetch
+            .is_some_and(|t| t.elapsed() < LIVE_MIN_GAP)
+        {
+            return Err(MuralisError::Source("rate limited".into()));
+        }
+        self.last_live_fetch = Some(Instant::now());
+        let source = self
+            .live_source()
+            .ok_or_else(|| MuralisError::Source("no enabled source".into()))?;
+
+        let fetch = LiveFetch {
+            source,
+            db_path: self.paths.db_path(),
+            manager: self.manager(),
+            query: self.config.display.live_query.clone(),
+            exclude_tags: self.config.filter.exclude_tags.clone(),
+            download_retries: self.config.general.download_retries,
+        };
+        let tx = self.live_tx.clone();
+        self.live_pending = true;
+        tokio::spawn(async move {
+            let _ = tx.send(fetch.run().await).await;
+        });
+        Ok(())
+    }
+
+    /// A background live fetch finished: show its wallpaper, or fall back
+    /// to the library when it failed.
+    async fn on_live_fetched(&mut self, result: LiveResult) {
+        self.live_pending = false;
+        match result {
+            Ok(id) => {
+                self.reload_wallpapers();
+                // switched away from live_random while fetching: keep the
+                // favorite, leave the wallpaper alone
+                if self.mode != DisplayMode::LiveRandom {
+                    return;
+                }
+                match self.set_wallpaper(&id).await {
+                    Ok(()) => {
+                        info!(id = %id, "live wallpaper set");
+                        return;
+                    }
+                    Err(e) => warn!("failed to set live wallpaper: {e}"),
+                }
+            }
+            Err(e) => warn!("live fetch failed, using library: {e}"),
+        }
+        if self.mode == DisplayMode::LiveRandom {
+            self.next_from_library().await;
+        }
+    }
+
+    /// The configured `live_source`, or a random enabled one when unset.
+    fn live_source(&self) -> Option<Arc<dyn WallpaperSource>> {
+        let source = match &self.config.display.live_source {
+            Some(key) => self.sources.get(key)?,
+            None => {
+                use rand::Rng;
+                let all: Vec<_> = self.sources.iter().collect();
+                if all.is_empty() {
+                    return None;
+                }
+                all[rand::rng().random_range(0..all.len())]
+            }
+        };
+        self.sources.shared(source.id())
+    }
+
+    /// Random index not shown recently. Remembers the last
+    /// `min(len / 2, [display] history)` picks; once every candidate is
+    /// recent the history restarts, still avoiding the current wallpaper.
+    fn pick_random(&mut self) -> usize {
+        use rand::Rng;
+        let len = self.wallpapers.len();
+        let size = (len / 2).min(self.config.display.history);
+        while self.recent.len() > size {
+            self.recent.pop_front();
+        }
+        let showing = self
+            .current_wallpaper
+            .as_ref()
+            .filter(|_| len > 1)
+            .map(|_| self.current_index);
+
+        let mut candidates: Vec<usize> = (0..len)
+            .filter(|i| !self.recent.contains(i) && Some(*i) != showing)
+            .collect();
+        if candidates.is_empty() {
+            self.recent.clear();
+            candidates = (0..len).filter(|i| Some(*i) != showing).collect();
+        }
+        let idx = candidates[rand::rng().random_range(0..candidates.len())];
+
+        if size > 0 {
+            if self.recent.len() == size {
+                self.recent.pop_front();
+            }
+            self.recent.push_back(idx);
+        }
+        idx
+    }
+
     async fn prev(&mut self) {
         if self.wallpapers.is_empty() {
             return;
@@ -200,13 +548,18 @@ impl DisplayEngine {
         if let Some(wp) = self.wallpapers.get(self.current_index) {
             let path = Path::new(&wp.file_path);
             if path.exists() {
-                match self.backend.set_wallpaper_all(path).await {
-                    Ok(()) => {
-                        self.current_wallpaper = Some(wp.id.clone());
+                let shown = self.display_path(wp).await;
+                let id = wp.id.clone();
+                match self.apply_all(&shown, false).await {
+                    Ok(false) => {}
+                    Ok(true) => {
+                        self.current_wallpaper = Some(id.clone());
+                        self.current_ephemeral = None;
                         if let Ok(db) = Database::open(&self.paths.db_path()) {
-                            let _ = db.mark_used(&wp.id);
+                            let _ = db.mark_used(&id);
+                            let _ = db.record_applied(&id, APPLIED_HISTORY_CAP);
                         }
-                        info!(id = %wp.id, "wallpaper set");
+                        info!(id = %id, "wallpaper set");
                     }
                     Err(e) => warn!("failed to set wallpaper: {e}"),
                 }
@@ -230,11 +583,24 @@ impl DisplayEngine {
 
         match wp {
             Some(wp) => {
-                let path = Path::new(&wp.file_path);
-                self.backend.set_wallpaper_all(path).await?;
+                let shown = self.display_path(&wp).await;
+                self.apply_all(&shown, true).await?;
                 self.current_wallpaper = Some(wp.id.clone());
+                self.current_ephemeral = None;
                 if let Ok(db) = Database::open(&self.paths.db_path()) {
                     let _ = db.mark_used(&wp.id);
+                    let _ = db.record_applied(&wp.id, APPLIED_HISTORY_CAP);
+                }
+                Ok(())
+            }
+            None if Path::new(id).is_file() => {
+                // not in the library: apply the file as-is without favoriting it
+                let path = PathBuf::from(id);
+                self.apply_all(&path, true).await?;
+                self.current_wallpaper = Some(path.to_string_lossy().to_string());
+                self.current_ephemeral = Some(path);
+                if let Ok(db) = Database::open(&self.paths.db_path()) {
+                    let _ = db.record_applied(id, APPLIED_HISTORY_CAP);
                 }
                 Ok(())
             }
@@ -244,34 +610,218 @@ impl DisplayEngine {
         }
     }
 
+    /// Hand `path` to the backend for all monitors. Unless `force`d or
+    /// `skip_unchanged` is off, a file already showing is not re-applied,
+    /// which would only flash a transition. Returns whether it was applied.
+    async fn apply_all(&mut self, path: &Path, force: bool) -> muralis_core::error::Result<bool> {
+        if !force && self.config.display.skip_unchanged && self.applied.as_deref() == Some(path) {
+            debug!(path = %path.display(), "wallpaper already shown; skipping");
+            return Ok(false);
+        }
+        // unknown state if the backend fails part way
+        self.applied = None;
+        // monitors may have been plugged in or out since the last apply
+        self.refresh_monitors().await;
+        let result = if self.config.display.per_monitor_crop && !self.monitors.is_empty() {
+            self.apply_per_monitor(path).await
+        } else {
+            self.backend.set_wallpaper_all(path).await
+        };
+        if let Err(e) = result {
+            self.last_error = Some(e.to_string());
+            return Err(e);
+        }
+        self.last_error = None;
+        self.applied = Some(path.to_path_buf());
+        if let Some(template) = &self.config.general.on_change_command {
+            hook::run_on_change(template, path);
+        }
+        Ok(true)
+    }
+
+    /// Apply `path` to each monitor as a copy fitted to that monitor.
+    async fn apply_per_monitor(&self, path: &Path) -> muralis_core::error::Result<()> {
+        let manager = self.manager();
+        let monitors = self.monitors.clone();
+        let source = path.to_path_buf();
+        // decoding and resizing a large image would stall the event loop
+        let fitted = tokio::task::spawn_blocking(move || {
+            monitors
+                .iter()
+                .map(|m| Ok((m.name.clone(), manager.monitor_path(&source, m)?)))
+                .collect::<muralis_core::error::Result<Vec<_>>>()
+        })
+        .await
+        .map_err(|e| {
+            muralis_core::error::MuralisError::Backend(format!("crop task failed: {e}"))
+        })??;
+        for (name, fitted) in fitted {
+            self.backend.set_wallpaper(&fitted, &name).await?;
+        }
+        Ok(())
+    }
+
+    /// Look up the color scheme to follow, or clear it when not following.
+    /// Returns whether it changed.
+    async fn refresh_theme(&mut self) -> bool {
+        let theme = &self.config.display.theme;
+        let scheme = if theme.follow {
+            Some(theme::current_scheme(theme).await)
+        } else {
+            None
+        };
+        let changed = scheme != self.theme;
+        self.theme = scheme;
+        changed
+    }
+
+    /// Tags the rotation is limited to by the followed color scheme.
+    fn theme_tags(&self) -> Vec<String> {
+        self.theme
+            .map(|s| theme_tags_for(s, &self.config.display.theme))
+            .unwrap_or_default()
+    }
+
+    /// Switch the rotation to the other tag set when the scheme flips,
+    /// showing one of its wallpapers right away.
+    async fn on_theme_tick(&mut self) {
+        if !self.refresh_theme().await {
+            return;
+        }
+        info!(scheme = ?self.theme, "color scheme changed");
+        self.reload_wallpapers();
+        if !self.paused && matches!(self.mode, DisplayMode::Random | DisplayMode::Sequential) {
+            self.next().await;
+        }
+    }
+
+    /// Detect monitors when `per_monitor_crop` needs them. A failed
+    /// detection keeps the monitors last seen.
+    async fn refresh_monitors(&mut self) {
+        if !self.config.display.per_monitor_crop {
+            self.monitors.clear();
+            return;
+        }
+        match monitor::detect_monitors().await {
+            Ok(monitors) => self.monitors = monitors,
+            Err(e) if self.monitors.is_empty() => {
+                warn!("monitor detection failed, applying one image to all: {e}")
+            }
+            Err(e) => warn!("monitor detection failed, keeping the last known monitors: {e}"),
+        }
+    }
+
+    /// Re-apply the wallpaper shown before the current one, going by what
+    /// was actually applied rather than the rotation order.
+    async fn restore_previous(&mut self) -> muralis_core::error::Result<String> {
+        let db = Database::open(&self.paths.db_path())?;
+        let Some(previous) = db.recent_applied(2)?.into_iter().nth(1) else {
+            return Err(muralis_core::error::MuralisError::WallpaperNotFound(
+                "no previous wallpaper".into(),
+            ));
+        };
+        self.set_wallpaper(&previous).await?;
+        info!(id = %previous, "restored previous wallpaper");
+        Ok(previous)
+    }
+
+    fn manager(&self) -> WallpaperManager {
+        WallpaperManager::new(self.paths.clone())
+            .with_thumbnails(self.config.thumbnails)
+            .with_square_thumbnails(self.config.general.square_thumbnails)
+            .with_hash_algo(self.config.general.hash_algo)
+    }
+
+    /// The file to hand to the backend: the stored crop if one is set,
+    /// falling back to the original when cropping fails.
+    async fn display_path(&self, wp: &Wallpaper) -> PathBuf {
+        let manager = self.manager();
+        let target = wp.clone();
+        // rendering a crop decodes the full image
+        let shown = tokio::task::spawn_blocking(move || manager.display_path(&target))
+            .await
+            .map_err(|e| {
+                muralis_core::error::MuralisError::Backend(format!("crop task failed: {e}"))
+            })
+            .and_then(|r| r);
+        shown.unwrap_or_else(|e| {
+            warn!(id = %wp.id, "failed to apply crop: {e}");
+            PathBuf::from(&wp.file_path)
+        })
+    }
+
+    /// Ensure the current wallpaper is in the library, favoriting it if it was
+    /// applied from a file outside the library. Returns the wallpaper ID.
+    fn keep_current(&mut self) -> muralis_core::error::Result<String> {
+        if let Some(path) = self.current_ephemeral.clone() {
+            let db = Database::open(&self.paths.db_path())?;
+            let id = self.manager().favorite_local(&db, &path)?;
+            info!(id = %id, path = %path.display(), "kept ephemeral wallpaper");
+            self.current_ephemeral = None;
+            self.current_wallpaper = Some(id.clone());
+            self.reload_wallpapers();
+            return Ok(id);
+        }
+
+        self.current_wallpaper.clone().ok_or_else(|| {
+            muralis_core::error::MuralisError::WallpaperNotFound("none applied".into())
+        })
+    }
+
+    /// Library record of the wallpaper being shown. Files applied by path
+    /// have none until kept.
+    fn current_record(&self) -> muralis_core::error::Result<Wallpaper> {
+        let not_found =
+            |what: &str| muralis_core::error::MuralisError::WallpaperNotFound(what.into());
+        let id = self
+            .current_wallpaper
+            .as_deref()
+            .ok_or_else(|| not_found("none applied"))?;
+        if let Some(path) = &self.current_ephemeral {
+            return Err(not_found(&format!(
+                "{} is not in the library (muralis keep adds it)",
+                path.display()
+            )));
+        }
+        if let Some(wp) = self.wallpapers.iter().find(|w| w.id == id) {
+            return Ok(wp.clone());
+        }
+        Database::open(&self.paths.db_path())?.get_wallpaper(id)
+    }
+
     /// Handle workspace change: look up workspace->wallpaper mapping from config.
     async fn handle_workspace_change(&mut self, workspace_id: u32) {
         if self.mode != DisplayMode::Workspace {
             return;
         }
 
-        // find matching workspace config
-        if let Some(ws_config) = self
-            .config
-            .workspaces
-            .iter()
-            .find(|w| w.workspace == workspace_id)
-        {
-            let wallpaper_key = &ws_config.wallpaper;
-
-            // try to find by tag or ID
-            let wp = self
-                .wallpapers
-                .iter()
-                .find(|w| w.id == *wallpaper_key || w.tags.iter().any(|t| t == wallpaper_key));
+        // explicit mapping, else the configured default
+        if let Some(wallpaper_key) = self.config.workspace_wallpaper(workspace_id) {
+            // "random" picks any wallpaper; otherwise find by tag or ID
+            let wp = if wallpaper_key == "random" && !self.wallpapers.is_empty() {
+                use rand::Rng;
+                self.wallpapers
+                    .get(rand::rng().random_range(0..self.wallpapers.len()))
+            } else {
+                self.wallpapers
+                    .iter()
+                    .find(|w| w.id == wallpaper_key || w.tags.iter().any(|t| t == wallpaper_key))
+            };
 
             if let Some(wp) = wp {
                 let path = Path::new(&wp.file_path);
                 if path.exists() {
-                    match self.backend.set_wallpaper_all(path).await {
-                        Ok(()) => {
-                            self.current_wallpaper = Some(wp.id.clone());
-                            info!(workspace = workspace_id, id = %wp.id, "workspace wallpaper set");
+                    let shown = self.display_path(wp).await;
+                    let id = wp.id.clone();
+                    match self.apply_all(&shown, false).await {
+                        Ok(false) => {}
+                        Ok(true) => {
+                            self.current_wallpaper = Some(id.clone());
+                            self.current_ephemeral = None;
+                            if let Ok(db) = Database::open(&self.paths.db_path()) {
+                                let _ = db.record_applied(&id, APPLIED_HISTORY_CAP);
+                            }
+                            info!(workspace = workspace_id, id = %id, "workspace wallpaper set");
                         }
                         Err(e) => warn!("failed to set workspace wallpaper: {e}"),
                     }
@@ -282,23 +832,37 @@ impl DisplayEngine {
         }
     }
 
-    /// Handle schedule mode: pick random wallpaper matching schedule tags.
-    async fn handle_schedule(&mut self) {
-        if let Some((_, tags)) = next_schedule_trigger(&self.config.schedules) {
-            let matching: Vec<usize> = self
-                .wallpapers
-                .iter()
-                .enumerate()
-                .filter(|(_, wp)| tags.iter().any(|t| wp.tags.contains(t)))
-                .map(|(i, _)| i)
-                .collect();
+    /// Point the schedule timer at the next entry from the current
+    /// `[[schedules]]`, wrapping past midnight.
+    fn arm_schedule(&mut self) {
+        self.schedule_at = next_schedule_trigger(&self.config.schedules).map(|(until, tags)| {
+            let until = TimeDelta::from_std(until).unwrap_or_default();
+            (Local::now() + until, tags)
+        });
+        if self.mode == DisplayMode::Schedule {
+            self.next_change = self
+                .schedule_at
+                .as_ref()
+                .filter(|_| !self.paused)
+                .map(|(at, _)| Instant::now() + wall_time_until(*at));
+        }
+    }
 
-            if !matching.is_empty() {
-                use rand::Rng;
-                let idx = matching[rand::rng().random_range(0..matching.len())];
-                self.current_index = idx;
-                self.apply_current().await;
-            }
+    /// A schedule entry fired: pick a random wallpaper matching its tags.
+    async fn handle_schedule(&mut self, tags: &[String]) {
+        let matching: Vec<usize> = self
+            .wallpapers
+            .iter()
+            .enumerate()
+            .filter(|(_, wp)| tags.iter().any(|t| wp.tags.contains(t)))
+            .map(|(i, _)| i)
+            .collect();
+
+        if !matching.is_empty() {
+            use rand::Rng;
+            let idx = matching[rand::rng().random_range(0..matching.len())];
+            self.current_index = idx;
+            self.apply_current().await;
         }
     }
 
@@ -324,14 +888,779 @@ impl DisplayEngine {
                 let remaining = t.saturating_duration_since(Instant::now());
                 format!("{}s", remaining.as_secs())
             }),
+            disabled_sources: self.sources.disabled_ids(),
+            backend: self.backend.name().to_string(),
+            last_error: self.last_error.clone(),
         }
     }
 
     fn update_next_change(&mut self, duration: Duration) {
-        if !self.paused && matches!(self.mode, DisplayMode::Random | DisplayMode::Sequential) {
+        if !self.paused
+            && matches!(
+                self.mode,
+                DisplayMode::Random | DisplayMode::Sequential | DisplayMode::LiveRandom
+            )
+        {
             self.next_change = Some(Instant::now() + duration);
         } else {
             self.next_change = None;
         }
     }
 }
+
+/// ID of the wallpaper a live fetch favorited.
+type LiveResult = muralis_core::error::Result<String>;
+
+/// A `live_random` fetch, detached from the engine so it can run as a task.
+struct LiveFetch {
+    source: Arc<dyn WallpaperSource>,
+    db_path: PathBuf,
+    manager: WallpaperManager,
+    query: String,
+    exclude_tags: Vec<String>,
+    download_retries: u32,
+}
+
+impl LiveFetch {
+    /// Search the source for a random result not already in the library
+    /// or blacklisted, favorite it and return its ID.
+    async fn run(self) -> LiveResult {
+        use muralis_core::error::MuralisError;
+        use rand::Rng;
+
+        let source = self.source.as_ref();
+        let query = self.query.as_str();
+        let page = rand::rng().random_range(1..=LIVE_MAX_PAGE);
+        let search = |page| {
+            with_retry(RetryPolicy::SEARCH, move || {
+                source.search(query, page, LIVE_PER_PAGE, AspectRatioFilter::All)
+            })
+        };
+        let mut previews = search(page).await?;
+        if previews.is_empty() && page > 1 {
+            previews = search(1).await?;
+        }
+
+        let mut candidates = {
+            let db = Database::open(&self.db_path)?;
+            let mut blocked = db.list_tag_blacklist()?;
+            blocked.extend(self.exclude_tags.iter().cloned());
+            previews
+                .into_iter()
+                .filter(|p| !p.has_blocked_tag(&blocked))
+                .filter(|p| {
+                    !db.is_favorited_by_source(p.source_type.as_str(), &p.source_id)
+                        .unwrap_or(false)
+                        && !db
+                            .is_blacklisted(&p.source_id, &p.source_type)
+                            .unwrap_or(false)
+                })
+                .collect::<Vec<_>>()
+        };
+        if candidates.is_empty() {
+            return Err(MuralisError::Source(format!(
+                "{} returned no new results",
+                source.name()
+            )));
+        }
+
+        let preview = candidates.swap_remove(rand::rng().random_range(0..candidates.len()));
+        let policy = RetryPolicy::download(self.download_retries);
+        let data = with_retry(policy, || source.download(&preview)).await?;
+        let db = Database::open(&self.db_path)?;
+        let id = self.manager.favorite(&db, &preview, &data)?;
+        info!(id = %id, source = %source.name(), "favorited live wallpaper");
+        Ok(id)
+    }
+}
+
+/// Time left until `at` by the wall clock, zero once it has passed.
+fn wall_time_until(at: DateTime<Local>) -> Duration {
+    (at - Local::now()).to_std().unwrap_or(Duration::ZERO)
+}
+
+/// When to wake for a schedule entry due at `due`: then, or after
+/// [`SCHEDULE_RECHECK`] to look at the wall clock again, whichever is first.
+fn schedule_wake(due: DateTime<Local>) -> Instant {
+    Instant::now() + wall_time_until(due).min(SCHEDULE_RECHECK)
+}
+
+fn min_switch_interval(config: &Config) -> Duration {
+    Duration::from_millis(config.display.min_switch_interval_ms)
+}
+
+/// Library wallpapers eligible for rotation under the current config,
+/// dropping any smaller than `min_res`.
+fn rotation_pool(
+    db: &Database,
+    config: &Config,
+    min_res: Option<(u32, u32)>,
+    tags: &[String],
+) -> muralis_core::error::Result<Vec<Wallpaper>> {
+    let filter = &config.filter;
+    let wallpapers = match &config.display.collection {
+        Some(name) => db.list_collection_wallpapers(name)?,
+        None => db.list_wallpapers()?,
+    };
+    let wallpapers = with_any_tag(wallpapers, tags);
+    let (allowed, blacklisted): (Vec<Wallpaper>, Vec<Wallpaper>) = wallpapers
+        .into_iter()
+        .filter(|wp| filter.allows_brightness(wp.brightness))
+        .filter(|wp| monitor::meets_min_resolution(wp.width, wp.height, min_res))
+        .partition(|wp| {
+            !db.is_blacklisted(&wp.source_id, &wp.source_type)
+                .unwrap_or(false)
+        });
+    if blacklisted.is_empty() {
+        return Ok(allowed);
+    }
+    // a blank screen is worse than showing something the user hid
+    if allowed.is_empty() {
+        warn!(
+            count = blacklisted.len(),
+            "every wallpaper in rotation is blacklisted, rotating them anyway"
+        );
+        return Ok(blacklisted);
+    }
+    info!(count = blacklisted.len(), "skipping blacklisted wallpapers");
+    Ok(allowed)
+}
+
+/// Wallpapers carrying any of `tags` (case-insensitive). No tags, or none
+/// matching, keeps them all rather than leave the rotation empty.
+fn with_any_tag(wallpapers: Vec<Wallpaper>, tags: &[String]) -> Vec<Wallpaper> {
+    if tags.is_empty() {
+        return wallpapers;
+    }
+    let matches = |wp: &Wallpaper| {
+        wp.tags
+            .iter()
+            .any(|t| tags.iter().any(|want| want.eq_ignore_ascii_case(t)))
+    };
+    if !wallpapers.iter().any(matches) {
+        warn!(
+            ?tags,
+            "no wallpapers tagged for the color scheme, rotating all"
+        );
+        return wallpapers;
+    }
+    wallpapers.into_iter().filter(matches).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    /// Backend that records every path it is asked to apply (and the
+    /// monitor, for per-monitor calls), or fails while `fail` is set.
+    #[derive(Clone, Default)]
+    struct MockBackend {
+        applied: Arc<Mutex<Vec<PathBuf>>>,
+        monitors: Arc<Mutex<Vec<String>>>,
+        fail: Arc<AtomicBool>,
+    }
+
+    #[async_trait]
+    impl WallpaperBackend for MockBackend {
+        async fn set_wallpaper(
+            &self,
+            path: &Path,
+            monitor: &str,
+        ) -> muralis_core::error::Result<()> {
+            self.applied.lock().unwrap().push(path.to_path_buf());
+            self.monitors.lock().unwrap().push(monitor.to_string());
+            Ok(())
+        }
+
+        async fn set_wallpaper_all(&self, path: &Path) -> muralis_core::error::Result<()> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(muralis_core::error::MuralisError::Backend(
+                    "daemon not running".into(),
+                ));
+            }
+            self.applied.lock().unwrap().push(path.to_path_buf());
+            Ok(())
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    fn test_paths(tmp: &tempfile::TempDir) -> MuralisPaths {
+        let paths = MuralisPaths::for_test(tmp.path());
+        paths.ensure_dirs().unwrap();
+        paths
+    }
+
+    fn write_test_image(path: &Path, w: u32, h: u32) {
+        let img = image::RgbImage::from_pixel(w, h, image::Rgb([40, 80, 120]));
+        let mut buf = Vec::new();
+        img.write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png)
+            .unwrap();
+        std::fs::write(path, buf).unwrap();
+    }
+
+    /// A library entry backed by a real `w`x`h` image at `dir/{id}.png`.
+    fn library_image(dir: &Path, id: &str, w: u32, h: u32) -> Wallpaper {
+        let mut wp = library_wallpaper(id, None);
+        let path = dir.join(format!("{id}.png"));
+        write_test_image(&path, w, h);
+        wp.file_path = path.to_string_lossy().into();
+        wp
+    }
+
+    #[tokio::test]
+    async fn test_keep_current_favorites_ephemeral() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = test_paths(&tmp);
+        let backend = MockBackend::default();
+        let mut engine =
+            DisplayEngine::new(Config::default(), paths.clone(), Box::new(backend.clone()));
+
+        let file = tmp.path().join("outside.png");
+        write_test_image(&file, 64, 48);
+
+        engine.set_wallpaper(file.to_str().unwrap()).await.unwrap();
+        assert_eq!(*backend.applied.lock().unwrap(), vec![file.clone()]);
+        assert_eq!(
+            Database::open(&paths.db_path())
+                .unwrap()
+                .wallpaper_count()
+                .unwrap(),
+            0
+        );
+
+        let id = engine.keep_current().unwrap();
+        let db = Database::open(&paths.db_path()).unwrap();
+        let wp = db.get_wallpaper(&id).unwrap();
+        assert_eq!(wp.width, 64);
+        assert_eq!(engine.current_wallpaper.as_deref(), Some(id.as_str()));
+        assert_eq!(engine.wallpapers.len(), 1);
+
+        // keeping again is a no-op returning the same ID
+        assert_eq!(engine.keep_current().unwrap(), id);
+        assert_eq!(db.wallpaper_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_prune_missing_skips_empty_library() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = test_paths(&tmp);
+        let engine = DisplayEngine::new(
+            Config::default(),
+            paths.clone(),
+            Box::new(MockBackend::default()),
+        );
+        let db = Database::open(&paths.db_path()).unwrap();
+        db.insert_wallpaper(&library_wallpaper("gone", None))
+            .unwrap();
+
+        // empty, then missing: looks unmounted, keep everything
+        engine.prune_missing();
+        std::fs::remove_dir(paths.wallpapers_dir()).unwrap();
+        engine.prune_missing();
+        assert_eq!(db.wallpaper_count().unwrap(), 1);
+
+        std::fs::create_dir(paths.wallpapers_dir()).unwrap();
+        write_test_image(&paths.wallpapers_dir().join("kept.png"), 8, 8);
+        engine.prune_missing();
+        assert_eq!(db.wallpaper_count().unwrap(), 0);
+    }
+
+    fn library_wallpaper(id: &str, brightness: Option<f32>) -> Wallpaper {
+        Wallpaper {
+            id: id.into(),
+            source_type: muralis_core::models::SourceType::new("local"),
+            source_id: id.into(),
+            source_url: None,
+            width: 1920,
+            height: 1080,
+            tags: Vec::new(),
+            file_path: format!("/tmp/{id}.jpg"),
+            added_at: "2025-01-01T00:00:00Z".into(),
+            last_used: None,
+            use_count: 0,
+            brightness,
+            crop: None,
+            colors: Vec::new(),
+            meta: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_schedule_wake_rechecks_wall_clock() {
+        let now = Instant::now();
+        // a far entry still wakes within SCHEDULE_RECHECK to look again
+        let far = schedule_wake(Local::now() + TimeDelta::hours(3));
+        assert!(far <= Instant::now() + SCHEDULE_RECHECK);
+        assert!(far >= now + SCHEDULE_RECHECK - Duration::from_secs(1));
+        // one overdue after a suspend fires right away
+        let overdue = schedule_wake(Local::now() - TimeDelta::hours(1));
+        assert!(overdue <= Instant::now());
+        let soon = schedule_wake(Local::now() + TimeDelta::seconds(5));
+        assert!(soon < now + Duration::from_secs(6));
+    }
+
+    #[test]
+    fn test_rotation_pool_brightness_filter() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&library_wallpaper("dark", Some(0.2)))
+            .unwrap();
+        db.insert_wallpaper(&library_wallpaper("bright", Some(0.9)))
+            .unwrap();
+        db.insert_wallpaper(&library_wallpaper("unknown", None))
+            .unwrap();
+
+        let mut config = Config::default();
+        assert_eq!(rotation_pool(&db, &config, None, &[]).unwrap().len(), 3);
+
+        config.filter.max_brightness = 0.5;
+        let mut ids: Vec<String> = rotation_pool(&db, &config, None, &[])
+            .unwrap()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["dark", "unknown"]);
+    }
+
+    #[test]
+    fn test_rotation_pool_collection() {
+        let db = Database::open_in_memory().unwrap();
+        for id in ["a", "b", "c"] {
+            db.insert_wallpaper(&library_wallpaper(id, None)).unwrap();
+        }
+        db.move_to_collection("a", "work").unwrap();
+        db.move_to_collection("c", "work").unwrap();
+
+        let mut config = Config::default();
+        config.display.collection = Some("work".into());
+        let mut ids: Vec<String> = rotation_pool(&db, &config, None, &[])
+            .unwrap()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn test_rotation_pool_theme_tags() {
+        let db = Database::open_in_memory().unwrap();
+        for (id, tag) in [("day", "Light"), ("night", "dark"), ("plain", "misc")] {
+            let mut wp = library_wallpaper(id, None);
+            wp.tags = vec![tag.into()];
+            db.insert_wallpaper(&wp).unwrap();
+        }
+        let config = Config::default();
+        let ids = |tags: &[String]| -> Vec<String> {
+            rotation_pool(&db, &config, None, tags)
+                .unwrap()
+                .into_iter()
+                .map(|w| w.id)
+                .collect()
+        };
+        assert_eq!(ids(&["light".into()]), vec!["day"]);
+        assert_eq!(ids(&["dark".into()]), vec!["night"]);
+        // nothing tagged: rotate everything rather than nothing
+        assert_eq!(ids(&["sepia".into()]).len(), 3);
+    }
+
+    #[test]
+    fn test_rotation_pool_skips_low_res() {
+        let db = Database::open_in_memory().unwrap();
+        let mut small = library_wallpaper("small", None);
+        small.width = 1280;
+        small.height = 720;
+        db.insert_wallpaper(&small).unwrap();
+        db.insert_wallpaper(&library_wallpaper("full_hd", None))
+            .unwrap();
+
+        let config = Config::default();
+        assert_eq!(rotation_pool(&db, &config, None, &[]).unwrap().len(), 2);
+        let ids: Vec<String> = rotation_pool(&db, &config, Some((1920, 1080)), &[])
+            .unwrap()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(ids, vec!["full_hd"]);
+    }
+
+    #[test]
+    fn test_rotation_pool_skips_blacklisted() {
+        let db = Database::open_in_memory().unwrap();
+        db.insert_wallpaper(&library_wallpaper("kept", None))
+            .unwrap();
+        let hidden = library_wallpaper("hidden", None);
+        db.insert_wallpaper(&hidden).unwrap();
+        db.add_blacklist(&hidden.source_id, &hidden.source_type)
+            .unwrap();
+
+        let config = Config::default();
+        let ids: Vec<String> = rotation_pool(&db, &config, None, &[])
+            .unwrap()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        assert_eq!(ids, vec!["kept"]);
+
+        // everything blacklisted: fall back rather than go blank
+        db.add_blacklist("kept", &hidden.source_type).unwrap();
+        assert_eq!(rotation_pool(&db, &config, None, &[]).unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_workspace_default_fallback() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.display.mode = DisplayMode::Workspace;
+        config.display.workspace_default = Some("fallback".into());
+        config
+            .workspaces
+            .push(muralis_core::config::WorkspaceConfig {
+                workspace: 1,
+                wallpaper: "mapped".into(),
+            });
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(config, test_paths(&tmp), Box::new(backend.clone()));
+
+        for id in ["mapped", "fallback"] {
+            engine.wallpapers.push(library_image(tmp.path(), id, 8, 8));
+        }
+
+        engine.handle_workspace_change(1).await;
+        assert_eq!(engine.current_wallpaper.as_deref(), Some("mapped"));
+        engine.handle_workspace_change(7).await;
+        assert_eq!(engine.current_wallpaper.as_deref(), Some("fallback"));
+        assert_eq!(backend.applied.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_apply_current_uses_stored_crop() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(
+            Config::default(),
+            test_paths(&tmp),
+            Box::new(backend.clone()),
+        );
+
+        let mut wp = library_image(tmp.path(), "cropped", 64, 48);
+        wp.crop = Some(muralis_core::models::CropRect {
+            x: 8,
+            y: 4,
+            width: 32,
+            height: 18,
+        });
+        engine.wallpapers.push(wp);
+
+        engine.apply_current().await;
+        let applied = backend.applied.lock().unwrap().clone();
+        assert_eq!(applied.len(), 1);
+        assert_ne!(applied[0], tmp.path().join("cropped.png"));
+        let img = image::open(&applied[0]).unwrap();
+        assert_eq!((img.width(), img.height()), (32, 18));
+    }
+
+    #[tokio::test]
+    async fn test_same_wallpaper_applied_once_unless_forced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(
+            Config::default(),
+            test_paths(&tmp),
+            Box::new(backend.clone()),
+        );
+        engine
+            .wallpapers
+            .push(library_image(tmp.path(), "same", 64, 48));
+
+        engine.apply_current().await;
+        engine.apply_current().await;
+        assert_eq!(backend.applied.lock().unwrap().len(), 1);
+
+        // an explicit set always goes through
+        engine.set_wallpaper("same").await.unwrap();
+        assert_eq!(backend.applied.lock().unwrap().len(), 2);
+
+        engine.config.display.skip_unchanged = false;
+        engine.apply_current().await;
+        assert_eq!(backend.applied.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_backend_and_last_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(
+            Config::default(),
+            test_paths(&tmp),
+            Box::new(backend.clone()),
+        );
+        engine
+            .wallpapers
+            .push(library_image(tmp.path(), "flaky", 64, 48));
+
+        backend.fail.store(true, Ordering::SeqCst);
+        engine.apply_current().await;
+        let status = engine.status();
+        assert_eq!(status.backend, "mock");
+        assert!(status.last_error.unwrap().contains("daemon not running"));
+
+        backend.fail.store(false, Ordering::SeqCst);
+        engine.apply_current().await;
+        assert_eq!(engine.status().last_error, None);
+    }
+
+    #[tokio::test]
+    async fn test_per_monitor_crop_fits_each_monitor() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = MockBackend::default();
+        let mut config = Config::default();
+        config.display.per_monitor_crop = true;
+        let mut engine = DisplayEngine::new(config, test_paths(&tmp), Box::new(backend.clone()));
+        let monitor = |name: &str, width, height| MonitorInfo {
+            name: name.into(),
+            width,
+            height,
+            scale: 1.0,
+            dpms: true,
+        };
+        engine.monitors = vec![monitor("DP-1", 160, 90), monitor("HDMI-A-1", 90, 160)];
+        engine
+            .wallpapers
+            .push(library_image(tmp.path(), "wide", 320, 100));
+
+        engine.apply_current().await;
+        // monitors are re-detected before applying; on a machine without
+        // Hyprland the ones above are kept
+        let names: Vec<String> = engine.monitors.iter().map(|m| m.name.clone()).collect();
+        let sizes: Vec<(u32, u32)> = engine
+            .monitors
+            .iter()
+            .map(|m| (m.width, m.height))
+            .collect();
+        assert_eq!(*backend.monitors.lock().unwrap(), names);
+        let applied = backend.applied.lock().unwrap().clone();
+        let dims: Vec<(u32, u32)> = applied
+            .iter()
+            .map(|p| image::image_dimensions(p).unwrap())
+            .collect();
+        assert_eq!(dims, sizes);
+
+        // videos go to each monitor untouched
+        let video = tmp.path().join("loop.mp4");
+        std::fs::write(&video, b"not an image").unwrap();
+        backend.applied.lock().unwrap().clear();
+        engine.apply_all(&video, true).await.unwrap();
+        assert_eq!(*backend.applied.lock().unwrap(), vec![video; names.len()]);
+    }
+
+    #[tokio::test]
+    async fn test_restore_previous_uses_applied_history() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(
+            Config::default(),
+            test_paths(&tmp),
+            Box::new(backend.clone()),
+        );
+        assert!(engine.restore_previous().await.is_err());
+
+        for id in ["a", "b"] {
+            engine.wallpapers.push(library_image(tmp.path(), id, 8, 8));
+        }
+        let outside = tmp.path().join("outside.png");
+        write_test_image(&outside, 8, 8);
+
+        engine.set_wallpaper("a").await.unwrap();
+        engine.set_wallpaper("b").await.unwrap();
+        engine
+            .set_wallpaper(outside.to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(engine.restore_previous().await.unwrap(), "b");
+        assert_eq!(engine.current_wallpaper.as_deref(), Some("b"));
+        assert_eq!(
+            backend.applied.lock().unwrap().last(),
+            Some(&tmp.path().join("b.png"))
+        );
+
+        // restoring again swaps back to the file outside the library
+        let restored = engine.restore_previous().await.unwrap();
+        assert_eq!(restored, outside.to_str().unwrap());
+        assert_eq!(engine.current_ephemeral.as_deref(), Some(outside.as_path()));
+    }
+
+    /// Source with a single result whose download is a small PNG.
+    struct LiveSource;
+
+    #[async_trait]
+    impl WallpaperSource for LiveSource {
+        fn name(&self) -> &str {
+            "Live"
+        }
+
+        fn source_type(&self) -> &str {
+            "live"
+        }
+
+        async fn search(
+            &self,
+            _query: &str,
+            _page: u32,
+            _per_page: u32,
+            _aspect: AspectRatioFilter,
+        ) -> muralis_core::error::Result<Vec<muralis_core::models::WallpaperPreview>> {
+            Ok(vec![muralis_core::models::WallpaperPreview {
+                source_type: muralis_core::models::SourceType::new("live"),
+                source_id: "live-1".into(),
+                source_url: "https://example.com/live-1".into(),
+                thumbnail_url: "https://example.com/live-1-thumb.png".into(),
+                full_url: "https://example.com/live-1.png".into(),
+                sample_url: None,
+                width: 64,
+                height: 36,
+                tags: Vec::new(),
+                meta: muralis_core::models::WallpaperMeta::default(),
+            }])
+        }
+
+        async fn download(
+            &self,
+            _preview: &muralis_core::models::WallpaperPreview,
+        ) -> muralis_core::error::Result<bytes::Bytes> {
+            let img = image::RgbImage::from_pixel(64, 36, image::Rgb([200, 120, 40]));
+            let mut buf = Vec::new();
+            img.write_to(&mut Cursor::new(&mut buf), image::ImageFormat::Png)
+                .unwrap();
+            Ok(buf.into())
+        }
+    }
+
+    async fn finish_live_fetch(engine: &mut DisplayEngine) {
+        let result = engine.live_rx.as_mut().unwrap().recv().await.unwrap();
+        engine.on_live_fetched(result).await;
+    }
+
+    #[tokio::test]
+    async fn test_live_random_favorites_and_applies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = test_paths(&tmp);
+        let mut config = Config::default();
+        config.display.mode = DisplayMode::LiveRandom;
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(config, paths.clone(), Box::new(backend.clone()))
+            .with_sources(|_, _| {
+                let mut registry = SourceRegistry::new();
+                registry.register(Box::new(LiveSource));
+                registry
+            });
+
+        engine.next().await;
+        // the fetch runs in the background; nothing applied yet
+        assert!(engine.live_pending);
+        assert!(backend.applied.lock().unwrap().is_empty());
+        finish_live_fetch(&mut engine).await;
+
+        let db = Database::open(&paths.db_path()).unwrap();
+        assert_eq!(db.wallpaper_count().unwrap(), 1);
+        assert!(db.is_favorited_by_source("live", "live-1").unwrap());
+        let id = engine.current_wallpaper.clone().unwrap();
+        let wp = db.get_wallpaper(&id).unwrap();
+        assert_eq!(
+            *backend.applied.lock().unwrap(),
+            vec![PathBuf::from(&wp.file_path)]
+        );
+        assert_eq!(engine.wallpapers.len(), 1);
+
+        // the only result is now a favorite: the next tick falls back to the
+        // library, whose only wallpaper is already showing
+        engine.last_live_fetch = None;
+        engine.next().await;
+        finish_live_fetch(&mut engine).await;
+        assert_eq!(db.wallpaper_count().unwrap(), 1);
+        assert_eq!(backend.applied.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_random_startup_holds_after_launch() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.display.mode = DisplayMode::RandomStartup;
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(config, test_paths(&tmp), Box::new(backend.clone()));
+        for id in ["a", "b", "c", "d"] {
+            engine.wallpapers.push(library_image(tmp.path(), id, 8, 8));
+        }
+
+        engine.pick_startup().await;
+        assert_eq!(backend.applied.lock().unwrap().len(), 1);
+        let picked = engine.current_index;
+
+        engine.update_next_change(Duration::from_secs(60));
+        assert!(engine.next_change.is_none());
+        for _ in 0..5 {
+            engine.on_tick(Duration::from_secs(60)).await;
+        }
+        assert_eq!(engine.current_index, picked);
+        assert_eq!(backend.applied.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_random_never_repeats_immediately() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = MockBackend::default();
+        let mut engine = DisplayEngine::new(
+            Config::default(),
+            test_paths(&tmp),
+            Box::new(backend.clone()),
+        );
+        for id in ["a", "b", "c", "d", "e"] {
+            engine.wallpapers.push(library_image(tmp.path(), id, 8, 8));
+        }
+
+        let mut shown = Vec::new();
+        for _ in 0..100 {
+            engine.next().await;
+            shown.push(engine.current_index);
+        }
+        // history of min(5 / 2, 20) = 2 plus the current one: no repeat within 3
+        for window in shown.windows(3) {
+            assert_ne!(window[0], window[1]);
+            assert_ne!(window[0], window[2]);
+            assert_ne!(window[1], window[2]);
+        }
+
+        // two wallpapers alternate, one just repeats
+        engine.wallpapers.truncate(2);
+        engine.recent.clear();
+        engine.current_index = 0;
+        for _ in 0..10 {
+            let before = engine.current_index;
+            engine.next().await;
+            assert_ne!(engine.current_index, before);
+        }
+        engine.wallpapers.truncate(1);
+        engine.next().await;
+        assert_eq!(engine.current_index, 0);
+    }
+
+    #[test]
+    fn test_keep_current_without_wallpaper_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut engine = DisplayEngine::new(
+            Config::default(),
+            test_paths(&tmp),
+            Box::new(MockBackend::default()),
+        );
+        assert!(engine.keep_current().is_err());
+    }
+}
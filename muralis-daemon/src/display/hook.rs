@@ -0,0 +1,120 @@
+use std::io;
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::time::Duration;
+
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+/// A hook still running after this long is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Environment variable holding the applied file; `{path}` expands to it.
+const PATH_ENV: &str = "MURALIS_PATH";
+
+/// Run the `on_change_command` template for a newly applied `path` in the
+/// background, logging failures. The engine never waits on it.
+pub fn run_on_change(template: &str, path: &Path) {
+    let mut cmd = hook_command(template, path);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    let template = template.to_string();
+    tokio::spawn(async move {
+        match run_with_timeout(cmd, HOOK_TIMEOUT).await {
+            Some(Ok(output)) if output.status.success() => {
+                debug!(command = %template, "on_change_command finished");
+            }
+            Some(Ok(output)) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                warn!(command = %template, status = %output.status, "on_change_command failed: {}", stderr.trim());
+            }
+            Some(Err(e)) => warn!(command = %template, "failed to run on_change_command: {e}"),
+            None => warn!(
+                command = %template,
+                "on_change_command still running after {}s; killed",
+                HOOK_TIMEOUT.as_secs()
+            ),
+        }
+    });
+}
+
+/// Run `cmd` in its own process group, killing the whole group if it
+/// outlives `limit` so nothing it spawned lingers. None on timeout.
+async fn run_with_timeout(mut cmd: Command, limit: Duration) -> Option<io::Result<Output>> {
+    let child = match cmd.process_group(0).kill_on_drop(true).spawn() {
+        Ok(child) => child,
+        Err(e) => return Some(Err(e)),
+    };
+    let pgid = child.id();
+    let result = tokio::time::timeout(limit, child.wait_with_output()).await;
+    if result.is_err() {
+        if let Some(pgid) = pgid {
+            unsafe { libc::killpg(pgid as libc::pid_t, libc::SIGKILL) };
+        }
+    }
+    result.ok()
+}
+
+/// `sh -c` running `template` with the path in `$MURALIS_PATH` and each
+/// `{path}`, quoted or not, replaced by `"$MURALIS_PATH"`.
+fn hook_command(template: &str, path: &Path) -> Command {
+    let var = format!("\"${PATH_ENV}\"");
+    let script = template
+        .replace("\"{path}\"", &var)
+        .replace("'{path}'", &var)
+        .replace("{path}", &var);
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(script).env(PATH_ENV, path);
+    cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        cmd.as_std()
+            .get_args()
+            .map(|a| a.to_string_lossy().to_string())
+            .collect()
+    }
+
+    /// What the hook's shell sees as its first word after substitution.
+    async fn expanded(template: &str, path: &str) -> String {
+        let mut cmd = hook_command(&format!("printf %s {template}"), Path::new(path));
+        let output = cmd.output().await.unwrap();
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hook_command_passes_path_intact() {
+        let path = "/tmp/it's a \"wall\" $HOME.png";
+        for template in ["{path}", "'{path}'", "\"{path}\"", "\"$MURALIS_PATH\""] {
+            assert_eq!(expanded(template, path).await, path, "{template}");
+        }
+
+        // no placeholder: run as written
+        let cmd = hook_command("pkill -USR1 waybar", Path::new("/tmp/a.jpg"));
+        assert_eq!(args(&cmd), vec!["-c", "pkill -USR1 waybar"]);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_kills_the_whole_group() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pid_file = tmp.path().join("pid");
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(format!(
+            "sleep 60 & echo $! > '{}'; wait",
+            pid_file.display()
+        ));
+        assert!(run_with_timeout(cmd, Duration::from_millis(300))
+            .await
+            .is_none());
+
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        // gone, or a zombie waiting on a reaper
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim()));
+        assert!(stat.map_or(true, |s| s.contains(") Z ")), "sleep survived");
+    }
+}
@@ -1,17 +1,25 @@
+pub mod cooldown;
 pub mod engine;
+pub mod hook;
+pub mod relay;
 pub mod scheduler;
+pub mod theme;
 
 use tokio::sync::oneshot;
 
 use muralis_core::ipc::DaemonStatus;
-use muralis_core::models::DisplayMode;
+use muralis_core::models::{DisplayMode, Wallpaper};
 
 pub enum DaemonCommand {
     Status {
         respond: oneshot::Sender<DaemonStatus>,
     },
-    Next,
-    Prev,
+    Next {
+        respond: oneshot::Sender<Result<(), String>>,
+    },
+    Prev {
+        respond: oneshot::Sender<Result<(), String>>,
+    },
     SetWallpaper {
         id: String,
         respond: oneshot::Sender<Result<(), String>>,
@@ -22,6 +30,23 @@ pub enum DaemonCommand {
     Pause,
     Resume,
     Reload,
+    KeepCurrent {
+        respond: oneshot::Sender<Result<String, String>>,
+    },
+    RestorePrevious {
+        respond: oneshot::Sender<Result<String, String>>,
+    },
+    CurrentWallpaper {
+        respond: oneshot::Sender<Result<Wallpaper, String>>,
+    },
+    ListWallpapers {
+        limit: Option<u32>,
+        respond: oneshot::Sender<Vec<Wallpaper>>,
+    },
+    SetSourceEnabled {
+        name: String,
+        enabled: bool,
+    },
     WorkspaceChanged {
         id: u32,
     },
@@ -0,0 +1,45 @@
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::DaemonCommand;
+
+/// Forwards commands from an unbounded queue into the engine's bounded channel.
+///
+/// Producers that must never block or drop (event listeners, bursty clicks) push
+/// into the returned sender; the relay task awaits capacity on the engine side.
+pub fn spawn_relay(engine_tx: mpsc::Sender<DaemonCommand>) -> mpsc::UnboundedSender<DaemonCommand> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(cmd) = rx.recv().await {
+            if engine_tx.send(cmd).await.is_err() {
+                warn!("engine channel closed, stopping command relay");
+                break;
+            }
+        }
+    });
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_relay_does_not_drop_burst() {
+        let (engine_tx, mut engine_rx) = mpsc::channel(2);
+        let relay = spawn_relay(engine_tx);
+
+        for id in 0..100 {
+            relay.send(DaemonCommand::WorkspaceChanged { id }).unwrap();
+        }
+        drop(relay);
+
+        let mut received = Vec::new();
+        while let Some(cmd) = engine_rx.recv().await {
+            if let DaemonCommand::WorkspaceChanged { id } = cmd {
+                received.push(id);
+            }
+        }
+        assert_eq!(received, (0..100).collect::<Vec<_>>());
+    }
+}
@@ -34,36 +34,34 @@ pub fn parse_interval(s: &str) -> Option<Duration> {
 
 /// Find the next schedule entry that should trigger, and how long until it fires.
 pub fn next_schedule_trigger(schedules: &[ScheduleEntry]) -> Option<(Duration, Vec<String>)> {
-    if schedules.is_empty() {
-        return None;
-    }
-
-    let now = Local::now().time();
-    let mut best_duration: Option<Duration> = None;
-    let mut best_tags = Vec::new();
-
-    for entry in schedules {
-        if let Ok(target) = NaiveTime::parse_from_str(&entry.time, "%H:%M") {
-            let secs_until = seconds_until(now, target);
-            let dur = Duration::from_secs(secs_until as u64);
-
-            if best_duration.is_none() || dur < best_duration.unwrap() {
-                best_duration = Some(dur);
-                best_tags = entry.tags.clone();
-            }
-        }
-    }
+    next_schedule_trigger_at(schedules, Local::now().time())
+}
 
-    best_duration.map(|d| (d, best_tags))
+/// [`next_schedule_trigger`] as seen from `now`. Entries at or just before
+/// `now` wrap to the same time tomorrow.
+pub fn next_schedule_trigger_at(
+    schedules: &[ScheduleEntry],
+    now: NaiveTime,
+) -> Option<(Duration, Vec<String>)> {
+    schedules
+        .iter()
+        .filter_map(|entry| {
+            let target = NaiveTime::parse_from_str(&entry.time, "%H:%M").ok()?;
+            Some((duration_until(now, target), &entry.tags))
+        })
+        .min_by_key(|(dur, _)| *dur)
+        .map(|(dur, tags)| (dur, tags.clone()))
 }
 
-fn seconds_until(now: NaiveTime, target: NaiveTime) -> i64 {
-    let diff = target.signed_duration_since(now).num_seconds();
-    if diff > 0 {
-        diff
-    } else {
-        diff + 86400 // wrap to next day
+/// Time from `now` until `target` next comes round. Anything under a second
+/// away counts as already fired, so a timer waking a hair early doesn't fire
+/// the same entry twice.
+fn duration_until(now: NaiveTime, target: NaiveTime) -> Duration {
+    let mut millis = target.signed_duration_since(now).num_milliseconds();
+    if millis < 1000 {
+        millis += 86_400_000; // wrap to next day
     }
+    Duration::from_millis(millis as u64)
 }
 
 #[cfg(test)]
@@ -81,17 +79,27 @@ mod tests {
     }
 
     #[test]
-    fn test_seconds_until_future() {
+    fn test_duration_until_future() {
         let now = NaiveTime::from_hms_opt(10, 0, 0).unwrap();
         let target = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
-        assert_eq!(seconds_until(now, target), 7200);
+        assert_eq!(duration_until(now, target), Duration::from_secs(7200));
     }
 
     #[test]
-    fn test_seconds_until_past_wraps() {
+    fn test_duration_until_past_wraps() {
         let now = NaiveTime::from_hms_opt(23, 0, 0).unwrap();
         let target = NaiveTime::from_hms_opt(1, 0, 0).unwrap();
-        assert_eq!(seconds_until(now, target), 7200);
+        assert_eq!(duration_until(now, target), Duration::from_secs(7200));
+    }
+
+    #[test]
+    fn test_duration_until_is_exact() {
+        let now = NaiveTime::from_hms_milli_opt(7, 59, 30, 250).unwrap();
+        let target = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        assert_eq!(duration_until(now, target), Duration::from_millis(29_750));
+        // just fired: the next one is tomorrow
+        let now = NaiveTime::from_hms_milli_opt(7, 59, 59, 900).unwrap();
+        assert!(duration_until(now, target) > Duration::from_secs(86_000));
     }
 
     #[test]
@@ -112,4 +120,30 @@ mod tests {
         let (_, tags) = result.unwrap();
         assert!(!tags.is_empty());
     }
+
+    fn entry(time: &str, tag: &str) -> ScheduleEntry {
+        ScheduleEntry {
+            time: time.into(),
+            tags: vec![tag.into()],
+        }
+    }
+
+    #[test]
+    fn test_next_schedule_trigger_near_future() {
+        let schedules = vec![entry("08:00", "morning"), entry("20:00", "evening")];
+        let now = NaiveTime::from_hms_opt(7, 58, 0).unwrap();
+        let (dur, tags) = next_schedule_trigger_at(&schedules, now).unwrap();
+        assert_eq!(dur, Duration::from_secs(120));
+        assert_eq!(tags, vec!["morning"]);
+    }
+
+    #[test]
+    fn test_next_schedule_trigger_past_wraps() {
+        let schedules = vec![entry("08:00", "morning"), entry("bogus", "never")];
+        let now = NaiveTime::from_hms_opt(8, 1, 0).unwrap();
+        let (dur, tags) = next_schedule_trigger_at(&schedules, now).unwrap();
+        assert_eq!(dur, Duration::from_secs(24 * 3600 - 60));
+        assert_eq!(tags, vec!["morning"]);
+        assert!(next_schedule_trigger_at(&[], now).is_none());
+    }
 }
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use chrono::{Local, NaiveTime};
+use tokio::process::Command;
+
+use muralis_core::config::ThemeConfig;
+
+/// Desktop light/dark preference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// Tags the rotation is limited to under `scheme`.
+pub fn theme_tags_for(scheme: ColorScheme, config: &ThemeConfig) -> Vec<String> {
+    match scheme {
+        ColorScheme::Light => config.light_tags.clone(),
+        ColorScheme::Dark => config.dark_tags.clone(),
+    }
+}
+
+/// The desktop's preference, else the time-of-day fallback.
+pub async fn current_scheme(config: &ThemeConfig) -> ColorScheme {
+    match portal_scheme().await {
+        Some(scheme) => scheme,
+        None => scheme_at(Local::now().time(), config),
+    }
+}
+
+/// How long to wait on the settings portal before using the fallback.
+const PORTAL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// `org.freedesktop.appearance color-scheme` from the settings portal.
+async fn portal_scheme() -> Option<ColorScheme> {
+    let mut busctl = Command::new("busctl");
+    busctl
+        .args([
+            "--user",
+            "call",
+            "org.freedesktop.portal.Desktop",
+            "/org/freedesktop/portal/desktop",
+            "org.freedesktop.portal.Settings",
+            "ReadOne",
+            "ss",
+            "org.freedesktop.appearance",
+            "color-scheme",
+        ])
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(PORTAL_TIMEOUT, busctl.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_portal_reply(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Scheme from a `busctl` reply such as `v u 1`: 1 prefers dark, 2 prefers
+/// light and 0 (no preference) is left to the fallback.
+fn parse_portal_reply(reply: &str) -> Option<ColorScheme> {
+    match reply.split_whitespace().last()? {
+        "1" => Some(ColorScheme::Dark),
+        "2" => Some(ColorScheme::Light),
+        _ => None,
+    }
+}
+
+/// Light between `light_from` and `dark_from`, dark otherwise. Unparseable
+/// times fall back to 07:00 and 19:00.
+pub fn scheme_at(now: NaiveTime, config: &ThemeConfig) -> ColorScheme {
+    let parse = |s: &str, default: (u32, u32)| {
+        NaiveTime::parse_from_str(s, "%H:%M")
+            .ok()
+            .or_else(|| NaiveTime::from_hms_opt(default.0, default.1, 0))
+            .unwrap_or_default()
+    };
+    let light = parse(&config.light_from, (7, 0));
+    let dark = parse(&config.dark_from, (19, 0));
+    let is_light = if light <= dark {
+        light <= now && now < dark
+    } else {
+        // light across midnight
+        now >= light || now < dark
+    };
+    if is_light {
+        ColorScheme::Light
+    } else {
+        ColorScheme::Dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_theme_tags_for() {
+        let config = ThemeConfig {
+            light_tags: vec!["bright".into(), "day".into()],
+            dark_tags: vec!["night".into()],
+            ..ThemeConfig::default()
+        };
+        assert_eq!(
+            theme_tags_for(ColorScheme::Light, &config),
+            vec!["bright", "day"]
+        );
+        assert_eq!(theme_tags_for(ColorScheme::Dark, &config), vec!["night"]);
+
+        let defaults = ThemeConfig::default();
+        assert_eq!(theme_tags_for(ColorScheme::Light, &defaults), vec!["light"]);
+        assert_eq!(theme_tags_for(ColorScheme::Dark, &defaults), vec!["dark"]);
+    }
+
+    #[test]
+    fn test_scheme_at_time_of_day() {
+        let config = ThemeConfig::default();
+        assert_eq!(scheme_at(at(6, 59), &config), ColorScheme::Dark);
+        assert_eq!(scheme_at(at(7, 0), &config), ColorScheme::Light);
+        assert_eq!(scheme_at(at(18, 59), &config), ColorScheme::Light);
+        assert_eq!(scheme_at(at(19, 0), &config), ColorScheme::Dark);
+
+        // night-shift hours: light across midnight
+        let config = ThemeConfig {
+            light_from: "22:00".into(),
+            dark_from: "06:00".into(),
+            ..ThemeConfig::default()
+        };
+        assert_eq!(scheme_at(at(23, 0), &config), ColorScheme::Light);
+        assert_eq!(scheme_at(at(3, 0), &config), ColorScheme::Light);
+        assert_eq!(scheme_at(at(12, 0), &config), ColorScheme::Dark);
+    }
+
+    #[test]
+    fn test_parse_portal_reply() {
+        assert_eq!(parse_portal_reply("v u 1\n"), Some(ColorScheme::Dark));
+        assert_eq!(parse_portal_reply("v u 2"), Some(ColorScheme::Light));
+        assert_eq!(parse_portal_reply("v u 0"), None);
+        assert_eq!(parse_portal_reply(""), None);
+    }
+}
@@ -1,6 +1,9 @@
+use std::time::Duration;
+
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::UnixListener;
 use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinSet;
 use tracing::{info, warn};
 
 use muralis_core::ipc::{IpcRequest, IpcResponse};
@@ -8,11 +11,14 @@ use muralis_core::paths::MuralisPaths;
 
 use crate::display::DaemonCommand;
 
+/// Serve requests until shutdown, then give in-flight ones up to `grace`
+/// to finish before aborting them.
 pub async fn serve_ipc(
     cmd_tx: mpsc::Sender<DaemonCommand>,
     mut shutdown: tokio::sync::watch::Receiver<bool>,
+    grace: Duration,
 ) -> anyhow::Result<()> {
-    let socket_path = MuralisPaths::socket_path();
+    let socket_path = MuralisPaths::socket_path()?;
 
     // clean up stale socket
     if socket_path.exists() {
@@ -22,13 +28,14 @@ pub async fn serve_ipc(
     let listener = UnixListener::bind(&socket_path)?;
     info!(path = %socket_path.display(), "IPC socket listening");
 
+    let mut connections = JoinSet::new();
     loop {
         tokio::select! {
             result = listener.accept() => {
                 match result {
                     Ok((stream, _)) => {
                         let tx = cmd_tx.clone();
-                        tokio::spawn(async move {
+                        connections.spawn(async move {
                             if let Err(e) = handle_connection(stream, tx).await {
                                 warn!("IPC connection error: {e}");
                             }
@@ -37,15 +44,31 @@ pub async fn serve_ipc(
                     Err(e) => warn!("IPC accept error: {e}"),
                 }
             }
+            // reap finished connections so the set doesn't grow
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
             _ = shutdown.changed() => {
                 info!("IPC server shutting down");
                 let _ = std::fs::remove_file(&socket_path);
+                drain(connections, grace).await;
                 return Ok(());
             }
         }
     }
 }
 
+/// Wait up to `grace` for `tasks`, then abort the rest.
+async fn drain(mut tasks: JoinSet<()>, grace: Duration) {
+    let finished =
+        tokio::time::timeout(grace, async { while tasks.join_next().await.is_some() {} }).await;
+    if finished.is_err() {
+        warn!(
+            remaining = tasks.len(),
+            "aborting IPC requests still running after shutdown grace"
+        );
+        tasks.shutdown().await;
+    }
+}
+
 async fn handle_connection(
     stream: tokio::net::UnixStream,
     cmd_tx: mpsc::Sender<DaemonCommand>,
@@ -96,12 +119,12 @@ async fn dispatch_request(
             }
         }
         IpcRequest::Next => {
-            let _ = cmd_tx.send(DaemonCommand::Next).await;
-            IpcResponse::ok()
+            let (tx, rx) = oneshot::channel();
+            switch_command(cmd_tx, DaemonCommand::Next { respond: tx }, rx).await
         }
         IpcRequest::Prev => {
-            let _ = cmd_tx.send(DaemonCommand::Prev).await;
-            IpcResponse::ok()
+            let (tx, rx) = oneshot::channel();
+            switch_command(cmd_tx, DaemonCommand::Prev { respond: tx }, rx).await
         }
         IpcRequest::SetWallpaper { id } => {
             let (tx, rx) = oneshot::channel();
@@ -118,25 +141,101 @@ async fn dispatch_request(
                 Err(_) => IpcResponse::error("engine dropped response"),
             }
         }
-        IpcRequest::SetMode { mode } => {
-            let _ = cmd_tx.send(DaemonCommand::SetMode { mode }).await;
-            IpcResponse::ok()
+        IpcRequest::SetMode { mode } => send_command(cmd_tx, DaemonCommand::SetMode { mode }).await,
+        IpcRequest::Pause => send_command(cmd_tx, DaemonCommand::Pause).await,
+        IpcRequest::Resume => send_command(cmd_tx, DaemonCommand::Resume).await,
+        IpcRequest::Reload => send_command(cmd_tx, DaemonCommand::Reload).await,
+        IpcRequest::KeepCurrent => {
+            let (tx, rx) = oneshot::channel();
+            if cmd_tx
+                .send(DaemonCommand::KeepCurrent { respond: tx })
+                .await
+                .is_err()
+            {
+                return IpcResponse::error("engine unavailable");
+            }
+            match rx.await {
+                Ok(Ok(id)) => IpcResponse::ok_with_data(serde_json::json!({ "id": id })),
+                Ok(Err(msg)) => IpcResponse::error(msg),
+                Err(_) => IpcResponse::error("engine dropped response"),
+            }
         }
-        IpcRequest::Pause => {
-            let _ = cmd_tx.send(DaemonCommand::Pause).await;
-            IpcResponse::ok()
+        IpcRequest::RestorePrevious => {
+            let (tx, rx) = oneshot::channel();
+            if cmd_tx
+                .send(DaemonCommand::RestorePrevious { respond: tx })
+                .await
+                .is_err()
+            {
+                return IpcResponse::error("engine unavailable");
+            }
+            match rx.await {
+                Ok(Ok(id)) => IpcResponse::ok_with_data(serde_json::json!({ "id": id })),
+                Ok(Err(msg)) => IpcResponse::error(msg),
+                Err(_) => IpcResponse::error("engine dropped response"),
+            }
         }
-        IpcRequest::Resume => {
-            let _ = cmd_tx.send(DaemonCommand::Resume).await;
-            IpcResponse::ok()
+        IpcRequest::CurrentWallpaper => {
+            let (tx, rx) = oneshot::channel();
+            if cmd_tx
+                .send(DaemonCommand::CurrentWallpaper { respond: tx })
+                .await
+                .is_err()
+            {
+                return IpcResponse::error("engine unavailable");
+            }
+            match rx.await {
+                Ok(Ok(wp)) => {
+                    IpcResponse::ok_with_data(serde_json::to_value(wp).unwrap_or_default())
+                }
+                Ok(Err(msg)) => IpcResponse::error(msg),
+                Err(_) => IpcResponse::error("engine dropped response"),
+            }
         }
-        IpcRequest::Reload => {
-            let _ = cmd_tx.send(DaemonCommand::Reload).await;
-            IpcResponse::ok()
+        IpcRequest::ListWallpapers { limit } => {
+            let (tx, rx) = oneshot::channel();
+            if cmd_tx
+                .send(DaemonCommand::ListWallpapers { limit, respond: tx })
+                .await
+                .is_err()
+            {
+                return IpcResponse::error("engine unavailable");
+            }
+            match rx.await {
+                Ok(wallpapers) => {
+                    IpcResponse::ok_with_data(serde_json::to_value(wallpapers).unwrap_or_default())
+                }
+                Err(_) => IpcResponse::error("engine dropped response"),
+            }
         }
-        IpcRequest::Quit => {
-            let _ = cmd_tx.send(DaemonCommand::Quit).await;
-            IpcResponse::ok()
+        IpcRequest::SetSourceEnabled { name, enabled } => {
+            send_command(cmd_tx, DaemonCommand::SetSourceEnabled { name, enabled }).await
         }
+        IpcRequest::Quit => send_command(cmd_tx, DaemonCommand::Quit).await,
+    }
+}
+
+/// Sends `next`/`prev` and reports a rejection from the switch cooldown.
+async fn switch_command(
+    cmd_tx: &mpsc::Sender<DaemonCommand>,
+    cmd: DaemonCommand,
+    rx: oneshot::Receiver<Result<(), String>>,
+) -> IpcResponse {
+    if cmd_tx.send(cmd).await.is_err() {
+        return IpcResponse::error("engine unavailable");
+    }
+    match rx.await {
+        Ok(Ok(())) => IpcResponse::ok(),
+        Ok(Err(msg)) => IpcResponse::error(msg),
+        Err(_) => IpcResponse::error("engine dropped response"),
+    }
+}
+
+/// Sends a fire-and-forget command, reporting a closed engine channel instead of ignoring it.
+async fn send_command(cmd_tx: &mpsc::Sender<DaemonCommand>, cmd: DaemonCommand) -> IpcResponse {
+    if cmd_tx.send(cmd).await.is_err() {
+        warn!("engine channel closed, dropping command");
+        return IpcResponse::error("engine unavailable");
     }
+    IpcResponse::ok()
 }
@@ -1,16 +1,26 @@
+mod config_watch;
 mod display;
 mod ipc;
+mod sources;
 mod workspace;
 
+use std::time::Duration;
+
 use tokio::sync::{mpsc, watch};
-use tracing::info;
+use tracing::{info, warn};
 
-use muralis_core::backend::create_backend;
+use muralis_core::backend::{create_backend, detect_conflicting_daemons, running_processes};
 use muralis_core::config::Config;
 use muralis_core::paths::MuralisPaths;
+use muralis_core::wallpapers::remove_partial_files;
 
 use display::engine::DisplayEngine;
 
+/// How long in-flight requests and the engine get to finish on shutdown.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+/// `.part` files younger than this may belong to a CLI download in progress.
+const STALE_PARTIAL_AGE: Duration = Duration::from_secs(600);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
@@ -25,14 +35,26 @@ async fn main() -> anyhow::Result<()> {
     let _ = paths.install_icon();
 
     let config = Config::load_or_default(&paths);
-    info!(backend = %config.general.backend, mode = %config.display.mode, "starting muralis-daemon");
+    let profile = MuralisPaths::active_profile();
+    info!(
+        backend = %config.general.backend,
+        mode = %config.display.mode,
+        profile = profile.as_deref().unwrap_or("default"),
+        "starting muralis-daemon"
+    );
 
     let backend = create_backend(&config);
+    for daemon in detect_conflicting_daemons(config.general.backend, &running_processes()) {
+        warn!(
+            "{daemon} is running alongside {}; wallpapers may be overridden",
+            config.general.backend
+        );
+    }
     let (cmd_tx, cmd_rx) = mpsc::channel(32);
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
-    // spawn workspace listener
-    let ws_tx = cmd_tx.clone();
+    // spawn workspace listener; events go through the relay so bursts never block or drop
+    let ws_tx = display::relay::spawn_relay(cmd_tx.clone());
     tokio::spawn(async move {
         workspace::listen_workspace_events(ws_tx).await;
     });
@@ -40,14 +62,25 @@ async fn main() -> anyhow::Result<()> {
     // spawn IPC server
     let ipc_shutdown = shutdown_rx.clone();
     let ipc_tx = cmd_tx.clone();
-    tokio::spawn(async move {
-        if let Err(e) = ipc::serve_ipc(ipc_tx, ipc_shutdown).await {
+    let ipc_handle = tokio::spawn(async move {
+        if let Err(e) = ipc::serve_ipc(ipc_tx, ipc_shutdown, SHUTDOWN_GRACE).await {
             tracing::error!("IPC server error: {e}");
         }
     });
 
+    // reload on config edits
+    if config.general.watch_config {
+        let watch_paths = paths.clone();
+        let watch_tx = cmd_tx.clone();
+        let watch_shutdown = shutdown_rx.clone();
+        tokio::spawn(async move {
+            config_watch::watch_config(watch_paths, watch_tx, watch_shutdown).await;
+        });
+    }
+
     // spawn display engine
-    let engine = DisplayEngine::new(config, paths.clone(), backend);
+    let engine =
+        DisplayEngine::new(config, paths.clone(), backend).with_sources(sources::build_registry);
     let engine_shutdown = shutdown_rx.clone();
     let engine_handle = tokio::spawn(async move {
         engine.run(cmd_rx, engine_shutdown).await;
@@ -58,12 +91,24 @@ async fn main() -> anyhow::Result<()> {
     info!("received ctrl+c, shutting down");
     let _ = shutdown_tx.send(true);
 
-    // wait for engine to finish
-    let _ = engine_handle.await;
+    // let in-flight requests (bounded by the IPC server) and the current
+    // engine step finish
+    let (_, engine_done) = tokio::join!(
+        ipc_handle,
+        tokio::time::timeout(SHUTDOWN_GRACE, engine_handle),
+    );
+    if engine_done.is_err() {
+        warn!("display engine still busy after shutdown grace; stopping anyway");
+    }
+    for dir in [paths.wallpapers_dir(), paths.cropped_dir()] {
+        let removed = remove_partial_files(&dir, STALE_PARTIAL_AGE);
+        if removed > 0 {
+            info!(removed, dir = %dir.display(), "removed partial files");
+        }
+    }
 
     // clean up socket
-    let socket = MuralisPaths::socket_path();
-    if socket.exists() {
+    if let Ok(socket) = MuralisPaths::socket_path() {
         let _ = std::fs::remove_file(socket);
     }
 
@@ -0,0 +1,12 @@
+use muralis_core::config::Config;
+use muralis_core::paths::MuralisPaths;
+use muralis_core::sources::SourceRegistry;
+
+/// Registry of every configured source, for modes that fetch on rotation.
+/// Empty, with a warning, when it can't be built.
+pub fn build_registry(config: &Config, paths: &MuralisPaths) -> SourceRegistry {
+    muralis_sources::build_registry(config, paths).unwrap_or_else(|e| {
+        tracing::warn!("failed to build source registry: {e}");
+        SourceRegistry::new()
+    })
+}
@@ -6,7 +6,7 @@ use tracing::{debug, error};
 use crate::display::DaemonCommand;
 
 /// Listens for Hyprland workspace change events and forwards them as DaemonCommands.
-pub async fn listen_workspace_events(cmd_tx: mpsc::Sender<DaemonCommand>) {
+pub async fn listen_workspace_events(cmd_tx: mpsc::UnboundedSender<DaemonCommand>) {
     let mut stream = EventStream::new();
 
     while let Some(event) = stream.next().await {
@@ -20,7 +20,6 @@ pub async fn listen_workspace_events(cmd_tx: mpsc::Sender<DaemonCommand>) {
                 debug!(id, "workspace changed");
                 if cmd_tx
                     .send(DaemonCommand::WorkspaceChanged { id: id as u32 })
-                    .await
                     .is_err()
                 {
                     error!("cmd channel closed, stopping workspace listener");
@@ -2,6 +2,7 @@ import QtQuick
 import QtQuick.Controls
 import QtQuick.Controls.Material
 import QtQuick.Layouts
+import "logic.js" as Logic
 
 Rectangle {
     id: root
@@ -13,6 +14,11 @@ Rectangle {
     property string activeSource: "All"
     property int currentPage: 1
     property string activeAspect: "all"
+    // Wallhaven palette hex, "" for any color
+    property string activeColor: ""
+    property var recentQueries: []
+    // Source tabs opened this session
+    property var openedTabs: ({})
 
     function focusSearch() {
         searchField.forceActiveFocus()
@@ -29,6 +35,17 @@ Rectangle {
         }
     }
 
+    function loadHistory() {
+        CLI.run("history", ["history", "--source", activeSource])
+    }
+
+    function selectHistory(query) {
+        historyPopup.close()
+        searchField.text = query
+        currentPage = 1
+        window.executeSearch(query, activeSource, currentPage, activeAspect)
+    }
+
     function nextPage() {
         if (searchView.hasMore) {
             currentPage++
@@ -59,15 +76,44 @@ Rectangle {
         return result
     }
 
+    property bool isWallhaven: {
+        for (var i = 0; i < window.sourceList.length; i++)
+            if (window.sourceList[i].id === activeSource)
+                return window.sourceList[i].source_type === "wallhaven"
+        return false
+    }
+
+    // Color filter palette of the active source, from `sources list`
+    property var colorPalette: {
+        for (var i = 0; i < window.sourceList.length; i++)
+            if (window.sourceList[i].id === activeSource)
+                return window.sourceList[i].colors || []
+        return []
+    }
+
     property bool isFeedSource: {
         if (activeSource === "All") return false
         for (var i = 0; i < feedSources.length; i++)
-            if (feedSources[i].name === activeSource) return true
+            if (feedSources[i].id === activeSource) return true
         return false
     }
 
-    function selectSource(name) {
-        activeSource = name
+    function sourceById(id) {
+        for (var i = 0; i < window.sourceList.length; i++)
+            if (window.sourceList[i].id === id) return window.sourceList[i]
+        return null
+    }
+
+    // Sources are selected by id; names are only labels and may repeat
+    function selectSource(id) {
+        var firstOpen = !openedTabs[id]
+        if (firstOpen) {
+            var opened = Object.assign({}, openedTabs)
+            opened[id] = true
+            openedTabs = opened
+        }
+        activeSource = id
+        loadHistory()
         // Reset feed combo when selecting non-feed source
         if (!isFeedSource) feedCombo.currentIndex = 0
         // Feeds load immediately, others need query or aspect
@@ -76,6 +122,10 @@ Rectangle {
             window.executeSearch("", activeSource, 1, "all")
         } else if (searchField.text.length > 0 || activeAspect !== "all") {
             executeSearch()
+        } else if (Logic.shouldWarmUp(sourceById(id), firstOpen, window.prefetchOnOpen)) {
+            // warm up with the source's latest instead of an empty grid
+            currentPage = 1
+            window.executeSearch("", activeSource, 1, "all")
         }
     }
 
@@ -88,7 +138,7 @@ Rectangle {
         // Source chip buttons (API sources only)
         Repeater {
             model: {
-                var items = [{ name: "All" }]
+                var items = [{ id: "All", name: "All" }]
                 for (var i = 0; i < root.apiSources.length; i++)
                     items.push(root.apiSources[i])
                 return items
@@ -99,21 +149,21 @@ Rectangle {
                 Layout.preferredHeight: 22
                 Layout.alignment: Qt.AlignVCenter
                 radius: 4
-                color: activeSource === modelData.name ? Theme.primaryContainer : "transparent"
+                color: activeSource === modelData.id ? Theme.primaryContainer : "transparent"
 
                 Label {
                     id: chipLabel
                     anchors.centerIn: parent
                     text: modelData.name
                     font.pixelSize: 11
-                    font.bold: activeSource === modelData.name
-                    color: activeSource === modelData.name ? Theme.surfaceText : Theme.withAlpha(Theme.surfaceText, 0.6)
+                    font.bold: activeSource === modelData.id
+                    color: activeSource === modelData.id ? Theme.surfaceText : Theme.withAlpha(Theme.surfaceText, 0.6)
                 }
 
                 MouseArea {
                     anchors.fill: parent
                     cursorShape: Qt.PointingHandCursor
-                    onClicked: selectSource(modelData.name)
+                    onClicked: selectSource(modelData.id)
                 }
             }
         }
@@ -134,7 +184,7 @@ Rectangle {
             Material.accent: Theme.primary
             Material.foreground: Theme.surfaceText
             onActivated: function(index) {
-                if (index > 0) selectSource(currentText)
+                if (index > 0) selectSource(root.feedSources[index - 1].id)
             }
         }
 
@@ -169,6 +219,41 @@ Rectangle {
 
             onActiveFocusChanged: {
                 if (activeFocus) window.keyboardMode = "SEARCH"
+                if (activeFocus && text.length === 0 && root.recentQueries.length > 0)
+                    historyPopup.open()
+                else if (!activeFocus)
+                    historyPopup.close()
+            }
+
+            // Recent queries dropdown
+            Popup {
+                id: historyPopup
+                y: searchField.height + 2
+                width: searchField.width
+                padding: Theme.spacingXS
+                closePolicy: Popup.CloseOnEscape | Popup.CloseOnPressOutsideParent
+
+                background: Rectangle {
+                    color: Theme.surfaceContainerHigh
+                    radius: 4
+                    border.width: 1
+                    border.color: Theme.withAlpha(Theme.outline, 0.2)
+                }
+
+                contentItem: ListView {
+                    implicitHeight: contentHeight
+                    clip: true
+                    model: root.recentQueries
+
+                    delegate: ItemDelegate {
+                        width: ListView.view.width
+                        height: 28
+                        text: modelData
+                        font.pixelSize: 12
+                        Material.foreground: Theme.surfaceText
+                        onClicked: root.selectHistory(modelData)
+                    }
+                }
             }
 
             Timer {
@@ -182,7 +267,10 @@ Rectangle {
                 }
             }
 
-            onTextChanged: debounce.restart()
+            onTextChanged: {
+                if (text.length > 0) historyPopup.close()
+                debounce.restart()
+            }
 
             Keys.onReturnPressed: root.executeSearch()
             Keys.onEnterPressed: root.executeSearch()
@@ -224,6 +312,54 @@ Rectangle {
                 }
             }
         }
+
+        // Wallhaven color filter
+        ComboBox {
+            id: colorCombo
+            visible: root.isWallhaven
+            Layout.preferredHeight: 32
+            Layout.preferredWidth: 96
+            Layout.alignment: Qt.AlignVCenter
+            model: ["Any"].concat(root.colorPalette)
+            font.pixelSize: 13
+            Material.accent: Theme.primary
+            Material.foreground: Theme.surfaceText
+            delegate: ItemDelegate {
+                width: colorCombo.width
+                contentItem: RowLayout {
+                    spacing: Theme.spacingS
+                    Rectangle {
+                        visible: index > 0
+                        width: 14
+                        height: 14
+                        radius: 3
+                        color: "#" + modelData
+                        border.color: Theme.withAlpha(Theme.outline, 0.4)
+                    }
+                    Label {
+                        text: modelData
+                        font.pixelSize: 12
+                        color: Theme.surfaceText
+                    }
+                }
+            }
+            onCurrentTextChanged: {
+                root.activeColor = currentIndex > 0 ? currentText : ""
+                if (root.isWallhaven) {
+                    root.currentPage = 1
+                    window.executeSearch(searchField.text, root.activeSource, 1, root.activeAspect)
+                }
+            }
+        }
+
+        // Settings
+        ToolButton {
+            text: "\u2699"
+            font.pixelSize: 16
+            Layout.alignment: Qt.AlignVCenter
+            Material.foreground: Theme.surfaceText
+            onClicked: settingsDialog.open()
+        }
     }
 
     // Bottom border
@@ -0,0 +1,66 @@
+import QtQuick
+import QtQuick.Controls
+import QtQuick.Controls.Material
+import "logic.js" as Logic
+
+// Toast stack for CLI warnings/errors. Newest at the bottom; each toast
+// expires after `lifetimeMs` or on click.
+Column {
+    id: root
+    spacing: Theme.spacingXS
+    width: 360
+
+    property int capacity: 5
+    property int lifetimeMs: 6000
+    property var toasts: []
+
+    function push(level, text) {
+        toasts = Logic.pushToast(toasts, level, text, Date.now(), lifetimeMs, capacity)
+    }
+
+    function pushStderr(requestId, stderrData, exitCode) {
+        var lines = Logic.stderrToasts(requestId, stderrData, exitCode)
+        for (var i = 0; i < lines.length; i++)
+            push(lines[i].level, lines[i].text)
+    }
+
+    function expire() {
+        toasts = Logic.expireToasts(toasts, Date.now())
+    }
+
+    Timer {
+        interval: 500
+        repeat: true
+        running: root.toasts.length > 0
+        onTriggered: root.expire()
+    }
+
+    Repeater {
+        model: root.toasts
+
+        Rectangle {
+            width: root.width
+            height: toastLabel.implicitHeight + Theme.spacingS * 2
+            radius: 6
+            color: Theme.surfaceContainer
+            border.width: 1
+            border.color: modelData.level === "error" ? Theme.error : Theme.warning
+
+            Label {
+                id: toastLabel
+                anchors.fill: parent
+                anchors.margins: Theme.spacingS
+                text: modelData.text
+                wrapMode: Text.Wrap
+                font.pixelSize: 12
+                color: Theme.surfaceText
+            }
+
+            MouseArea {
+                anchors.fill: parent
+                cursorShape: Qt.PointingHandCursor
+                onClicked: root.toasts = Logic.dismissToast(root.toasts, index)
+            }
+        }
+    }
+}
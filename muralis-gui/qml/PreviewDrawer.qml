@@ -3,6 +3,7 @@ import QtQuick.Controls
 import QtQuick.Controls.Material
 import QtQuick.Layouts
 import QtQuick.Window
+import "logic.js" as Logic
 
 Drawer {
     id: root
@@ -24,14 +25,49 @@ Drawer {
 
     property var currentItem: null
     property bool showMonitorOverlay: true
+    property int selectedMonitor: 0
+    property int maxVisibleTags: 12
+    property bool tagsExpanded: false
+    // Where the monitor crop sits in the image's spare room, 0..1 per axis
+    property real cropPanX: 0.5
+    property real cropPanY: 0.5
+
+    onCurrentItemChanged: {
+        previewFlick.recenter()
+        cropPanX = 0.5
+        cropPanY = 0.5
+    }
+
+    // Store the overlay's crop, in source pixels, on the current favorite.
+    function saveCrop() {
+        var o = monitorOverlay
+        if (!root.currentItem || !root.currentItem.favorite_id || o.paintedW <= 0)
+            return
+        var scale = o.imgW / o.paintedW
+        var rect = [Math.round((o.cropX - o.paintedX) * scale),
+                    Math.round((o.cropY - o.paintedY) * scale),
+                    Math.round(o.cropW * scale),
+                    Math.round(o.cropH * scale)]
+        CLI.run("crop", ["favorites", "crop", root.currentItem.favorite_id, "--rect", rect.join(",")])
+    }
 
     function openPreview(idx) {
         if (idx >= 0 && idx < window.searchResults.length) {
             currentItem = window.searchResults[idx]
+            tagsExpanded = false
             open()
         }
     }
 
+    // Move the preview to the adjacent result without closing it.
+    function step(delta) {
+        var next = Logic.wrapIndex(window.selectedIndex, delta, window.searchResults.length)
+        if (next >= 0) {
+            window.selectedIndex = next
+            openPreview(next)
+        }
+    }
+
     ColumnLayout {
         anchors.fill: parent
         anchors.margins: Theme.spacingL
@@ -47,6 +83,33 @@ Drawer {
                 color: Theme.surfaceText
                 Layout.fillWidth: true
             }
+            Label {
+                visible: window.searchResults.length > 1
+                text: (window.selectedIndex + 1) + " / " + window.searchResults.length
+                font.pixelSize: 12
+                color: Theme.withAlpha(Theme.surfaceText, 0.6)
+            }
+            ToolButton {
+                // [gui] preview_fit: fit to the pane or 1:1 pixels
+                text: window.previewFit === "actual" ? "Fit" : "1:1"
+                font.pixelSize: 12
+                onClicked: window.togglePreviewFit()
+                Material.foreground: Theme.surfaceText
+            }
+            ToolButton {
+                text: "\u2039"
+                font.pixelSize: 18
+                enabled: window.searchResults.length > 1
+                onClicked: root.step(-1)
+                Material.foreground: Theme.surfaceText
+            }
+            ToolButton {
+                text: "\u203a"
+                font.pixelSize: 18
+                enabled: window.searchResults.length > 1
+                onClicked: root.step(1)
+                Material.foreground: Theme.surfaceText
+            }
             ToolButton {
                 text: "\u2715"
                 font.pixelSize: 16
@@ -67,85 +130,108 @@ Drawer {
             color: Theme.surfaceContainerHigh
             clip: true
 
-            Image {
-                id: previewImage
+            // At actual size the frame takes the image's pixel size and scrolls
+            Flickable {
+                id: previewFlick
                 anchors.fill: parent
-                source: root.currentItem ? root.currentItem.full_url : ""
-                fillMode: Image.PreserveAspectFit
-                asynchronous: true
-                cache: true
-
-                BusyIndicator {
-                    anchors.centerIn: parent
-                    running: previewImage.status === Image.Loading
-                    Material.accent: Theme.primary
+                readonly property bool actual: window.previewFit === "actual"
+                readonly property real imgW: root.currentItem && root.currentItem.width > 0
+                                             ? root.currentItem.width : width
+                readonly property real imgH: root.currentItem && root.currentItem.height > 0
+                                             ? root.currentItem.height : height
+                contentWidth: actual ? Math.max(width, imgW) : width
+                contentHeight: actual ? Math.max(height, imgH) : height
+                interactive: actual
+                boundsBehavior: Flickable.StopAtBounds
+                ScrollBar.horizontal: ScrollBar { policy: previewFlick.actual ? ScrollBar.AsNeeded : ScrollBar.AlwaysOff }
+                ScrollBar.vertical: ScrollBar { policy: previewFlick.actual ? ScrollBar.AsNeeded : ScrollBar.AlwaysOff }
+
+                function recenter() {
+                    contentX = Math.max(0, (contentWidth - width) / 2)
+                    contentY = Math.max(0, (contentHeight - height) / 2)
+                }
+                onActualChanged: recenter()
+
+                Item {
+                    width: previewFlick.actual ? previewFlick.imgW : previewFlick.width
+                    height: previewFlick.actual ? previewFlick.imgH : previewFlick.height
+                    x: (previewFlick.contentWidth - width) / 2
+                    y: (previewFlick.contentHeight - height) / 2
+
+                    // Sample shows right away; the original covers it once loaded
+                    Image {
+                        id: previewImage
+                        anchors.fill: parent
+                        source: root.currentItem ? root.currentItem.sample_url : ""
+                        fillMode: Image.PreserveAspectFit
+                        asynchronous: true
+                        cache: true
+                    }
+
+                    Image {
+                        id: originalImage
+                        anchors.fill: parent
+                        source: root.currentItem && root.currentItem.full_url !== root.currentItem.sample_url
+                                ? root.currentItem.full_url : ""
+                        visible: status === Image.Ready
+                        fillMode: Image.PreserveAspectFit
+                        asynchronous: true
+                        cache: true
+                    }
                 }
             }
 
+            BusyIndicator {
+                anchors.centerIn: parent
+                running: previewImage.status === Image.Loading || originalImage.status === Image.Loading
+                Material.accent: Theme.primary
+            }
+
             // Monitor crop overlay
             Item {
                 id: monitorOverlay
                 anchors.fill: parent
-                visible: root.showMonitorOverlay && previewImage.status === Image.Ready && root.currentItem
+                visible: root.showMonitorOverlay && !previewFlick.actual
+                         && previewImage.status === Image.Ready && root.currentItem
 
-                // Compute the painted image rect within the container
                 property real imgW: root.currentItem ? root.currentItem.width : 1
                 property real imgH: root.currentItem ? root.currentItem.height : 1
-                property real imgAspect: imgW / imgH
-                property real containerAspect: previewContainer.width / Math.max(1, previewContainer.height)
-
-                // Painted image dimensions (PreserveAspectFit)
-                property real paintedW: imgAspect > containerAspect ? previewContainer.width : previewContainer.height * imgAspect
-                property real paintedH: imgAspect > containerAspect ? previewContainer.width / imgAspect : previewContainer.height
-                property real paintedX: (previewContainer.width - paintedW) / 2
-                property real paintedY: (previewContainer.height - paintedH) / 2
-
-                // Monitor dimensions
-                property real monW: Screen.width
-                property real monH: Screen.height
-                property real monAspect: monW / monH
-
-                // Crop region in image-space (fill mode crops to fit monitor)
-                // visibleW/visibleH = fraction of image that would be visible
-                property real visibleFracW: Math.min(1.0, monAspect / imgAspect)
-                property real visibleFracH: Math.min(1.0, imgAspect / monAspect)
-
-                // Map to painted coordinates
-                property real cropW: paintedW * visibleFracW
-                property real cropH: paintedH * visibleFracH
-                property real cropX: paintedX + (paintedW - cropW) / 2
-                property real cropY: paintedY + (paintedH - cropH) / 2
-
-                // Dim areas outside the crop (4 rectangles for the letterbox)
-                // Top
-                Rectangle {
-                    x: monitorOverlay.paintedX; y: monitorOverlay.paintedY
-                    width: monitorOverlay.paintedW
-                    height: monitorOverlay.cropY - monitorOverlay.paintedY
-                    color: Theme.withAlpha("#000000", 0.55)
-                }
-                // Bottom
-                Rectangle {
-                    x: monitorOverlay.paintedX
-                    y: monitorOverlay.cropY + monitorOverlay.cropH
-                    width: monitorOverlay.paintedW
-                    height: (monitorOverlay.paintedY + monitorOverlay.paintedH) - (monitorOverlay.cropY + monitorOverlay.cropH)
-                    color: Theme.withAlpha("#000000", 0.55)
-                }
-                // Left
-                Rectangle {
-                    x: monitorOverlay.paintedX; y: monitorOverlay.cropY
-                    width: monitorOverlay.cropX - monitorOverlay.paintedX
-                    height: monitorOverlay.cropH
-                    color: Theme.withAlpha("#000000", 0.55)
-                }
-                // Right
-                Rectangle {
-                    x: monitorOverlay.cropX + monitorOverlay.cropW
-                    y: monitorOverlay.cropY
-                    width: (monitorOverlay.paintedX + monitorOverlay.paintedW) - (monitorOverlay.cropX + monitorOverlay.cropW)
-                    height: monitorOverlay.cropH
-                    color: Theme.withAlpha("#000000", 0.55)
+
+                // Painted image rect within the container (PreserveAspectFit)
+                property var painted: Logic.fitRect(imgW, imgH, previewContainer.width,
+                                                    Math.max(1, previewContainer.height))
+                property real paintedW: painted.width
+                property real paintedH: painted.height
+                property real paintedX: painted.x
+                property real paintedY: painted.y
+
+                // Selected monitor's dimensions, falling back to the current screen
+                property var monitor: root.selectedMonitor < window.monitors.length
+                                      ? window.monitors[root.selectedMonitor] : null
+                property real monW: monitor ? monitor.width : Screen.width
+                property real monH: monitor ? monitor.height : Screen.height
+
+                // What the monitor shows once the image fills it, in painted coordinates
+                property var crop: Logic.cropRect(painted, imgW, imgH, monW, monH,
+                                                  root.cropPanX, root.cropPanY)
+                property real cropW: crop.width
+                property real cropH: crop.height
+                property real cropX: crop.x
+                property real cropY: crop.y
+
+                // [display] overlay_style / overlay_darken
+                property bool outlineOnly: window.overlayStyle === "outline"
+                property color dimColor: Theme.withAlpha("#000000", Math.max(0, Math.min(1, 1.0 - window.overlayDarken)))
+                property var regions: Logic.overlayRegions(window.overlayStyle, painted, crop)
+
+                // Dim areas outside the crop (none in outline mode)
+                Repeater {
+                    model: monitorOverlay.regions.dim
+                    Rectangle {
+                        x: modelData.x; y: modelData.y
+                        width: modelData.width; height: modelData.height
+                        color: monitorOverlay.dimColor
+                    }
                 }
 
                 // Crop border
@@ -153,8 +239,27 @@ Drawer {
                     x: monitorOverlay.cropX; y: monitorOverlay.cropY
                     width: monitorOverlay.cropW; height: monitorOverlay.cropH
                     color: "transparent"
-                    border.width: 1
-                    border.color: Theme.withAlpha("#d8a657", 0.7)
+                    border.width: monitorOverlay.regions.borderWidth
+                    border.color: Theme.withAlpha("#d8a657", monitorOverlay.outlineOnly ? 1.0 : 0.7)
+
+                    // Drag to pick the part of a favorite the daemon shows
+                    MouseArea {
+                        anchors.fill: parent
+                        enabled: root.currentItem && root.currentItem.is_favorited
+                        cursorShape: enabled ? Qt.SizeAllCursor : Qt.ArrowCursor
+                        property point last
+                        onPressed: function(mouse) { last = mapToItem(monitorOverlay, mouse.x, mouse.y) }
+                        onPositionChanged: function(mouse) {
+                            var p = mapToItem(monitorOverlay, mouse.x, mouse.y)
+                            var spareW = monitorOverlay.paintedW - monitorOverlay.cropW
+                            var spareH = monitorOverlay.paintedH - monitorOverlay.cropH
+                            if (spareW > 0)
+                                root.cropPanX = Math.max(0, Math.min(1, root.cropPanX + (p.x - last.x) / spareW))
+                            if (spareH > 0)
+                                root.cropPanY = Math.max(0, Math.min(1, root.cropPanY + (p.y - last.y) / spareH))
+                            last = p
+                        }
+                    }
                 }
 
                 // Monitor label
@@ -169,7 +274,8 @@ Drawer {
                     Label {
                         id: monLabel
                         anchors.centerIn: parent
-                        text: Math.round(monitorOverlay.monW) + "x" + Math.round(monitorOverlay.monH)
+                        text: (monitorOverlay.monitor ? monitorOverlay.monitor.name + "  " : "")
+                              + Math.round(monitorOverlay.monW) + "x" + Math.round(monitorOverlay.monH)
                         font.pixelSize: 10
                         color: "#d8a657"
                     }
@@ -194,6 +300,33 @@ Drawer {
                 color: Theme.withAlpha(Theme.surfaceText, 0.7)
                 Layout.fillWidth: true
             }
+            Button {
+                text: "Use this crop"
+                flat: true
+                visible: monitorOverlay.visible && root.currentItem && root.currentItem.is_favorited
+                Material.foreground: Theme.primary
+                onClicked: root.saveCrop()
+            }
+        }
+
+        // Monitor selector (only with more than one monitor)
+        TabBar {
+            id: monitorTabs
+            Layout.fillWidth: true
+            visible: root.showMonitorOverlay && window.monitors.length > 1
+            currentIndex: root.selectedMonitor
+            onCurrentIndexChanged: root.selectedMonitor = currentIndex
+            Material.accent: Theme.primary
+            Material.background: Theme.surfaceContainer
+
+            Repeater {
+                model: window.monitors
+                TabButton {
+                    text: modelData.name
+                    font.pixelSize: 11
+                    Material.foreground: Theme.surfaceText
+                }
+            }
         }
 
         // Metadata
@@ -231,12 +364,57 @@ Drawer {
                 font.pixelSize: 12
                 Layout.alignment: Qt.AlignTop
             }
-            Label {
-                text: root.currentItem && root.currentItem.tags ? root.currentItem.tags.join(", ") : ""
-                color: Theme.surfaceText
-                font.pixelSize: 12
-                wrapMode: Text.Wrap
+            Flow {
+                id: tagFlow
                 Layout.fillWidth: true
+                spacing: Theme.spacingXS
+
+                property var tagInfo: Logic.formatTags(root.currentItem && root.currentItem.tags ? root.currentItem.tags : [],
+                                                       root.tagsExpanded ? -1 : root.maxVisibleTags)
+
+                Repeater {
+                    model: tagFlow.tagInfo.visible
+
+                    Rectangle {
+                        width: tagLabel.implicitWidth + Theme.spacingS * 2
+                        height: tagLabel.implicitHeight + Theme.spacingXS
+                        radius: 4
+                        color: Theme.surfaceContainerHigh
+
+                        Label {
+                            id: tagLabel
+                            anchors.centerIn: parent
+                            text: modelData
+                            font.pixelSize: 11
+                            color: Theme.surfaceText
+                        }
+                    }
+                }
+
+                // "+M more" / "show less" toggle
+                Rectangle {
+                    visible: tagFlow.tagInfo.overflow > 0 || root.tagsExpanded
+                    width: moreLabel.implicitWidth + Theme.spacingS * 2
+                    height: moreLabel.implicitHeight + Theme.spacingXS
+                    radius: 4
+                    color: "transparent"
+                    border.width: 1
+                    border.color: Theme.withAlpha(Theme.outline, 0.3)
+
+                    Label {
+                        id: moreLabel
+                        anchors.centerIn: parent
+                        text: root.tagsExpanded ? "show less" : "+" + tagFlow.tagInfo.overflow + " more"
+                        font.pixelSize: 11
+                        color: Theme.primary
+                    }
+
+                    MouseArea {
+                        anchors.fill: parent
+                        cursorShape: Qt.PointingHandCursor
+                        onClicked: root.tagsExpanded = !root.tagsExpanded
+                    }
+                }
             }
         }
 
@@ -272,6 +450,18 @@ Drawer {
                     }
                 }
             }
+
+            Button {
+                Layout.fillWidth: true
+                text: "Open Externally"
+                flat: true
+                Material.foreground: Theme.primary
+                onClicked: {
+                    if (root.currentItem) {
+                        CLI.run("open", ["open", root.currentItem.full_url])
+                    }
+                }
+            }
         }
 
         // Spacer
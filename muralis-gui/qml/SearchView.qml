@@ -15,7 +15,8 @@ Item {
         anchors.fill: parent
         anchors.margins: Theme.spacingS
         cellWidth: root.cellSize
-        cellHeight: root.cellSize * 0.65
+        // square cells crop every result (Cover) for a uniform grid
+        cellHeight: window.squareThumbnails ? root.cellSize : root.cellSize * 0.65
         clip: true
 
         model: window.searchResults
@@ -25,7 +26,9 @@ Item {
             height: grid.cellHeight - Theme.spacingS
             thumbnailUrl: modelData.thumbnail_url
             resolution: modelData.width + "x" + modelData.height
+            ratio: modelData.cell_ratio || 0
             isFavorited: modelData.is_favorited || false
+            colors: modelData.colors || []
             isSelected: index === window.selectedIndex
 
             onClicked: {
@@ -44,12 +47,64 @@ Item {
             policy: ScrollBar.AsNeeded
         }
 
+        // Onboarding: nothing to search until a source is enabled
+        Column {
+            anchors.centerIn: parent
+            spacing: Theme.spacingM
+            visible: window.sourcesLoaded && window.sourceList.length === 0
+
+            Label {
+                anchors.horizontalCenter: parent.horizontalCenter
+                text: "No sources enabled"
+                color: Theme.surfaceText
+                font.pixelSize: 18
+                font.bold: true
+            }
+
+            Label {
+                anchors.horizontalCenter: parent.horizontalCenter
+                width: Math.min(420, grid.width - 2 * Theme.spacingL)
+                horizontalAlignment: Text.AlignHCenter
+                wrapMode: Text.Wrap
+                text: "Turn on Wallhaven (no key needed), or add an Unsplash or Pexels key, "
+                    + "in the sources settings. Feeds can be added under [[sources.feeds]] in config.toml."
+                color: Theme.withAlpha(Theme.surfaceText, 0.7)
+                font.pixelSize: 13
+            }
+
+            Button {
+                anchors.horizontalCenter: parent.horizontalCenter
+                text: "Open source settings"
+                highlighted: true
+                Material.accent: Theme.primary
+                onClicked: settingsDialog.open()
+            }
+        }
+
         // Empty state
         Label {
             anchors.centerIn: parent
             visible: !window.loading && window.searchResults.length === 0
-            text: "Search for wallpapers to get started"
-            color: Theme.withAlpha(Theme.surfaceText, 0.5)
+                && !(window.sourcesLoaded && window.sourceList.length === 0)
+            width: Math.min(480, grid.width - 2 * Theme.spacingL)
+            horizontalAlignment: Text.AlignHCenter
+            wrapMode: Text.Wrap
+            text: {
+                switch (window.searchStatus) {
+                case "no_results":
+                    return window.searchedQuery.length > 0
+                        ? "No results for \"" + window.searchedQuery + "\""
+                        : "No results"
+                case "failed":
+                    return window.searchError.length > 0
+                        ? "Search failed: " + window.searchError
+                        : "Search failed"
+                default:
+                    return "Search for wallpapers to get started"
+                }
+            }
+            color: window.searchStatus === "failed"
+                ? Theme.error : Theme.withAlpha(Theme.surfaceText, 0.5)
             font.pixelSize: 16
         }
 
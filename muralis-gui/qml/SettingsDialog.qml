@@ -0,0 +1,241 @@
+import QtQuick
+import QtQuick.Controls
+import QtQuick.Controls.Material
+import QtQuick.Layouts
+
+Dialog {
+    id: root
+    title: "Sources"
+    modal: true
+    anchors.centerIn: parent
+    width: 480
+    standardButtons: Dialog.Close
+
+    Material.background: Theme.surfaceContainer
+    Material.foreground: Theme.surfaceText
+    Material.accent: Theme.primary
+
+    // Built-in sources and whether they take an API key (and a client secret),
+    // or a folder path for local
+    property var knownSources: [
+        { name: "wallhaven", label: "Wallhaven", hasKey: true, keyHint: "API key (optional, needed for NSFW)" },
+        { name: "unsplash", label: "Unsplash", hasKey: true, keyHint: "Access key" },
+        { name: "pexels", label: "Pexels", hasKey: true, keyHint: "API key" },
+        { name: "deviantart", label: "DeviantArt", hasKey: true, keyHint: "Client ID",
+          hasSecret: true, secretHint: "Client secret" },
+        { name: "bing", label: "Bing", hasKey: false, keyHint: "" },
+        { name: "apod", label: "APOD", hasKey: true, keyHint: "API key (optional, uses DEMO_KEY)" },
+        { name: "local", label: "Local", hasKey: false, keyHint: "",
+          hasPath: true, pathHint: "Folder, e.g. ~/Pictures/Wallpapers" }
+    ]
+
+    function isRegistered(sourceType) {
+        for (var i = 0; i < window.allSources.length; i++)
+            if (window.allSources[i].source_type === sourceType) return true
+        return false
+    }
+
+    property var blockedTags: []
+
+    onOpened: {
+        CLI.run("filter", ["filter", "show"])
+        CLI.run("blacklist-tags", ["blacklist", "tags"])
+    }
+
+    // Called by main.qml with the parsed `filter show` output
+    function loadFilter(filter) {
+        brightnessSlider.first.value = filter.min_brightness
+        brightnessSlider.second.value = filter.max_brightness
+    }
+
+    function editBlockedTag(action, tag) {
+        tag = tag.trim()
+        if (tag.length === 0) return
+        CLI.run("blacklist-edit", ["blacklist", action, tag])
+    }
+
+    function applySource(name, enabled, key, secret, path) {
+        var args = ["sources", enabled ? "enable" : "disable", name, "--persist"]
+        if (enabled && key.length > 0) {
+            args.push("--key")
+            args.push(key)
+        }
+        if (enabled && secret.length > 0) {
+            args.push("--secret")
+            args.push(secret)
+        }
+        if (enabled && path.length > 0) {
+            args.push("--path")
+            args.push(path)
+        }
+        CLI.run("source-toggle", args)
+    }
+
+    contentItem: ColumnLayout {
+        spacing: Theme.spacingM
+
+        Label {
+            Layout.fillWidth: true
+            text: "Changes are written to config.toml."
+            font.pixelSize: 12
+            color: Theme.withAlpha(Theme.surfaceText, 0.6)
+            wrapMode: Text.Wrap
+        }
+
+        Repeater {
+            model: root.knownSources
+
+            RowLayout {
+                Layout.fillWidth: true
+                spacing: Theme.spacingS
+
+                Switch {
+                    id: enableSwitch
+                    checked: root.isRegistered(modelData.name)
+                    Material.accent: Theme.primary
+                }
+
+                Label {
+                    text: modelData.label
+                    font.pixelSize: 13
+                    color: Theme.surfaceText
+                    Layout.preferredWidth: 80
+                }
+
+                TextField {
+                    id: keyField
+                    visible: modelData.hasKey
+                    Layout.fillWidth: true
+                    placeholderText: modelData.keyHint
+                    echoMode: TextInput.Password
+                    font.pixelSize: 12
+                    Material.accent: Theme.primary
+                }
+
+                TextField {
+                    id: secretField
+                    visible: modelData.hasSecret === true
+                    Layout.fillWidth: true
+                    placeholderText: modelData.secretHint || ""
+                    echoMode: TextInput.Password
+                    font.pixelSize: 12
+                    Material.accent: Theme.primary
+                }
+
+                TextField {
+                    id: pathField
+                    visible: modelData.hasPath === true
+                    Layout.fillWidth: true
+                    placeholderText: modelData.pathHint || ""
+                    font.pixelSize: 12
+                    Material.accent: Theme.primary
+                }
+
+                Button {
+                    text: "Save"
+                    flat: true
+                    Material.foreground: Theme.primary
+                    onClicked: root.applySource(modelData.name, enableSwitch.checked,
+                                                keyField.text, secretField.text, pathField.text.trim())
+                }
+            }
+        }
+
+        Label {
+            text: "Rotation brightness"
+            font.pixelSize: 14
+            font.bold: true
+            color: Theme.surfaceText
+            Layout.topMargin: Theme.spacingS
+        }
+
+        RowLayout {
+            Layout.fillWidth: true
+            spacing: Theme.spacingS
+
+            Label {
+                text: brightnessSlider.first.value.toFixed(2)
+                font.pixelSize: 12
+                color: Theme.withAlpha(Theme.surfaceText, 0.7)
+            }
+
+            RangeSlider {
+                id: brightnessSlider
+                Layout.fillWidth: true
+                from: 0.0
+                to: 1.0
+                stepSize: 0.05
+                first.value: 0.0
+                second.value: 1.0
+                Material.accent: Theme.primary
+            }
+
+            Label {
+                text: brightnessSlider.second.value.toFixed(2)
+                font.pixelSize: 12
+                color: Theme.withAlpha(Theme.surfaceText, 0.7)
+            }
+
+            Button {
+                text: "Save"
+                flat: true
+                Material.foreground: Theme.primary
+                onClicked: CLI.run("filter-save", ["filter", "brightness",
+                    "--min", brightnessSlider.first.value.toFixed(2),
+                    "--max", brightnessSlider.second.value.toFixed(2)])
+            }
+        }
+
+        Label {
+            text: "Hidden tags"
+            font.pixelSize: 14
+            font.bold: true
+            color: Theme.surfaceText
+            Layout.topMargin: Theme.spacingS
+        }
+
+        Flow {
+            Layout.fillWidth: true
+            spacing: Theme.spacingXS
+            visible: root.blockedTags.length > 0
+
+            Repeater {
+                model: root.blockedTags
+
+                Button {
+                    text: modelData + "  \u2715"
+                    flat: true
+                    font.pixelSize: 12
+                    ToolTip.visible: hovered
+                    ToolTip.text: "Show results tagged " + modelData + " again"
+                    onClicked: root.editBlockedTag("remove-tag", modelData)
+                }
+            }
+        }
+
+        RowLayout {
+            Layout.fillWidth: true
+            spacing: Theme.spacingS
+
+            TextField {
+                id: tagField
+                Layout.fillWidth: true
+                placeholderText: "Tag to hide from results"
+                font.pixelSize: 12
+                Material.accent: Theme.primary
+                onAccepted: addTagButton.clicked()
+            }
+
+            Button {
+                id: addTagButton
+                text: "Hide"
+                flat: true
+                Material.foreground: Theme.primary
+                onClicked: {
+                    root.editBlockedTag("add-tag", tagField.text)
+                    tagField.text = ""
+                }
+            }
+        }
+    }
+}
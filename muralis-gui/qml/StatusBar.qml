@@ -40,6 +40,28 @@ Rectangle {
             }
         }
 
+        // Daemon backend, or its last failure
+        Label {
+            readonly property var status: window.daemonStatus
+            font.pixelSize: 11
+            font.family: Theme.monoFontFamily
+            elide: Text.ElideRight
+            Layout.maximumWidth: 320
+            color: status && status.last_error ? Theme.error
+                                               : Theme.withAlpha(Theme.surfaceText, 0.7)
+            text: !status ? "daemon stopped"
+                  : status.last_error ? status.backend + ": " + status.last_error
+                  : status.backend
+            ToolTip.visible: hoverArea.containsMouse && status && status.last_error
+            ToolTip.text: status && status.last_error ? status.last_error : ""
+
+            MouseArea {
+                id: hoverArea
+                anchors.fill: parent
+                hoverEnabled: true
+            }
+        }
+
         // Spacer
         Item { Layout.fillWidth: true }
 
@@ -9,6 +9,11 @@ Item {
     property string resolution: ""
     property bool isFavorited: false
     property bool isSelected: false
+    // Favorite's dominant colors as [r, g, b] (0-255), most common first
+    property var colors: []
+    // Height/width reserved for the image; 0 fills the cell
+    property real ratio: 0
+    readonly property bool fitRatio: ratio > 0 && !window.squareThumbnails
 
     signal clicked()
     signal doubleClicked()
@@ -25,10 +30,15 @@ Item {
         border.color: "#d8a657"
 
         // Thumbnail image
+        // Sized from the preview's ratio up front so the slot doesn't change
+        // shape when the thumbnail arrives
         Image {
             id: thumb
-            anchors.fill: parent
-            anchors.margins: root.isSelected ? 2 : 0
+            readonly property real availW: parent.width - (root.isSelected ? 4 : 0)
+            readonly property real availH: parent.height - (root.isSelected ? 4 : 0)
+            anchors.centerIn: parent
+            width: root.fitRatio ? Math.min(availW, availH / root.ratio) : availW
+            height: root.fitRatio ? Math.min(availH, availW * root.ratio) : availH
             source: root.thumbnailUrl
             fillMode: Image.PreserveAspectCrop
             asynchronous: true
@@ -70,6 +80,27 @@ Item {
             }
         }
 
+        // Palette swatch
+        Row {
+            anchors.bottom: parent.bottom
+            anchors.left: parent.left
+            anchors.margins: Theme.spacingXS
+            spacing: 2
+            visible: root.colors.length > 0
+
+            Repeater {
+                model: root.colors
+                Rectangle {
+                    width: 12
+                    height: 12
+                    radius: 3
+                    color: Qt.rgba(modelData[0] / 255, modelData[1] / 255, modelData[2] / 255, 1)
+                    border.width: 1
+                    border.color: Theme.withAlpha(Theme.surface, 0.8)
+                }
+            }
+        }
+
         // Favorite badge
         Rectangle {
             anchors.top: parent.top
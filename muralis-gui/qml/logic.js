@@ -0,0 +1,140 @@
+.pragma library
+
+// View logic with no UI dependencies, so tests/ can cover it.
+
+// Split tags into the visible chips and the count hidden behind "+M more".
+// A negative max shows everything.
+function formatTags(tags, max) {
+    if (max < 0 || tags.length <= max)
+        return { visible: tags, overflow: 0 }
+    return { visible: tags.slice(0, max), overflow: tags.length - max }
+}
+
+// Pending-search counts per tab ("All" or a source ID) with `delta` applied
+// to `tab`. Returns a new object so bindings on the old one update.
+function trackSearch(pending, tab, delta) {
+    var next = Object.assign({}, pending)
+    next[tab] = Math.max(0, (next[tab] || 0) + delta)
+    return next
+}
+
+function isLoading(pending, tab) {
+    return (pending[tab] || 0) > 0
+}
+
+// Sequence number and tab of a "search:<seq>:<tab>" request ID. Source IDs
+// may contain ':' themselves, e.g. "feed:NASA APOD".
+function parseSearchId(requestId) {
+    var rest = requestId.substring("search:".length)
+    var sep = rest.indexOf(":")
+    return { seq: parseInt(rest.substring(0, sep)), tab: rest.substring(sep + 1) }
+}
+
+// Whether opening `source` (a `sources list` entry) should browse it right
+// away: only on its first open, with prefetch enabled, and only if an empty
+// query lists something.
+function shouldWarmUp(source, firstOpen, prefetchOnOpen) {
+    return firstOpen && prefetchOnOpen && !!source && source.browsable === true
+}
+
+// Index `delta` steps from `idx`, wrapping at both ends of a list of `count`.
+// -1 for an empty list.
+function wrapIndex(idx, delta, count) {
+    if (count <= 0)
+        return -1
+    return ((idx + delta) % count + count) % count
+}
+
+// The preview fit the toggle (Z / the 1:1 button) switches to: "actual"
+// goes back to "contain", anything else to "actual".
+function toggledFit(fit) {
+    return fit === "actual" ? "contain" : "actual"
+}
+
+// Where an `imgW`x`imgH` image lands when fitted into a `boxW`x`boxH` box
+// (PreserveAspectFit): {x, y, width, height}, centered.
+function fitRect(imgW, imgH, boxW, boxH) {
+    var scale = Math.min(boxW / imgW, boxH / imgH)
+    var width = imgW * scale
+    var height = imgH * scale
+    return { x: (boxW - width) / 2, y: (boxH - height) / 2, width: width, height: height }
+}
+
+// What a `monW`x`monH` monitor shows of the image drawn at `painted` when the
+// image fills it. `panX`/`panY` (0..1) slide the crop through the spare room;
+// 0.5 centers it as the backends do.
+function cropRect(painted, imgW, imgH, monW, monH, panX, panY) {
+    var imgAspect = imgW / imgH
+    var monAspect = monW / monH
+    var width = painted.width * Math.min(1, monAspect / imgAspect)
+    var height = painted.height * Math.min(1, imgAspect / monAspect)
+    return {
+        x: painted.x + (painted.width - width) * panX,
+        y: painted.y + (painted.height - height) * panY,
+        width: width,
+        height: height
+    }
+}
+
+// How the monitor overlay marks `crop` on the image drawn at `painted`.
+// "darken" dims the four strips around the crop (top, bottom, left, right);
+// "outline" leaves every pixel of the image as is and only draws a heavier
+// border along the crop.
+function overlayRegions(style, painted, crop) {
+    if (style === "outline")
+        return { dim: [], borderWidth: 2 }
+    var cropBottom = crop.y + crop.height
+    var cropRight = crop.x + crop.width
+    return {
+        dim: [
+            { x: painted.x, y: painted.y, width: painted.width, height: crop.y - painted.y },
+            { x: painted.x, y: cropBottom, width: painted.width,
+              height: painted.y + painted.height - cropBottom },
+            { x: painted.x, y: crop.y, width: crop.x - painted.x, height: crop.height },
+            { x: cropRight, y: crop.y, width: painted.x + painted.width - cropRight,
+              height: crop.height }
+        ],
+        borderWidth: 1
+    }
+}
+
+// Toast queue for Notifications.qml: an array of {level, text, expiresAt},
+// oldest first. Each function returns a new array so bindings update.
+
+// Append a toast living `lifetimeMs` from `now`, dropping the oldest ones
+// beyond `capacity`.
+function pushToast(queue, level, text, now, lifetimeMs, capacity) {
+    var next = queue.concat([{ level: level, text: text, expiresAt: now + lifetimeMs }])
+    return next.slice(Math.max(0, next.length - capacity))
+}
+
+// The toasts still alive at `now`.
+function expireToasts(queue, now) {
+    return queue.filter(function(toast) { return toast.expiresAt > now })
+}
+
+// The queue without the toast at `index` (clicked away).
+function dismissToast(queue, index) {
+    return queue.filter(function(toast, i) { return i !== index })
+}
+
+// Split CLI stderr into {level, text} toasts: "warning: ..." and "error: ..."
+// lines keep their level, anything else is an error if the command failed and
+// a warning otherwise. A failure with nothing on stderr still gets a toast.
+function stderrToasts(requestId, stderrData, exitCode) {
+    var toasts = []
+    var lines = stderrData.split("\n")
+    for (var i = 0; i < lines.length; i++) {
+        var line = lines[i].trim()
+        if (line.length === 0)
+            continue
+        var m = line.match(/^(warning|error):\s*(.*)$/i)
+        if (m)
+            toasts.push({ level: m[1].toLowerCase(), text: m[2] })
+        else
+            toasts.push({ level: exitCode !== 0 ? "error" : "warning", text: line })
+    }
+    if (toasts.length === 0 && exitCode !== 0)
+        toasts.push({ level: "error", text: requestId + " failed (exit " + exitCode + ")" })
+    return toasts
+}
@@ -3,12 +3,59 @@ import QtQuick.Controls
 import QtQuick.Controls.Material
 import QtQuick.Layouts
 import MuralisGui
+import "logic.js" as Logic
 
 ApplicationWindow {
     id: window
     width: 1400
     height: 900
     visible: true
+
+    // GUI state (gui-state.toml): restored from settings, saved on change
+    property bool guiStateLoaded: false
+    property string restoreTab: "All"
+
+    onWidthChanged: if (guiStateLoaded) saveSizeTimer.restart()
+    onHeightChanged: if (guiStateLoaded) saveSizeTimer.restart()
+
+    Timer {
+        id: saveSizeTimer
+        interval: 500
+        onTriggered: CLI.run("gui-state", ["gui-state",
+                                           "--width", Math.round(window.width).toString(),
+                                           "--height", Math.round(window.height).toString()])
+    }
+
+    // Daemon status for the status bar; null while the daemon is down
+    property var daemonStatus: null
+
+    Timer {
+        interval: 10000
+        running: true
+        repeat: true
+        triggeredOnStart: true
+        onTriggered: CLI.run("daemon-status", ["status"])
+    }
+
+    Connections {
+        target: filterBar
+        function onActiveSourceChanged() {
+            if (window.guiStateLoaded)
+                CLI.run("gui-state", ["gui-state", "--tab", filterBar.activeSource])
+        }
+    }
+
+    // Select the saved tab once both settings and sources are in
+    function restoreLastTab() {
+        if (!guiStateLoaded || !sourcesLoaded || restoreTab === "All") return
+        for (var i = 0; i < sourceList.length; i++) {
+            if (sourceList[i].id === restoreTab) {
+                filterBar.selectSource(restoreTab)
+                break
+            }
+        }
+        restoreTab = "All"
+    }
     title: "Muralis"
 
     Material.theme: Theme.isDark ? Material.Dark : Material.Light
@@ -23,12 +70,32 @@ ApplicationWindow {
     property string keyboardMode: "SEARCH"
     property var searchResults: []
     property var sourceList: []
+    property var allSources: []
+    property bool sourcesLoaded: false
+    property var monitors: []
+    property bool squareThumbnails: false
+    property real overlayDarken: 0.3
+    property string overlayStyle: "darken"
+    property bool prefetchOnOpen: true
+    // preview_fit from the GUI state: "contain" or "actual"
+    property string previewFit: "contain"
     property int selectedIndex: -1
-    property bool loading: false
+    // In-flight searches per source tab ("All" or a source ID)
+    property var pendingSearches: ({})
+    property int searchSeq: 0
+    // Outcome of the last search: "" before any, else "results",
+    // "no_results" or "failed"
+    property string searchStatus: ""
+    property string searchError: ""
+    property string searchedQuery: ""
+    readonly property bool loading: Logic.isLoading(pendingSearches, filterBar.activeSource)
 
     // Load sources on startup
     Component.onCompleted: {
         CLI.run("sources", ["sources", "list"])
+        CLI.run("monitors", ["monitors"])
+        CLI.run("settings", ["settings"])
+        filterBar.loadHistory()
         filterBar.focusSearch()
     }
 
@@ -42,29 +109,85 @@ ApplicationWindow {
 
     Connections {
         target: CLI
+        function onDiagnostics(requestId, stderrData, exitCode) {
+            // polled; a stopped daemon shows in the status bar instead
+            if (requestId === "daemon-status") return
+            var label = requestId.startsWith("search:") ? "search" : requestId
+            notifications.pushStderr(label, stderrData, exitCode)
+        }
         function onFinished(requestId, stdout, exitCode) {
+            if (requestId.startsWith("search:")) {
+                finishSearch(requestId, stdout, exitCode)
+                return
+            }
+            if (requestId === "daemon-status") {
+                try {
+                    daemonStatus = exitCode === 0 ? JSON.parse(stdout) : null
+                } catch (e) {
+                    daemonStatus = null
+                }
+                return
+            }
             if (exitCode !== 0) {
                 console.error("CLI failed:", requestId, stdout)
-                loading = false
                 return
             }
 
             if (requestId === "sources") {
                 try {
-                    sourceList = JSON.parse(stdout)
+                    allSources = JSON.parse(stdout)
+                    sourceList = allSources.filter(function(s) { return s.enabled !== false })
+                    sourcesLoaded = true
+                    restoreLastTab()
                 } catch (e) {
                     console.error("Failed to parse sources:", e)
                 }
-            } else if (requestId === "search") {
+            } else if (requestId === "monitors") {
+                try {
+                    monitors = JSON.parse(stdout)
+                } catch (e) {
+                    console.error("Failed to parse monitors:", e)
+                }
+            } else if (requestId === "settings") {
                 try {
-                    var data = JSON.parse(stdout)
-                    searchResults = data.results || []
-                    searchView.hasMore = data.has_more || false
+                    var settings = JSON.parse(stdout)
+                    squareThumbnails = settings.square_thumbnails || false
+                    if (settings.overlay_darken !== undefined) overlayDarken = settings.overlay_darken
+                    overlayStyle = settings.overlay_style || "darken"
+                    if (settings.prefetch_on_open !== undefined) prefetchOnOpen = settings.prefetch_on_open
+                    if (settings.preview_fit) previewFit = settings.preview_fit
+                    if (!guiStateLoaded) {
+                        if (settings.window_width) width = settings.window_width
+                        if (settings.window_height) height = settings.window_height
+                        restoreTab = settings.last_tab || "All"
+                        guiStateLoaded = true
+                        restoreLastTab()
+                    }
                 } catch (e) {
-                    console.error("Failed to parse search:", e)
-                    searchResults = []
+                    console.error("Failed to parse settings:", e)
+                }
+            } else if (requestId === "filter") {
+                try {
+                    settingsDialog.loadFilter(JSON.parse(stdout))
+                } catch (e) {
+                    console.error("Failed to parse filter:", e)
+                }
+            } else if (requestId === "blacklist-tags") {
+                try {
+                    settingsDialog.blockedTags = JSON.parse(stdout)
+                } catch (e) {
+                    console.error("Failed to parse tag blacklist:", e)
+                }
+            } else if (requestId === "blacklist-edit") {
+                CLI.run("blacklist-tags", ["blacklist", "tags"])
+            } else if (requestId === "source-toggle") {
+                CLI.run("sources", ["sources", "list"])
+            } else if (requestId === "history") {
+                try {
+                    filterBar.recentQueries = JSON.parse(stdout)
+                } catch (e) {
+                    console.error("Failed to parse history:", e)
                 }
-                loading = false
             } else if (requestId.startsWith("fav-")) {
                 var idx = parseInt(requestId.substring(4))
                 if (idx >= 0 && idx < searchResults.length) {
@@ -132,7 +255,7 @@ ApplicationWindow {
         } else if (event.key >= Qt.Key_1 && event.key <= Qt.Key_4) {
             var idx = event.key - Qt.Key_1
             if (idx < sourceList.length) {
-                filterBar.selectSource(sourceList[idx].name)
+                filterBar.selectSource(sourceList[idx].id)
             }
             event.accepted = true
         }
@@ -144,20 +267,17 @@ ApplicationWindow {
             keyboardMode = "GRID"
             event.accepted = true
         } else if (event.key === Qt.Key_H || event.key === Qt.Key_Left) {
-            if (selectedIndex > 0) {
-                selectedIndex--
-                previewDrawer.openPreview(selectedIndex)
-            }
+            previewDrawer.step(-1)
             event.accepted = true
         } else if (event.key === Qt.Key_L || event.key === Qt.Key_Right) {
-            if (selectedIndex < searchResults.length - 1) {
-                selectedIndex++
-                previewDrawer.openPreview(selectedIndex)
-            }
+            previewDrawer.step(1)
             event.accepted = true
         } else if (event.key === Qt.Key_F) {
             if (selectedIndex >= 0) favoriteItem(selectedIndex)
             event.accepted = true
+        } else if (event.key === Qt.Key_Z && previewDrawer.opened) {
+            togglePreviewFit()
+            event.accepted = true
         } else if (event.key === Qt.Key_O) {
             if (selectedIndex >= 0 && searchResults[selectedIndex]) {
                 Qt.openUrlExternally(searchResults[selectedIndex].source_url)
@@ -166,9 +286,44 @@ ApplicationWindow {
         }
     }
 
+    function togglePreviewFit() {
+        previewFit = Logic.toggledFit(previewFit)
+        CLI.run("gui-state", ["gui-state", "--preview-fit", previewFit])
+    }
+
+    function finishSearch(requestId, stdout, exitCode) {
+        var id = Logic.parseSearchId(requestId)
+        pendingSearches = Logic.trackSearch(pendingSearches, id.tab, -1)
+        // only the latest search fills the grid; older ones may be for another tab
+        if (id.seq !== searchSeq) return
+        if (exitCode !== 0) {
+            console.error("CLI failed:", requestId, stdout)
+            searchResults = []
+            searchStatus = "failed"
+            searchError = ""
+            return
+        }
+        try {
+            var data = JSON.parse(stdout)
+            searchResults = data.results || []
+            searchView.hasMore = data.has_more || false
+            searchStatus = data.status || "results"
+            searchError = data.error || ""
+        } catch (e) {
+            console.error("Failed to parse search:", e)
+            searchResults = []
+            searchStatus = "failed"
+            searchError = ""
+        }
+        filterBar.loadHistory()
+    }
+
     function executeSearch(query, source, page, aspect) {
-        loading = true
+        var tab = source || "All"
+        searchSeq++
+        pendingSearches = Logic.trackSearch(pendingSearches, tab, 1)
         selectedIndex = -1
+        searchedQuery = query || ""
         var args = ["search"]
         if (query && query.length > 0) args.push(query)
         if (source && source !== "All") {
@@ -183,7 +338,11 @@ ApplicationWindow {
             args.push("--aspect")
             args.push(aspect)
         }
-        CLI.run("search", args)
+        if (filterBar.isWallhaven && filterBar.activeColor.length > 0) {
+            args.push("--color")
+            args.push(filterBar.activeColor)
+        }
+        CLI.run("search:" + searchSeq + ":" + tab, args)
     }
 
     function favoriteItem(idx) {
@@ -218,4 +377,17 @@ ApplicationWindow {
     PreviewDrawer {
         id: previewDrawer
     }
+
+    SettingsDialog {
+        id: settingsDialog
+    }
+
+    Notifications {
+        id: notifications
+        anchors.right: parent.right
+        anchors.bottom: parent.bottom
+        anchors.margins: Theme.spacingL
+        anchors.bottomMargin: statusBar.height + Theme.spacingL
+        z: 10
+    }
 }
@@ -1,6 +1,7 @@
 #include <QDebug>
 #include <QGuiApplication>
 #include <cstdio>
+#include <fcntl.h>
 
 void messageHandler(QtMsgType type, const QMessageLogContext &ctx, const QString &msg) {
     Q_UNUSED(ctx);
@@ -35,6 +36,15 @@ int main(int argc, char *argv[]) {
     qInstallMessageHandler(messageHandler);
     qputenv("QML_XHR_ALLOW_FILE_READ", "1");
 
+    // Hold the single-instance lock `muralis gui` handed us, but keep it
+    // from the CLI processes we spawn
+    bool hasLockFd = false;
+    int lockFd = qEnvironmentVariableIntValue("MURALIS_LOCK_FD", &hasLockFd);
+    if (hasLockFd) {
+        fcntl(lockFd, F_SETFD, FD_CLOEXEC);
+        qunsetenv("MURALIS_LOCK_FD");
+    }
+
     // Determine dark mode before creating the app
     QString stateDir = QStandardPaths::writableLocation(QStandardPaths::GenericDataLocation)
                            .replace("/share", "/state");
@@ -7,6 +7,9 @@ void ProcessRunner::run(const QString &requestId, const QStringList &args) {
     connect(proc, QOverload<int, QProcess::ExitStatus>::of(&QProcess::finished),
             this, [this, proc, requestId](int exitCode, QProcess::ExitStatus) {
                 QString output = QString::fromUtf8(proc->readAllStandardOutput());
+                QString errors = QString::fromUtf8(proc->readAllStandardError());
+                if (!errors.trimmed().isEmpty() || exitCode != 0)
+                    emit diagnostics(requestId, errors, exitCode);
                 emit finished(requestId, output, exitCode);
                 proc->deleteLater();
             });
@@ -14,6 +14,8 @@ public:
 
 signals:
     void finished(const QString &requestId, const QString &stdoutData, int exitCode);
+    // Emitted before finished() when the CLI wrote warnings/errors to stderr
+    void diagnostics(const QString &requestId, const QString &stderrData, int exitCode);
 };
 
 #endif // PROCESSRUNNER_H
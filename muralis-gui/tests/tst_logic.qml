@@ -0,0 +1,219 @@
+import QtQuick
+import QtTest
+import "../qml/logic.js" as Logic
+
+TestCase {
+    name: "Logic"
+
+    function test_formatTags_under_limit() {
+        var info = Logic.formatTags(["sky", "sea"], 3)
+        compare(info.visible, ["sky", "sea"])
+        compare(info.overflow, 0)
+
+        info = Logic.formatTags(["sky", "sea", "sand"], 3)
+        compare(info.visible.length, 3)
+        compare(info.overflow, 0)
+    }
+
+    function test_formatTags_over_limit() {
+        var info = Logic.formatTags(["a", "b", "c", "d", "e"], 2)
+        compare(info.visible, ["a", "b"])
+        compare(info.overflow, 3)
+    }
+
+    function test_formatTags_expanded() {
+        var info = Logic.formatTags(["a", "b", "c", "d", "e"], -1)
+        compare(info.visible.length, 5)
+        compare(info.overflow, 0)
+    }
+
+    function test_loading_is_per_tab() {
+        var pending = {}
+        pending = Logic.trackSearch(pending, "wallhaven", 1)
+        verify(Logic.isLoading(pending, "wallhaven"))
+        verify(!Logic.isLoading(pending, "All"))
+
+        // overlapping searches on one tab: loading until both finish
+        pending = Logic.trackSearch(pending, "wallhaven", 1)
+        pending = Logic.trackSearch(pending, "All", 1)
+        pending = Logic.trackSearch(pending, "wallhaven", -1)
+        verify(Logic.isLoading(pending, "wallhaven"))
+        pending = Logic.trackSearch(pending, "wallhaven", -1)
+        verify(!Logic.isLoading(pending, "wallhaven"))
+        verify(Logic.isLoading(pending, "All"))
+
+        // a stray finish never goes negative
+        pending = Logic.trackSearch(pending, "wallhaven", -1)
+        pending = Logic.trackSearch(pending, "wallhaven", 1)
+        verify(Logic.isLoading(pending, "wallhaven"))
+    }
+
+    function test_trackSearch_returns_a_copy() {
+        var pending = { "All": 1 }
+        var next = Logic.trackSearch(pending, "All", -1)
+        compare(pending["All"], 1)
+        compare(next["All"], 0)
+    }
+
+    function test_parseSearchId() {
+        var id = Logic.parseSearchId("search:12:feed:NASA APOD")
+        compare(id.seq, 12)
+        compare(id.tab, "feed:NASA APOD")
+        compare(Logic.parseSearchId("search:3:All").tab, "All")
+    }
+
+    function test_warm_up_on_first_open() {
+        var wallhaven = { id: "wallhaven", source_type: "wallhaven", browsable: true }
+        var pexels = { id: "pexels", source_type: "pexels", browsable: false }
+
+        verify(Logic.shouldWarmUp(wallhaven, true, true))
+        // search-only sources wait for a query
+        verify(!Logic.shouldWarmUp(pexels, true, true))
+        // only the first open, and only with prefetch_on_open
+        verify(!Logic.shouldWarmUp(wallhaven, false, true))
+        verify(!Logic.shouldWarmUp(wallhaven, true, false))
+        // unknown tab, or a CLI too old to report browsable
+        verify(!Logic.shouldWarmUp(null, true, true))
+        verify(!Logic.shouldWarmUp({ id: "old" }, true, true))
+    }
+
+    function test_wrapIndex() {
+        // stepping inside the list
+        compare(Logic.wrapIndex(2, 1, 5), 3)
+        compare(Logic.wrapIndex(2, -1, 5), 1)
+        // past the last result back to the first, and the other way round
+        compare(Logic.wrapIndex(4, 1, 5), 0)
+        compare(Logic.wrapIndex(0, -1, 5), 4)
+        // a single result stays put
+        compare(Logic.wrapIndex(0, 1, 1), 0)
+        compare(Logic.wrapIndex(0, -1, 1), 0)
+        // nothing to step to
+        compare(Logic.wrapIndex(0, 1, 0), -1)
+        compare(Logic.wrapIndex(-1, -1, 0), -1)
+    }
+
+    function test_toggle_preview_fit() {
+        compare(Logic.toggledFit("contain"), "actual")
+        compare(Logic.toggledFit("actual"), "contain")
+        // toggling twice lands back where it started
+        compare(Logic.toggledFit(Logic.toggledFit("contain")), "contain")
+        // an unset or unknown value is treated as "contain"
+        compare(Logic.toggledFit(""), "actual")
+        compare(Logic.toggledFit(undefined), "actual")
+    }
+
+    function test_toast_capacity_evicts_oldest() {
+        var queue = []
+        for (var i = 0; i < 7; i++)
+            queue = Logic.pushToast(queue, "warning", "w" + i, 1000 + i, 6000, 5)
+        compare(queue.length, 5)
+        // w0 and w1 went first; the newest stays at the end
+        compare(queue[0].text, "w2")
+        compare(queue[4].text, "w6")
+        compare(queue[4].expiresAt, 7006)
+    }
+
+    function test_toast_expiry() {
+        var queue = Logic.pushToast([], "error", "old", 0, 6000, 5)
+        queue = Logic.pushToast(queue, "error", "new", 3000, 6000, 5)
+        compare(Logic.expireToasts(queue, 5999).length, 2)
+        // a toast is gone once its expiry time arrives
+        var left = Logic.expireToasts(queue, 6000)
+        compare(left.length, 1)
+        compare(left[0].text, "new")
+        compare(Logic.expireToasts(queue, 9000).length, 0)
+
+        // clicking a toast drops just that one
+        var dismissed = Logic.dismissToast(queue, 0)
+        compare(dismissed.length, 1)
+        compare(dismissed[0].text, "new")
+        compare(queue.length, 2)
+    }
+
+    function test_stderr_toasts() {
+        var toasts = Logic.stderrToasts("search:1:All",
+            "warning: wallhaven rate limited\n\n  Error: unsplash: 401  \nplain line\n", 1)
+        compare(toasts.length, 3)
+        compare(toasts[0].level, "warning")
+        compare(toasts[0].text, "wallhaven rate limited")
+        compare(toasts[1].level, "error")
+        compare(toasts[1].text, "unsplash: 401")
+        // unprefixed lines follow the exit code
+        compare(toasts[2].level, "error")
+        compare(toasts[2].text, "plain line")
+        compare(Logic.stderrToasts("set", "plain line", 0)[0].level, "warning")
+
+        // a silent failure still surfaces, a silent success doesn't
+        var silent = Logic.stderrToasts("set", "", 2)
+        compare(silent.length, 1)
+        compare(silent[0].text, "set failed (exit 2)")
+        compare(Logic.stderrToasts("set", "\n", 0).length, 0)
+    }
+
+    function compareRect(actual, expected) {
+        fuzzyCompare(actual.x, expected.x, 0.01)
+        fuzzyCompare(actual.y, expected.y, 0.01)
+        fuzzyCompare(actual.width, expected.width, 0.01)
+        fuzzyCompare(actual.height, expected.height, 0.01)
+    }
+
+    function test_fitRect() {
+        // wide image in a squarer box: full width, centered vertically
+        compareRect(Logic.fitRect(3440, 1440, 860, 600), { x: 0, y: 120, width: 860, height: 360 })
+        // portrait image: full height, centered horizontally
+        compareRect(Logic.fitRect(1080, 1920, 860, 600), { x: 261.25, y: 0, width: 337.5, height: 600 })
+    }
+
+    function test_cropRect_per_monitor() {
+        var painted = { x: 0, y: 120, width: 860, height: 360 }
+        // 21:9 image on a 21:9 monitor: nothing cropped
+        compareRect(Logic.cropRect(painted, 3440, 1440, 3440, 1440, 0.5, 0.5), painted)
+        // same image on 16:9: sides cropped, 2560 of 3440 source pixels kept
+        compareRect(Logic.cropRect(painted, 3440, 1440, 1920, 1080, 0.5, 0.5),
+                    { x: 110, y: 120, width: 640, height: 360 })
+        // portrait monitor: a narrow 810-pixel-wide slice
+        compareRect(Logic.cropRect(painted, 3440, 1440, 1080, 1920, 0.5, 0.5),
+                    { x: 328.75, y: 120, width: 202.5, height: 360 })
+    }
+
+    function test_cropRect_tall_image_and_pan() {
+        // 4:3 image on 16:9: top and bottom cropped
+        var painted = { x: 30, y: 0, width: 800, height: 600 }
+        compareRect(Logic.cropRect(painted, 1600, 1200, 1920, 1080, 0.5, 0.5),
+                    { x: 30, y: 75, width: 800, height: 450 })
+        // panned to either edge of the spare room
+        compareRect(Logic.cropRect(painted, 1600, 1200, 1920, 1080, 0.5, 0),
+                    { x: 30, y: 0, width: 800, height: 450 })
+        compareRect(Logic.cropRect(painted, 1600, 1200, 1920, 1080, 0.5, 1),
+                    { x: 30, y: 150, width: 800, height: 450 })
+        // portrait image on a portrait monitor of another ratio
+        compareRect(Logic.cropRect({ x: 0, y: 0, width: 300, height: 600 }, 1000, 2000, 1080, 1920, 0.5, 0.5),
+                    { x: 0, y: 33.33, width: 300, height: 533.33 })
+    }
+
+    function test_overlay_outline_leaves_image_untouched() {
+        var painted = { x: 0, y: 120, width: 860, height: 360 }
+        var crop = { x: 110, y: 120, width: 640, height: 360 }
+        var regions = Logic.overlayRegions("outline", painted, crop)
+        // nothing dims the image, inside or outside the crop
+        compare(regions.dim.length, 0)
+        compare(regions.borderWidth, 2)
+    }
+
+    function test_overlay_darken_surrounds_crop() {
+        var painted = { x: 30, y: 0, width: 800, height: 600 }
+        var crop = { x: 130, y: 75, width: 600, height: 450 }
+        var regions = Logic.overlayRegions("darken", painted, crop)
+        compare(regions.borderWidth, 1)
+        compareRect(regions.dim[0], { x: 30, y: 0, width: 800, height: 75 })
+        compareRect(regions.dim[1], { x: 30, y: 525, width: 800, height: 75 })
+        compareRect(regions.dim[2], { x: 30, y: 75, width: 100, height: 450 })
+        compareRect(regions.dim[3], { x: 730, y: 75, width: 100, height: 450 })
+
+        // the strips cover exactly the image outside the crop
+        var dimmed = 0
+        for (var i = 0; i < regions.dim.length; i++)
+            dimmed += regions.dim[i].width * regions.dim[i].height
+        compare(dimmed, 800 * 600 - 600 * 450)
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeDelta, Utc, Weekday};
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+
+use muralis_core::error::Result;
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    build_headers, download_preview, AspectRatioFilter, HttpClient, WallpaperSource,
+};
+
+const API_URL: &str = "https://api.nasa.gov/planetary/apod";
+
+/// NASA's shared, heavily rate-limited key for trying the API out.
+const DEMO_KEY: &str = "DEMO_KEY";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ApodConfig {
+    pub enabled: bool,
+    /// api.nasa.gov key; `DEMO_KEY` works for a handful of requests an hour.
+    pub api_key: String,
+    /// Days of pictures per page, newest first.
+    pub count: u32,
+    /// Extra request headers.
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for ApodConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_key: DEMO_KEY.into(),
+            count: 10,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
+    let Some(val) = table.get("apod") else {
+        return Vec::new();
+    };
+    let config: ApodConfig = val.clone().try_into().unwrap_or_default();
+    if !config.enabled {
+        return Vec::new();
+    }
+    vec![Box::new(ApodClient {
+        api_key: config.api_key,
+        count: config.count.max(1),
+        headers: build_headers(&[], &config.headers),
+        client,
+    })]
+}
+
+pub struct ApodClient {
+    api_key: String,
+    count: u32,
+    headers: HeaderMap,
+    client: HttpClient,
+}
+
+/// Dates covered by `page`, `days` per page counting back from `today`.
+/// The first page has no end date so the API stops at the latest picture,
+/// which may still be yesterday's.
+fn date_window(today: NaiveDate, page: u32, days: u32) -> (NaiveDate, Option<NaiveDate>) {
+    let skipped = u64::from(page.saturating_sub(1)) * u64::from(days);
+    let end = today - Days::new(skipped);
+    let start = end - Days::new(u64::from(days.saturating_sub(1)));
+    (start, (skipped > 0).then_some(end))
+}
+
+/// Today's date in New York, where the API's day turns over; asking for
+/// a UTC date that hasn't started there yet is a 400.
+fn nasa_today(now: DateTime<Utc>) -> NaiveDate {
+    let year = now.year();
+    // US daylight time: 2:00 local on the second Sunday in March until
+    // 2:00 local on the first Sunday in November
+    let sunday = |month, n| NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n);
+    let dst = match (sunday(3, 2), sunday(11, 1)) {
+        (Some(start), Some(end)) => {
+            let start = start.and_hms_opt(7, 0, 0).unwrap_or_default().and_utc();
+            let end = end.and_hms_opt(6, 0, 0).unwrap_or_default().and_utc();
+            now >= start && now < end
+        }
+        _ => false,
+    };
+    let offset = if dst { 4 } else { 5 };
+    (now - TimeDelta::hours(offset)).date_naive()
+}
+
+impl ApodClient {
+    fn apod_get(&self, start: NaiveDate, end: Option<NaiveDate>) -> reqwest::RequestBuilder {
+        let mut params = vec![
+            ("api_key", self.api_key.clone()),
+            ("start_date", start.to_string()),
+        ];
+        if let Some(end) = end {
+            params.push(("end_date", end.to_string()));
+        }
+        self.client
+            .get(API_URL)
+            .headers(self.headers.clone())
+            .query(&params)
+    }
+}
+
+#[async_trait]
+impl WallpaperSource for ApodClient {
+    fn name(&self) -> &str {
+        "NASA APOD"
+    }
+
+    fn source_type(&self) -> &str {
+        "apod"
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        page: u32,
+        _per_page: u32,
+        _aspect: AspectRatioFilter,
+    ) -> Result<Vec<WallpaperPreview>> {
+        let (start, end) = date_window(nasa_today(Utc::now()), page, self.count);
+        let request = self.apod_get(start, end);
+        let entries: Vec<ApodEntry> = self
+            .client
+            .send(request)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        // no search API; match the query against the title and date
+        let query = query.trim().to_lowercase();
+        Ok(to_previews(entries)
+            .into_iter()
+            .filter(|p| {
+                query.is_empty() || p.tags.iter().any(|t| t.to_lowercase().contains(&query))
+            })
+            .collect())
+    }
+
+    async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+        download_preview(&self.client, preview).await
+    }
+}
+
+/// Image entries as previews, newest first; videos and other media are
+/// dropped.
+fn to_previews(mut entries: Vec<ApodEntry>) -> Vec<WallpaperPreview> {
+    entries.sort_by(|a, b| b.date.cmp(&a.date));
+    entries
+        .into_iter()
+        .filter(|e| e.media_type == "image")
+        .map(ApodEntry::into_preview)
+        .collect()
+}
+
+// -- API response types --
+
+#[derive(Debug, Deserialize)]
+struct ApodEntry {
+    /// `YYYY-MM-DD`, one picture per day.
+    date: String,
+    #[serde(default)]
+    title: String,
+    media_type: String,
+    url: String,
+    /// Full resolution image, missing on some days.
+    #[serde(default)]
+    hdurl: Option<String>,
+}
+
+impl ApodEntry {
+    /// The day's page on apod.nasa.gov, e.g. `ap250301.html`.
+    fn page_url(&self) -> String {
+        let compact: String = self.date.chars().filter(char::is_ascii_digit).collect();
+        let short = compact.get(2..).unwrap_or(&compact);
+        format!("https://apod.nasa.gov/apod/ap{short}.html")
+    }
+
+    fn into_preview(self) -> WallpaperPreview {
+        let source_url = self.page_url();
+        let mut tags = vec![self.date.clone()];
+        if !self.title.is_empty() {
+            tags.insert(0, self.title);
+        }
+        WallpaperPreview {
+            source_type: SourceType::new("apod"),
+            source_id: self.date,
+            source_url,
+            thumbnail_url: self.url.clone(),
+            full_url: self.hdurl.unwrap_or_else(|| self.url.clone()),
+            sample_url: Some(self.url),
+            // APOD doesn't report image sizes
+            width: 0,
+            height: 0,
+            tags,
+            meta: WallpaperMeta::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_RESPONSE: &str = r#"[
+        {
+            "date": "2025-03-01",
+            "title": "The Horsehead Nebula",
+            "explanation": "A dark cloud of dust in Orion.",
+            "media_type": "image",
+            "url": "https://apod.nasa.gov/apod/image/2503/Horsehead_1024.jpg",
+            "hdurl": "https://apod.nasa.gov/apod/image/2503/Horsehead_4096.jpg",
+            "service_version": "v1"
+        },
+        {
+            "date": "2025-03-02",
+            "title": "Total Lunar Eclipse Timelapse",
+            "explanation": "The Moon turns red.",
+            "media_type": "video",
+            "url": "https://www.youtube.com/embed/abc123",
+            "service_version": "v1"
+        },
+        {
+            "date": "2025-03-03",
+            "title": "Aurora over Iceland",
+            "explanation": "Green curtains.",
+            "media_type": "image",
+            "url": "https://apod.nasa.gov/apod/image/2503/Aurora_1024.jpg",
+            "service_version": "v1"
+        }
+    ]"#;
+
+    #[test]
+    fn test_parse_drops_videos() {
+        let entries: Vec<ApodEntry> = serde_json::from_str(MOCK_RESPONSE).unwrap();
+        let previews = to_previews(entries);
+
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].source_id, "2025-03-03");
+        assert_eq!(previews[0].tags, vec!["Aurora over Iceland", "2025-03-03"]);
+        // no hdurl: the regular image is the full one
+        assert_eq!(
+            previews[0].full_url,
+            "https://apod.nasa.gov/apod/image/2503/Aurora_1024.jpg"
+        );
+
+        let horsehead = &previews[1];
+        assert_eq!(
+            horsehead.full_url,
+            "https://apod.nasa.gov/apod/image/2503/Horsehead_4096.jpg"
+        );
+        assert_eq!(
+            horsehead.thumbnail_url,
+            "https://apod.nasa.gov/apod/image/2503/Horsehead_1024.jpg"
+        );
+        assert_eq!(
+            horsehead.source_url,
+            "https://apod.nasa.gov/apod/ap250301.html"
+        );
+        assert_eq!(horsehead.source_type.as_str(), "apod");
+    }
+
+    #[test]
+    fn test_date_window_pages_back() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 10).unwrap();
+        let date = |d| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+        assert_eq!(date_window(today, 1, 5), (date(6), None));
+        assert_eq!(date_window(today, 2, 5), (date(1), Some(date(5))));
+        assert_eq!(date_window(today, 1, 1), (today, None));
+    }
+
+    #[test]
+    fn test_nasa_today_follows_new_york() {
+        let at = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let day = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+        // winter, UTC-5
+        assert_eq!(nasa_today(at("2025-01-10T04:59:00Z")), day(1, 9));
+        assert_eq!(nasa_today(at("2025-01-10T05:00:00Z")), day(1, 10));
+        // summer, UTC-4
+        assert_eq!(nasa_today(at("2025-07-10T03:59:00Z")), day(7, 9));
+        assert_eq!(nasa_today(at("2025-07-10T04:00:00Z")), day(7, 10));
+        // DST starts 2025-03-09 07:00 UTC and ends 2025-11-02 06:00 UTC
+        assert_eq!(nasa_today(at("2025-03-10T04:30:00Z")), day(3, 10));
+        assert_eq!(nasa_today(at("2025-11-03T04:30:00Z")), day(11, 2));
+    }
+
+    #[test]
+    fn test_default_config_uses_demo_key() {
+        let table: toml::Table = "[apod]\nenabled = true".parse().unwrap();
+        let config: ApodConfig = table["apod"].clone().try_into().unwrap();
+        assert_eq!(config.api_key, "DEMO_KEY");
+        assert_eq!(create_sources(&table, HttpClient::default()).len(), 1);
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+
+use muralis_core::error::Result;
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    build_headers, download_preview, AspectRatioFilter, HttpClient, WallpaperSource,
+};
+
+const BING_BASE: &str = "https://www.bing.com";
+
+/// The archive serves at most this many images per request.
+const ARCHIVE_LIMIT: u32 = 8;
+
+/// Nominal size of the `_UHD` rendition.
+const UHD_SIZE: (u32, u32) = (3840, 2160);
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BingConfig {
+    pub enabled: bool,
+    /// Market code, e.g. "en-US" or "de-DE". Picks the regional image set.
+    pub market: String,
+    /// Extra request headers.
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Default for BingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            market: "en-US".into(),
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
+    let Some(val) = table.get("bing") else {
+        return Vec::new();
+    };
+    let config: BingConfig = val.clone().try_into().unwrap_or_default();
+    if !config.enabled {
+        return Vec::new();
+    }
+    vec![Box::new(BingClient {
+        market: config.market,
+        headers: build_headers(&[], &config.headers),
+        client,
+    })]
+}
+
+pub struct BingClient {
+    market: String,
+    headers: HeaderMap,
+    client: HttpClient,
+}
+
+impl BingClient {
+    fn archive_get(&self, count: u32) -> reqwest::RequestBuilder {
+        self.client
+            .get(format!("{BING_BASE}/HPImageArchive.aspx"))
+            .headers(self.headers.clone())
+            .query(&[
+                ("format", "js"),
+                ("idx", "0"),
+                ("n", &count.to_string()),
+                ("mkt", &self.market),
+            ])
+    }
+}
+
+#[async_trait]
+impl WallpaperSource for BingClient {
+    fn name(&self) -> &str {
+        "Bing"
+    }
+
+    fn source_type(&self) -> &str {
+        "bing"
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+        _aspect: AspectRatioFilter,
+    ) -> Result<Vec<WallpaperPreview>> {
+        // the archive is a single short list; there is nothing past page 1
+        if page > 1 {
+            return Ok(Vec::new());
+        }
+        let request = self.archive_get(per_page.clamp(1, ARCHIVE_LIMIT));
+        let resp: ArchiveResponse = self
+            .client
+            .send(request)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        let query = query.trim().to_lowercase();
+        Ok(resp
+            .images
+            .into_iter()
+            // no search API; match the query against title and copyright
+            .filter(|img| query.is_empty() || img.mentions(&query))
+            .map(ArchiveImage::into_preview)
+            .collect())
+    }
+
+    async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+        download_preview(&self.client, preview).await
+    }
+}
+
+// -- API response types --
+
+#[derive(Debug, Deserialize)]
+struct ArchiveResponse {
+    #[serde(default)]
+    images: Vec<ArchiveImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ArchiveImage {
+    /// Relative image path without size suffix, e.g. `/th?id=OHR.Foo_EN-US123`.
+    urlbase: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    copyright: String,
+    #[serde(default)]
+    copyrightlink: String,
+}
+
+impl ArchiveImage {
+    fn mentions(&self, query: &str) -> bool {
+        self.title.to_lowercase().contains(query) || self.copyright.to_lowercase().contains(query)
+    }
+
+    /// Stable image name, e.g. `OHR.Foo_EN-US123`.
+    fn image_id(&self) -> &str {
+        self.urlbase
+            .strip_prefix("/th?id=")
+            .unwrap_or(&self.urlbase)
+    }
+
+    fn into_preview(self) -> WallpaperPreview {
+        let full_url = format!("{BING_BASE}{}_UHD.jpg", self.urlbase);
+        let sample_url = format!("{BING_BASE}{}_1920x1080.jpg", self.urlbase);
+        let source_url = if self.copyrightlink.is_empty() {
+            full_url.clone()
+        } else {
+            self.copyrightlink.clone()
+        };
+        let tags = if self.title.is_empty() {
+            Vec::new()
+        } else {
+            vec![self.title.clone()]
+        };
+        WallpaperPreview {
+            source_type: SourceType::new("bing"),
+            source_id: self.image_id().to_string(),
+            source_url,
+            thumbnail_url: sample_url.clone(),
+            full_url,
+            sample_url: Some(sample_url),
+            width: UHD_SIZE.0,
+            height: UHD_SIZE.1,
+            tags,
+            meta: WallpaperMeta::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOCK_RESPONSE: &str = r#"{
+        "images": [
+            {
+                "startdate": "20250301",
+                "fullstartdate": "202503010800",
+                "enddate": "20250302",
+                "url": "/th?id=OHR.PuffinsIceland_EN-US4829373020_1920x1080.jpg&rf=LaDigue_1920x1080.jpg&pid=hp",
+                "urlbase": "/th?id=OHR.PuffinsIceland_EN-US4829373020",
+                "copyright": "Atlantic puffins, Westman Islands, Iceland (© Jane Doe/Getty Images)",
+                "copyrightlink": "https://www.bing.com/search?q=Atlantic+puffin&form=hpcapt",
+                "title": "Puffin paradise",
+                "quiz": "/search?q=Bing+homepage+quiz",
+                "wp": true,
+                "hsh": "6f2b1c0e5d7a8e9f",
+                "drk": 1,
+                "top": 1,
+                "bot": 1,
+                "hs": []
+            },
+            {
+                "startdate": "20250228",
+                "fullstartdate": "202502280800",
+                "enddate": "20250301",
+                "url": "/th?id=OHR.DolomitesSnow_EN-US1122334455_1920x1080.jpg&rf=LaDigue_1920x1080.jpg&pid=hp",
+                "urlbase": "/th?id=OHR.DolomitesSnow_EN-US1122334455",
+                "copyright": "Tre Cime di Lavaredo, Dolomites, Italy (© John Roe/Alamy)",
+                "copyrightlink": "",
+                "title": "Peaks in winter",
+                "wp": true,
+                "hsh": "0a1b2c3d4e5f6a7b",
+                "hs": []
+            }
+        ],
+        "tooltips": {
+            "loading": "Loading...",
+            "previous": "Previous image",
+            "next": "Next image"
+        }
+    }"#;
+
+    #[test]
+    fn test_parse_archive_response() {
+        let resp: ArchiveResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
+        assert_eq!(resp.images.len(), 2);
+
+        let previews: Vec<WallpaperPreview> = resp
+            .images
+            .into_iter()
+            .map(ArchiveImage::into_preview)
+            .collect();
+        let p = &previews[0];
+        assert_eq!(p.source_type.as_str(), "bing");
+        assert_eq!(p.source_id, "OHR.PuffinsIceland_EN-US4829373020");
+        assert_eq!(
+            p.full_url,
+            "https://www.bing.com/th?id=OHR.PuffinsIceland_EN-US4829373020_UHD.jpg"
+        );
+        assert_eq!(
+            p.thumbnail_url,
+            "https://www.bing.com/th?id=OHR.PuffinsIceland_EN-US4829373020_1920x1080.jpg"
+        );
+        assert_eq!(
+            p.source_url,
+            "https://www.bing.com/search?q=Atlantic+puffin&form=hpcapt"
+        );
+        assert_eq!(p.tags, vec!["Puffin paradise"]);
+        // no copyright link: fall back to the image itself
+        assert_eq!(previews[1].source_url, previews[1].full_url);
+    }
+
+    #[test]
+    fn test_query_matches_title_and_copyright() {
+        let resp: ArchiveResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
+        assert!(resp.images[0].mentions("puffin"));
+        assert!(resp.images[1].mentions("italy"));
+        assert!(!resp.images[1].mentions("iceland"));
+    }
+
+    #[test]
+    fn test_create_sources_reads_market() {
+        let table: toml::Table = toml::from_str(
+            r#"
+[bing]
+enabled = true
+market = "de-DE"
+"#,
+        )
+        .unwrap();
+        let sources = create_sources(&table, HttpClient::default());
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].source_type(), "bing");
+
+        let client = BingClient {
+            market: "de-DE".into(),
+            headers: HeaderMap::new(),
+            client: HttpClient::default(),
+        };
+        let req = client.archive_get(8).build().unwrap();
+        assert_eq!(
+            req.url().as_str(),
+            "https://www.bing.com/HPImageArchive.aspx?format=js&idx=0&n=8&mkt=de-DE"
+        );
+
+        let disabled: toml::Table = toml::from_str("[bing]\nmarket = \"en-GB\"").unwrap();
+        assert!(create_sources(&disabled, HttpClient::default()).is_empty());
+    }
+}
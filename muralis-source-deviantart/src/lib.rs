@@ -0,0 +1,424 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use muralis_core::error::{MuralisError, Result};
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    build_headers, download_preview, AspectRatioFilter, HttpClient, WallpaperSource,
+};
+
+const API_BASE: &str = "https://www.deviantart.com/api/v1/oauth2";
+const TOKEN_URL: &str = "https://www.deviantart.com/oauth2/token";
+
+/// Browse endpoints return at most this many deviations per request.
+const MAX_LIMIT: u32 = 50;
+
+/// Refresh a token this long before DeviantArt says it expires, so a
+/// request never goes out with one that lapses in flight.
+const TOKEN_MARGIN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct DeviantArtConfig {
+    pub enabled: bool,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// Extra request headers; there are no defaults.
+    pub headers: BTreeMap<String, String>,
+}
+
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
+    let Some(val) = table.get("deviantart") else {
+        return Vec::new();
+    };
+    let config: DeviantArtConfig = val.clone().try_into().unwrap_or_default();
+    if !config.enabled {
+        return Vec::new();
+    }
+    let (Some(client_id), Some(client_secret)) = (config.client_id, config.client_secret) else {
+        return Vec::new();
+    };
+    vec![Box::new(DeviantArtClient {
+        client_id,
+        client_secret,
+        headers: build_headers(&[], &config.headers),
+        client,
+        token: Mutex::new(None),
+        api_base: API_BASE.into(),
+        token_url: TOKEN_URL.into(),
+    })]
+}
+
+/// A client-credentials access token, kept in memory only.
+#[derive(Debug, Clone)]
+struct Token {
+    access_token: String,
+    expires_at: Instant,
+}
+
+impl Token {
+    fn new(access_token: String, expires_in: u64, now: Instant) -> Self {
+        Self {
+            access_token,
+            expires_at: now + Duration::from_secs(expires_in),
+        }
+    }
+
+    /// Whether the token is still good for a request made at `now`.
+    fn is_fresh(&self, now: Instant) -> bool {
+        now + TOKEN_MARGIN < self.expires_at
+    }
+}
+
+pub struct DeviantArtClient {
+    client_id: String,
+    client_secret: String,
+    headers: HeaderMap,
+    client: HttpClient,
+    token: Mutex<Option<Token>>,
+    api_base: String,
+    token_url: String,
+}
+
+impl DeviantArtClient {
+    /// The cached access token, fetching a new one when it is missing or
+    /// about to expire.
+    async fn access_token(&self) -> Result<String> {
+        let cached = self.token.lock().unwrap().clone();
+        if let Some(token) = cached.filter(|t| t.is_fresh(Instant::now())) {
+            return Ok(token.access_token);
+        }
+
+        let request = self.client.post(&self.token_url).form(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", &self.client_id),
+            ("client_secret", &self.client_secret),
+        ]);
+        let resp = self.client.send(request).await?;
+        if !resp.status().is_success() {
+            return Err(MuralisError::HttpStatus {
+                status: resp.status().as_u16(),
+                context: "deviantart token request".into(),
+            });
+        }
+        let body: TokenResponse = resp.json().await?;
+        let token = Token::new(body.access_token, body.expires_in, Instant::now());
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(token.access_token)
+    }
+
+    /// GET `path` with `query`. A token DeviantArt rejects before its
+    /// expiry (revoked, or the app's credentials changed) is dropped and
+    /// the request retried once with a new one.
+    async fn api_get(&self, path: &str, query: &[(&str, String)]) -> Result<reqwest::Response> {
+        let resp = self.send_authorized(path, query).await?;
+        if resp.status() != StatusCode::UNAUTHORIZED {
+            return Ok(resp);
+        }
+        tracing::debug!("deviantart rejected the access token, fetching a new one");
+        *self.token.lock().unwrap() = None;
+        self.send_authorized(path, query).await
+    }
+
+    async fn send_authorized(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<reqwest::Response> {
+        let token = self.access_token().await?;
+        let request = self
+            .client
+            .get(format!("{}{path}", self.api_base))
+            .headers(self.headers.clone())
+            .bearer_auth(token)
+            .query(query);
+        self.client.send(request).await
+    }
+}
+
+/// Single tag DeviantArt can browse by: the query's words run together,
+/// as tags can't contain spaces.
+fn query_tag(query: &str) -> String {
+    query.split_whitespace().collect::<String>().to_lowercase()
+}
+
+/// Previews for deviations with a full image; literature, journals and
+/// other deviations without one are skipped.
+fn to_previews(resp: BrowseResponse) -> Vec<WallpaperPreview> {
+    resp.results
+        .into_iter()
+        .filter_map(|d| {
+            let content = d.content.filter(|c| !c.src.is_empty())?;
+            // thumbs are ordered smallest first
+            let thumbnail_url = d
+                .thumbs
+                .last()
+                .map(|t| t.src.clone())
+                .or_else(|| d.preview.as_ref().map(|p| p.src.clone()))
+                .unwrap_or_else(|| content.src.clone());
+            Some(WallpaperPreview {
+                source_type: SourceType::new("deviantart"),
+                source_id: d.deviationid,
+                source_url: d.url,
+                thumbnail_url,
+                full_url: content.src,
+                sample_url: d.preview.map(|p| p.src),
+                width: content.width,
+                height: content.height,
+                tags: d.category.into_iter().collect(),
+                meta: WallpaperMeta::default(),
+            })
+        })
+        .collect()
+}
+
+#[async_trait]
+impl WallpaperSource for DeviantArtClient {
+    fn name(&self) -> &str {
+        "DeviantArt"
+    }
+
+    fn source_type(&self) -> &str {
+        "deviantart"
+    }
+
+    /// Browses the newest deviations without a query, otherwise the
+    /// query as a tag.
+    async fn search(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+        _aspect: AspectRatioFilter,
+    ) -> Result<Vec<WallpaperPreview>> {
+        let limit = per_page.clamp(1, MAX_LIMIT);
+        let offset = page.saturating_sub(1) * limit;
+        let tag = query_tag(query);
+        let mut params = vec![
+            ("offset", offset.to_string()),
+            ("limit", limit.to_string()),
+            ("mature_content", "false".to_string()),
+        ];
+        let path = if tag.is_empty() {
+            "/browse/newest"
+        } else {
+            params.push(("tag", tag));
+            "/browse/tags"
+        };
+        let resp: BrowseResponse = self
+            .api_get(path, &params)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(to_previews(resp))
+    }
+
+    async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+        download_preview(&self.client, preview).await
+    }
+}
+
+// -- API response types --
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BrowseResponse {
+    results: Vec<Deviation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Deviation {
+    deviationid: String,
+    url: String,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    content: Option<DeviationImage>,
+    #[serde(default)]
+    preview: Option<DeviationImage>,
+    #[serde(default)]
+    thumbs: Vec<DeviationImage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviationImage {
+    src: String,
+    width: u32,
+    height: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use muralis_core::testing::{MockResponse, MockServer};
+
+    const MOCK_BROWSE: &str = r##"{
+        "has_more": true,
+        "next_offset": 2,
+        "results": [
+            {
+                "deviationid": "1A2B3C4D-0000-1111-2222-333344445555",
+                "url": "https://www.deviantart.com/painter/art/Misty-Valley-1001",
+                "title": "Misty Valley",
+                "category": "Landscapes & Scenery",
+                "is_downloadable": true,
+                "content": {
+                    "src": "https://images-wixmp.example/f/misty-valley.jpg",
+                    "width": 3840,
+                    "height": 2160,
+                    "filesize": 2411520
+                },
+                "preview": {
+                    "src": "https://images-wixmp.example/v1/fill/w_1024/misty-valley.jpg",
+                    "width": 1024,
+                    "height": 576
+                },
+                "thumbs": [
+                    {"src": "https://images-wixmp.example/150/misty-valley.jpg", "width": 150, "height": 84},
+                    {"src": "https://images-wixmp.example/300/misty-valley.jpg", "width": 300, "height": 169}
+                ]
+            },
+            {
+                "deviationid": "9F8E7D6C-0000-1111-2222-333344445555",
+                "url": "https://www.deviantart.com/writer/art/A-Poem-1002",
+                "title": "A Poem",
+                "category": "Poetry",
+                "excerpt": "Words, not pixels",
+                "thumbs": []
+            }
+        ]
+    }"##;
+
+    #[test]
+    fn test_parse_browse_response() {
+        let resp: BrowseResponse = serde_json::from_str(MOCK_BROWSE).unwrap();
+        let previews = to_previews(resp);
+
+        // the poem has no image to download
+        assert_eq!(previews.len(), 1);
+        let p = &previews[0];
+        assert_eq!(p.source_id, "1A2B3C4D-0000-1111-2222-333344445555");
+        assert_eq!(
+            p.source_url,
+            "https://www.deviantart.com/painter/art/Misty-Valley-1001"
+        );
+        assert_eq!(
+            p.full_url,
+            "https://images-wixmp.example/f/misty-valley.jpg"
+        );
+        assert_eq!(
+            p.thumbnail_url,
+            "https://images-wixmp.example/300/misty-valley.jpg"
+        );
+        assert_eq!((p.width, p.height), (3840, 2160));
+        assert_eq!(p.tags, vec!["Landscapes & Scenery"]);
+    }
+
+    #[test]
+    fn test_token_expiry() {
+        let now = Instant::now();
+        let token = Token::new("abc".into(), 3600, now);
+        assert!(token.is_fresh(now));
+        assert!(token.is_fresh(now + Duration::from_secs(3000)));
+        // refreshed ahead of the actual expiry
+        assert!(!token.is_fresh(now + Duration::from_secs(3590)));
+        assert!(!token.is_fresh(now + Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn test_requires_both_credentials() {
+        let table: toml::Table = r#"
+            [deviantart]
+            enabled = true
+            client_id = "123"
+        "#
+        .parse()
+        .unwrap();
+        assert!(create_sources(&table, HttpClient::default()).is_empty());
+
+        let mut table = table;
+        table["deviantart"]
+            .as_table_mut()
+            .unwrap()
+            .insert("client_secret".into(), "s3cret".into());
+        assert_eq!(create_sources(&table, HttpClient::default()).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token_is_replaced_once() {
+        let server = MockServer::start(|request| {
+            if request.starts_with("post /oauth2/token") {
+                return MockResponse::ok(r#"{"access_token": "fresh", "expires_in": 3600}"#);
+            }
+            if request.contains("authorization: bearer fresh") {
+                return MockResponse::ok(MOCK_BROWSE);
+            }
+            MockResponse::new(401)
+        })
+        .await;
+        let source = DeviantArtClient {
+            client_id: "123".into(),
+            client_secret: "s3cret".into(),
+            headers: HeaderMap::new(),
+            client: HttpClient::default(),
+            // still fresh by its expiry, but revoked server-side
+            token: Mutex::new(Some(Token::new("revoked".into(), 3600, Instant::now()))),
+            api_base: server.url("/api/v1/oauth2"),
+            token_url: server.url("/oauth2/token"),
+        };
+
+        let previews = source
+            .search("", 1, 24, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(previews.len(), 1);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[0].contains("authorization: bearer revoked"));
+        assert!(requests[1].starts_with("post /oauth2/token"));
+        assert!(requests[2].contains("authorization: bearer fresh"));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_new_token_is_an_error() {
+        let server = MockServer::start(|request| {
+            if request.starts_with("post /oauth2/token") {
+                return MockResponse::ok(r#"{"access_token": "fresh", "expires_in": 3600}"#);
+            }
+            MockResponse::new(401)
+        })
+        .await;
+        let source = DeviantArtClient {
+            client_id: "123".into(),
+            client_secret: "s3cret".into(),
+            headers: HeaderMap::new(),
+            client: HttpClient::default(),
+            token: Mutex::new(None),
+            api_base: server.url("/api/v1/oauth2"),
+            token_url: server.url("/oauth2/token"),
+        };
+
+        let result = source.search("", 1, 24, AspectRatioFilter::All).await;
+        assert!(result.is_err());
+        // token, browse, new token, browse: no loop
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    #[test]
+    fn test_query_tag() {
+        assert_eq!(query_tag("Misty Valley"), "mistyvalley");
+        assert_eq!(query_tag("  "), "");
+    }
+}
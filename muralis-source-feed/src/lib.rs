@@ -1,19 +1,87 @@
-use std::io::{BufReader, Cursor};
+use std::collections::{HashMap, HashSet};
+use std::io::Cursor;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, LazyLock};
 
 use async_trait::async_trait;
-use image::ImageReader;
+use encoding_rs::{Encoding, UTF_8};
+use image::metadata::Orientation;
+use image::{ImageDecoder, ImageReader};
+use reqwest::header::{
+    HeaderMap, HeaderName, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::StatusCode;
 use scraper::{Html, Selector};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::sync::Semaphore;
 
 use muralis_core::error::Result;
-use muralis_core::models::{SourceType, WallpaperPreview};
-use muralis_core::sources::{AspectRatioFilter, WallpaperSource};
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    download_preview, instance_id, AspectRatioFilter, HttpClient, WallpaperSource,
+};
+use muralis_core::wallpapers::write_atomic;
 
 static IMG_SEL: LazyLock<Selector> =
     LazyLock::new(|| Selector::parse("img[src]").expect("valid selector"));
 
+/// A feed's validators and the previews parsed from it, dimensions
+/// included. Kept on disk so conditional GETs work across CLI runs.
+#[derive(Serialize, Deserialize)]
+struct CachedFeed {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    previews: Vec<WallpaperPreview>,
+}
+
+impl CachedFeed {
+    /// Where the last response from `url` is cached under `dir`.
+    fn path(dir: &Path, url: &str) -> PathBuf {
+        let key = blake3::hash(url.as_bytes()).to_hex();
+        dir.join(format!("{}.json", &key[..16]))
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_vec(self)?;
+        write_atomic(path, &json)
+    }
+
+    fn conditional_headers(&self) -> Vec<(HeaderName, &str)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.etag {
+            headers.push((IF_NONE_MATCH, etag.as_str()));
+        }
+        if let Some(modified) = &self.last_modified {
+            headers.push((IF_MODIFIED_SINCE, modified.as_str()));
+        }
+        headers
+    }
+
+    /// Dimensions already probed, by image URL.
+    fn known_dimensions(&self) -> HashMap<&str, (u32, u32)> {
+        self.previews
+            .iter()
+            .filter(|p| p.width > 0 && p.height > 0)
+            .map(|p| (p.thumbnail_url.as_str(), (p.width, p.height)))
+            .collect()
+    }
+}
+
+fn header_string(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
 /// Max concurrent dimension fetch tasks.
 const MAX_DIM_CONCURRENCY: usize = 8;
 
@@ -26,11 +94,34 @@ pub struct FeedConfig {
     pub url: String,
     #[serde(default)]
     pub enabled: bool,
+    /// Base for resolving relative inline image URLs.
+    /// Defaults to the entry link, then the feed URL.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Gallery entries emit one preview per image, up to this many.
+    #[serde(default = "default_max_images_per_entry")]
+    pub max_images_per_entry: usize,
+}
+
+fn default_max_images_per_entry() -> usize {
+    10
+}
+
+/// Stable preview ID for the `n`th image of an entry. The first image keeps
+/// the bare entry ID so favorites from single-image feeds still match.
+fn image_id(entry_id: &str, n: usize) -> String {
+    if n == 0 {
+        entry_id.to_string()
+    } else {
+        format!("{entry_id}_{n}")
+    }
 }
 
+/// Feed sources from `table`, caching responses in `cache_dir`.
 pub fn create_sources(
     table: &toml::Table,
-    client: reqwest::Client,
+    client: HttpClient,
+    cache_dir: &Path,
 ) -> Vec<Box<dyn WallpaperSource>> {
     let Some(val) = table.get("feeds") else {
         return Vec::new();
@@ -39,6 +130,7 @@ pub fn create_sources(
         return Vec::new();
     };
     let mut sources: Vec<Box<dyn WallpaperSource>> = Vec::new();
+    let mut taken = HashSet::new();
     for item in arr {
         let config: FeedConfig = match item.clone().try_into() {
             Ok(c) => c,
@@ -49,6 +141,8 @@ pub fn create_sources(
         };
         if config.enabled {
             sources.push(Box::new(FeedSource {
+                id: instance_id("feed", &config.name, &mut taken),
+                cache_file: CachedFeed::path(cache_dir, &config.url),
                 config,
                 client: client.clone(),
             }));
@@ -58,66 +152,121 @@ pub fn create_sources(
 }
 
 pub struct FeedSource {
+    id: String,
     config: FeedConfig,
-    client: reqwest::Client,
+    client: HttpClient,
+    cache_file: PathBuf,
 }
 
-#[async_trait]
-impl WallpaperSource for FeedSource {
-    fn name(&self) -> &str {
-        &self.config.name
-    }
-
-    fn source_type(&self) -> &str {
-        "feed"
-    }
-
-    async fn search(
-        &self,
-        _query: &str,
-        _page: u32,
-        _per_page: u32,
-        _aspect: AspectRatioFilter,
-    ) -> Result<Vec<WallpaperPreview>> {
-        let body = self
-            .client
-            .get(&self.config.url)
-            .send()
-            .await?
-            .bytes()
-            .await?;
-        let feed = feed_rs::parser::parse(&body[..]).map_err(|e| {
+impl FeedSource {
+    /// One preview per image in the feed body; sizes are 0 when the feed
+    /// doesn't give them.
+    fn parse_previews(&self, body: &[u8]) -> Result<Vec<WallpaperPreview>> {
+        let body = decode_feed_body(body);
+        let feed = feed_rs::parser::parse(body.as_bytes()).map_err(|e| {
             muralis_core::error::MuralisError::Source(format!("feed parse error: {e}"))
         })?;
 
         let mut previews = Vec::new();
 
         for entry in &feed.entries {
-            if let Some((image_url, width, height)) = extract_image(entry) {
-                let id = entry.id.replace(['/', ':', '.'], "_");
-                let title = entry
-                    .title
-                    .as_ref()
-                    .map(|t| t.content.clone())
-                    .unwrap_or_default();
+            let base = self
+                .config
+                .base_url
+                .as_deref()
+                .or_else(|| entry.links.first().map(|l| l.href.as_str()))
+                .unwrap_or(&self.config.url);
+            let images = extract_images(entry, base);
+            let entry_id = entry.id.replace(['/', ':', '.'], "_");
+            let title = entry
+                .title
+                .as_ref()
+                .map(|t| t.content.clone())
+                .unwrap_or_default();
 
+            for (n, (image_url, width, height)) in images
+                .into_iter()
+                .take(self.config.max_images_per_entry)
+                .enumerate()
+            {
                 previews.push(WallpaperPreview {
                     source_type: SourceType::new("feed"),
-                    source_id: id,
+                    source_id: image_id(&entry_id, n),
                     source_url: entry
                         .links
                         .first()
                         .map(|l| l.href.clone())
                         .unwrap_or_default(),
                     thumbnail_url: image_url.clone(),
+                    sample_url: media_sample(entry, &image_url),
                     full_url: image_url,
                     width,
                     height,
-                    tags: vec![title, self.config.name.clone()],
+                    tags: vec![title.clone(), self.config.name.clone()],
+                    meta: WallpaperMeta::default(),
                 });
             }
         }
 
+        Ok(previews)
+    }
+}
+
+#[async_trait]
+impl WallpaperSource for FeedSource {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn source_type(&self) -> &str {
+        "feed"
+    }
+
+    async fn search(
+        &self,
+        _query: &str,
+        _page: u32,
+        _per_page: u32,
+        _aspect: AspectRatioFilter,
+    ) -> Result<Vec<WallpaperPreview>> {
+        let cached = CachedFeed::load(&self.cache_file);
+
+        let mut request = self.client.get(&self.config.url);
+        if let Some(cached) = &cached {
+            for (name, value) in cached.conditional_headers() {
+                request = request.header(name, value);
+            }
+        }
+        let resp = self.client.send(request).await?;
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                tracing::debug!(feed = %self.config.name, "feed not modified");
+                return Ok(cached.previews);
+            }
+        }
+        let etag = header_string(resp.headers(), ETAG);
+        let last_modified = header_string(resp.headers(), LAST_MODIFIED);
+        let body = resp.bytes().await?;
+        let mut previews = self.parse_previews(&body)?;
+
+        // reuse dimensions probed on earlier fetches
+        if let Some(cached) = &cached {
+            let known = cached.known_dimensions();
+            for p in previews
+                .iter_mut()
+                .filter(|p| p.width == 0 && p.height == 0)
+            {
+                if let Some(&(w, h)) = known.get(p.thumbnail_url.as_str()) {
+                    p.width = w;
+                    p.height = h;
+                }
+            }
+        }
+
         // Fetch dimensions for entries with unknown sizes
         let semaphore = Arc::new(Semaphore::new(MAX_DIM_CONCURRENCY));
         let mut handles = Vec::new();
@@ -147,23 +296,71 @@ impl WallpaperSource for FeedSource {
             }
         }
 
-        Ok(previews)
+        let cached = CachedFeed {
+            etag,
+            last_modified,
+            previews,
+        };
+        if let Err(e) = cached.save(&self.cache_file) {
+            tracing::warn!(feed = %self.config.name, "failed to cache feed: {e}");
+        }
+        Ok(cached.previews)
     }
 
     async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
-        let bytes = self
-            .client
-            .get(&preview.full_url)
-            .send()
-            .await?
-            .bytes()
-            .await?;
-        Ok(bytes)
+        download_preview(&self.client, preview).await
     }
 }
 
+/// The feed body as UTF-8, transcoded from its BOM or the charset its XML
+/// prolog declares. The declaration is rewritten to match, so the parser
+/// doesn't decode it a second time.
+fn decode_feed_body(body: &[u8]) -> String {
+    let encoding = Encoding::for_bom(body)
+        .map(|(e, _)| e)
+        .or_else(|| {
+            declared_encoding(body)
+                .and_then(|span| Encoding::for_label(&body[span]))
+                .filter(|e| e.is_ascii_compatible())
+        })
+        .unwrap_or(UTF_8);
+    let mut text = encoding.decode(body).0.into_owned();
+    if encoding != UTF_8 {
+        if let Some(span) = declared_encoding(text.as_bytes()) {
+            text.replace_range(span, "UTF-8");
+        }
+    }
+    text
+}
+
+/// Byte range of the `encoding="..."` value in an XML prolog.
+fn declared_encoding(body: &[u8]) -> Option<Range<usize>> {
+    let start = body.iter().position(|b| !b.is_ascii_whitespace())?;
+    if !body[start..].starts_with(b"<?xml") {
+        return None;
+    }
+    let end = start + body[start..].windows(2).position(|w| w == b"?>")?;
+    let prolog = &body[..end];
+    let mut i = prolog.windows(8).position(|w| w == b"encoding")? + 8;
+    let skip_space = |mut i: usize| {
+        while prolog.get(i).is_some_and(u8::is_ascii_whitespace) {
+            i += 1;
+        }
+        i
+    };
+    i = skip_space(i);
+    if prolog.get(i) != Some(&b'=') {
+        return None;
+    }
+    i = skip_space(i + 1);
+    let quote = *prolog.get(i).filter(|q| matches!(q, b'"' | b'\''))?;
+    let value_start = i + 1;
+    let len = prolog[value_start..].iter().position(|&b| b == quote)?;
+    Some(value_start..value_start + len)
+}
+
 /// Fetch image dimensions via partial HTTP download (first 32KB).
-async fn fetch_dimensions(client: &reqwest::Client, url: &str) -> (u32, u32) {
+async fn fetch_dimensions(client: &HttpClient, url: &str) -> (u32, u32) {
     let resp = match client
         .get(url)
         .header("Range", "bytes=0-32767")
@@ -192,28 +389,47 @@ async fn fetch_dimensions(client: &reqwest::Client, url: &str) -> (u32, u32) {
             return (0, 0);
         }
     };
-    let cursor = Cursor::new(bytes.as_ref());
-    let reader = match ImageReader::new(BufReader::new(cursor)).with_guessed_format() {
-        Ok(r) => r,
-        Err(e) => {
-            tracing::debug!("dimension fetch format guess failed for {url}: {e}");
-            return (0, 0);
-        }
-    };
-    match reader.into_dimensions() {
-        Ok(dims) => dims,
-        Err(e) => {
-            tracing::debug!("dimension fetch decode failed for {url}: {e}");
-            (0, 0)
-        }
+    oriented_dimensions(&bytes).unwrap_or_else(|| {
+        tracing::debug!("dimension fetch decode failed for {url}");
+        (0, 0)
+    })
+}
+
+/// Width and height as displayed: swapped when the EXIF orientation
+/// turns the image a quarter, as phone photos often do.
+fn oriented_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .ok()?;
+    let mut decoder = reader.into_decoder().ok()?;
+    let (width, height) = decoder.dimensions();
+    match decoder.orientation().unwrap_or(Orientation::NoTransforms) {
+        Orientation::Rotate90
+        | Orientation::Rotate270
+        | Orientation::Rotate90FlipH
+        | Orientation::Rotate270FlipH => Some((height, width)),
+        _ => Some((width, height)),
     }
 }
 
-/// Extract image URL and dimensions from feed entry.
-/// Returns (url, width, height). Dimensions are 0 when unknown from metadata.
-fn extract_image(entry: &feed_rs::model::Entry) -> Option<(String, u32, u32)> {
-    // 1. media content (may include dimensions)
+/// Extract every candidate image from a feed entry, in feed order.
+/// Returns (url, width, height); dimensions are 0 when unknown from metadata.
+/// Only the first kind of image found is used (media, then enclosures, then
+/// inline HTML), so galleries don't also pick up their own thumbnails.
+/// Relative inline `<img>` URLs are resolved against `base`.
+fn extract_images(entry: &feed_rs::model::Entry, base: &str) -> Vec<(String, u32, u32)> {
+    let mut images: Vec<(String, u32, u32)> = Vec::new();
+    let mut push = |url: String, w: u32, h: u32| {
+        if !images.iter().any(|(u, _, _)| *u == url) {
+            images.push((url, w, h));
+        }
+    };
+
+    // 1. media content (may include dimensions); a group's thumbnail stands
+    //    in when it has no image content
+    let mut found = false;
     for media in &entry.media {
+        let mut group_found = false;
         for content in &media.content {
             if let Some(ref url) = content.url {
                 let url_str = url.as_str();
@@ -225,15 +441,23 @@ fn extract_image(entry: &feed_rs::model::Entry) -> Option<(String, u32, u32)> {
                 {
                     let w = content.width.unwrap_or(0);
                     let h = content.height.unwrap_or(0);
-                    return Some((url_str.to_string(), w, h));
+                    push(url_str.to_string(), w, h);
+                    group_found = true;
                 }
             }
         }
-        if let Some(thumb) = media.thumbnails.first() {
-            let w = thumb.image.width.unwrap_or(0);
-            let h = thumb.image.height.unwrap_or(0);
-            return Some((thumb.image.uri.clone(), w, h));
+        if !group_found {
+            if let Some(thumb) = media.thumbnails.first() {
+                let w = thumb.image.width.unwrap_or(0);
+                let h = thumb.image.height.unwrap_or(0);
+                push(thumb.image.uri.clone(), w, h);
+                group_found = true;
+            }
         }
+        found |= group_found;
+    }
+    if found {
+        return images;
     }
 
     // 2. enclosures / links with image type
@@ -243,33 +467,68 @@ fn extract_image(entry: &feed_rs::model::Entry) -> Option<(String, u32, u32)> {
             .as_deref()
             .is_some_and(|t| t.starts_with("image/"))
         {
-            return Some((link.href.clone(), 0, 0));
+            push(link.href.clone(), 0, 0);
+            found = true;
         }
     }
+    if found {
+        return images;
+    }
 
     // 3. inline HTML img extraction
-    if let Some(ref content) = entry.content {
-        if let Some(ref body) = content.body {
-            if let Some(url) = extract_img_from_html(body) {
-                return Some((url, 0, 0));
-            }
+    let mut inline = entry
+        .content
+        .as_ref()
+        .and_then(|c| c.body.as_deref())
+        .map(extract_imgs_from_html)
+        .unwrap_or_default();
+    if inline.is_empty() {
+        if let Some(ref summary) = entry.summary {
+            inline = extract_imgs_from_html(&summary.content);
         }
     }
-    if let Some(ref summary) = entry.summary {
-        if let Some(url) = extract_img_from_html(&summary.content) {
-            return Some((url, 0, 0));
-        }
+    for url in inline {
+        push(resolve_relative(base, &url), 0, 0);
     }
 
-    None
+    images
 }
 
-fn extract_img_from_html(html: &str) -> Option<String> {
+/// The media thumbnail standing beside `full_url` in its group, as booru
+/// feeds publish their sample next to the original. Galleries with several
+/// images in one group have no sample, since the thumbnail can't be
+/// attributed to one of them.
+fn media_sample(entry: &feed_rs::model::Entry, full_url: &str) -> Option<String> {
+    let media = entry.media.iter().find(|m| {
+        m.content
+            .iter()
+            .any(|c| c.url.as_ref().is_some_and(|u| u.as_str() == full_url))
+    })?;
+    if media.content.len() != 1 {
+        return None;
+    }
+    media
+        .thumbnails
+        .first()
+        .map(|t| t.image.uri.clone())
+        .filter(|uri| uri != full_url)
+}
+
+fn extract_imgs_from_html(html: &str) -> Vec<String> {
     let doc = Html::parse_fragment(html);
     doc.select(&IMG_SEL)
-        .next()
-        .and_then(|el| el.value().attr("src"))
+        .filter_map(|el| el.value().attr("src"))
         .map(|s| s.to_string())
+        .collect()
+}
+
+/// Resolve `src` against `base`. Absolute URLs pass through unchanged, and
+/// `src` is returned verbatim if either side can't be parsed.
+fn resolve_relative(base: &str, src: &str) -> String {
+    url::Url::parse(base)
+        .and_then(|b| b.join(src))
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| src.to_string())
 }
 
 fn is_image_url(url: &str) -> bool {
@@ -283,6 +542,158 @@ fn is_image_url(url: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use muralis_core::testing::{MockResponse, MockServer};
+
+    const BASE: &str = "https://example.com/feed.xml";
+
+    fn extract_image(entry: &feed_rs::model::Entry, base: &str) -> Option<(String, u32, u32)> {
+        extract_images(entry, base).into_iter().next()
+    }
+
+    fn extract_img_from_html(html: &str) -> Option<String> {
+        extract_imgs_from_html(html).into_iter().next()
+    }
+
+    /// An offline source for `parse_previews`; `extra` is appended to its config.
+    fn parse_only_source(extra: &str) -> FeedSource {
+        FeedSource {
+            id: "feed:test".into(),
+            config: toml::from_str(&format!(
+                "name = \"test\"\nurl = \"https://example.com/feed.xml\"\n{extra}"
+            ))
+            .unwrap(),
+            client: HttpClient::default(),
+            cache_file: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn test_gallery_entry_yields_every_image() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+            <channel>
+                <title>Test</title>
+                <item>
+                    <title>Gallery</title>
+                    <guid>gallery-001</guid>
+                    <media:group>
+                        <media:content url="https://example.com/a.jpg" type="image/jpeg" width="1920" height="1080"/>
+                        <media:content url="https://example.com/b.jpg" type="image/jpeg" width="2560" height="1440"/>
+                        <media:content url="https://example.com/c.jpg" type="image/jpeg"/>
+                    </media:group>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = feed_rs::parser::parse(&xml[..]).unwrap();
+        let images = extract_images(&feed.entries[0], BASE);
+        assert_eq!(
+            images,
+            vec![
+                ("https://example.com/a.jpg".to_string(), 1920, 1080),
+                ("https://example.com/b.jpg".to_string(), 2560, 1440),
+                ("https://example.com/c.jpg".to_string(), 0, 0),
+            ]
+        );
+        assert_eq!(image_id("gallery-001", 0), "gallery-001");
+        assert_eq!(image_id("gallery-001", 2), "gallery-001_2");
+
+        // max_images_per_entry keeps the first images of the entry
+        let previews = parse_only_source("max_images_per_entry = 2")
+            .parse_previews(xml)
+            .unwrap();
+        let ids: Vec<&str> = previews.iter().map(|p| p.source_id.as_str()).collect();
+        assert_eq!(ids, vec!["gallery-001", "gallery-001_1"]);
+        assert_eq!(parse_only_source("").parse_previews(xml).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_two_media_contents_yield_two_previews() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+            <channel>
+                <title>Test</title>
+                <item>
+                    <title>Pair</title>
+                    <guid>https://example.com/post/7</guid>
+                    <media:content url="https://example.com/left.jpg" type="image/jpeg" width="1920" height="1080"/>
+                    <media:content url="https://example.com/right.jpg" type="image/jpeg" width="1920" height="1080"/>
+                    <media:content url="https://example.com/left.jpg" type="image/jpeg" width="1920" height="1080"/>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let previews = parse_only_source("").parse_previews(xml).unwrap();
+        let got: Vec<(&str, &str)> = previews
+            .iter()
+            .map(|p| (p.source_id.as_str(), p.full_url.as_str()))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("https___example_com_post_7", "https://example.com/left.jpg"),
+                (
+                    "https___example_com_post_7_1",
+                    "https://example.com/right.jpg"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_inline_gallery_images() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+            <channel>
+                <title>Test</title>
+                <item>
+                    <title>Inline</title>
+                    <guid>inline-001</guid>
+                    <description><![CDATA[<img src="/a.jpg"><p>x</p><img src="/b.jpg"><img src="/a.jpg">]]></description>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = feed_rs::parser::parse(&xml[..]).unwrap();
+        let urls: Vec<String> = extract_images(&feed.entries[0], BASE)
+            .into_iter()
+            .map(|(u, _, _)| u)
+            .collect();
+        assert_eq!(
+            urls,
+            vec!["https://example.com/a.jpg", "https://example.com/b.jpg"]
+        );
+    }
+
+    #[test]
+    fn test_each_feed_registers_as_own_source() {
+        let table: toml::Table = r#"
+            [[feeds]]
+            name = "Bing Daily"
+            url = "https://example.com/bing.xml"
+            enabled = true
+
+            [[feeds]]
+            name = "NASA APOD"
+            url = "https://example.com/apod.xml"
+            enabled = true
+
+            [[feeds]]
+            name = "NASA APOD"
+            url = "https://example.com/apod2.xml"
+            enabled = true
+        "#
+        .parse()
+        .unwrap();
+        let sources = create_sources(&table, HttpClient::default(), Path::new("/tmp"));
+        let names: Vec<&str> = sources.iter().map(|s| s.name()).collect();
+        let ids: Vec<&str> = sources.iter().map(|s| s.id()).collect();
+        assert_eq!(names, vec!["Bing Daily", "NASA APOD", "NASA APOD"]);
+        assert_eq!(
+            ids,
+            vec!["feed:Bing Daily", "feed:NASA APOD", "feed:NASA APOD-2"]
+        );
+    }
 
     #[test]
     fn test_extract_img_from_html() {
@@ -298,6 +709,47 @@ mod tests {
         assert!(extract_img_from_html(html).is_none());
     }
 
+    #[test]
+    fn test_resolve_relative() {
+        let base = "https://example.com/blog/post.html";
+        assert_eq!(
+            resolve_relative(base, "https://cdn.example.org/a.jpg"),
+            "https://cdn.example.org/a.jpg"
+        );
+        assert_eq!(
+            resolve_relative(base, "/images/foo.jpg"),
+            "https://example.com/images/foo.jpg"
+        );
+        assert_eq!(
+            resolve_relative(base, "//cdn.example.org/b.png"),
+            "https://cdn.example.org/b.png"
+        );
+        assert_eq!(
+            resolve_relative(base, "img/c.png"),
+            "https://example.com/blog/img/c.png"
+        );
+        assert_eq!(resolve_relative("not a url", "/x.jpg"), "/x.jpg");
+    }
+
+    #[test]
+    fn test_relative_inline_img_resolved() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0">
+            <channel>
+                <title>Test</title>
+                <item>
+                    <title>Relative</title>
+                    <guid>rel-001</guid>
+                    <description><![CDATA[<img src="/images/foo.jpg">]]></description>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = feed_rs::parser::parse(&xml[..]).unwrap();
+        let (url, _, _) = extract_image(&feed.entries[0], BASE).unwrap();
+        assert_eq!(url, "https://example.com/images/foo.jpg");
+    }
+
     #[test]
     fn test_is_image_url() {
         assert!(is_image_url("https://example.com/photo.jpg"));
@@ -336,21 +788,21 @@ mod tests {
         assert_eq!(feed.entries.len(), 3);
 
         // entry with enclosure
-        let img = extract_image(&feed.entries[0]);
+        let img = extract_image(&feed.entries[0], BASE);
         assert_eq!(
             img.as_ref().map(|(u, _, _)| u.as_str()),
             Some("https://example.com/sunset.jpg")
         );
 
         // entry with inline img
-        let img = extract_image(&feed.entries[1]);
+        let img = extract_image(&feed.entries[1], BASE);
         assert_eq!(
             img.as_ref().map(|(u, _, _)| u.as_str()),
             Some("https://example.com/mountain.jpg")
         );
 
         // entry with no image
-        assert!(extract_image(&feed.entries[2]).is_none());
+        assert!(extract_image(&feed.entries[2], BASE).is_none());
     }
 
     #[test]
@@ -368,12 +820,37 @@ mod tests {
         </rss>"#;
 
         let feed = feed_rs::parser::parse(&xml[..]).unwrap();
-        let (url, w, h) = extract_image(&feed.entries[0]).unwrap();
+        let (url, w, h) = extract_image(&feed.entries[0], BASE).unwrap();
         assert_eq!(url, "https://example.com/wide.jpg");
         assert_eq!(w, 1920);
         assert_eq!(h, 1080);
     }
 
+    #[test]
+    fn test_booru_entry_carries_sample_and_original() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+        <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+            <channel>
+                <title>Booru</title>
+                <item>
+                    <title>Post 4242</title>
+                    <guid>post-4242</guid>
+                    <media:content url="https://booru.example/original/4242.png" type="image/png" width="3840" height="2160"/>
+                    <media:thumbnail url="https://booru.example/sample/sample_4242.jpg" width="850" height="478"/>
+                </item>
+            </channel>
+        </rss>"#;
+
+        let feed = feed_rs::parser::parse(&xml[..]).unwrap();
+        let entry = &feed.entries[0];
+        let (full, _, _) = extract_image(entry, BASE).unwrap();
+        assert_eq!(full, "https://booru.example/original/4242.png");
+        assert_eq!(
+            media_sample(entry, &full).as_deref(),
+            Some("https://booru.example/sample/sample_4242.jpg")
+        );
+    }
+
     #[test]
     fn test_media_thumbnail_fallback_with_dimensions() {
         let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
@@ -389,7 +866,7 @@ mod tests {
         </rss>"#;
 
         let feed = feed_rs::parser::parse(&xml[..]).unwrap();
-        let (url, w, h) = extract_image(&feed.entries[0]).unwrap();
+        let (url, w, h) = extract_image(&feed.entries[0], BASE).unwrap();
         assert_eq!(url, "https://example.com/thumb.jpg");
         assert_eq!(w, 640);
         assert_eq!(h, 480);
@@ -411,7 +888,7 @@ mod tests {
         let feed = feed_rs::parser::parse(&xml[..]).unwrap();
         assert_eq!(feed.entries.len(), 1);
 
-        let (url, w, h) = extract_image(&feed.entries[0]).unwrap();
+        let (url, w, h) = extract_image(&feed.entries[0], BASE).unwrap();
         assert_eq!(url, "https://example.com/atom.jpg");
         assert_eq!(w, 0);
         assert_eq!(h, 0);
@@ -435,12 +912,151 @@ mod tests {
             url = "https://example.com/feed3.xml"
         "#;
         let table: toml::Table = toml_str.parse().unwrap();
-        let client = reqwest::Client::new();
-        let sources = create_sources(&table, client);
+        let client = HttpClient::default();
+        let sources = create_sources(&table, client, Path::new("/tmp"));
         assert_eq!(sources.len(), 1);
         assert_eq!(sources[0].name(), "active");
     }
 
+    /// One feed with ETag "v1" and one PNG.
+    async fn serve_feed() -> MockServer {
+        let mut png = Vec::new();
+        image::RgbImage::new(4, 3)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        MockServer::start(move |head| {
+            if head.starts_with("get /img.png") {
+                return MockResponse::ok(png.clone()).header("content-type", "image/png");
+            }
+            if head.contains("if-none-match: \"v1\"") {
+                return MockResponse::new(304).header("etag", "\"v1\"");
+            }
+            let host = head
+                .lines()
+                .find_map(|l| l.strip_prefix("host:"))
+                .unwrap_or_default()
+                .trim();
+            let feed = format!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+                <rss version="2.0"><channel><title>Mock</title>
+                    <item><title>Pic</title><guid>pic-1</guid>
+                        <enclosure url="http://{host}/img.png" type="image/png" length="100"/>
+                    </item>
+                </channel></rss>"#
+            );
+            MockResponse::ok(feed).header("etag", "\"v1\"")
+        })
+        .await
+    }
+
+    fn mock_source(server: &MockServer, cache_dir: &Path) -> FeedSource {
+        let url = server.url("/feed.xml");
+        FeedSource {
+            id: "feed:mock".into(),
+            cache_file: CachedFeed::path(cache_dir, &url),
+            config: FeedConfig {
+                name: "mock".into(),
+                url,
+                enabled: true,
+                base_url: None,
+                max_images_per_entry: 10,
+            },
+            client: HttpClient::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_not_modified_returns_cached_previews() {
+        let server = serve_feed().await;
+        let tmp = tempfile::tempdir().unwrap();
+
+        let first = mock_source(&server, tmp.path())
+            .search("", 1, 24, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!((first[0].width, first[0].height), (4, 3));
+
+        // a fresh source, as in the next CLI run, reads the cache from disk
+        let second = mock_source(&server, tmp.path())
+            .search("", 1, 24, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].full_url, first[0].full_url);
+        assert_eq!((second[0].width, second[0].height), (4, 3));
+
+        // feed, image probe, then a conditional feed request and no re-probe
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[1].starts_with("get /img.png"));
+        assert!(requests[2].contains("if-none-match: \"v1\""));
+    }
+
+    /// A `w`x`h` JPEG carrying an EXIF orientation tag.
+    fn jpeg_with_orientation(w: u32, h: u32, orientation: u8) -> Vec<u8> {
+        let mut jpeg = Vec::new();
+        // noisy so most of the file is scan data, as in a real photo
+        image::RgbImage::from_fn(w, h, |x, y| {
+            image::Rgb([(x * 7) as u8, (y * 13) as u8, (x ^ y) as u8])
+        })
+        .write_to(&mut Cursor::new(&mut jpeg), image::ImageFormat::Jpeg)
+        .unwrap();
+        // big-endian TIFF with one IFD entry: 0x0112 (orientation), SHORT
+        let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01\x01\x12\0\x03\0\0\0\x01\0".to_vec();
+        exif.extend_from_slice(&[orientation, 0, 0, 0, 0, 0, 0]);
+        let len = (exif.len() + 2) as u16;
+        let mut segment = vec![0xFF, 0xE1];
+        segment.extend_from_slice(&len.to_be_bytes());
+        segment.extend_from_slice(&exif);
+        // right after SOI
+        jpeg.splice(2..2, segment);
+        jpeg
+    }
+
+    #[test]
+    fn test_oriented_dimensions_swap_rotated() {
+        // orientation 6: stored landscape, shown rotated 90° clockwise
+        let rotated = jpeg_with_orientation(256, 128, 6);
+        assert_eq!(oriented_dimensions(&rotated), Some((128, 256)));
+
+        let upright = jpeg_with_orientation(256, 128, 1);
+        assert_eq!(oriented_dimensions(&upright), Some((256, 128)));
+
+        // a partial download still has the header
+        assert_eq!(
+            oriented_dimensions(&rotated[..rotated.len() / 2]),
+            Some((128, 256))
+        );
+        assert_eq!(oriented_dimensions(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_decode_utf8_feed_body() {
+        let xml = "<?xml version=\"1.0\" encoding=\"UTF-8\"?><rss><title>Café</title></rss>";
+        assert_eq!(decode_feed_body(xml.as_bytes()), xml);
+        assert_eq!(
+            decode_feed_body("<rss>Café</rss>".as_bytes()),
+            "<rss>Café</rss>"
+        );
+    }
+
+    #[test]
+    fn test_decode_latin1_feed_body() {
+        let mut xml = b"<?xml version='1.0' encoding = 'ISO-8859-1'?>\n".to_vec();
+        xml.extend_from_slice(b"<rss version=\"2.0\"><channel><title>Test</title>");
+        xml.extend_from_slice(
+            b"<item><title>Caf\xe9 \xe0 Z\xfcrich</title><guid>c-1</guid></item>",
+        );
+        xml.extend_from_slice(b"</channel></rss>");
+
+        let decoded = decode_feed_body(&xml);
+        assert!(decoded.starts_with("<?xml version='1.0' encoding = 'UTF-8'?>"));
+        let feed = feed_rs::parser::parse(decoded.as_bytes()).unwrap();
+        let title = feed.entries[0].title.as_ref().unwrap();
+        assert_eq!(title.content, "Café à Zürich");
+    }
+
     #[test]
     fn test_feed_config_defaults() {
         let toml_str = r#"
@@ -451,5 +1067,6 @@ mod tests {
         assert_eq!(config.name, "test");
         assert_eq!(config.url, "https://example.com/feed.xml");
         assert!(!config.enabled);
+        assert_eq!(config.max_images_per_entry, 10);
     }
 }
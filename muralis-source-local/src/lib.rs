@@ -0,0 +1,276 @@
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use image::ImageReader;
+use serde::Deserialize;
+
+use muralis_core::error::{MuralisError, Result};
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{AspectRatioFilter, WallpaperSource};
+
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "gif", "tif", "tiff"];
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
+pub struct LocalConfig {
+    pub enabled: bool,
+    /// Folders to index. `~/` expands to the home directory.
+    pub paths: Vec<String>,
+    /// Also index subfolders.
+    pub recursive: bool,
+}
+
+pub fn create_sources(table: &toml::Table) -> Vec<Box<dyn WallpaperSource>> {
+    let Some(val) = table.get("local") else {
+        return Vec::new();
+    };
+    let config: LocalConfig = val.clone().try_into().unwrap_or_default();
+    if !config.enabled || config.paths.is_empty() {
+        return Vec::new();
+    }
+    vec![Box::new(LocalSource {
+        dirs: config.paths.iter().map(|p| expand_home(p)).collect(),
+        recursive: config.recursive,
+    })]
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => Path::new(&home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+pub struct LocalSource {
+    dirs: Vec<PathBuf>,
+    recursive: bool,
+}
+
+impl LocalSource {
+    /// Image files under the configured folders whose name contains `query`,
+    /// sorted by path so pages are stable.
+    fn list_images(&self, query: &str) -> Vec<PathBuf> {
+        let query = query.trim().to_lowercase();
+        let mut files = Vec::new();
+        for dir in &self.dirs {
+            collect_images(dir, self.recursive, &mut files);
+        }
+        files.retain(|p| {
+            query.is_empty()
+                || p.file_name()
+                    .is_some_and(|n| n.to_string_lossy().to_lowercase().contains(&query))
+        });
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+/// Images in `dir`, and in its subfolders when `recursive`. Symlinked
+/// folders aren't descended into, so a link back up the tree can't loop;
+/// symlinked images are kept.
+fn collect_images(dir: &Path, recursive: bool, out: &mut Vec<PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!(dir = %dir.display(), "cannot read local source folder: {e}");
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if file_type.is_dir() {
+            if recursive {
+                collect_images(&path, recursive, out);
+            }
+        } else if (file_type.is_file() || path.is_file()) && is_image(&path) {
+            out.push(path);
+        }
+    }
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Preview for one file; dimensions come from the image header only.
+fn preview_for(path: &Path) -> WallpaperPreview {
+    let (width, height) = ImageReader::open(path)
+        .and_then(|r| r.with_guessed_format())
+        .ok()
+        .and_then(|r| r.into_dimensions().ok())
+        .unwrap_or((0, 0));
+    let url = url::Url::from_file_path(path)
+        .map(String::from)
+        .unwrap_or_else(|_| format!("file://{}", path.display()));
+    WallpaperPreview {
+        source_type: SourceType::new("local"),
+        source_id: path.to_string_lossy().to_string(),
+        source_url: url.clone(),
+        thumbnail_url: url.clone(),
+        full_url: url,
+        sample_url: None,
+        width,
+        height,
+        tags: Vec::new(),
+        meta: WallpaperMeta::default(),
+    }
+}
+
+#[async_trait]
+impl WallpaperSource for LocalSource {
+    fn name(&self) -> &str {
+        "Local"
+    }
+
+    fn source_type(&self) -> &str {
+        "local"
+    }
+
+    async fn search(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+        _aspect: AspectRatioFilter,
+    ) -> Result<Vec<WallpaperPreview>> {
+        let skip = page.saturating_sub(1) as usize * per_page as usize;
+        let take = per_page as usize;
+        let source = LocalSource {
+            dirs: self.dirs.clone(),
+            recursive: self.recursive,
+        };
+        let query = query.to_string();
+        // folder walks and header reads are blocking I/O
+        tokio::task::spawn_blocking(move || {
+            source
+                .list_images(&query)
+                .iter()
+                .skip(skip)
+                .take(take)
+                .map(|p| preview_for(p))
+                .collect()
+        })
+        .await
+        .map_err(|e| MuralisError::Source(format!("local folder scan failed: {e}")))
+    }
+
+    async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
+        let path = PathBuf::from(&preview.source_id);
+        match tokio::fs::read(&path).await {
+            Ok(data) => Ok(data.into()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Err(MuralisError::FileNotFound(path))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        match self.dirs.iter().find(|d| !d.is_dir()) {
+            Some(missing) => Err(MuralisError::FileNotFound(missing.clone())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_png(path: &Path, w: u32, h: u32) {
+        image::RgbImage::new(w, h).save(path).unwrap();
+    }
+
+    fn source_for(dir: &Path, recursive: bool) -> Box<dyn WallpaperSource> {
+        let table: toml::Table = toml::from_str(&format!(
+            "[local]\nenabled = true\nrecursive = {recursive}\npaths = [{:?}]",
+            dir.display().to_string()
+        ))
+        .unwrap();
+        let mut sources = create_sources(&table);
+        assert_eq!(sources.len(), 1);
+        sources.remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_search_folder_paginated() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_png(&tmp.path().join("a.png"), 40, 20);
+        write_png(&tmp.path().join("b.PNG"), 30, 30);
+        write_png(&tmp.path().join("c.png"), 16, 9);
+        std::fs::write(tmp.path().join("notes.txt"), "not an image").unwrap();
+        std::fs::create_dir(tmp.path().join("nested")).unwrap();
+        write_png(&tmp.path().join("nested/d.png"), 8, 8);
+
+        let source = source_for(tmp.path(), false);
+        let first = source
+            .search("", 1, 2, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        let second = source
+            .search("", 2, 2, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(second.len(), 1);
+
+        let a = &first[0];
+        assert_eq!(a.source_type.as_str(), "local");
+        assert!(a.source_id.ends_with("a.png"));
+        assert!(a.full_url.starts_with("file:///"));
+        assert_eq!((a.width, a.height), (40, 20));
+        assert_eq!((first[1].width, first[1].height), (30, 30));
+        assert_eq!((second[0].width, second[0].height), (16, 9));
+
+        let data = source.download(a).await.unwrap();
+        assert_eq!(data, std::fs::read(tmp.path().join("a.png")).unwrap());
+
+        let recursive = source_for(tmp.path(), true);
+        let all = recursive
+            .search("", 1, 10, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 4);
+        let matched = recursive
+            .search("D.p", 1, 10, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(matched.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_recursive_skips_symlinked_folders() {
+        let tmp = tempfile::tempdir().unwrap();
+        write_png(&tmp.path().join("a.png"), 8, 8);
+        std::fs::create_dir(tmp.path().join("nested")).unwrap();
+        write_png(&tmp.path().join("nested/b.png"), 8, 8);
+        // a loop back to the root, and a linked image
+        std::os::unix::fs::symlink(tmp.path(), tmp.path().join("nested/up")).unwrap();
+        std::os::unix::fs::symlink(tmp.path().join("a.png"), tmp.path().join("link.png")).unwrap();
+
+        let all = source_for(tmp.path(), true)
+            .search("", 1, 10, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        let mut names: Vec<_> = all
+            .iter()
+            .map(|p| p.source_id.rsplit('/').next().unwrap().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.png", "b.png", "link.png"]);
+    }
+
+    #[test]
+    fn test_disabled_or_empty_config() {
+        let disabled: toml::Table = toml::from_str("[local]\npaths = [\"/tmp\"]").unwrap();
+        assert!(create_sources(&disabled).is_empty());
+        let empty: toml::Table = toml::from_str("[local]\nenabled = true").unwrap();
+        assert!(create_sources(&empty).is_empty());
+    }
+}
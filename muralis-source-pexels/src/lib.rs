@@ -1,9 +1,14 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
+use reqwest::header::HeaderMap;
 use serde::Deserialize;
 
 use muralis_core::error::Result;
-use muralis_core::models::{SourceType, WallpaperPreview};
-use muralis_core::sources::{AspectRatioFilter, WallpaperSource};
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    build_headers, download_preview, url_path, AspectRatioFilter, HttpClient, WallpaperSource,
+};
 
 const API_BASE: &str = "https://api.pexels.com/v1";
 
@@ -12,12 +17,12 @@ const API_BASE: &str = "https://api.pexels.com/v1";
 pub struct PexelsConfig {
     pub enabled: bool,
     pub api_key: Option<String>,
+    /// Extra request headers. Pexels versions its API in the URL, so there
+    /// are no defaults.
+    pub headers: BTreeMap<String, String>,
 }
 
-pub fn create_sources(
-    table: &toml::Table,
-    client: reqwest::Client,
-) -> Vec<Box<dyn WallpaperSource>> {
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
     let Some(val) = table.get("pexels") else {
         return Vec::new();
     };
@@ -30,13 +35,24 @@ pub fn create_sources(
     };
     vec![Box::new(PexelsClient {
         api_key: key,
+        headers: build_headers(&[], &config.headers),
         client,
     })]
 }
 
 pub struct PexelsClient {
     api_key: String,
-    client: reqwest::Client,
+    headers: HeaderMap,
+    client: HttpClient,
+}
+
+impl PexelsClient {
+    fn api_get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .get(format!("{API_BASE}{path}"))
+            .headers(self.headers.clone())
+            .header("Authorization", &self.api_key)
+    }
 }
 
 #[async_trait]
@@ -49,6 +65,11 @@ impl WallpaperSource for PexelsClient {
         "pexels"
     }
 
+    /// The search endpoint requires a query.
+    fn browsable(&self) -> bool {
+        false
+    }
+
     async fn search(
         &self,
         query: &str,
@@ -57,90 +78,59 @@ impl WallpaperSource for PexelsClient {
         _aspect: AspectRatioFilter,
     ) -> Result<Vec<WallpaperPreview>> {
         let clamped = per_page.min(80);
+        let request = self.api_get("/search").query(&[
+            ("query", query),
+            ("page", &page.to_string()),
+            ("per_page", &clamped.to_string()),
+            ("orientation", "landscape"),
+        ]);
         let resp: PexelsSearchResponse = self
             .client
-            .get(format!("{API_BASE}/search"))
-            .header("Authorization", &self.api_key)
-            .query(&[
-                ("query", query),
-                ("page", &page.to_string()),
-                ("per_page", &clamped.to_string()),
-                ("orientation", "landscape"),
-            ])
-            .send()
+            .send(request)
             .await?
+            .error_for_status()?
             .json()
             .await?;
 
-        let previews = resp
-            .photos
-            .into_iter()
-            .map(|p| {
-                let full_url = p.src.original.clone();
-                WallpaperPreview {
-                    source_type: SourceType::new("pexels"),
-                    source_id: p.id.to_string(),
-                    source_url: p.url,
-                    thumbnail_url: p.src.medium,
-                    full_url,
-                    width: p.width,
-                    height: p.height,
-                    tags: Vec::new(),
-                }
-            })
-            .collect();
-        Ok(previews)
+        Ok(resp.photos.into_iter().map(Into::into).collect())
     }
 
     async fn resolve_url(&self, url: &str) -> Result<Option<WallpaperPreview>> {
-        // Match pexels.com/photo/<slug>-<id>/
-        let id = if url.contains("pexels.com/photo/") {
-            // Extract trailing numeric ID: pexels.com/photo/some-slug-12345/
-            url.trim_end_matches('/')
-                .rsplit('-')
-                .next()
-                .and_then(|s| s.parse::<u64>().ok())
-        } else {
-            None
-        };
-
-        let Some(id) = id else {
+        let Some(id) = photo_id(url) else {
             return Ok(None);
         };
 
+        let request = self.api_get(&format!("/photos/{id}"));
         let resp: PexelsPhoto = self
             .client
-            .get(format!("{API_BASE}/photos/{id}"))
-            .header("Authorization", &self.api_key)
-            .send()
+            .send(request)
             .await?
+            .error_for_status()?
             .json()
             .await?;
 
-        Ok(Some(WallpaperPreview {
-            source_type: SourceType::new("pexels"),
-            source_id: resp.id.to_string(),
-            source_url: resp.url,
-            thumbnail_url: resp.src.medium,
-            full_url: resp.src.original,
-            width: resp.width,
-            height: resp.height,
-            tags: Vec::new(),
-        }))
+        Ok(Some(resp.into()))
     }
 
     async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
-        let bytes = self
-            .client
-            .get(&preview.full_url)
-            .send()
-            .await?
-            .bytes()
-            .await?;
-        Ok(bytes)
+        download_preview(&self.client, preview).await
     }
 }
 
+/// Numeric ID from `pexels.com/photo/<slug>-<id>/`, `pexels.com/photo/<id>/`
+/// or a localized form such as `pexels.com/de-de/foto/<slug>-<id>/`.
+fn photo_id(url: &str) -> Option<u64> {
+    let path = url_path(url, "pexels.com")?;
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    let slug = loop {
+        let segment = segments.next()?;
+        if segment == "photo" || segment == "foto" {
+            break segments.next()?;
+        }
+    };
+    slug.rsplit('-').next()?.parse().ok()
+}
+
 // -- API response types --
 
 #[derive(Debug, Deserialize)]
@@ -155,11 +145,34 @@ struct PexelsPhoto {
     height: u32,
     url: String,
     src: PexelsSrc,
+    #[serde(default)]
+    photographer: Option<String>,
+}
+
+impl From<PexelsPhoto> for WallpaperPreview {
+    fn from(p: PexelsPhoto) -> Self {
+        WallpaperPreview {
+            source_type: SourceType::new("pexels"),
+            source_id: p.id.to_string(),
+            source_url: p.url,
+            thumbnail_url: p.src.medium,
+            full_url: p.src.original,
+            sample_url: Some(p.src.large2x),
+            width: p.width,
+            height: p.height,
+            tags: Vec::new(),
+            meta: WallpaperMeta {
+                photographer: p.photographer,
+                ..WallpaperMeta::default()
+            },
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct PexelsSrc {
     original: String,
+    large2x: String,
     medium: String,
 }
 
@@ -197,6 +210,35 @@ mod tests {
         ]
     }"##;
 
+    #[test]
+    fn test_photo_id_from_url() {
+        assert_eq!(
+            photo_id("https://www.pexels.com/photo/green-trees-near-lake-1287145/"),
+            Some(1287145)
+        );
+        assert_eq!(photo_id("https://pexels.com/photo/1287145"), Some(1287145));
+        assert_eq!(
+            photo_id("https://www.pexels.com/de-de/foto/berge-im-nebel-417173/?utm=x"),
+            Some(417173)
+        );
+        assert_eq!(photo_id("https://www.pexels.com/search/lake/"), None);
+        assert_eq!(photo_id("https://notpexels.com/photo/x-1/"), None);
+    }
+
+    #[test]
+    fn test_config_headers_merged() {
+        let mut extra = BTreeMap::new();
+        extra.insert("X-Extra".to_string(), "yes".to_string());
+        let client = PexelsClient {
+            api_key: "k".into(),
+            headers: build_headers(&[], &extra),
+            client: HttpClient::default(),
+        };
+        let req = client.api_get("/search").build().unwrap();
+        assert_eq!(req.headers()["x-extra"], "yes");
+        assert_eq!(req.headers()["authorization"], "k");
+    }
+
     #[test]
     fn test_parse_pexels_response() {
         let resp: PexelsSearchResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
@@ -209,23 +251,11 @@ mod tests {
     #[test]
     fn test_pexels_to_preview() {
         let resp: PexelsSearchResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
-        let previews: Vec<WallpaperPreview> = resp
-            .photos
-            .into_iter()
-            .map(|p| WallpaperPreview {
-                source_type: SourceType::new("pexels"),
-                source_id: p.id.to_string(),
-                source_url: p.url,
-                thumbnail_url: p.src.medium,
-                full_url: p.src.original,
-                width: p.width,
-                height: p.height,
-                tags: Vec::new(),
-            })
-            .collect();
+        let previews: Vec<WallpaperPreview> = resp.photos.into_iter().map(Into::into).collect();
 
         assert_eq!(previews.len(), 1);
         assert_eq!(previews[0].source_id, "12345");
         assert_eq!(previews[0].width, 4000);
+        assert_eq!(previews[0].meta.photographer.as_deref(), Some("Test"));
     }
 }
@@ -0,0 +1,331 @@
+use std::collections::{HashSet, VecDeque};
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures_lite::{Stream, StreamExt};
+use serde::Deserialize;
+
+use muralis_core::error::Result;
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    download_preview, instance_id, AspectRatioFilter, HttpClient, PreviewStream, WallpaperSource,
+};
+
+/// A push source: an HTTP endpoint emitting one JSON object per line,
+/// e.g. a live photo stream. Finite endpoints work too.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamConfig {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// One line of the stream.
+#[derive(Debug, Deserialize)]
+struct StreamItem {
+    id: String,
+    url: String,
+    #[serde(default)]
+    thumbnail_url: Option<String>,
+    /// Medium-size rendition shown while the original loads.
+    #[serde(default)]
+    sample_url: Option<String>,
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    width: u32,
+    #[serde(default)]
+    height: u32,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+impl From<StreamItem> for WallpaperPreview {
+    fn from(item: StreamItem) -> Self {
+        WallpaperPreview {
+            source_type: SourceType::new("stream"),
+            source_id: item.id,
+            source_url: item.link.unwrap_or_else(|| item.url.clone()),
+            thumbnail_url: item.thumbnail_url.unwrap_or_else(|| item.url.clone()),
+            full_url: item.url,
+            sample_url: item.sample_url,
+            width: item.width,
+            height: item.height,
+            tags: item.tags,
+            meta: WallpaperMeta::default(),
+        }
+    }
+}
+
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
+    let Some(arr) = table.get("streams").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    let mut sources: Vec<Box<dyn WallpaperSource>> = Vec::new();
+    let mut taken = HashSet::new();
+    for item in arr {
+        let config: StreamConfig = match item.clone().try_into() {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::warn!("failed to parse stream config: {e}");
+                continue;
+            }
+        };
+        if config.enabled {
+            sources.push(Box::new(StreamSource {
+                id: instance_id("stream", &config.name, &mut taken),
+                config,
+                client: client.clone(),
+            }));
+        }
+    }
+    sources
+}
+
+/// Longest line kept while waiting for its newline; longer ones are dropped.
+const MAX_LINE_LEN: usize = 64 * 1024;
+
+pub struct StreamSource {
+    id: String,
+    config: StreamConfig,
+    client: HttpClient,
+}
+
+#[async_trait]
+impl WallpaperSource for StreamSource {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn source_type(&self) -> &str {
+        "stream"
+    }
+
+    /// Takes `per_page` items from the stream, skipping earlier pages, so live
+    /// endpoints don't block a search.
+    async fn search(
+        &self,
+        _query: &str,
+        page: u32,
+        per_page: u32,
+        _aspect: AspectRatioFilter,
+    ) -> Result<Vec<WallpaperPreview>> {
+        let skip = page.saturating_sub(1) as usize * per_page as usize;
+        let stream = self.subscribe().await?;
+        Ok(stream.skip(skip).take(per_page as usize).collect().await)
+    }
+
+    async fn download(&self, preview: &WallpaperPreview) -> Result<Bytes> {
+        download_preview(&self.client, preview).await
+    }
+
+    async fn subscribe(&self) -> Result<PreviewStream> {
+        let resp = self
+            .client
+            .send(self.client.get(&self.config.url))
+            .await?
+            .error_for_status()?;
+        let chunks = futures_lite::stream::unfold(Some(resp), |resp| async move {
+            let mut resp = resp?;
+            match resp.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(resp))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e.into()), None)),
+            }
+        });
+        Ok(Box::pin(preview_lines(chunks)))
+    }
+}
+
+struct LineState<S> {
+    chunks: Pin<Box<S>>,
+    buf: Vec<u8>,
+    ready: VecDeque<WallpaperPreview>,
+    /// Dropping the rest of an over-long line.
+    discarding: bool,
+    done: bool,
+}
+
+/// Turn a byte-chunk stream into previews, one per complete JSON line.
+/// Lines may span chunks; malformed lines and lines over [`MAX_LINE_LEN`]
+/// are skipped.
+fn preview_lines<S>(chunks: S) -> impl Stream<Item = WallpaperPreview> + Send
+where
+    S: Stream<Item = Result<Bytes>> + Send + 'static,
+{
+    let state = LineState {
+        chunks: Box::pin(chunks),
+        buf: Vec::new(),
+        ready: VecDeque::new(),
+        discarding: false,
+        done: false,
+    };
+    futures_lite::stream::unfold(state, |mut st| async move {
+        loop {
+            if let Some(preview) = st.ready.pop_front() {
+                return Some((preview, st));
+            }
+            if st.done {
+                return None;
+            }
+            match st.chunks.next().await {
+                Some(Ok(chunk)) => {
+                    let mut chunk = &chunk[..];
+                    if st.discarding {
+                        let Some(pos) = chunk.iter().position(|&b| b == b'\n') else {
+                            continue;
+                        };
+                        chunk = &chunk[pos + 1..];
+                        st.discarding = false;
+                    }
+                    st.buf.extend_from_slice(chunk);
+                    while let Some(pos) = st.buf.iter().position(|&b| b == b'\n') {
+                        let line: Vec<u8> = st.buf.drain(..=pos).collect();
+                        st.ready.extend(parse_line(&line));
+                    }
+                    if st.buf.len() > MAX_LINE_LEN {
+                        tracing::warn!("skipping stream line over {MAX_LINE_LEN} bytes");
+                        st.buf.clear();
+                        st.discarding = true;
+                    }
+                }
+                Some(Err(e)) => {
+                    tracing::warn!("stream read failed: {e}");
+                    st.done = true;
+                }
+                None => {
+                    let rest = std::mem::take(&mut st.buf);
+                    st.ready.extend(parse_line(&rest));
+                    st.done = true;
+                }
+            }
+        }
+    })
+}
+
+fn parse_line(line: &[u8]) -> Option<WallpaperPreview> {
+    let text = std::str::from_utf8(line).ok()?.trim();
+    if text.is_empty() {
+        return None;
+    }
+    match serde_json::from_str::<StreamItem>(text) {
+        Ok(item) => Some(item.into()),
+        Err(e) => {
+            tracing::debug!("skipping malformed stream line: {e}");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use muralis_core::testing::{MockResponse, MockServer};
+
+    fn chunks(parts: &[&'static str]) -> impl Stream<Item = Result<Bytes>> + Send + 'static {
+        let parts: Vec<Result<Bytes>> = parts.iter().map(|p| Ok(Bytes::from(*p))).collect();
+        futures_lite::stream::iter(parts)
+    }
+
+    #[tokio::test]
+    async fn test_collects_previews_from_finite_stream() {
+        let stream = preview_lines(chunks(&[
+            "{\"id\":\"a\",\"url\":\"https://x/a.jpg\",\"width\":1920,\"height\":1080}\n{\"id\":\"b\",",
+            "\"url\":\"https://x/b.jpg\",\"thumbnail_url\":\"https://x/b_t.jpg\",\"tags\":[\"sky\"]}\n",
+            "not json\n\n",
+            "{\"id\":\"c\",\"url\":\"https://x/c.jpg\"}",
+        ]));
+        let previews: Vec<WallpaperPreview> = stream.collect().await;
+
+        let ids: Vec<&str> = previews.iter().map(|p| p.source_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+        assert_eq!(previews[0].width, 1920);
+        assert_eq!(previews[0].thumbnail_url, "https://x/a.jpg");
+        assert_eq!(previews[1].thumbnail_url, "https://x/b_t.jpg");
+        assert_eq!(previews[1].tags, vec!["sky"]);
+        assert_eq!(previews[2].source_type.as_str(), "stream");
+    }
+
+    #[tokio::test]
+    async fn test_skips_overlong_lines() {
+        let long = "x".repeat(MAX_LINE_LEN);
+        let parts: Vec<Result<Bytes>> = vec![
+            Ok(Bytes::from("{\"id\":\"a\",\"url\":\"u\",\"tags\":[\"")),
+            Ok(Bytes::from(long.clone())),
+            Ok(Bytes::from(long)),
+            Ok(Bytes::from("\"]}\n{\"id\":\"b\",\"url\":\"u\"}\n")),
+        ];
+        let previews: Vec<WallpaperPreview> = preview_lines(futures_lite::stream::iter(parts))
+            .collect()
+            .await;
+        let ids: Vec<&str> = previews.iter().map(|p| p.source_id.as_str()).collect();
+        assert_eq!(ids, vec!["b"]);
+    }
+
+    #[tokio::test]
+    async fn test_search_pages_through_stream() {
+        let body: String = (0..5)
+            .map(|i| format!("{{\"id\":\"{i}\",\"url\":\"https://x/{i}.jpg\"}}\n"))
+            .collect();
+        let server = MockServer::start(move |_| MockResponse::ok(body.clone())).await;
+        let table: toml::Table = format!(
+            "[[streams]]\nname = \"live\"\nurl = \"{}\"\nenabled = true\n",
+            server.url("/live.ndjson")
+        )
+        .parse()
+        .unwrap();
+        let source = create_sources(&table, HttpClient::default()).remove(0);
+
+        let ids = |previews: Vec<WallpaperPreview>| -> Vec<String> {
+            previews.into_iter().map(|p| p.source_id).collect()
+        };
+        let first = source
+            .search("", 1, 2, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(ids(first), vec!["0", "1"]);
+        let third = source
+            .search("", 3, 2, AspectRatioFilter::All)
+            .await
+            .unwrap();
+        assert_eq!(ids(third), vec!["4"]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_stops_on_read_error() {
+        let parts: Vec<Result<Bytes>> = vec![
+            Ok(Bytes::from("{\"id\":\"a\",\"url\":\"u\"}\n")),
+            Err(muralis_core::error::MuralisError::Source("reset".into())),
+            Ok(Bytes::from("{\"id\":\"b\",\"url\":\"u\"}\n")),
+        ];
+        let previews: Vec<WallpaperPreview> = preview_lines(futures_lite::stream::iter(parts))
+            .collect()
+            .await;
+        assert_eq!(previews.len(), 1);
+    }
+
+    #[test]
+    fn test_create_sources_skips_disabled() {
+        let table: toml::Table = r#"
+            [[streams]]
+            name = "live"
+            url = "https://example.com/live.ndjson"
+            enabled = true
+
+            [[streams]]
+            name = "off"
+            url = "https://example.com/off.ndjson"
+        "#
+        .parse()
+        .unwrap();
+        let sources = create_sources(&table, HttpClient::default());
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].name(), "live");
+    }
+}
@@ -1,23 +1,30 @@
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
+use reqwest::header::HeaderMap;
 use serde::Deserialize;
 
 use muralis_core::error::Result;
-use muralis_core::models::{SourceType, WallpaperPreview};
-use muralis_core::sources::{AspectRatioFilter, WallpaperSource};
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{
+    build_headers, download_preview, url_path, AspectRatioFilter, HttpClient, WallpaperSource,
+};
 
 const API_BASE: &str = "https://api.unsplash.com";
 
+/// Unsplash asks clients to pin the API version.
+const DEFAULT_HEADERS: &[(&str, &str)] = &[("Accept-Version", "v1")];
+
 #[derive(Debug, Default, Clone, Deserialize)]
 #[serde(default)]
 pub struct UnsplashConfig {
     pub enabled: bool,
     pub access_key: Option<String>,
+    /// Extra request headers, overriding the defaults.
+    pub headers: BTreeMap<String, String>,
 }
 
-pub fn create_sources(
-    table: &toml::Table,
-    client: reqwest::Client,
-) -> Vec<Box<dyn WallpaperSource>> {
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
     let Some(val) = table.get("unsplash") else {
         return Vec::new();
     };
@@ -30,13 +37,38 @@ pub fn create_sources(
     };
     vec![Box::new(UnsplashClient {
         access_key: key,
+        headers: build_headers(DEFAULT_HEADERS, &config.headers),
         client,
     })]
 }
 
 pub struct UnsplashClient {
     access_key: String,
-    client: reqwest::Client,
+    headers: HeaderMap,
+    client: HttpClient,
+}
+
+/// Photo ID from `unsplash.com/photos/<id>` or the slugged
+/// `unsplash.com/photos/<slug>-<id>` form. IDs are 11 characters and may
+/// themselves contain `-`.
+fn photo_id(url: &str) -> Option<&str> {
+    let rest = url_path(url, "unsplash.com")?.strip_prefix("/photos/")?;
+    let segment = rest.split('/').next().filter(|s| !s.is_empty())?;
+    let len = segment.len();
+    if len > 11 && segment.as_bytes()[len - 12] == b'-' {
+        Some(&segment[len - 11..])
+    } else {
+        Some(segment)
+    }
+}
+
+impl UnsplashClient {
+    fn api_get(&self, path: &str) -> reqwest::RequestBuilder {
+        self.client
+            .get(format!("{API_BASE}{path}"))
+            .headers(self.headers.clone())
+            .header("Authorization", format!("Client-ID {}", self.access_key))
+    }
 }
 
 #[async_trait]
@@ -49,6 +81,11 @@ impl WallpaperSource for UnsplashClient {
         "unsplash"
     }
 
+    /// The search endpoint requires a query.
+    fn browsable(&self) -> bool {
+        false
+    }
+
     async fn search(
         &self,
         query: &str,
@@ -57,76 +94,42 @@ impl WallpaperSource for UnsplashClient {
         _aspect: AspectRatioFilter,
     ) -> Result<Vec<WallpaperPreview>> {
         let clamped = per_page.min(30);
+        let request = self.api_get("/search/photos").query(&[
+            ("query", query),
+            ("page", &page.to_string()),
+            ("per_page", &clamped.to_string()),
+            ("orientation", "landscape"),
+        ]);
         let resp: UnsplashSearchResponse = self
             .client
-            .get(format!("{API_BASE}/search/photos"))
-            .header("Authorization", format!("Client-ID {}", self.access_key))
-            .query(&[
-                ("query", query),
-                ("page", &page.to_string()),
-                ("per_page", &clamped.to_string()),
-                ("orientation", "landscape"),
-            ])
-            .send()
+            .send(request)
             .await?
+            .error_for_status()?
             .json()
             .await?;
 
-        let previews = resp
-            .results
-            .into_iter()
-            .map(|p| WallpaperPreview {
-                source_type: SourceType::new("unsplash"),
-                source_id: p.id.clone(),
-                source_url: p.links.html,
-                thumbnail_url: p.urls.regular,
-                full_url: p.urls.raw,
-                width: p.width,
-                height: p.height,
-                tags: p.tags.into_iter().map(|t| t.title).collect(),
-            })
-            .collect();
-        Ok(previews)
+        Ok(resp.results.into_iter().map(Into::into).collect())
     }
 
     async fn resolve_url(&self, url: &str) -> Result<Option<WallpaperPreview>> {
-        // Match unsplash.com/photos/<id>
-        let id = if let Some(rest) = url.strip_prefix("https://unsplash.com/photos/") {
-            rest.split('/').next().unwrap_or(rest).trim_end_matches('/')
-        } else {
+        let Some(id) = photo_id(url) else {
             return Ok(None);
         };
 
+        let request = self.api_get(&format!("/photos/{id}"));
         let resp: UnsplashPhoto = self
             .client
-            .get(format!("{API_BASE}/photos/{id}"))
-            .header("Authorization", format!("Client-ID {}", self.access_key))
-            .send()
+            .send(request)
             .await?
+            .error_for_status()?
             .json()
             .await?;
 
-        Ok(Some(WallpaperPreview {
-            source_type: SourceType::new("unsplash"),
-            source_id: resp.id.clone(),
-            source_url: resp.links.html,
-            thumbnail_url: resp.urls.regular,
-            full_url: resp.urls.raw,
-            width: resp.width,
-            height: resp.height,
-            tags: resp.tags.into_iter().map(|t| t.title).collect(),
-        }))
+        Ok(Some(resp.into()))
     }
 
     async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
-        let bytes = self
-            .client
-            .get(&preview.full_url)
-            .send()
-            .await?
-            .bytes()
-            .await?;
-        Ok(bytes)
+        download_preview(&self.client, preview).await
     }
 }
 
@@ -146,6 +149,31 @@ struct UnsplashPhoto {
     links: UnsplashLinks,
     #[serde(default)]
     tags: Vec<UnsplashTag>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    user: Option<UnsplashUser>,
+}
+
+impl From<UnsplashPhoto> for WallpaperPreview {
+    fn from(p: UnsplashPhoto) -> Self {
+        WallpaperPreview {
+            source_type: SourceType::new("unsplash"),
+            source_id: p.id,
+            source_url: p.links.html,
+            thumbnail_url: p.urls.regular.clone(),
+            full_url: p.urls.raw,
+            sample_url: Some(p.urls.regular),
+            width: p.width,
+            height: p.height,
+            tags: p.tags.into_iter().map(|t| t.title).collect(),
+            meta: WallpaperMeta {
+                captured_at: p.created_at,
+                photographer: p.user.map(|u| u.name),
+                file_size: None,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -164,6 +192,11 @@ struct UnsplashTag {
     title: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct UnsplashUser {
+    name: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,11 +225,70 @@ mod tests {
                 "tags": [
                     {"title": "mountain"},
                     {"title": "sky"}
-                ]
+                ],
+                "created_at": "2024-05-01T10:00:00Z",
+                "user": {"name": "Ada Lens", "username": "adalens"}
             }
         ]
     }"##;
 
+    fn client_with(headers: &str) -> UnsplashClient {
+        let table: toml::Table =
+            format!("[unsplash]\nenabled = true\naccess_key = \"k\"\n{headers}")
+                .parse()
+                .unwrap();
+        let config: UnsplashConfig = table["unsplash"].clone().try_into().unwrap();
+        UnsplashClient {
+            access_key: config.access_key.unwrap(),
+            headers: build_headers(DEFAULT_HEADERS, &config.headers),
+            client: HttpClient::default(),
+        }
+    }
+
+    #[test]
+    fn test_photo_id_from_url() {
+        assert_eq!(
+            photo_id("https://unsplash.com/photos/Dwu85P9SOIk"),
+            Some("Dwu85P9SOIk")
+        );
+        assert_eq!(
+            photo_id("https://unsplash.com/photos/a-misty-lake-at-dawn-Dwu85P9SOIk?utm_source=x"),
+            Some("Dwu85P9SOIk")
+        );
+        assert_eq!(
+            photo_id("https://www.unsplash.com/photos/-8hQ7sL1K4A/download"),
+            Some("-8hQ7sL1K4A")
+        );
+        assert_eq!(photo_id("https://unsplash.com/@someone"), None);
+        assert_eq!(photo_id("https://wallhaven.cc/w/abc123"), None);
+    }
+
+    #[test]
+    fn test_search_only() {
+        assert!(!client_with("").browsable());
+    }
+
+    #[test]
+    fn test_default_request_headers() {
+        let req = client_with("").api_get("/search/photos").build().unwrap();
+        assert_eq!(req.headers()["accept-version"], "v1");
+        assert_eq!(req.headers()["authorization"], "Client-ID k");
+    }
+
+    #[test]
+    fn test_config_headers_merged() {
+        let client = client_with(
+            r#"
+            [unsplash.headers]
+            "Accept-Version" = "v2"
+            X-Extra = "yes"
+            "#,
+        );
+        let req = client.api_get("/photos/x").build().unwrap();
+        assert_eq!(req.headers()["accept-version"], "v2");
+        assert_eq!(req.headers()["x-extra"], "yes");
+    }
+
     #[test]
     fn test_parse_unsplash_response() {
         let resp: UnsplashSearchResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
@@ -210,23 +302,15 @@ mod tests {
     #[test]
     fn test_unsplash_to_preview() {
         let resp: UnsplashSearchResponse = serde_json::from_str(MOCK_RESPONSE).unwrap();
-        let previews: Vec<WallpaperPreview> = resp
-            .results
-            .into_iter()
-            .map(|p| WallpaperPreview {
-                source_type: SourceType::new("unsplash"),
-                source_id: p.id.clone(),
-                source_url: p.links.html,
-                thumbnail_url: p.urls.regular,
-                full_url: p.urls.raw,
-                width: p.width,
-                height: p.height,
-                tags: p.tags.into_iter().map(|t| t.title).collect(),
-            })
-            .collect();
+        let previews: Vec<WallpaperPreview> = resp.results.into_iter().map(Into::into).collect();
 
         assert_eq!(previews.len(), 1);
         assert_eq!(previews[0].source_id, "uns_001");
         assert_eq!(previews[0].tags, vec!["mountain", "sky"]);
+        assert_eq!(previews[0].meta.photographer.as_deref(), Some("Ada Lens"));
+        assert_eq!(
+            previews[0].meta.captured_at.as_deref(),
+            Some("2024-05-01T10:00:00Z")
+        );
     }
 }
@@ -1,11 +1,31 @@
 use async_trait::async_trait;
 use serde::Deserialize;
 
-use muralis_core::error::Result;
-use muralis_core::models::{SourceType, WallpaperPreview};
-use muralis_core::sources::{AspectRatioFilter, WallpaperSource};
+use muralis_core::error::{MuralisError, Result};
+use muralis_core::models::{SourceType, WallpaperMeta, WallpaperPreview};
+use muralis_core::sources::{download_preview, AspectRatioFilter, HttpClient, WallpaperSource};
 
 const API_BASE: &str = "https://wallhaven.cc/api/v1";
+/// Page sizes Wallhaven accepts; anonymous requests always get the first.
+const PAGE_SIZES: &[u32] = &[24, 32, 64];
+/// Values the `sorting` parameter accepts.
+const SORTINGS: &[&str] = &[
+    "date_added",
+    "relevance",
+    "random",
+    "views",
+    "favorites",
+    "toplist",
+];
+/// Values `topRange` accepts with `sorting = "toplist"`.
+const TOP_RANGES: &[&str] = &["1d", "3d", "1w", "1M", "3M", "6M", "1y"];
+/// Colors the `colors` search filter accepts.
+const PALETTE: &[&str] = &[
+    "660000", "990000", "cc0000", "cc3333", "ea4c88", "993399", "663399", "333399", "0066cc",
+    "0099cc", "66cccc", "77cc33", "669900", "336600", "666600", "999900", "cccc33", "ffff00",
+    "ffcc33", "ff9900", "ff6600", "cc6633", "996633", "663300", "000000", "999999", "cccccc",
+    "ffffff", "424153",
+];
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
@@ -14,6 +34,17 @@ pub struct WallhavenConfig {
     pub api_key: Option<String>,
     pub categories: String,
     pub purity: String,
+    /// Minimum resolution, e.g. "2560x1440"
+    pub atleast: Option<String>,
+    /// Only wallpapers with this palette color, e.g. "336600"
+    pub colors: Option<String>,
+    /// Result order, e.g. "toplist" or "views"; browsing without a query
+    /// defaults to "date_added"
+    pub sorting: Option<String>,
+    /// "desc" or "asc"
+    pub order: Option<String>,
+    /// Toplist period, e.g. "1w" or "1M"
+    pub top_range: Option<String>,
 }
 
 impl Default for WallhavenConfig {
@@ -23,27 +54,175 @@ impl Default for WallhavenConfig {
             api_key: None,
             categories: "100".into(),
             purity: "100".into(),
+            atleast: None,
+            colors: None,
+            sorting: None,
+            order: None,
+            top_range: None,
         }
     }
 }
 
-pub fn create_sources(
-    table: &toml::Table,
-    client: reqwest::Client,
-) -> Vec<Box<dyn WallpaperSource>> {
-    let Some(val) = table.get("wallhaven") else {
-        return Vec::new();
-    };
-    let config: WallhavenConfig = val.clone().try_into().unwrap_or_default();
+pub fn create_sources(table: &toml::Table, client: HttpClient) -> Vec<Box<dyn WallpaperSource>> {
+    match from_table(table, client) {
+        Some(source) => vec![Box::new(source)],
+        None => Vec::new(),
+    }
+}
+
+/// The `[sources.wallhaven]` client with its settings validated, unless
+/// absent or disabled.
+fn from_table(table: &toml::Table, client: HttpClient) -> Option<WallhavenClient> {
+    let val = table.get("wallhaven")?;
+    let mut config: WallhavenConfig = val.clone().try_into().unwrap_or_default();
     if !config.enabled {
-        return Vec::new();
+        return None;
+    }
+    config.sorting = validated("sorting", config.sorting, SORTINGS);
+    config.order = validated("order", config.order, &["desc", "asc"]);
+    config.top_range = validated("top_range", config.top_range, TOP_RANGES);
+    let (purity, purity_clamped) = effective_purity(&config.purity, config.api_key.as_deref());
+    if purity_clamped {
+        tracing::warn!(
+            purity = %config.purity,
+            "wallhaven sketchy/NSFW purity needs an api_key; showing SFW only"
+        );
+    }
+    let mut source = WallhavenClient {
+        colors: None,
+        config,
+        client,
+        purity,
+        purity_clamped,
+    };
+    if let Some(hex) = source.config.colors.clone() {
+        source.with_color(&hex);
+    }
+    Some(source)
+}
+
+/// Wallhaven silently drops sketchy/NSFW purity bits without an API key, so
+/// clamp to SFW up front. Returns the purity to send and whether it was clamped.
+fn effective_purity(purity: &str, api_key: Option<&str>) -> (String, bool) {
+    let has_key = api_key.is_some_and(|k| !k.trim().is_empty());
+    let restricted = purity.chars().skip(1).any(|c| c == '1');
+    if has_key || !restricted {
+        (purity.to_string(), false)
+    } else {
+        ("100".into(), true)
     }
-    vec![Box::new(WallhavenClient { config, client })]
+}
+
+/// `value` if Wallhaven accepts it for `key`; otherwise warn and drop it.
+fn validated(key: &str, value: Option<String>, allowed: &[&str]) -> Option<String> {
+    let value = value?;
+    if allowed.contains(&value.as_str()) {
+        Some(value)
+    } else {
+        tracing::warn!(%value, "unsupported wallhaven {key}; expected one of {allowed:?}");
+        None
+    }
+}
+
+/// `hex` as Wallhaven expects it (lowercase, no `#`), if it's in the palette.
+fn palette_color(hex: &str) -> Option<String> {
+    let hex = hex.trim().trim_start_matches('#').to_ascii_lowercase();
+    PALETTE.contains(&hex.as_str()).then_some(hex)
+}
+
+/// Nearest supported page size for `per_page`. Only honored with an API key.
+fn page_size(per_page: u32) -> u32 {
+    PAGE_SIZES
+        .iter()
+        .copied()
+        .min_by_key(|size| size.abs_diff(per_page))
+        .unwrap_or(PAGE_SIZES[0])
 }
 
 pub struct WallhavenClient {
     config: WallhavenConfig,
-    client: reqwest::Client,
+    client: HttpClient,
+    /// `config.purity`, clamped to SFW when no API key is set.
+    purity: String,
+    purity_clamped: bool,
+    /// Validated `colors` filter.
+    colors: Option<String>,
+}
+
+impl WallhavenClient {
+    /// Only find wallpapers with `hex`, one of Wallhaven's palette colors.
+    /// Anything else is logged and the filter cleared.
+    pub fn with_color(&mut self, hex: &str) -> &mut Self {
+        self.colors = palette_color(hex);
+        if self.colors.is_none() {
+            tracing::warn!(color = hex, "not a wallhaven palette color; ignoring");
+        }
+        self
+    }
+
+    fn search_params(
+        &self,
+        query: &str,
+        page: u32,
+        per_page: u32,
+        aspect: AspectRatioFilter,
+    ) -> Vec<(&'static str, String)> {
+        let key = self
+            .config
+            .api_key
+            .as_ref()
+            .filter(|k| !k.trim().is_empty());
+        let mut params = vec![
+            ("q", query.to_string()),
+            ("page", page.to_string()),
+            ("categories", self.config.categories.clone()),
+            ("purity", self.purity.clone()),
+        ];
+
+        match key {
+            Some(key) => {
+                params.push(("apikey", key.clone()));
+                params.push(("per_page", page_size(per_page).to_string()));
+            }
+            None if per_page != PAGE_SIZES[0] => {
+                tracing::debug!(per_page, "wallhaven per_page needs an api_key; using 24");
+            }
+            None => {}
+        }
+
+        if let Some(ref atleast) = self.config.atleast {
+            params.push(("atleast", atleast.clone()));
+        }
+
+        if let Some(ref colors) = self.colors {
+            params.push(("colors", colors.clone()));
+        }
+
+        // browsing by relevance is just noise, so show the newest instead
+        let browsing = query.trim().is_empty();
+        let sorting = self
+            .config
+            .sorting
+            .as_deref()
+            .or(browsing.then_some("date_added"));
+        if let Some(sorting) = sorting {
+            params.push(("sorting", sorting.to_string()));
+            if sorting == "toplist" {
+                if let Some(ref range) = self.config.top_range {
+                    params.push(("topRange", range.clone()));
+                }
+            }
+        }
+        let order = self.config.order.as_deref().or(browsing.then_some("desc"));
+        if let Some(order) = order {
+            params.push(("order", order.to_string()));
+        }
+
+        if let Some(ratio) = aspect.to_wallhaven_ratio() {
+            params.push(("ratios", ratio.to_string()));
+        }
+        params
+    }
 }
 
 #[async_trait]
@@ -60,25 +239,19 @@ impl WallpaperSource for WallhavenClient {
         &self,
         query: &str,
         page: u32,
-        _per_page: u32,
+        per_page: u32,
         aspect: AspectRatioFilter,
     ) -> Result<Vec<WallpaperPreview>> {
-        let mut req = self.client.get(format!("{API_BASE}/search")).query(&[
-            ("q", query),
-            ("page", &page.to_string()),
-            ("categories", &self.config.categories),
-            ("purity", &self.config.purity),
-        ]);
-
-        if let Some(ref key) = self.config.api_key {
-            req = req.query(&[("apikey", key)]);
-        }
+        let params = self.search_params(query, page, per_page, aspect);
+        let req = self.client.get(format!("{API_BASE}/search")).query(&params);
 
-        if let Some(ratio) = aspect.to_wallhaven_ratio() {
-            req = req.query(&[("ratios", ratio)]);
-        }
-
-        let resp: WallhavenResponse = req.send().await?.json().await?;
+        let resp: WallhavenResponse = self
+            .client
+            .send(req)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
         let previews = resp
             .data
             .into_iter()
@@ -88,14 +261,20 @@ impl WallpaperSource for WallhavenClient {
                 source_url: w.url,
                 thumbnail_url: w.thumbs.original,
                 full_url: w.path,
+                sample_url: None,
                 width: w.dimension_x,
                 height: w.dimension_y,
                 tags: w.tags.into_iter().map(|t| t.name).collect(),
+                meta: WallpaperMeta::default(),
             })
             .collect();
         Ok(previews)
     }
 
+    fn colors(&self) -> &[&'static str] {
+        PALETTE
+    }
+
     async fn resolve_url(&self, url: &str) -> Result<Option<WallpaperPreview>> {
         // Match wallhaven.cc/w/<id> or whvn.cc/<id>
         let id = if let Some(rest) = url.strip_prefix("https://wallhaven.cc/w/") {
@@ -111,7 +290,13 @@ impl WallpaperSource for WallhavenClient {
             req = req.query(&[("apikey", key)]);
         }
 
-        let resp: WallhavenDetailResponse = req.send().await?.json().await?;
+        let resp: WallhavenDetailResponse = self
+            .client
+            .send(req)
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
         let w = resp.data;
         Ok(Some(WallpaperPreview {
             source_type: SourceType::new("wallhaven"),
@@ -119,21 +304,28 @@ impl WallpaperSource for WallhavenClient {
             source_url: w.url,
             thumbnail_url: w.thumbs.original,
             full_url: w.path,
+            sample_url: None,
             width: w.dimension_x,
             height: w.dimension_y,
             tags: w.tags.into_iter().map(|t| t.name).collect(),
+            meta: WallpaperMeta::default(),
         }))
     }
 
     async fn download(&self, preview: &WallpaperPreview) -> Result<bytes::Bytes> {
-        let bytes = self
-            .client
-            .get(&preview.full_url)
-            .send()
-            .await?
-            .bytes()
-            .await?;
-        Ok(bytes)
+        download_preview(&self.client, preview).await
+    }
+
+    async fn health_check(&self) -> Result<()> {
+        if self.purity_clamped {
+            return Err(MuralisError::Source(format!(
+                "purity {:?} needs an api_key; showing SFW results only",
+                self.config.purity
+            )));
+        }
+        self.search("", 1, 1, AspectRatioFilter::All)
+            .await
+            .map(|_| ())
     }
 }
 
@@ -238,9 +430,11 @@ mod tests {
                 source_url: w.url,
                 thumbnail_url: w.thumbs.original,
                 full_url: w.path,
+                sample_url: None,
                 width: w.dimension_x,
                 height: w.dimension_y,
                 tags: w.tags.into_iter().map(|t| t.name).collect(),
+                meta: WallpaperMeta::default(),
             })
             .collect();
 
@@ -249,4 +443,112 @@ mod tests {
         assert_eq!(previews[0].width, 3840);
         assert_eq!(previews[0].tags, vec!["landscape", "nature"]);
     }
+
+    #[test]
+    fn test_effective_purity_clamps_without_api_key() {
+        let cases = [
+            ("100", None, "100", false),
+            ("110", None, "100", true),
+            ("111", None, "100", true),
+            ("001", Some("  "), "100", true),
+            ("111", Some("key"), "111", false),
+            ("010", Some("key"), "010", false),
+        ];
+        for (purity, key, expected, clamped) in cases {
+            assert_eq!(
+                effective_purity(purity, key),
+                (expected.to_string(), clamped),
+                "purity={purity} key={key:?}"
+            );
+        }
+    }
+
+    fn client(api_key: Option<&str>) -> WallhavenClient {
+        let config = WallhavenConfig {
+            api_key: api_key.map(String::from),
+            ..WallhavenConfig::default()
+        };
+        WallhavenClient {
+            purity: config.purity.clone(),
+            config,
+            client: HttpClient::default(),
+            purity_clamped: false,
+            colors: None,
+        }
+    }
+
+    #[test]
+    fn test_search_params_per_page_needs_key() {
+        let params = client(Some("secret")).search_params("sky", 2, 60, AspectRatioFilter::All);
+        assert!(params.contains(&("per_page", "64".into())));
+        assert!(params.contains(&("apikey", "secret".into())));
+
+        let params = client(None).search_params("sky", 2, 60, AspectRatioFilter::All);
+        assert!(params.iter().all(|(k, _)| *k != "per_page"));
+    }
+
+    #[test]
+    fn test_configured_color_in_query() {
+        let table: toml::Table = r##"
+            [wallhaven]
+            colors = "#336600"
+        "##
+        .parse()
+        .unwrap();
+        let config: WallhavenConfig = table["wallhaven"].clone().try_into().unwrap();
+        let mut source = client(None);
+        source.with_color(config.colors.as_deref().unwrap());
+        let params = source.search_params("forest", 1, 24, AspectRatioFilter::All);
+        assert!(params.contains(&("colors", "336600".into())));
+
+        // not in the palette: dropped rather than sent
+        source.with_color("123456");
+        let params = source.search_params("forest", 1, 24, AspectRatioFilter::All);
+        assert!(params.iter().all(|(k, _)| *k != "colors"));
+    }
+
+    #[test]
+    fn test_search_params_toplist() {
+        let table: toml::Table = r#"
+            [wallhaven]
+            sorting = "toplist"
+            top_range = "1w"
+            order = "sideways"
+        "#
+        .parse()
+        .unwrap();
+        let source = from_table(&table, HttpClient::default()).unwrap();
+        let params = source.search_params("sky", 1, 24, AspectRatioFilter::All);
+        assert!(params.contains(&("sorting", "toplist".into())));
+        assert!(params.contains(&("topRange", "1w".into())));
+        // the unsupported order is dropped rather than sent
+        assert!(params.iter().all(|(k, _)| *k != "order"));
+    }
+
+    #[test]
+    fn test_browse_defaults_to_newest() {
+        let params = client(None).search_params("", 1, 24, AspectRatioFilter::All);
+        assert!(params.contains(&("sorting", "date_added".into())));
+        assert!(params.contains(&("order", "desc".into())));
+
+        // a query keeps wallhaven's relevance default
+        let params = client(None).search_params("sky", 1, 24, AspectRatioFilter::All);
+        assert!(params.iter().all(|(k, _)| *k != "sorting" && *k != "order"));
+    }
+
+    #[test]
+    fn test_page_size_snaps_to_supported() {
+        assert_eq!(page_size(10), 24);
+        assert_eq!(page_size(30), 32);
+        assert_eq!(page_size(200), 64);
+    }
+
+    #[test]
+    fn test_default_config_registers_wallhaven() {
+        let config = muralis_core::config::Config::default();
+        let sources = create_sources(&config.sources, HttpClient::default());
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].source_type(), "wallhaven");
+        assert!(sources[0].browsable());
+    }
 }
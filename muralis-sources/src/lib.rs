@@ -0,0 +1,43 @@
+//! Every source crate wired into one registry. Lives outside muralis-core,
+//! which the source crates depend on, so the CLI and daemon share it.
+
+use muralis_core::config::Config;
+use muralis_core::error::Result;
+use muralis_core::paths::MuralisPaths;
+use muralis_core::sources::SourceRegistry;
+
+/// Registry of every source configured in `config`.
+pub fn build_registry(config: &Config, paths: &MuralisPaths) -> Result<SourceRegistry> {
+    let client = muralis_core::sources::http_client(&config.general)?;
+    let sources = &config.sources;
+    let mut registry = SourceRegistry::new();
+
+    for s in muralis_source_wallhaven::create_sources(sources, client.clone()) {
+        registry.register(s);
+    }
+    for s in muralis_source_unsplash::create_sources(sources, client.clone()) {
+        registry.register(s);
+    }
+    for s in muralis_source_pexels::create_sources(sources, client.clone()) {
+        registry.register(s);
+    }
+    for s in muralis_source_deviantart::create_sources(sources, client.clone()) {
+        registry.register(s);
+    }
+    for s in muralis_source_bing::create_sources(sources, client.clone()) {
+        registry.register(s);
+    }
+    for s in muralis_source_apod::create_sources(sources, client.clone()) {
+        registry.register(s);
+    }
+    for s in muralis_source_local::create_sources(sources) {
+        registry.register(s);
+    }
+    for s in muralis_source_feed::create_sources(sources, client.clone(), &paths.feed_cache_dir()) {
+        registry.register(s);
+    }
+    for s in muralis_source_stream::create_sources(sources, client) {
+        registry.register(s);
+    }
+    Ok(registry)
+}